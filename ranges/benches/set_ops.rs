@@ -0,0 +1,79 @@
+use std::ops::Range;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use utils_ranges::{Difference, DifferenceMut, Intersection, RangeSet, Union, UnionMut};
+
+/// Returns a set of `count` random ranges with gaps between them.
+fn random_set(rng: &mut StdRng, count: usize) -> RangeSet<usize> {
+    let mut start = 0;
+    let ranges: Vec<Range<usize>> = (0..count)
+        .map(|_| {
+            start += rng.gen_range(1..16);
+            let end = start + rng.gen_range(1..16);
+            let range = start..end;
+            start = end;
+            range
+        })
+        .collect();
+
+    RangeSet::from(ranges)
+}
+
+fn bench_set_ops(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let mut group = c.benchmark_group("set_ops");
+    for count in [1_000, 10_000, 50_000] {
+        let a = random_set(&mut rng, count);
+        let b = random_set(&mut rng, count);
+
+        group.bench_with_input(BenchmarkId::new("union", count), &count, |bench, _| {
+            bench.iter(|| black_box(&a).union(black_box(&b)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("intersection", count),
+            &count,
+            |bench, _| bench.iter(|| black_box(&a).intersection(black_box(&b))),
+        );
+        group.bench_with_input(BenchmarkId::new("difference", count), &count, |bench, _| {
+            bench.iter(|| black_box(&a).difference(black_box(&b)))
+        });
+
+        // A range covering half of the set, which must be merged in a single pass.
+        let (start, end) = (a.min().unwrap(), a.end().unwrap());
+        let range = start + (end - start) / 4..start + 3 * (end - start) / 4;
+        group.bench_with_input(
+            BenchmarkId::new("union_mut_range", count),
+            &count,
+            |bench, _| {
+                bench.iter_batched_ref(
+                    || a.clone(),
+                    |set| set.union_mut(black_box(&range)),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("difference_mut_range", count),
+            &count,
+            |bench, _| {
+                bench.iter_batched_ref(
+                    || a.clone(),
+                    |set| set.difference_mut(black_box(&range)),
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+
+        let ranges: Vec<_> = a.iter_ranges().rev().collect();
+        group.bench_with_input(BenchmarkId::new("new", count), &count, |bench, _| {
+            bench.iter(|| RangeSet::new(black_box(&ranges)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_set_ops);
+criterion_main!(benches);
@@ -0,0 +1,164 @@
+use std::{
+    fmt,
+    ops::{Bound, Range, RangeBounds, RangeInclusive, RangeTo, RangeToInclusive},
+};
+
+use crate::{RangeSet, Step};
+
+/// An error returned when converting a std range into a [`RangeSet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The exclusive end of the range exceeds the maximum value of its type, e.g. for
+    /// `0..=u8::MAX`.
+    Overflow,
+    /// The range is not contained in the universe it is bounded by.
+    OutOfBounds,
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::Overflow => write!(f, "range conversion overflowed"),
+            ConvertError::OutOfBounds => write!(f, "range is out of bounds of the universe"),
+        }
+    }
+}
+
+impl std::error::Error for ConvertError {}
+
+impl<T: Copy + Ord + Step> RangeSet<T> {
+    /// Returns a new set from any std range, such as `5..` or `..=10`, with its unbounded
+    /// ends taken from `universe`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an inclusive end overflows, or if the range is not contained in
+    /// `universe`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils_ranges::{ConvertError, RangeSet};
+    ///
+    /// assert_eq!(RangeSet::try_from_bounds(5.., 0..10), Ok(RangeSet::from(5..10)));
+    /// assert_eq!(RangeSet::try_from_bounds(..=3, 0..10), Ok(RangeSet::from(0..4)));
+    /// assert_eq!(
+    ///     RangeSet::try_from_bounds(5..20, 0..10),
+    ///     Err(ConvertError::OutOfBounds)
+    /// );
+    /// ```
+    pub fn try_from_bounds(
+        bounds: impl RangeBounds<T>,
+        universe: Range<T>,
+    ) -> Result<Self, ConvertError> {
+        let start = match bounds.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => Step::forward(*start, 1).ok_or(ConvertError::Overflow)?,
+            Bound::Unbounded => universe.start,
+        };
+        let end = match bounds.end_bound() {
+            Bound::Included(end) => Step::forward(*end, 1).ok_or(ConvertError::Overflow)?,
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => universe.end,
+        };
+
+        if start >= end {
+            return Ok(Self::default());
+        }
+
+        if start < universe.start || end > universe.end {
+            return Err(ConvertError::OutOfBounds);
+        }
+
+        Ok(Self::from(start..end))
+    }
+}
+
+impl<T: Copy + Ord + Step> TryFrom<RangeInclusive<T>> for RangeSet<T> {
+    type Error = ConvertError;
+
+    /// Converts an inclusive range into a set, returning an error if its exclusive end
+    /// overflows.
+    fn try_from(range: RangeInclusive<T>) -> Result<Self, Self::Error> {
+        if range.is_empty() {
+            return Ok(Self::default());
+        }
+
+        let (start, end) = range.into_inner();
+        let end = Step::forward(end, 1).ok_or(ConvertError::Overflow)?;
+
+        Ok(Self::from(start..end))
+    }
+}
+
+macro_rules! impl_convert {
+    ($($ty:ty),+) => {
+        $(
+            impl From<RangeTo<$ty>> for RangeSet<$ty> {
+                /// Converts a range into a set starting at the minimum value of the type.
+                fn from(range: RangeTo<$ty>) -> Self {
+                    Self::from(<$ty>::MIN..range.end)
+                }
+            }
+
+            impl TryFrom<RangeToInclusive<$ty>> for RangeSet<$ty> {
+                type Error = ConvertError;
+
+                /// Converts a range into a set starting at the minimum value of the type,
+                /// returning an error if its exclusive end overflows.
+                fn try_from(range: RangeToInclusive<$ty>) -> Result<Self, Self::Error> {
+                    Self::try_from(<$ty>::MIN..=range.end)
+                }
+            }
+        )*
+    };
+}
+
+impl_convert!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_try_from_inclusive() {
+        assert_eq!(RangeSet::try_from(3u8..=5), Ok(RangeSet::from(3..6)));
+        #[allow(clippy::reversed_empty_ranges)]
+        let empty = 5u8..=3;
+        assert_eq!(RangeSet::try_from(empty), Ok(RangeSet::default()));
+        assert_eq!(
+            RangeSet::try_from(0u8..=u8::MAX),
+            Err(ConvertError::Overflow)
+        );
+        assert_eq!(RangeSet::try_from(..=9u8), Ok(RangeSet::from(0..10)));
+        assert_eq!(RangeSet::from(..4i8), RangeSet::from(i8::MIN..4));
+    }
+
+    #[test]
+    fn test_try_from_bounds() {
+        assert_eq!(
+            RangeSet::try_from_bounds(.., 2usize..8),
+            Ok(RangeSet::from(2..8))
+        );
+        assert_eq!(
+            RangeSet::try_from_bounds((Bound::Excluded(2usize), Bound::Included(4)), 0..8),
+            Ok(RangeSet::from(3..5))
+        );
+        assert_eq!(
+            RangeSet::try_from_bounds(0u8.., 0..u8::MAX),
+            Ok(RangeSet::from(0..u8::MAX))
+        );
+        assert_eq!(
+            RangeSet::try_from_bounds(..=u8::MAX, 0..u8::MAX),
+            Err(ConvertError::Overflow)
+        );
+        assert_eq!(
+            RangeSet::try_from_bounds(1usize.., 2..8),
+            Err(ConvertError::OutOfBounds)
+        );
+        assert_eq!(
+            RangeSet::try_from_bounds(9usize.., 2..8),
+            Ok(RangeSet::default())
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::ops::{Range, Sub, SubAssign};
 
-use crate::range::{Disjoint, RangeSet, Subset};
+use crate::{Disjoint, RangeSet, Subset};
 
 pub trait Difference<Rhs> {
     type Output;
@@ -16,60 +16,40 @@ pub trait DifferenceMut<Rhs> {
 }
 
 impl<T: Copy + Ord> DifferenceMut<Range<T>> for RangeSet<T> {
+    /// Runs in `O(n)` time, where `n` is the number of ranges of `self`.
     fn difference_mut(&mut self, other: &Range<T>) {
         if other.is_empty() || self.ranges.is_empty() {
             return;
         }
 
-        let mut i = 0;
         let ranges = &mut self.ranges;
-        while i < ranges.len() {
-            // If the current range is entirely before other
-            if ranges[i].end <= other.start {
-                // no-op
-            }
-            // If the current range is entirely after other
-            else if ranges[i].start >= other.end {
-                // we're done
-                break;
-            }
-            // If the current range is entirely contained within other
-            else if ranges[i].is_subset(other) {
-                ranges.remove(i);
-                continue;
-            }
-            // If other is a subset of the current range
-            else if other.is_subset(&ranges[i]) {
-                if ranges[i].start == other.start {
-                    ranges[i].start = other.end;
-                } else if ranges[i].end == other.end {
-                    ranges[i].end = other.start;
-                } else {
-                    ranges.insert(i + 1, other.end..ranges[i].end);
-                    ranges[i].end = other.start;
-                }
-            } else {
-                // Trim end
-                if ranges[i].start < other.start {
-                    ranges[i].end = other.start;
-                }
-
-                // Trim start
-                if ranges[i].end > other.end {
-                    ranges[i].start = other.end;
-                }
-            }
 
-            i += 1;
+        // The ranges which intersect `other` are replaced by the parts outside of it.
+        let start = ranges.partition_point(|range| range.end <= other.start);
+        let end = ranges.partition_point(|range| range.start < other.end);
+        if start >= end {
+            return;
         }
+
+        let left = ranges[start].start..other.start;
+        let right = other.end..ranges[end - 1].end;
+        ranges.splice(
+            start..end,
+            [left, right].into_iter().filter(|range| !range.is_empty()),
+        );
+
+        self.debug_assert_invariants();
     }
 }
 
 impl<T: Copy + Ord> DifferenceMut<RangeSet<T>> for RangeSet<T> {
+    /// Runs in `O(n + m)` time, where `n` and `m` are the number of ranges of the sets.
     fn difference_mut(&mut self, other: &RangeSet<T>) {
-        for range in &other.ranges {
-            self.difference_mut(range);
+        if other.ranges.is_empty() || self.ranges.is_empty() {
+            return;
         }
+
+        *self = self.difference(other);
     }
 }
 
@@ -110,6 +90,7 @@ impl<T: Copy + Ord> Difference<Range<T>> for Range<T> {
 impl<T: Copy + Ord> Difference<Range<T>> for RangeSet<T> {
     type Output = RangeSet<T>;
 
+    /// Runs in `O(n)` time, where `n` is the number of ranges of `self`.
     fn difference(&self, other: &Range<T>) -> Self::Output {
         let mut diff = self.clone();
         diff.difference_mut(other);
@@ -120,10 +101,9 @@ impl<T: Copy + Ord> Difference<Range<T>> for RangeSet<T> {
 impl<T: Copy + Ord> Difference<RangeSet<T>> for Range<T> {
     type Output = RangeSet<T>;
 
+    /// Runs in `O(n)` time, where `n` is the number of ranges of `other`.
     fn difference(&self, other: &RangeSet<T>) -> Self::Output {
-        let mut diff = RangeSet {
-            ranges: vec![self.clone()],
-        };
+        let mut diff = RangeSet::from(self.clone());
         diff.difference_mut(other);
         diff
     }
@@ -132,10 +112,12 @@ impl<T: Copy + Ord> Difference<RangeSet<T>> for Range<T> {
 impl<T: Copy + Ord> Difference<RangeSet<T>> for RangeSet<T> {
     type Output = RangeSet<T>;
 
+    /// Runs in `O(n + m)` time, where `n` and `m` are the number of ranges of the sets.
     fn difference(&self, other: &RangeSet<T>) -> Self::Output {
-        let mut diff = self.clone();
-        diff.difference_mut(other);
-        diff
+        self.debug_assert_invariants();
+        other.debug_assert_invariants();
+
+        RangeSet::from_output(self.iter_difference(other))
     }
 }
 
@@ -203,7 +185,7 @@ impl<T: Copy + Ord> Sub<&RangeSet<T>> for RangeSet<T> {
 #[allow(clippy::all)]
 mod tests {
     use super::*;
-    use crate::range::Union;
+    use crate::Union;
 
     use itertools::iproduct;
 
@@ -0,0 +1,143 @@
+use std::ops::Range;
+
+use crate::{RangeSet, ToRangeSet};
+
+/// A set of ranges which is fixed at compile time.
+///
+/// `RangeSet` stores its ranges on the heap, so a non-empty set can not be declared as a
+/// constant. A `StaticRangeSet` borrows its ranges from a static slice instead, and checks
+/// the invariants of `RangeSet` when it is constructed, which fails compilation when used in
+/// a constant.
+///
+/// # Examples
+///
+/// ```
+/// use utils_ranges::{RangeSet, StaticRangeSet, ToRangeSet};
+///
+/// const STATUS_LINE: StaticRangeSet<usize> = StaticRangeSet::<usize>::new(&[0..12]);
+/// static EMPTY: RangeSet<usize> = RangeSet::empty();
+///
+/// assert_eq!(STATUS_LINE.to_range_set(), RangeSet::from(0..12));
+/// assert!(EMPTY.is_empty());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StaticRangeSet<T: 'static> {
+    /// The ranges of the set.
+    ///
+    /// The ranges *MUST* be sorted, non-adjacent, non-intersecting, and non-empty.
+    ranges: &'static [Range<T>],
+}
+
+impl<T> StaticRangeSet<T> {
+    /// Returns an empty set.
+    pub const fn empty() -> Self {
+        Self { ranges: &[] }
+    }
+
+    /// Returns the ranges of the set.
+    pub const fn ranges(&self) -> &'static [Range<T>] {
+        self.ranges
+    }
+
+    /// Returns the number of ranges in the set.
+    pub const fn len_ranges(&self) -> usize {
+        self.ranges.len()
+    }
+}
+
+macro_rules! impl_static_range_set {
+    ($($ty:ty),+) => {
+        $(
+            impl StaticRangeSet<$ty> {
+                /// Returns a new set from the given ranges.
+                ///
+                /// # Panics
+                ///
+                /// Panics if the ranges are not sorted, non-adjacent, non-intersecting and
+                /// non-empty.
+                pub const fn new(ranges: &'static [Range<$ty>]) -> Self {
+                    let mut i = 0;
+                    while i < ranges.len() {
+                        assert!(ranges[i].start < ranges[i].end, "range is empty");
+                        if i > 0 {
+                            assert!(
+                                ranges[i - 1].end < ranges[i].start,
+                                "ranges are not sorted, or are adjacent or intersecting"
+                            );
+                        }
+                        i += 1;
+                    }
+
+                    Self { ranges }
+                }
+
+                /// Returns `true` if the set contains the value.
+                pub const fn contains(&self, value: $ty) -> bool {
+                    let mut i = 0;
+                    while i < self.ranges.len() {
+                        if self.ranges[i].start <= value && value < self.ranges[i].end {
+                            return true;
+                        }
+                        i += 1;
+                    }
+
+                    false
+                }
+            }
+        )*
+    };
+}
+
+impl_static_range_set!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl<T: Copy + Ord> ToRangeSet<T> for StaticRangeSet<T> {
+    fn to_range_set(&self) -> RangeSet<T> {
+        RangeSet {
+            ranges: self.ranges.to_vec(),
+        }
+    }
+}
+
+impl<T: Copy + Ord> From<StaticRangeSet<T>> for RangeSet<T> {
+    fn from(set: StaticRangeSet<T>) -> Self {
+        set.to_range_set()
+    }
+}
+
+impl<T: Copy + Ord> PartialEq<StaticRangeSet<T>> for RangeSet<T> {
+    fn eq(&self, other: &StaticRangeSet<T>) -> bool {
+        self.ranges == other.ranges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEAD: StaticRangeSet<usize> = StaticRangeSet::<usize>::new(&[(0..12), (14..20)]);
+
+    #[test]
+    fn test_static_range_set() {
+        assert_eq!(HEAD.len_ranges(), 2);
+        assert!(HEAD.contains(0));
+        assert!(!HEAD.contains(12));
+        assert!(HEAD.contains(19));
+        assert_eq!(RangeSet::from([(0..12), (14..20)]), HEAD);
+        assert_eq!(
+            StaticRangeSet::<u8>::empty().to_range_set(),
+            RangeSet::empty()
+        );
+    }
+
+    #[test]
+    #[should_panic = "range is empty"]
+    fn test_static_range_set_empty_range() {
+        StaticRangeSet::<usize>::new(&[(0..2), (5..5)]);
+    }
+
+    #[test]
+    #[should_panic = "ranges are not sorted"]
+    fn test_static_range_set_adjacent() {
+        StaticRangeSet::<usize>::new(&[(0..5), (5..10)]);
+    }
+}
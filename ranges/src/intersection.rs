@@ -1,6 +1,6 @@
 use std::ops::{BitAnd, BitAndAssign};
 
-use crate::range::{Range, RangeSet};
+use crate::{Range, RangeSet};
 
 pub trait Intersection<Rhs> {
     type Output;
@@ -28,10 +28,16 @@ impl<T: Copy + Ord> Intersection<Range<T>> for Range<T> {
 impl<T: Copy + Ord> Intersection<RangeSet<T>> for Range<T> {
     type Output = RangeSet<T>;
 
+    /// Runs in `O(log n + k)` time, where `n` is the number of ranges of `other` and `k` is
+    /// the number of ranges of the intersection.
     fn intersection(&self, other: &RangeSet<T>) -> Self::Output {
         let mut set = RangeSet::default();
 
-        for other in &other.ranges {
+        // Skip the ranges which end before `self` starts.
+        let start = other
+            .ranges
+            .partition_point(|range| range.end <= self.start);
+        for other in &other.ranges[start..] {
             if self.end <= other.start {
                 // `self` is leftward of `other`, so we can break early.
                 break;
@@ -49,6 +55,8 @@ impl<T: Copy + Ord> Intersection<RangeSet<T>> for Range<T> {
 impl<T: Copy + Ord> Intersection<Range<T>> for RangeSet<T> {
     type Output = RangeSet<T>;
 
+    /// Runs in `O(log n + k)` time, where `n` is the number of ranges of `self` and `k` is
+    /// the number of ranges of the intersection.
     fn intersection(&self, other: &Range<T>) -> Self::Output {
         other.intersection(self)
     }
@@ -57,38 +65,12 @@ impl<T: Copy + Ord> Intersection<Range<T>> for RangeSet<T> {
 impl<T: Copy + Ord> Intersection<RangeSet<T>> for RangeSet<T> {
     type Output = RangeSet<T>;
 
+    /// Runs in `O(n + m)` time, where `n` and `m` are the number of ranges of the sets.
     fn intersection(&self, other: &RangeSet<T>) -> Self::Output {
-        let mut set = RangeSet::default();
-
-        let mut i = 0;
-        let mut j = 0;
-
-        while i < self.ranges.len() && j < other.ranges.len() {
-            let a = &self.ranges[i];
-            let b = &other.ranges[j];
-
-            if a.end <= b.start {
-                // `a` is leftward of `b`, so we can proceed to the next range in `self`.
-                i += 1;
-            } else if b.end <= a.start {
-                // `b` is leftward of `a`, so we can proceed to the next range in `other`.
-                j += 1;
-            } else if let Some(intersection) = a.intersection(b) {
-                // Given that `self` and `other` contain sorted, non-adjacent, non-intersecting, and
-                // non-empty ranges, the new set will also have these properties.
-                set.ranges.push(intersection);
-
-                if a.end <= b.end {
-                    i += 1;
-                }
+        self.debug_assert_invariants();
+        other.debug_assert_invariants();
 
-                if b.end <= a.end {
-                    j += 1;
-                }
-            }
-        }
-
-        set
+        RangeSet::from_output(self.iter_intersection(other))
     }
 }
 
@@ -171,7 +153,7 @@ mod tests {
 
     use itertools::iproduct;
 
-    use crate::range::assert_invariants;
+    use crate::assert_invariants;
 
     use super::*;
 
@@ -0,0 +1,242 @@
+use std::{iter::Peekable, ops::Range, slice::Iter};
+
+use crate::RangeSet;
+
+impl<T: Copy + Ord> RangeSet<T> {
+    /// Returns an iterator over the ranges of the union of `self` and `other`.
+    ///
+    /// The ranges are computed lazily, in order, and are the ranges of `self.union(other)`.
+    pub fn iter_union<'a>(&'a self, other: &'a RangeSet<T>) -> UnionIter<'a, T> {
+        UnionIter {
+            a: self.ranges.iter().peekable(),
+            b: other.ranges.iter().peekable(),
+            pending: None,
+        }
+    }
+
+    /// Returns an iterator over the ranges of the intersection of `self` and `other`.
+    ///
+    /// The ranges are computed lazily, in order, and are the ranges of
+    /// `self.intersection(other)`.
+    pub fn iter_intersection<'a>(&'a self, other: &'a RangeSet<T>) -> IntersectionIter<'a, T> {
+        IntersectionIter {
+            a: &self.ranges,
+            b: &other.ranges,
+        }
+    }
+
+    /// Returns an iterator over the ranges of the difference of `self` and `other`.
+    ///
+    /// The ranges are computed lazily, in order, and are the ranges of
+    /// `self.difference(other)`.
+    pub fn iter_difference<'a>(&'a self, other: &'a RangeSet<T>) -> DifferenceIter<'a, T> {
+        DifferenceIter {
+            a: self.ranges.iter(),
+            b: &other.ranges,
+            current: None,
+        }
+    }
+}
+
+/// An iterator over the ranges of the union of two sets.
+///
+/// Created by [`RangeSet::iter_union`].
+#[derive(Debug, Clone)]
+pub struct UnionIter<'a, T> {
+    a: Peekable<Iter<'a, Range<T>>>,
+    b: Peekable<Iter<'a, Range<T>>>,
+    /// The range being merged with the ranges that follow it.
+    pending: Option<Range<T>>,
+}
+
+impl<T: Copy + Ord> Iterator for UnionIter<'_, T> {
+    type Item = Range<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Take the range which starts first, then merge it with the pending range if they
+            // overlap or are adjacent.
+            let range = match (self.a.peek(), self.b.peek()) {
+                (Some(x), Some(y)) if x.start <= y.start => self.a.next(),
+                (Some(_), Some(_)) => self.b.next(),
+                (Some(_), None) => self.a.next(),
+                (None, Some(_)) => self.b.next(),
+                (None, None) => return self.pending.take(),
+            }
+            .expect("range is present");
+
+            match &mut self.pending {
+                Some(pending) if range.start <= pending.end => {
+                    pending.end = pending.end.max(range.end)
+                }
+                Some(pending) => return Some(std::mem::replace(pending, range.clone())),
+                None => self.pending = Some(range.clone()),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.a.len() + self.b.len() + usize::from(self.pending.is_some());
+
+        (usize::from(remaining > 0), Some(remaining))
+    }
+}
+
+/// An iterator over the ranges of the intersection of two sets.
+///
+/// Created by [`RangeSet::iter_intersection`].
+#[derive(Debug, Clone)]
+pub struct IntersectionIter<'a, T> {
+    a: &'a [Range<T>],
+    b: &'a [Range<T>],
+}
+
+impl<T: Copy + Ord> Iterator for IntersectionIter<'_, T> {
+    type Item = Range<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let (Some(a), Some(b)) = (self.a.first(), self.b.first()) {
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+
+            // Proceed past whichever range ends first, as it can not intersect any other range.
+            if a.end <= b.end {
+                self.a = &self.a[1..];
+            }
+            if b.end <= a.end {
+                self.b = &self.b[1..];
+            }
+
+            // Given that both sets contain sorted, non-adjacent, non-intersecting, and
+            // non-empty ranges, the intersections will also have these properties.
+            if start < end {
+                return Some(start..end);
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Each intersection ends at the end of a range of either set, and the last range of
+        // one of the sets is never followed by another intersection.
+        (0, Some((self.a.len() + self.b.len()).saturating_sub(1)))
+    }
+}
+
+/// An iterator over the ranges of the difference of two sets.
+///
+/// Created by [`RangeSet::iter_difference`].
+#[derive(Debug, Clone)]
+pub struct DifferenceIter<'a, T> {
+    a: Iter<'a, Range<T>>,
+    /// The ranges of the subtracted set which may intersect the current or later ranges.
+    b: &'a [Range<T>],
+    /// The part of the current range which has not been yielded or subtracted yet.
+    current: Option<Range<T>>,
+}
+
+impl<T: Copy + Ord> Iterator for DifferenceIter<'_, T> {
+    type Item = Range<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let current = match &mut self.current {
+                Some(current) => current,
+                None => self.current.insert(self.a.next()?.clone()),
+            };
+
+            // Skip the subtracted ranges which are leftward of the current range, and
+            // therefore of all remaining ranges.
+            while self.b.first().is_some_and(|b| b.end <= current.start) {
+                self.b = &self.b[1..];
+            }
+
+            match self.b.first() {
+                Some(b) if b.start < current.end => {
+                    let gap = current.start..b.start;
+                    current.start = current.start.max(b.end);
+                    if current.start >= current.end {
+                        self.current = None;
+                    }
+
+                    if !gap.is_empty() {
+                        return Some(gap);
+                    }
+                }
+                _ => return self.current.take(),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // Each subtracted range splits at most one range in two.
+        let remaining = self.a.len() + usize::from(self.current.is_some());
+
+        (0, Some(remaining + self.b.len()))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    use crate::{Difference, Intersection, Union};
+
+    fn sets() -> Vec<RangeSet<usize>> {
+        vec![
+            RangeSet::default(),
+            RangeSet::from([0..10]),
+            RangeSet::from([(0..2), (4..6), (8..10)]),
+            RangeSet::from([(1..5), (9..12)]),
+            RangeSet::from([(2..4), (6..8), (10..20)]),
+            RangeSet::from([(0..1), (3..4), (5..6), (7..8), (9..10)]),
+        ]
+    }
+
+    #[test]
+    fn test_lazy_ops_match_eager_ops() {
+        for a in sets() {
+            for b in sets() {
+                let union = a.iter_union(&b).collect::<Vec<_>>();
+                assert_eq!(union, a.union(&b).into_inner(), "{a:?} | {b:?}");
+
+                let intersection = a.iter_intersection(&b).collect::<Vec<_>>();
+                assert_eq!(
+                    intersection,
+                    a.intersection(&b).into_inner(),
+                    "{a:?} & {b:?}"
+                );
+
+                let difference = a.iter_difference(&b).collect::<Vec<_>>();
+                assert_eq!(difference, a.difference(&b).into_inner(), "{a:?} - {b:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_ops_size_hint() {
+        for a in sets() {
+            for b in sets() {
+                let union = a.iter_union(&b);
+                assert!(union.size_hint().1.unwrap() >= union.clone().count());
+
+                let intersection = a.iter_intersection(&b);
+                assert!(intersection.size_hint().1.unwrap() >= intersection.clone().count());
+
+                let difference = a.iter_difference(&b);
+                assert!(difference.size_hint().1.unwrap() >= difference.clone().count());
+            }
+        }
+    }
+
+    #[test]
+    fn test_lazy_ops_fold() {
+        let a = RangeSet::from([(0..10), (20..30)]);
+        let b = RangeSet::from([(5..25)]);
+
+        let len: usize = a.iter_intersection(&b).map(|range| range.len()).sum();
+        assert_eq!(len, 10);
+    }
+}
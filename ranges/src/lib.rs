@@ -1,18 +1,44 @@
+//! Range sets and range set algebra.
+//!
+//! This crate has no required dependencies, so it can be used by crates which only need
+//! `RangeSet` without pulling in the rest of `tlsn-utils`, which re-exports it as
+//! `utils::range`.
+
+mod builder;
+mod convert;
 mod difference;
+mod fixed;
 mod index;
 mod intersection;
+mod lazy;
+mod map;
+mod partition;
+mod redaction;
+mod shift;
 mod subset;
 mod symmetric_difference;
 mod union;
 
+pub use builder::RangeSetBuilder;
+pub use convert::ConvertError;
 pub use difference::{Difference, DifferenceMut};
+pub use fixed::StaticRangeSet;
 pub use index::IndexRanges;
 pub use intersection::Intersection;
+pub use lazy::{DifferenceIter, IntersectionIter, UnionIter};
+pub use map::RangeSetMap;
+pub use partition::Partition;
+pub use redaction::{check_redaction, Leak, RedactionError};
+pub use shift::{CheckedShift, SaturatingShift, ShiftError};
 pub use subset::Subset;
 pub use symmetric_difference::{SymmetricDifference, SymmetricDifferenceMut};
 pub use union::{Union, UnionMut};
 
-use std::ops::{Add, Range, Sub};
+use std::{
+    ops::{Add, Range, Sub},
+    rc::Rc,
+    sync::Arc,
+};
 
 /// A set of values represented using ranges.
 ///
@@ -29,11 +55,18 @@ use std::ops::{Add, Range, Sub};
 /// - The ranges are non-empty.
 ///
 /// This is enforced in the constructor, and guaranteed to hold after applying any operation on a range or set.
+/// In debug builds, set operations assert that their inputs and outputs hold these invariants.
+///
+/// # Complexity
+///
+/// Operations between two sets with `n` and `m` ranges merge their ranges in a single scan,
+/// taking `O(n + m)` time. Operations between a set and a range locate the affected ranges by
+/// binary search and take `O(n)` time, as the ranges following them may need to be shifted.
 ///
 /// # Examples
 ///
 /// ```
-/// use utils::range::*;
+/// use utils_ranges::*;
 ///
 /// let a = 10..20;
 ///
@@ -67,6 +100,16 @@ pub struct RangeSet<T> {
     ranges: Vec<Range<T>>,
 }
 
+/// The output of a set operation is shrunk if its capacity is this many times its length.
+const SHRINK_FACTOR: usize = 4;
+
+// Range sets must be shareable across threads.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<RangeSet<usize>>();
+};
+
 impl<T: Copy + Ord> From<Vec<Range<T>>> for RangeSet<T> {
     fn from(ranges: Vec<Range<T>>) -> Self {
         Self::new(&ranges)
@@ -81,13 +124,18 @@ impl<T> From<RangeSet<T>> for Vec<Range<T>> {
 
 impl<T: Copy + Ord> Default for RangeSet<T> {
     fn default() -> Self {
-        Self {
-            ranges: Default::default(),
-        }
+        Self::empty()
     }
 }
 
 impl<T> RangeSet<T> {
+    /// Returns an empty set.
+    ///
+    /// Unlike `RangeSet::default`, this can be used in constants and statics.
+    pub const fn empty() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
     /// Returns the ranges of the set.
     pub fn into_inner(self) -> Vec<Range<T>> {
         self.ranges
@@ -102,23 +150,80 @@ impl<T> RangeSet<T> {
     pub fn clear(&mut self) {
         self.ranges.clear();
     }
+
+    /// Returns an empty set with capacity for at least `capacity` ranges.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            ranges: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of ranges the set can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.ranges.capacity()
+    }
+
+    /// Reserves capacity for at least `additional` more ranges.
+    pub fn reserve(&mut self, additional: usize) {
+        self.ranges.reserve(additional);
+    }
+
+    /// Shrinks the capacity of the set as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        self.ranges.shrink_to_fit();
+    }
+
+    /// Returns a set from the ranges yielded by a set operation.
+    ///
+    /// The buffer is preallocated for the largest possible output, according to the size hint
+    /// of the iterator, and is shrunk if most of it is unused.
+    ///
+    /// The ranges *MUST* be sorted, non-adjacent, non-intersecting, and non-empty.
+    pub(crate) fn from_output(iter: impl Iterator<Item = Range<T>>) -> Self
+    where
+        T: Ord,
+    {
+        let (lower, upper) = iter.size_hint();
+        let mut ranges = Vec::with_capacity(upper.unwrap_or(lower));
+        ranges.extend(iter);
+
+        if ranges.len() < ranges.capacity() / SHRINK_FACTOR {
+            ranges.shrink_to_fit();
+        }
+
+        let set = Self { ranges };
+        set.debug_assert_invariants();
+        set
+    }
+
+    /// Asserts, in debug builds, that the ranges are sorted, non-adjacent, non-intersecting,
+    /// and non-empty.
+    ///
+    /// Set operations rely on these invariants to merge ranges in a single scan, so a
+    /// violation would silently produce wrong results in release builds.
+    #[inline]
+    pub(crate) fn debug_assert_invariants(&self)
+    where
+        T: Ord,
+    {
+        debug_assert!(
+            self.ranges.iter().all(|range| range.start < range.end)
+                && self.ranges.windows(2).all(|w| w[0].end < w[1].start),
+            "range set invariants violated"
+        );
+    }
 }
 
 impl<T: Copy + Ord> RangeSet<T> {
     /// Returns a new `RangeSet` from the given ranges.
     ///
-    /// The `RangeSet` is constructed by computing the union of the given ranges.
+    /// The `RangeSet` is constructed by computing the union of the given ranges, which takes
+    /// `O(n log n)` time for `n` ranges.
     pub fn new(ranges: &[Range<T>]) -> Self
     where
         Self: Union<Range<T>, Output = Self>,
     {
-        let mut set = Self::default();
-
-        for range in ranges {
-            set = set.union(range);
-        }
-
-        set
+        RangeSetBuilder::from(ranges.to_vec()).build()
     }
 
     /// Returns an iterator over the values in the set.
@@ -137,8 +242,13 @@ impl<T: Copy + Ord> RangeSet<T> {
     }
 
     /// Returns `true` if the set contains the given value.
+    ///
+    /// This takes `O(log n)` time for a set of `n` ranges.
     pub fn contains(&self, value: &T) -> bool {
-        self.ranges.iter().any(|range| range.contains(value))
+        let idx = self.ranges.partition_point(|range| range.end <= *value);
+        self.ranges
+            .get(idx)
+            .is_some_and(|range| range.contains(value))
     }
 
     /// Returns the minimum value in the set, or `None` if the set is empty.
@@ -155,6 +265,72 @@ impl<T: Copy + Ord> RangeSet<T> {
     pub fn end(&self) -> Option<T> {
         self.ranges.last().map(|range| range.end)
     }
+
+    /// Returns the smallest range containing every value in the set, or `None` if the set is
+    /// empty.
+    ///
+    /// This is `min()..end()`, including any gaps between the ranges of the set.
+    pub fn bounds(&self) -> Option<Range<T>> {
+        Some(self.min()?..self.end()?)
+    }
+
+    /// Splits the set at the provided value, which need not be in the set.
+    ///
+    /// Returns a set containing the values `< at` and a set containing the values `>= at`.
+    /// See [`RangeSet::split_off`] for splitting in place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils_ranges::RangeSet;
+    ///
+    /// let set = RangeSet::from([(0..10), (20..30)]);
+    ///
+    /// let (left, right) = set.split_at(25);
+    /// assert_eq!(left, RangeSet::from([(0..10), (20..25)]));
+    /// assert_eq!(right, RangeSet::from(25..30));
+    /// ```
+    pub fn split_at(&self, at: T) -> (Self, Self) {
+        // The ranges which end at or before `at` are entirely on the left.
+        let idx = self.ranges.partition_point(|range| range.end <= at);
+
+        let mut left = self.ranges[..idx].to_vec();
+        let mut right = self.ranges[idx..].to_vec();
+
+        if let Some(first) = right.first_mut() {
+            if first.start < at {
+                left.push(first.start..at);
+                first.start = at;
+            }
+        }
+
+        (Self { ranges: left }, Self { ranges: right })
+    }
+
+    /// Partitions the ranges of the set by a predicate.
+    ///
+    /// Returns a set of the ranges for which the predicate returns `true` and a set of the
+    /// ranges for which it returns `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils_ranges::RangeSet;
+    ///
+    /// let set = RangeSet::from([(0..2), (5..15), (20..22)]);
+    ///
+    /// let (long, short) = set.partition(|range| range.end - range.start > 2);
+    /// assert_eq!(long, RangeSet::from(5..15));
+    /// assert_eq!(short, RangeSet::from([(0..2), (20..22)]));
+    /// ```
+    pub fn partition<F>(&self, mut f: F) -> (Self, Self)
+    where
+        F: FnMut(&Range<T>) -> bool,
+    {
+        let (matching, rest) = self.ranges.iter().cloned().partition(|range| f(range));
+
+        (Self { ranges: matching }, Self { ranges: rest })
+    }
 }
 
 impl<T: Copy + Ord + Step + Sub<Output = T>> RangeSet<T> {
@@ -207,7 +383,8 @@ impl<T: Copy + Ord + Sub<Output = T>> RangeSet<T> {
     ///
     /// # Panics
     ///
-    /// Panics if the shift causes an underflow.
+    /// Panics if the shift causes an underflow. See [`CheckedShift`] and [`SaturatingShift`]
+    /// for non-panicking alternatives.
     pub fn shift_left(&mut self, offset: &T) {
         self.ranges.iter_mut().for_each(|range| {
             range.start = range.start - *offset;
@@ -221,7 +398,8 @@ impl<T: Copy + Ord + Add<Output = T>> RangeSet<T> {
     ///
     /// # Panics
     ///
-    /// Panics if the the shift causes an overflow.
+    /// Panics if the the shift causes an overflow. See [`CheckedShift`] and [`SaturatingShift`]
+    /// for non-panicking alternatives.
     pub fn shift_right(&mut self, offset: &T) {
         self.ranges.iter_mut().for_each(|range| {
             range.start = range.start + *offset;
@@ -245,6 +423,53 @@ where
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Returns the number of values spanned by the set, from its minimum to its maximum
+    /// value inclusive, or `0` if the set is empty.
+    ///
+    /// This is the length of [`RangeSet::bounds`], which is at least [`RangeSet::len`].
+    #[must_use]
+    pub fn span_len(&self) -> usize {
+        self.bounds().map_or(0, |bounds| bounds.len())
+    }
+
+    /// Returns an iterator over the set in chunks of at most `size` values, in order.
+    ///
+    /// Every chunk except the last contains exactly `size` values, so the chunks of a set
+    /// are deterministic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use utils_ranges::RangeSet;
+    ///
+    /// let set = RangeSet::from([(0..3), (5..10)]);
+    /// let chunks: Vec<_> = set.chunks(4).collect();
+    ///
+    /// assert_eq!(
+    ///     chunks,
+    ///     vec![
+    ///         RangeSet::from([(0..3), (5..6)]),
+    ///         RangeSet::from(6..10),
+    ///     ]
+    /// );
+    /// ```
+    pub fn chunks(&self, size: usize) -> RangeSetChunks<'_, T>
+    where
+        T: Step,
+    {
+        assert!(size != 0, "chunk size must be non-zero");
+
+        RangeSetChunks {
+            iter: self.ranges.iter(),
+            current: None,
+            size,
+        }
+    }
 }
 
 impl<T: Copy + Ord> TryFrom<RangeSet<T>> for Range<T> {
@@ -378,12 +603,102 @@ where
     }
 }
 
+/// An iterator over a set in chunks of at most a given number of values.
+///
+/// Returned by [`RangeSet::chunks`].
+pub struct RangeSetChunks<'a, T> {
+    iter: std::slice::Iter<'a, Range<T>>,
+    current: Option<Range<T>>,
+    size: usize,
+}
+
+impl<'a, T> Iterator for RangeSetChunks<'a, T>
+where
+    T: Copy + Ord + Step,
+    Range<T>: ExactSizeIterator<Item = T>,
+{
+    type Item = RangeSet<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut ranges = Vec::new();
+        let mut remaining = self.size;
+
+        while remaining > 0 {
+            let Some(range) = self.current.take().or_else(|| self.iter.next().cloned()) else {
+                break;
+            };
+
+            let len = range.len();
+            if len <= remaining {
+                remaining -= len;
+                ranges.push(range);
+            } else {
+                let mid = Step::forward(range.start, remaining).expect("value is in the range");
+                ranges.push(range.start..mid);
+                self.current = Some(mid..range.end);
+                remaining = 0;
+            }
+        }
+
+        (!ranges.is_empty()).then_some(RangeSet { ranges })
+    }
+}
+
 /// A type which has a corresponding range set.
+///
+/// The trait is object safe, so values of different types can be collected as
+/// `Box<dyn ToRangeSet<T>>`. It is implemented for references and smart pointers to
+/// implementors, and for slices of implementors as the union of their range sets.
 pub trait ToRangeSet<T: Copy + Ord> {
     /// Returns a corresponding range set.
     fn to_range_set(&self) -> RangeSet<T>;
 }
 
+impl<T: Copy + Ord, U: ToRangeSet<T> + ?Sized> ToRangeSet<T> for &U {
+    fn to_range_set(&self) -> RangeSet<T> {
+        (**self).to_range_set()
+    }
+}
+
+impl<T: Copy + Ord, U: ToRangeSet<T> + ?Sized> ToRangeSet<T> for &mut U {
+    fn to_range_set(&self) -> RangeSet<T> {
+        (**self).to_range_set()
+    }
+}
+
+impl<T: Copy + Ord, U: ToRangeSet<T> + ?Sized> ToRangeSet<T> for Box<U> {
+    fn to_range_set(&self) -> RangeSet<T> {
+        (**self).to_range_set()
+    }
+}
+
+impl<T: Copy + Ord, U: ToRangeSet<T> + ?Sized> ToRangeSet<T> for Rc<U> {
+    fn to_range_set(&self) -> RangeSet<T> {
+        (**self).to_range_set()
+    }
+}
+
+impl<T: Copy + Ord, U: ToRangeSet<T> + ?Sized> ToRangeSet<T> for Arc<U> {
+    fn to_range_set(&self) -> RangeSet<T> {
+        (**self).to_range_set()
+    }
+}
+
+impl<T: Copy + Ord, U: ToRangeSet<T>> ToRangeSet<T> for [U] {
+    fn to_range_set(&self) -> RangeSet<T> {
+        self.iter()
+            .flat_map(|item| item.to_range_set().ranges)
+            .collect::<RangeSetBuilder<T>>()
+            .build()
+    }
+}
+
+impl<T: Copy + Ord, U: ToRangeSet<T>> ToRangeSet<T> for Vec<U> {
+    fn to_range_set(&self) -> RangeSet<T> {
+        self.as_slice().to_range_set()
+    }
+}
+
 impl<T: Copy + Ord> ToRangeSet<T> for RangeSet<T> {
     fn to_range_set(&self) -> RangeSet<T> {
         self.clone()
@@ -444,14 +759,27 @@ impl<T: Copy + Ord> Disjoint<Range<T>> for Range<T> {
 }
 
 impl<T: Copy + Ord> Disjoint<RangeSet<T>> for Range<T> {
+    /// Runs in `O(log n)` time, where `n` is the number of ranges of `other`.
     fn is_disjoint(&self, other: &RangeSet<T>) -> bool {
-        other.ranges.iter().all(|range| self.is_disjoint(range))
+        // The first range which ends after the start of `self` is the only one which may
+        // intersect it without starting after its end.
+        let idx = other
+            .ranges
+            .partition_point(|range| range.end <= self.start);
+        other
+            .ranges
+            .get(idx)
+            .is_none_or(|range| self.is_disjoint(range))
     }
 }
 
 impl<T: Copy + Ord> Disjoint<RangeSet<T>> for RangeSet<T> {
+    /// Runs in `O(n + m)` time, where `n` and `m` are the number of ranges of the sets.
     fn is_disjoint(&self, other: &RangeSet<T>) -> bool {
-        self.ranges.iter().all(|range| range.is_disjoint(other))
+        self.debug_assert_invariants();
+        other.debug_assert_invariants();
+
+        self.iter_intersection(other).next().is_none()
     }
 }
 
@@ -476,6 +804,16 @@ mod tests {
     use super::*;
     use rstest::*;
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "range set invariants violated")]
+    fn test_debug_assert_invariants() {
+        let invalid = RangeSet {
+            ranges: vec![5..10, 0..3],
+        };
+        let _ = invalid.union(&RangeSet::from(20..30));
+    }
+
     #[test]
     fn test_range_disjoint() {
         let a = 10..20;
@@ -551,6 +889,45 @@ mod tests {
         assert!(a.iter().chain(b.iter()).eq(set.iter()));
     }
 
+    #[rstest]
+    #[case(1)]
+    #[case(3)]
+    #[case(5)]
+    #[case(100)]
+    fn test_range_set_chunks(#[case] size: usize) {
+        let set = RangeSet::from([(0..5), (7..10), (12..13), (20..27)]);
+        let chunks: Vec<_> = set.chunks(size).collect();
+
+        let (last, rest) = chunks.split_last().unwrap();
+        assert!(rest.iter().all(|chunk| chunk.len() == size));
+        assert!(last.len() <= size);
+        chunks.iter().for_each(assert_invariants);
+        assert!(chunks.iter().flat_map(RangeSet::iter).eq(set.iter()));
+    }
+
+    #[test]
+    fn test_range_set_chunks_empty() {
+        assert_eq!(RangeSet::<usize>::default().chunks(4).count(), 0);
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(3)]
+    #[case(5)]
+    #[case(7)]
+    #[case(10)]
+    #[case(20)]
+    fn test_range_set_split_at(#[case] at: usize) {
+        let set = RangeSet::from([(0..5), (7..10)]);
+        let (a, b) = set.split_at(at);
+
+        assert_invariants(&a);
+        assert_invariants(&b);
+        assert!(a.iter().all(|value| value < at));
+        assert!(b.iter().all(|value| value >= at));
+        assert!(a.iter().chain(b.iter()).eq(set.iter()));
+    }
+
     #[test]
     #[should_panic = "`at` is in the set"]
     fn test_range_set_split_off_panic_not_in_set() {
@@ -580,4 +957,51 @@ mod tests {
         assert_eq!(RangeSet::from([0..2]).max(), Some(1));
         assert_eq!(RangeSet::from([(0..5), (6..10)]).max(), Some(9));
     }
+
+    #[test]
+    fn test_range_set_bounds() {
+        let empty = RangeSet::<usize>::default();
+        assert_eq!(empty.bounds(), None);
+        assert_eq!(empty.span_len(), 0);
+
+        let set = RangeSet::from([(3..5), (8..10), (20..21)]);
+        assert_eq!(set.bounds(), Some(3..21));
+        assert_eq!(set.span_len(), 18);
+        assert_eq!(set.span_len(), set.max().unwrap() - set.min().unwrap() + 1);
+    }
+
+    #[test]
+    fn test_range_set_capacity() {
+        let mut set = RangeSet::<usize>::with_capacity(8);
+        assert!(set.capacity() >= 8);
+        set.shrink_to_fit();
+        assert_eq!(set.capacity(), 0);
+
+        // Sparse outputs do not hold on to their preallocated capacity.
+        let a = RangeSet::from((0..100).map(|i| i * 4..i * 4 + 1).collect::<Vec<_>>());
+        let b = RangeSet::from((0..100).map(|i| i * 4 + 2..i * 4 + 3).collect::<Vec<_>>());
+        let intersection = a.intersection(&b);
+        assert!(intersection.is_empty());
+        assert_eq!(intersection.capacity(), 0);
+
+        let union = a.union(&b);
+        assert_eq!(union.len_ranges(), 200);
+        assert_eq!(union.capacity(), 200);
+    }
+
+    #[test]
+    fn test_to_range_set_trait_objects() {
+        let items: Vec<Box<dyn ToRangeSet<usize>>> = vec![
+            Box::new(0..5),
+            Box::new(RangeSet::from([(3..8), (20..25)])),
+            Box::new(Arc::new(10..12)),
+            Box::new(&(30..31)),
+        ];
+
+        assert_eq!(
+            items.to_range_set(),
+            RangeSet::from([(0..8), (10..12), (20..25), (30..31)])
+        );
+        assert!(Vec::<Range<usize>>::new().to_range_set().is_empty());
+    }
 }
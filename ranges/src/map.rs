@@ -0,0 +1,399 @@
+use std::ops::Range;
+
+use crate::{Difference, Intersection, RangeSet, RangeSetBuilder, UnionMut};
+
+/// A collection of range sets over the same source, keyed by a label such as a field name.
+///
+/// The ranges of all sets are stored contiguously, ordered by key, which keeps large
+/// collections of small sets compact and makes iteration and serialization deterministic
+/// regardless of insertion order.
+///
+/// # Examples
+///
+/// ```
+/// use utils_ranges::*;
+///
+/// let mut map = RangeSetMap::new();
+/// map.insert("method", 0..4);
+/// map.insert("host", 22..35);
+/// map.insert("path", RangeSet::from([(5..10), (11..16)]));
+///
+/// assert_eq!(map.get(&"path"), Some(RangeSet::from([(5..10), (11..16)])));
+/// assert_eq!(map.union_all(), RangeSet::from([(0..4), (5..10), (11..16), (22..35)]));
+/// assert_eq!(map.union_of(&["method", "host"]), RangeSet::from([(0..4), (22..35)]));
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        bound = "for<'a> K: serde::Serialize + serde::de::Deserialize<'a> + Clone + Ord, \
+                 for<'a> T: serde::Serialize + serde::de::Deserialize<'a> + Copy + Ord",
+        from = "Vec<(K, RangeSet<T>)>",
+        into = "Vec<(K, RangeSet<T>)>"
+    )
+)]
+pub struct RangeSetMap<K, T> {
+    /// The keys, sorted.
+    keys: Vec<K>,
+    /// The end of the ranges of each key in `ranges`.
+    ///
+    /// The ranges of the key at index `i` are `ranges[ends[i - 1]..ends[i]]`.
+    ends: Vec<usize>,
+    /// The ranges of all sets.
+    ///
+    /// The ranges of each set *MUST* satisfy the invariants of `RangeSet`.
+    ranges: Vec<Range<T>>,
+}
+
+impl<K, T> Default for RangeSetMap<K, T> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            ends: Vec::new(),
+            ranges: Vec::new(),
+        }
+    }
+}
+
+impl<K, T> RangeSetMap<K, T> {
+    /// Returns the number of sets in the map.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if the map contains no sets.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns the keys of the map, in order.
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.keys.iter()
+    }
+
+    /// Returns an iterator over the keys and ranges of each set, ordered by key.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &[Range<T>])> {
+        self.keys
+            .iter()
+            .enumerate()
+            .map(|(idx, key)| (key, &self.ranges[self.bounds(idx)]))
+    }
+
+    fn bounds(&self, idx: usize) -> Range<usize> {
+        let start = if idx == 0 { 0 } else { self.ends[idx - 1] };
+
+        start..self.ends[idx]
+    }
+}
+
+impl<K: Ord, T: Copy + Ord> RangeSetMap<K, T> {
+    /// Returns a new, empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the map contains a set for the key.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.keys.binary_search(key).is_ok()
+    }
+
+    /// Returns the ranges of the set for the key.
+    pub fn get_ranges(&self, key: &K) -> Option<&[Range<T>]> {
+        let idx = self.keys.binary_search(key).ok()?;
+
+        Some(&self.ranges[self.bounds(idx)])
+    }
+
+    /// Returns a copy of the set for the key.
+    pub fn get(&self, key: &K) -> Option<RangeSet<T>> {
+        self.get_ranges(key).map(|ranges| RangeSet {
+            ranges: ranges.to_vec(),
+        })
+    }
+
+    /// Inserts a set for the key, returning the set it replaces.
+    pub fn insert(&mut self, key: K, set: impl Into<RangeSet<T>>) -> Option<RangeSet<T>> {
+        let set = set.into();
+
+        match self.keys.binary_search(&key) {
+            Ok(idx) => {
+                let old = self.splice(idx, set.ranges);
+
+                Some(RangeSet { ranges: old })
+            }
+            Err(idx) => {
+                let start = if idx == 0 { 0 } else { self.ends[idx - 1] };
+                self.keys.insert(idx, key);
+                self.ends.insert(idx, start);
+                self.splice(idx, set.ranges);
+
+                None
+            }
+        }
+    }
+
+    /// Adds the ranges of `set` to the set for the key, inserting it if it is not present.
+    pub fn union_mut(&mut self, key: K, set: impl Into<RangeSet<T>>) {
+        let mut set = set.into();
+        if let Some(current) = self.get(&key) {
+            set.union_mut(&current);
+        }
+
+        self.insert(key, set);
+    }
+
+    /// Removes the set for the key, returning it if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<RangeSet<T>> {
+        let idx = self.keys.binary_search(key).ok()?;
+        let ranges = self.splice(idx, Vec::new());
+
+        self.keys.remove(idx);
+        self.ends.remove(idx);
+
+        Some(RangeSet { ranges })
+    }
+
+    /// Returns the union of all sets in the map.
+    pub fn union_all(&self) -> RangeSet<T> {
+        RangeSetBuilder::from(self.ranges.clone()).build()
+    }
+
+    /// Returns the union of the sets for the given keys.
+    ///
+    /// Keys which are not present in the map are ignored.
+    pub fn union_of<'a>(&self, keys: impl IntoIterator<Item = &'a K>) -> RangeSet<T>
+    where
+        K: 'a,
+    {
+        keys.into_iter()
+            .filter_map(|key| self.get_ranges(key))
+            .flatten()
+            .cloned()
+            .collect::<RangeSetBuilder<T>>()
+            .build()
+    }
+
+    /// Returns the keys whose sets contain the given value, in order.
+    pub fn keys_containing(&self, value: T) -> impl Iterator<Item = &K> {
+        self.iter().filter_map(move |(key, ranges)| {
+            ranges
+                .iter()
+                .any(|range| range.contains(&value))
+                .then_some(key)
+        })
+    }
+
+    /// Returns the values of a set which are not in any set of the map.
+    pub fn unlabeled(&self, set: &RangeSet<T>) -> RangeSet<T> {
+        set.difference(&self.union_all())
+    }
+
+    /// Returns the map with each set intersected with `set`, keeping the keys whose sets are
+    /// not empty.
+    ///
+    /// This carries the keys through an intersection, e.g. to label the committed parts of
+    /// each field.
+    pub fn restrict(&self, set: &RangeSet<T>) -> Self
+    where
+        K: Clone,
+    {
+        self.map_sets(|ranges| ranges.intersection(set))
+    }
+
+    /// Returns the map with `set` removed from each set, keeping the keys whose sets are not
+    /// empty.
+    pub fn exclude(&self, set: &RangeSet<T>) -> Self
+    where
+        K: Clone,
+    {
+        self.map_sets(|ranges| ranges.difference(set))
+    }
+
+    /// Applies a set operation to each set of the map, keeping the keys whose sets are not
+    /// empty.
+    fn map_sets(&self, f: impl Fn(RangeSet<T>) -> RangeSet<T>) -> Self
+    where
+        K: Clone,
+    {
+        let mut map = Self::new();
+        for (key, ranges) in self.iter() {
+            let set = f(RangeSet {
+                ranges: ranges.to_vec(),
+            });
+            if !set.ranges.is_empty() {
+                // The keys are visited in order, so they remain sorted.
+                map.keys.push(key.clone());
+                map.ranges.extend(set.ranges);
+                map.ends.push(map.ranges.len());
+            }
+        }
+
+        map
+    }
+
+    /// Replaces the ranges of the key at `idx`, returning the old ranges.
+    fn splice(&mut self, idx: usize, ranges: Vec<Range<T>>) -> Vec<Range<T>> {
+        let bounds = self.bounds(idx);
+        let old_len = bounds.len();
+        let new_len = ranges.len();
+
+        let old = self.ranges.splice(bounds, ranges).collect();
+
+        self.ends[idx..].iter_mut().for_each(|end| {
+            *end = *end + new_len - old_len;
+        });
+
+        old
+    }
+}
+
+impl<K: Ord, T: Copy + Ord> From<Vec<(K, RangeSet<T>)>> for RangeSetMap<K, T> {
+    /// Creates a map from key and set pairs, merging the sets of duplicate keys.
+    fn from(entries: Vec<(K, RangeSet<T>)>) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+impl<K: Clone, T: Copy> From<RangeSetMap<K, T>> for Vec<(K, RangeSet<T>)> {
+    fn from(map: RangeSetMap<K, T>) -> Self {
+        map.iter()
+            .map(|(key, ranges)| {
+                (
+                    key.clone(),
+                    RangeSet {
+                        ranges: ranges.to_vec(),
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+impl<K: Ord, T: Copy + Ord, S: Into<RangeSet<T>>> FromIterator<(K, S)> for RangeSetMap<K, T> {
+    fn from_iter<I: IntoIterator<Item = (K, S)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (key, set) in iter {
+            map.union_mut(key, set);
+        }
+
+        map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{assert_invariants, Union};
+
+    #[test]
+    fn test_range_set_map_insert_get() {
+        let mut map = RangeSetMap::new();
+        assert!(map.is_empty());
+
+        assert_eq!(map.insert("b", 10..20), None);
+        assert_eq!(map.insert("a", RangeSet::from([(0..2), (4..6)])), None);
+        assert_eq!(map.insert("c", 30..40), None);
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(map.get_ranges(&"a"), Some([(0..2), (4..6)].as_slice()));
+        assert_eq!(map.get(&"b"), Some(RangeSet::from(10..20)));
+        assert_eq!(map.get(&"d"), None);
+
+        assert_eq!(
+            map.insert("a", RangeSet::from([(1..3), (5..7), (9..10)])),
+            Some(RangeSet::from([(0..2), (4..6)]))
+        );
+        assert_eq!(
+            map.get(&"a"),
+            Some(RangeSet::from([(1..3), (5..7), (9..10)]))
+        );
+        assert_eq!(map.get(&"b"), Some(RangeSet::from(10..20)));
+        assert_eq!(map.get(&"c"), Some(RangeSet::from(30..40)));
+
+        assert_eq!(map.remove(&"b"), Some(RangeSet::from(10..20)));
+        assert_eq!(map.remove(&"b"), None);
+        assert!(!map.contains_key(&"b"));
+        assert_eq!(map.get(&"c"), Some(RangeSet::from(30..40)));
+    }
+
+    #[test]
+    fn test_range_set_map_union() {
+        let mut map = RangeSetMap::new();
+        map.union_mut(0, 0..5);
+        map.union_mut(0, 5..10);
+        map.union_mut(1, RangeSet::from([(8..12), (20..25)]));
+        map.union_mut(2, 40..45);
+
+        assert_eq!(map.get(&0), Some(RangeSet::from(0..10)));
+
+        let all = map.union_all();
+        assert_eq!(all, RangeSet::from([(0..12), (20..25), (40..45)]));
+        assert_invariants(&all);
+
+        let expected = map.iter().fold(RangeSet::default(), |acc, (_, ranges)| {
+            acc.union(&RangeSet::from(ranges))
+        });
+        assert_eq!(all, expected);
+
+        assert_eq!(
+            map.union_of(&[2, 1, 7]),
+            RangeSet::from([(8..12), (20..25), (40..45)])
+        );
+        assert_eq!(map.union_of(&[]), RangeSet::default());
+    }
+
+    #[test]
+    fn test_range_set_map_order_independent() {
+        let a: RangeSetMap<_, usize> = [("x", 0..1), ("y", 2..3), ("z", 4..5)]
+            .into_iter()
+            .collect();
+        let b: RangeSetMap<_, usize> = [("z", 4..5), ("x", 0..1), ("y", 2..3)]
+            .into_iter()
+            .collect();
+
+        assert_eq!(a, b);
+        assert_eq!(
+            Vec::from(a),
+            vec![
+                ("x", RangeSet::from(0..1)),
+                ("y", RangeSet::from(2..3)),
+                ("z", RangeSet::from(4..5))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_range_set_map_set_ops() {
+        let map: RangeSetMap<_, usize> = [
+            ("header:authorization", RangeSet::from(10..30)),
+            ("header:host", RangeSet::from(40..50)),
+            ("body", RangeSet::from([(60..70), (80..90)])),
+        ]
+        .into_iter()
+        .collect();
+
+        let commit = RangeSet::from([(0..15), (40..50), (85..100)]);
+        assert_eq!(
+            Vec::from(map.restrict(&commit)),
+            vec![
+                ("body", RangeSet::from(85..90)),
+                ("header:authorization", RangeSet::from(10..15)),
+                ("header:host", RangeSet::from(40..50)),
+            ]
+        );
+
+        let excluded = map.exclude(&RangeSet::from(0..50));
+        assert_eq!(excluded.keys().copied().collect::<Vec<_>>(), ["body"]);
+        assert_eq!(excluded.get(&"body"), map.get(&"body"));
+
+        assert_eq!(map.unlabeled(&commit), RangeSet::from([(0..10), (90..100)]));
+        assert_eq!(
+            map.keys_containing(45).copied().collect::<Vec<_>>(),
+            ["header:host"]
+        );
+        assert_eq!(map.keys_containing(30).count(), 0);
+    }
+}
@@ -0,0 +1,177 @@
+use std::ops::{Add, Range};
+
+use crate::RangeSet;
+
+/// A partition of a range into contiguous segments, such as the TLS records of a transcript.
+///
+/// Range sets can be split into the sub-ranges of each segment they touch, which is useful
+/// for commitment schemes which operate per segment.
+///
+/// # Examples
+///
+/// ```
+/// use utils_ranges::*;
+///
+/// // Records of 10, 5 and 10 bytes.
+/// let records = Partition::from_lens(0, [10, 5, 10]);
+///
+/// let field = RangeSet::from([(8..12), (20..22)]);
+///
+/// assert_eq!(records.touched(&field), vec![0, 1, 2]);
+/// assert_eq!(
+///     records.split(&field),
+///     vec![
+///         (0, RangeSet::from(8..10)),
+///         (1, RangeSet::from(10..12)),
+///         (2, RangeSet::from(20..22)),
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Partition<T> {
+    /// The boundaries of the segments, strictly increasing.
+    ///
+    /// Segment `i` is `bounds[i]..bounds[i + 1]`.
+    bounds: Vec<T>,
+}
+
+impl<T: Copy + Ord> Partition<T> {
+    /// Creates a new partition from the boundaries of its segments.
+    ///
+    /// The first boundary is the start of the first segment, and each following boundary is
+    /// the end of a segment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the boundaries are not strictly increasing.
+    pub fn new(bounds: Vec<T>) -> Self {
+        assert!(
+            bounds.windows(2).all(|w| w[0] < w[1]),
+            "boundaries are strictly increasing"
+        );
+
+        Self { bounds }
+    }
+
+    /// Creates a new partition from the start of the first segment and the length of each
+    /// segment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any length is zero.
+    pub fn from_lens(start: T, lens: impl IntoIterator<Item = T>) -> Self
+    where
+        T: Add<Output = T>,
+    {
+        let mut bounds = vec![start];
+        for len in lens {
+            let end = *bounds.last().expect("bounds are not empty") + len;
+            bounds.push(end);
+        }
+
+        Self::new(bounds)
+    }
+
+    /// Returns the number of segments.
+    pub fn len(&self) -> usize {
+        self.bounds.len().saturating_sub(1)
+    }
+
+    /// Returns `true` if there are no segments.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the segment at the given index.
+    pub fn segment(&self, index: usize) -> Option<Range<T>> {
+        Some(*self.bounds.get(index)?..*self.bounds.get(index + 1)?)
+    }
+
+    /// Returns an iterator over the segments, in order.
+    pub fn iter(&self) -> impl Iterator<Item = Range<T>> + '_ {
+        self.bounds.windows(2).map(|w| w[0]..w[1])
+    }
+
+    /// Returns the index of the segment containing the given value.
+    pub fn segment_of(&self, value: T) -> Option<usize> {
+        let idx = self.bounds.partition_point(|bound| *bound <= value);
+        (idx > 0 && idx < self.bounds.len()).then(|| idx - 1)
+    }
+
+    /// Splits a range set into the sub-ranges of each segment it touches, ordered by segment.
+    ///
+    /// Values outside of the partition are not included.
+    pub fn split(&self, ranges: &RangeSet<T>) -> Vec<(usize, RangeSet<T>)> {
+        let mut split: Vec<(usize, Vec<Range<T>>)> = Vec::new();
+
+        for range in &ranges.ranges {
+            // The end of the first segment which ends after the start of the range.
+            let first = self
+                .bounds
+                .partition_point(|bound| *bound <= range.start)
+                .max(1);
+
+            for idx in first..self.bounds.len() {
+                let segment = self.bounds[idx - 1]..self.bounds[idx];
+                if segment.start >= range.end {
+                    break;
+                }
+
+                let sub_range = range.start.max(segment.start)..range.end.min(segment.end);
+                match split.last_mut() {
+                    Some((last, ranges)) if *last == idx - 1 => ranges.push(sub_range),
+                    _ => split.push((idx - 1, vec![sub_range])),
+                }
+            }
+        }
+
+        split
+            .into_iter()
+            .map(|(idx, ranges)| (idx, RangeSet::from(ranges)))
+            .collect()
+    }
+
+    /// Returns the indices of the segments touched by a range set, in order.
+    pub fn touched(&self, ranges: &RangeSet<T>) -> Vec<usize> {
+        self.split(ranges).into_iter().map(|(idx, _)| idx).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_split() {
+        let partition = Partition::new(vec![10, 20, 30, 40]);
+
+        assert_eq!(partition.len(), 3);
+        assert_eq!(partition.segment(1), Some(20..30));
+        assert_eq!(partition.segment(3), None);
+        assert_eq!(partition.segment_of(9), None);
+        assert_eq!(partition.segment_of(20), Some(1));
+        assert_eq!(partition.segment_of(40), None);
+
+        let ranges = RangeSet::from([(0..12), (15..16), (18..35), (38..50)]);
+        assert_eq!(
+            partition.split(&ranges),
+            vec![
+                (0, RangeSet::from([(10..12), (15..16), (18..20)])),
+                (1, RangeSet::from(20..30)),
+                (2, RangeSet::from([(30..35), (38..40)])),
+            ]
+        );
+
+        assert!(partition
+            .split(&RangeSet::from([(0..10), (40..50)]))
+            .is_empty());
+        assert_eq!(partition.touched(&RangeSet::from(25..26)), vec![1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_partition_not_increasing() {
+        Partition::new(vec![0, 10, 10]);
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+
+use crate::{Intersection, RangeSet, ToRangeSet, Union};
+
+/// Checks that a set of revealed values includes none of the sensitive values.
+///
+/// This is intended as a final check before revealing data, with the sensitive values
+/// collected from every detector or policy, so that a mistake in how the reveal set was
+/// built can not leak them.
+///
+/// # Arguments
+///
+/// * `reveal` - The values to reveal.
+/// * `sensitive` - The sets of sensitive values, such as the spans of tokens or passwords.
+///
+/// # Example
+///
+/// ```
+/// use utils_ranges::{check_redaction, RangeSet};
+///
+/// let reveal = RangeSet::from([0..10, 20..30]);
+///
+/// assert!(check_redaction(&reveal, [10..20]).is_ok());
+///
+/// let err = check_redaction(&reveal, [10..20, 25..40]).unwrap_err();
+/// assert_eq!(err.leaks()[0].index, 1);
+/// assert_eq!(err.ranges(), RangeSet::from(25..30));
+/// ```
+pub fn check_redaction<T, S>(
+    reveal: &RangeSet<T>,
+    sensitive: impl IntoIterator<Item = S>,
+) -> Result<(), RedactionError<T>>
+where
+    T: Copy + Ord,
+    S: ToRangeSet<T>,
+{
+    let leaks: Vec<_> = sensitive
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, sensitive)| {
+            let ranges = reveal.intersection(&sensitive.to_range_set());
+            (ranges.len_ranges() > 0).then_some(Leak { index, ranges })
+        })
+        .collect();
+
+    if leaks.is_empty() {
+        Ok(())
+    } else {
+        Err(RedactionError { leaks })
+    }
+}
+
+/// A set of sensitive values which is included in a reveal set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leak<T> {
+    /// The index of the set of sensitive values, in the order they were provided.
+    pub index: usize,
+    /// The sensitive values which are revealed.
+    pub ranges: RangeSet<T>,
+}
+
+/// An error returned by [`check_redaction`] when sensitive values would be revealed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedactionError<T> {
+    leaks: Vec<Leak<T>>,
+}
+
+impl<T: Copy + Ord> RedactionError<T> {
+    /// Returns the sets of sensitive values which are revealed, in the order they were
+    /// provided.
+    pub fn leaks(&self) -> &[Leak<T>] {
+        &self.leaks
+    }
+
+    /// Returns all the sensitive values which are revealed.
+    pub fn ranges(&self) -> RangeSet<T> {
+        self.leaks.iter().fold(RangeSet::default(), |ranges, leak| {
+            ranges.union(&leak.ranges)
+        })
+    }
+}
+
+impl<T: Copy + Ord + fmt::Debug> fmt::Display for RedactionError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sensitive values are revealed: {:?}", self.ranges())
+    }
+}
+
+impl<T: Copy + Ord + fmt::Debug> std::error::Error for RedactionError<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_redaction() {
+        let reveal = RangeSet::from([0..10, 20..30, 40..50]);
+
+        assert_eq!(
+            check_redaction(&reveal, Vec::<RangeSet<usize>>::new()),
+            Ok(())
+        );
+        assert_eq!(
+            check_redaction(
+                &reveal,
+                [RangeSet::from([10..20, 30..40]), RangeSet::from(50..60)]
+            ),
+            Ok(())
+        );
+
+        let err = check_redaction(&reveal, [5..25, 12..18, 28..45]).unwrap_err();
+        assert_eq!(
+            err.leaks(),
+            [
+                Leak {
+                    index: 0,
+                    ranges: RangeSet::from([5..10, 20..25])
+                },
+                Leak {
+                    index: 2,
+                    ranges: RangeSet::from([28..30, 40..45])
+                },
+            ]
+        );
+        assert_eq!(
+            err.ranges(),
+            RangeSet::from([5..10, 20..25, 28..30, 40..45])
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use std::{fmt, ops::Range};
+
+use crate::RangeSet;
+
+/// An error returned when shifting a range would overflow or underflow its type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShiftError {
+    /// Shifting to the right would exceed the maximum value.
+    Overflow,
+    /// Shifting to the left would go below the minimum value.
+    Underflow,
+}
+
+impl fmt::Display for ShiftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShiftError::Overflow => write!(f, "range shift overflowed"),
+            ShiftError::Underflow => write!(f, "range shift underflowed"),
+        }
+    }
+}
+
+impl std::error::Error for ShiftError {}
+
+pub trait CheckedShift<T>: Sized {
+    /// Returns `self` shifted to the left by `offset`, or an error if it would underflow.
+    fn checked_shift_left(&self, offset: &T) -> Result<Self, ShiftError>;
+
+    /// Returns `self` shifted to the right by `offset`, or an error if it would overflow.
+    fn checked_shift_right(&self, offset: &T) -> Result<Self, ShiftError>;
+}
+
+pub trait SaturatingShift<T> {
+    /// Returns `self` shifted to the left by `offset`, clamping values at the minimum of `T`.
+    #[must_use]
+    fn saturating_shift_left(&self, offset: &T) -> Self;
+
+    /// Returns `self` shifted to the right by `offset`, clamping values at the maximum of `T`.
+    #[must_use]
+    fn saturating_shift_right(&self, offset: &T) -> Self;
+}
+
+macro_rules! impl_shift {
+    ($($ty:ty),+) => {
+        $(
+            impl CheckedShift<$ty> for Range<$ty> {
+                fn checked_shift_left(&self, offset: &$ty) -> Result<Self, ShiftError> {
+                    let start = self.start.checked_sub(*offset).ok_or(ShiftError::Underflow)?;
+                    let end = self.end.checked_sub(*offset).ok_or(ShiftError::Underflow)?;
+
+                    Ok(start..end)
+                }
+
+                fn checked_shift_right(&self, offset: &$ty) -> Result<Self, ShiftError> {
+                    let start = self.start.checked_add(*offset).ok_or(ShiftError::Overflow)?;
+                    let end = self.end.checked_add(*offset).ok_or(ShiftError::Overflow)?;
+
+                    Ok(start..end)
+                }
+            }
+
+            impl SaturatingShift<$ty> for Range<$ty> {
+                fn saturating_shift_left(&self, offset: &$ty) -> Self {
+                    self.start.saturating_sub(*offset)..self.end.saturating_sub(*offset)
+                }
+
+                fn saturating_shift_right(&self, offset: &$ty) -> Self {
+                    self.start.saturating_add(*offset)..self.end.saturating_add(*offset)
+                }
+            }
+
+            impl CheckedShift<$ty> for RangeSet<$ty> {
+                fn checked_shift_left(&self, offset: &$ty) -> Result<Self, ShiftError> {
+                    // Shifting preserves the order and spacing of the ranges, so the invariants
+                    // hold if no range underflows.
+                    let ranges = self
+                        .ranges
+                        .iter()
+                        .map(|range| range.checked_shift_left(offset))
+                        .collect::<Result<_, _>>()?;
+
+                    Ok(Self { ranges })
+                }
+
+                fn checked_shift_right(&self, offset: &$ty) -> Result<Self, ShiftError> {
+                    let ranges = self
+                        .ranges
+                        .iter()
+                        .map(|range| range.checked_shift_right(offset))
+                        .collect::<Result<_, _>>()?;
+
+                    Ok(Self { ranges })
+                }
+            }
+
+            impl SaturatingShift<$ty> for RangeSet<$ty> {
+                fn saturating_shift_left(&self, offset: &$ty) -> Self {
+                    // Clamped ranges may become empty or touch, so the set is rebuilt.
+                    let ranges = self
+                        .ranges
+                        .iter()
+                        .map(|range| range.saturating_shift_left(offset))
+                        .collect::<Vec<_>>();
+
+                    Self::from(ranges)
+                }
+
+                fn saturating_shift_right(&self, offset: &$ty) -> Self {
+                    let ranges = self
+                        .ranges
+                        .iter()
+                        .map(|range| range.saturating_shift_right(offset))
+                        .collect::<Vec<_>>();
+
+                    Self::from(ranges)
+                }
+            }
+        )*
+    };
+}
+
+impl_shift!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::assert_invariants;
+
+    #[test]
+    fn test_range_checked_shift() {
+        assert_eq!((1..5u8).checked_shift_left(&1), Ok(0..4));
+        assert_eq!((1..5u8).checked_shift_left(&2), Err(ShiftError::Underflow));
+        assert_eq!((1..5u8).checked_shift_right(&250), Ok(251..255));
+        assert_eq!(
+            (1..5u8).checked_shift_right(&251),
+            Err(ShiftError::Overflow)
+        );
+        assert_eq!(
+            (0..10usize).checked_shift_right(&usize::MAX),
+            Err(ShiftError::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_range_saturating_shift() {
+        assert_eq!((1..5u8).saturating_shift_left(&2), 0..3);
+        assert_eq!((1..5u8).saturating_shift_right(&252), 253..255);
+        assert!((250..255u8).saturating_shift_right(&10).is_empty());
+    }
+
+    #[test]
+    fn test_set_checked_shift() {
+        let set = RangeSet::from([(1..5u8), (10..20)]);
+
+        assert_eq!(
+            set.checked_shift_right(&10),
+            Ok(RangeSet::from([(11..15), (20..30)]))
+        );
+        assert_eq!(
+            set.checked_shift_left(&1),
+            Ok(RangeSet::from([(0..4), (9..19)]))
+        );
+        assert_eq!(set.checked_shift_left(&2), Err(ShiftError::Underflow));
+        assert_eq!(set.checked_shift_right(&240), Err(ShiftError::Overflow));
+        assert_eq!(
+            RangeSet::<u8>::default().checked_shift_right(&u8::MAX),
+            Ok(RangeSet::default())
+        );
+    }
+
+    #[test]
+    fn test_set_saturating_shift() {
+        let set = RangeSet::from([(1..5u8), (10..20), (30..40)]);
+
+        let left = set.saturating_shift_left(&15);
+        assert_eq!(left, RangeSet::from([(0..5), (15..25)]));
+        assert_invariants(&left);
+
+        let right = set.saturating_shift_right(&230);
+        assert_eq!(right, RangeSet::from([(231..235), (240..250)]));
+        assert_invariants(&right);
+
+        let collapsed = set.saturating_shift_right(&250);
+        assert_eq!(collapsed, 251..255);
+        assert_invariants(&collapsed);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::range::{Range, RangeSet};
+use crate::{Range, RangeSet};
 
 pub trait Subset<Rhs> {
     /// Returns `true` if `self` is a subset of `other`.
@@ -13,6 +13,7 @@ impl<T: Copy + Ord> Subset<Range<T>> for Range<T> {
 }
 
 impl<T: Copy + Ord> Subset<RangeSet<T>> for Range<T> {
+    /// Runs in `O(log n)` time, where `n` is the number of ranges of `other`.
     fn is_subset(&self, other: &RangeSet<T>) -> bool {
         if self.is_empty() {
             // empty range is subset of any set
@@ -22,16 +23,15 @@ impl<T: Copy + Ord> Subset<RangeSet<T>> for Range<T> {
             return false;
         }
 
-        for other in &other.ranges {
-            if self.start >= other.end {
-                // self is rightward of other, proceed to next other
-                continue;
-            } else {
-                return self.is_subset(other);
-            }
-        }
-
-        false
+        // The first range which ends after the start of self is the only one which may
+        // contain it.
+        let idx = other
+            .ranges
+            .partition_point(|range| self.start >= range.end);
+        other
+            .ranges
+            .get(idx)
+            .is_some_and(|other| self.is_subset(other))
     }
 }
 
@@ -48,7 +48,11 @@ impl<T: Copy + Ord> Subset<Range<T>> for RangeSet<T> {
 }
 
 impl<T: Copy + Ord> Subset<RangeSet<T>> for RangeSet<T> {
+    /// Runs in `O(n + m)` time, where `n` and `m` are the number of ranges of the sets.
     fn is_subset(&self, other: &RangeSet<T>) -> bool {
+        self.debug_assert_invariants();
+        other.debug_assert_invariants();
+
         if self.ranges.is_empty() {
             // empty set is subset of any set
             return true;
@@ -1,6 +1,6 @@
 use std::ops::{BitXor, BitXorAssign, Range};
 
-use crate::range::{DifferenceMut, Intersection, RangeSet, UnionMut};
+use crate::{DifferenceMut, Intersection, RangeSet, UnionMut};
 
 pub trait SymmetricDifferenceMut<Rhs> {
     /// Replaces `self` with the set symmetric difference of `self` and `other`.
@@ -15,6 +15,7 @@ pub trait SymmetricDifference<Rhs> {
 }
 
 impl<T: Copy + Ord> SymmetricDifferenceMut<Range<T>> for RangeSet<T> {
+    /// Runs in `O(n)` time, where `n` is the number of ranges of `self`.
     fn symmetric_difference_mut(&mut self, other: &Range<T>) {
         let intersection = self.intersection(other);
         self.union_mut(other);
@@ -23,6 +24,7 @@ impl<T: Copy + Ord> SymmetricDifferenceMut<Range<T>> for RangeSet<T> {
 }
 
 impl<T: Copy + Ord> SymmetricDifferenceMut<RangeSet<T>> for RangeSet<T> {
+    /// Runs in `O(n + m)` time, where `n` and `m` are the number of ranges of the sets.
     fn symmetric_difference_mut(&mut self, other: &RangeSet<T>) {
         let intersection = self.intersection(other);
         self.union_mut(other);
@@ -1,6 +1,6 @@
 use std::ops::{BitOr, BitOrAssign, Range};
 
-use crate::range::{Disjoint, RangeSet, Subset};
+use crate::{Disjoint, RangeSet, Subset};
 
 pub trait UnionMut<Rhs> {
     /// Replaces `self` with the set union of `self` and `other`.
@@ -16,49 +16,37 @@ pub trait Union<Rhs> {
 }
 
 impl<T: Copy + Ord> UnionMut<Range<T>> for RangeSet<T> {
+    /// Runs in `O(n)` time, where `n` is the number of ranges of `self`.
     fn union_mut(&mut self, other: &Range<T>) {
         if other.is_empty() {
             return;
-        } else if self.ranges.is_empty() {
-            self.ranges.push(other.clone());
-            return;
         }
 
         let ranges = &mut self.ranges;
 
-        let mut i = 0;
-        let mut new_range = other.clone();
-        while i < ranges.len() {
-            // If the new_range comes before the current range without overlapping
-            if new_range.end < ranges[i].start {
-                ranges.insert(i, new_range);
+        // The ranges which overlap or are adjacent to `other` are merged with it.
+        let start = ranges.partition_point(|range| range.end < other.start);
+        let end = ranges.partition_point(|range| range.start <= other.end);
 
-                return;
-            }
-            // If the new_range overlaps or is adjacent with the current range
-            else if new_range.start <= ranges[i].end {
-                // Expand new_range to include the current range
-                new_range.start = new_range.start.min(ranges[i].start);
-                new_range.end = new_range.end.max(ranges[i].end);
-                // Remove the current range as it is now included in new_range
-                ranges.remove(i);
-            }
-            // If the new_range comes after the current range
-            else {
-                i += 1;
-            }
+        let mut new_range = other.clone();
+        if start < end {
+            new_range.start = new_range.start.min(ranges[start].start);
+            new_range.end = new_range.end.max(ranges[end - 1].end);
         }
+        ranges.splice(start..end, [new_range]);
 
-        // If the new_range comes after all the ranges, add it to the end
-        ranges.push(new_range);
+        self.debug_assert_invariants();
     }
 }
 
 impl<T: Copy + Ord> UnionMut<RangeSet<T>> for RangeSet<T> {
+    /// Runs in `O(n + m)` time, where `n` and `m` are the number of ranges of the sets.
     fn union_mut(&mut self, other: &RangeSet<T>) {
-        for range in &other.ranges {
-            self.union_mut(range);
+        if other.ranges.is_empty() {
+            return;
         }
+
+        *self = self.union(other);
     }
 }
 
@@ -97,6 +85,7 @@ impl<T: Copy + Ord> Union<Range<T>> for Range<T> {
 impl<T: Copy + Ord> Union<RangeSet<T>> for Range<T> {
     type Output = RangeSet<T>;
 
+    /// Runs in `O(n)` time, where `n` is the number of ranges of `other`.
     fn union(&self, other: &RangeSet<T>) -> Self::Output {
         let mut other = other.clone();
         other.union_mut(self);
@@ -107,6 +96,7 @@ impl<T: Copy + Ord> Union<RangeSet<T>> for Range<T> {
 impl<T: Copy + Ord> Union<Range<T>> for RangeSet<T> {
     type Output = RangeSet<T>;
 
+    /// Runs in `O(n)` time, where `n` is the number of ranges of `self`.
     fn union(&self, other: &Range<T>) -> Self::Output {
         other.union(self)
     }
@@ -115,10 +105,12 @@ impl<T: Copy + Ord> Union<Range<T>> for RangeSet<T> {
 impl<T: Copy + Ord> Union<RangeSet<T>> for RangeSet<T> {
     type Output = RangeSet<T>;
 
+    /// Runs in `O(n + m)` time, where `n` and `m` are the number of ranges of the sets.
     fn union(&self, other: &RangeSet<T>) -> Self::Output {
-        let mut union = self.clone();
-        union.union_mut(other);
-        union
+        self.debug_assert_invariants();
+        other.debug_assert_invariants();
+
+        RangeSet::from_output(self.iter_union(other))
     }
 }
 
@@ -152,6 +144,23 @@ impl<T: Copy + Ord> BitOr<&Range<T>> for RangeSet<T> {
     }
 }
 
+impl<T: Copy + Ord> BitOr<RangeSet<T>> for Range<T> {
+    type Output = RangeSet<T>;
+
+    fn bitor(self, mut other: RangeSet<T>) -> Self::Output {
+        other.union_mut(&self);
+        other
+    }
+}
+
+impl<T: Copy + Ord> BitOr<&RangeSet<T>> for Range<T> {
+    type Output = RangeSet<T>;
+
+    fn bitor(self, other: &RangeSet<T>) -> Self::Output {
+        self.union(other)
+    }
+}
+
 impl<T: Copy + Ord> BitOrAssign<RangeSet<T>> for RangeSet<T> {
     fn bitor_assign(&mut self, other: RangeSet<T>) {
         self.union_mut(&other);
@@ -325,6 +334,41 @@ mod tests {
         assert_eq!(a.union(&b), a);
     }
 
+    #[test]
+    fn test_union_operators() {
+        let a = RangeSet::from([(10..20), (30..40)]);
+        let b = RangeSet::from([(15..25), (50..60)]);
+        let expected_range = RangeSet::from([(10..20), (25..40)]);
+        let expected_set = RangeSet::from([(10..25), (30..40), (50..60)]);
+
+        assert_eq!((25..30) | a.clone(), expected_range);
+        assert_eq!((25..30) | &a, expected_range);
+        assert_eq!(a.clone() | (25..30), expected_range);
+        assert_eq!(a.clone() | &(25..30), expected_range);
+        assert_eq!(a.clone() | b.clone(), expected_set);
+        assert_eq!(a.clone() | &b, expected_set);
+
+        let mut c = a.clone();
+        c |= 25..30;
+        assert_eq!(c, expected_range);
+
+        let mut c = a.clone();
+        c |= &(25..30);
+        assert_eq!(c, expected_range);
+
+        let mut c = a.clone();
+        c |= b.clone();
+        assert_eq!(c, expected_set);
+
+        let mut c = a.clone();
+        c |= &b;
+        assert_eq!(c, expected_set);
+
+        // empty
+        assert_eq!((0..0) | &a, a);
+        assert_eq!((0..0) | RangeSet::default(), RangeSet::<usize>::default());
+    }
+
     // This proves the union operation for 3 sets, up to size 16.
     #[test]
     #[ignore = "expensive"]
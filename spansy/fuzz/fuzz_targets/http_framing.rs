@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use spansy_fuzz::assert_framing;
+
+fuzz_target!(|data: &[u8]| {
+    assert_framing(data);
+});
@@ -0,0 +1,287 @@
+//! Reference HTTP/1.1 message framing, used to check the framing decisions of spansy.
+//!
+//! The head is parsed by `httparse`, and the body length is determined independently
+//! following RFC 9112, section 6.3, including the chunked transfer coding. Anything the
+//! reference cannot frame unambiguously is rejected.
+
+use std::ops::Range;
+
+use spansy::http::{parse_request_ref, parse_response_ref};
+
+const MAX_HEADERS: usize = 128;
+
+/// Checks that spansy never frames a message which the reference rejects, and that it frames
+/// every message it accepts identically.
+///
+/// A framing accepted by spansy but rejected by other parsers would allow a prover to commit
+/// to a body which the server did not see.
+///
+/// # Panics
+///
+/// Panics if the framing of spansy differs from the reference.
+pub fn assert_framing(data: &[u8]) {
+    if let Ok(request) = parse_request_ref(data) {
+        let expected = request_framing(data)
+            .unwrap_or_else(|| panic!("spansy accepted a request the reference rejects"));
+
+        assert_eq!(request.span.range(), expected.message());
+        match &request.body {
+            Some(body) => assert_eq!(body.range(), expected.body),
+            None => assert!(expected.body.is_empty()),
+        }
+        assert_eq!(
+            request
+                .chunks
+                .as_ref()
+                .map(|chunks| chunks.iter().map(|chunk| chunk.data.range()).collect()),
+            expected.chunks
+        );
+    }
+
+    if let Ok(response) = parse_response_ref(data) {
+        let expected = response_framing(data)
+            .unwrap_or_else(|| panic!("spansy accepted a response the reference rejects"));
+
+        assert_eq!(response.span.range(), expected.message());
+        match &response.body {
+            Some(body) => assert_eq!(body.range(), expected.body),
+            None => assert!(expected.body.is_empty()),
+        }
+        assert_eq!(
+            response
+                .chunks
+                .as_ref()
+                .map(|chunks| chunks.iter().map(|chunk| chunk.data.range()).collect()),
+            expected.chunks
+        );
+    }
+}
+
+/// The framing of a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Framing {
+    /// The range of the message head, including the empty line.
+    pub head: Range<usize>,
+    /// The range of the message body, including the chunk framing of a chunked body.
+    pub body: Range<usize>,
+    /// The ranges of the chunk data, if the body is chunked.
+    pub chunks: Option<Vec<Range<usize>>>,
+}
+
+impl Framing {
+    /// Returns the range of the entire message.
+    pub fn message(&self) -> Range<usize> {
+        self.head.start..self.body.end
+    }
+}
+
+/// Returns the framing of the request at the start of `src`, or `None` if it is rejected.
+pub fn request_framing(src: &[u8]) -> Option<Framing> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let mut request = httparse::Request::new(&mut headers);
+    let head_end = match request.parse(src).ok()? {
+        httparse::Status::Complete(head_end) => head_end,
+        httparse::Status::Partial => return None,
+    };
+
+    // A request without Content-Length or Transfer-Encoding has no body.
+    let body_len = body_len(request.headers)?.unwrap_or(BodyLen::Fixed(0));
+
+    framing(src, head_end, body_len)
+}
+
+/// Returns the framing of the response at the start of `src`, or `None` if it is rejected.
+pub fn response_framing(src: &[u8]) -> Option<Framing> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let mut response = httparse::Response::new(&mut headers);
+    let head_end = match response.parse(src).ok()? {
+        httparse::Status::Complete(head_end) => head_end,
+        httparse::Status::Partial => return None,
+    };
+
+    let body_len = match response.code? {
+        100..=199 | 204 | 304 => BodyLen::Fixed(0),
+        // A response without Content-Length or Transfer-Encoding is delimited by the
+        // connection closing, which is only unambiguous if the response closes it.
+        _ => match body_len(response.headers)? {
+            Some(body_len) => body_len,
+            None if closes_connection(response.version?, response.headers) => {
+                BodyLen::Fixed(src.len() - head_end)
+            }
+            None => return None,
+        },
+    };
+
+    framing(src, head_end, body_len)
+}
+
+/// Returns `true` if the connection is closed after a message with the given minor HTTP
+/// version and headers, following RFC 9112, section 9.3.
+fn closes_connection(version: u8, headers: &[httparse::Header<'_>]) -> bool {
+    let options: Vec<_> = headers
+        .iter()
+        .filter(|h| h.name.eq_ignore_ascii_case("Connection"))
+        .flat_map(|h| h.value.split(|b| *b == b','))
+        .map(<[u8]>::trim_ascii)
+        .collect();
+    let has = |option: &[u8]| options.iter().any(|o| o.eq_ignore_ascii_case(option));
+
+    has(b"close") || (version == 0 && !has(b"keep-alive"))
+}
+
+/// The length of a body.
+enum BodyLen {
+    Fixed(usize),
+    Chunked,
+}
+
+fn framing(src: &[u8], head_end: usize, body_len: BodyLen) -> Option<Framing> {
+    let (body_end, chunks) = match body_len {
+        BodyLen::Fixed(len) => (head_end.checked_add(len)?, None),
+        BodyLen::Chunked => {
+            let (end, chunks) = chunked_framing(src, head_end)?;
+            (end, Some(chunks))
+        }
+    };
+    if body_end > src.len() {
+        return None;
+    }
+
+    Some(Framing {
+        head: 0..head_end,
+        body: head_end..body_end,
+        chunks,
+    })
+}
+
+/// Returns the end of the chunked body starting at `pos` and the ranges of the chunk data,
+/// following RFC 9112, section 7.1.
+fn chunked_framing(src: &[u8], mut pos: usize) -> Option<(usize, Vec<Range<usize>>)> {
+    let mut chunks = Vec::new();
+    loop {
+        let line = line(src, pos)?;
+        pos = line.end + 2;
+
+        // chunk-size [ BWS ";" chunk-ext ]
+        let digits = src[line.clone()]
+            .iter()
+            .position(|b| !b.is_ascii_hexdigit())
+            .unwrap_or(line.len());
+        let size = usize::from_str_radix(
+            std::str::from_utf8(&src[line.start..line.start + digits]).ok()?,
+            16,
+        )
+        .ok()?;
+        let ext = src[line.start + digits..line.end].trim_ascii_start();
+        if !ext.is_empty() && ext[0] != b';' {
+            return None;
+        }
+
+        if size == 0 {
+            break;
+        }
+
+        let data = pos..pos.checked_add(size)?;
+        if src.get(data.end..data.end.checked_add(2)?)? != b"\r\n" {
+            return None;
+        }
+        pos = data.end + 2;
+        chunks.push(data);
+    }
+
+    // Skip the trailer section. Like the head, its field lines are parsed by httparse, which
+    // also accepts lines ending with a bare LF.
+    loop {
+        let len = src.get(pos..)?.iter().position(|b| *b == b'\n')?;
+        let line = &src[pos..pos + len];
+        pos += len + 1;
+        if line.is_empty() || line == b"\r" {
+            return Some((pos, chunks));
+        }
+    }
+}
+
+/// Returns the range of the line starting at `pos`, excluding the CRLF.
+fn line(src: &[u8], pos: usize) -> Option<Range<usize>> {
+    let len = src.get(pos..)?.windows(2).position(|w| w == b"\r\n")?;
+    Some(pos..pos + len)
+}
+
+/// Returns the body length signaled by the headers, `Some(None)` if no length is signaled,
+/// or `None` if the framing is rejected.
+fn body_len(headers: &[httparse::Header<'_>]) -> Option<Option<BodyLen>> {
+    let has = |name: &str| headers.iter().any(|h| h.name.eq_ignore_ascii_case(name));
+
+    if has("Transfer-Encoding") {
+        // A message with both Transfer-Encoding and Content-Length is a request smuggling
+        // vector.
+        if has("Content-Length") {
+            return None;
+        }
+
+        // The chunked coding must be applied last, and only once.
+        let codings: Vec<_> = headers
+            .iter()
+            .filter(|h| h.name.eq_ignore_ascii_case("Transfer-Encoding"))
+            .flat_map(|h| h.value.split(|b| *b == b','))
+            .map(<[u8]>::trim_ascii)
+            .filter(|coding| !coding.is_empty())
+            .collect();
+
+        return match codings.split_last() {
+            Some((last, rest))
+                if last.eq_ignore_ascii_case(b"chunked")
+                    && !rest
+                        .iter()
+                        .any(|coding| coding.eq_ignore_ascii_case(b"chunked")) =>
+            {
+                Some(Some(BodyLen::Chunked))
+            }
+            _ => None,
+        };
+    }
+
+    // Multiple Content-Length values, whether in separate fields or a list, must all be
+    // identical valid integers.
+    let mut len = None;
+    for header in headers
+        .iter()
+        .filter(|h| h.name.eq_ignore_ascii_case("Content-Length"))
+    {
+        for value in header.value.split(|b| *b == b',') {
+            let value = std::str::from_utf8(value).ok()?.trim_matches([' ', '\t']);
+            if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+
+            let value = value.parse::<usize>().ok()?;
+            if len.is_some_and(|len| len != value) {
+                return None;
+            }
+            len = Some(value);
+        }
+    }
+
+    Some(len.map(BodyLen::Fixed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_framing_bare_lf() {
+        for data in [
+            b"GET / HTTP/1.1\nA: b\n\n".as_slice(),
+            b"POST / HTTP/1.1\nContent-Length: 2\n\nhi",
+            b"HTTP/1.1 200 OK\nContent-Length: 0\n\n",
+            b"HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\n2\r\nhi\r\n0\r\nA: b\n\r\n",
+            b"HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\n2\nhi\n0\n\n",
+        ] {
+            assert_framing(data);
+            for len in 0..data.len() {
+                assert_framing(&data[..len]);
+            }
+        }
+    }
+}
@@ -0,0 +1,343 @@
+//! Export of byte-range annotations for external viewers.
+//!
+//! [`Annotations`] collects labeled ranges of a source, such as the parts of parsed messages,
+//! the problems reported by the parser and the ranges selected by a policy. With the `serde`
+//! feature, it serializes to a simple format which hex viewers and other tools can overlay on
+//! the source without linking this crate:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "annotations": [
+//!     { "offset": 0, "length": 16, "label": "request line", "severity": "note", "message": null }
+//!   ]
+//! }
+//! ```
+//!
+//! Offsets and lengths are in bytes of the source. A value spanning several ranges, such as the
+//! content of a chunked body, is exported as one annotation per range with the same label.
+
+use std::ops::Range;
+
+use utils::range::ToRangeSet;
+
+use crate::{
+    http::{Body, Fallback, FramingWarning, Header, ParseInfo, Request, Response},
+    ParseError,
+};
+
+/// The version of the annotation format.
+///
+/// Changes:
+///
+/// - 1: Initial version.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The severity of an [`Annotation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Severity {
+    /// Informational, such as the part of a message.
+    Note,
+    /// A problem which was recovered from.
+    Warning,
+    /// A problem which prevents the data from being accepted.
+    Error,
+}
+
+/// A labeled range of a source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Annotation {
+    /// The offset of the range in the source.
+    pub offset: usize,
+    /// The length of the range.
+    pub length: usize,
+    /// A short label, such as `header host`.
+    pub label: String,
+    /// The severity of the annotation.
+    pub severity: Severity,
+    /// A description of the annotation, if any.
+    pub message: Option<String>,
+}
+
+impl Annotation {
+    /// Returns the range of the annotation in the source.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.length
+    }
+}
+
+/// A collection of annotations of a source, in the order they were pushed.
+///
+/// # Example
+///
+/// ```
+/// use spansy::{
+///     annotate::{Annotations, Severity},
+///     http::parse_request,
+/// };
+/// use utils::range::RangeSet;
+///
+/// let request = parse_request(b"GET / HTTP/1.1\r\nAuthorization: abcd\r\n\r\n").unwrap();
+///
+/// let mut annotations = Annotations::new();
+/// annotations.push_request(&request);
+/// annotations.push_policy("redacted", Severity::Warning, RangeSet::from(31..35));
+///
+/// let redacted = annotations.iter().last().unwrap();
+/// assert_eq!(redacted.range(), 31..35);
+/// assert_eq!(annotations.iter().next().unwrap().label, "request line");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Annotations {
+    version: u32,
+    annotations: Vec<Annotation>,
+}
+
+impl Default for Annotations {
+    fn default() -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            annotations: Vec::new(),
+        }
+    }
+}
+
+impl Annotations {
+    /// Returns an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an annotation for each range of `ranges`, ignoring empty ranges.
+    pub fn push(
+        &mut self,
+        label: impl Into<String>,
+        severity: Severity,
+        message: Option<String>,
+        ranges: impl ToRangeSet<usize>,
+    ) {
+        let label = label.into();
+        for range in ranges.to_range_set().iter_ranges() {
+            self.annotations.push(Annotation {
+                offset: range.start,
+                length: range.len(),
+                label: label.clone(),
+                severity,
+                message: message.clone(),
+            });
+        }
+    }
+
+    /// Adds the ranges selected by a policy, such as the ranges to reveal or redact.
+    pub fn push_policy(
+        &mut self,
+        label: impl Into<String>,
+        severity: Severity,
+        ranges: impl ToRangeSet<usize>,
+    ) {
+        self.push(label, severity, None, ranges);
+    }
+
+    /// Adds notes for the request line, headers and body of a request, and warnings for the
+    /// problems recorded in its [`ParseInfo`].
+    pub fn push_request(&mut self, request: &Request) {
+        self.push("request line", Severity::Note, None, &request.request);
+        self.push_message(&request.headers, request.body(), request.parse_info());
+    }
+
+    /// Adds notes for the status line, headers and body of a response, and warnings for the
+    /// problems recorded in its [`ParseInfo`].
+    ///
+    /// Interim responses are annotated before the response.
+    pub fn push_response(&mut self, response: &Response) {
+        for interim in &response.interim {
+            self.push_response(interim);
+        }
+
+        self.push("status line", Severity::Note, None, &response.status);
+        self.push_message(&response.headers, response.body(), response.parse_info());
+    }
+
+    fn push_message(&mut self, headers: &[Header], body: Option<&Body>, info: Option<&ParseInfo>) {
+        for header in headers {
+            let label = format!("header {}", header.name.as_str().to_ascii_lowercase());
+            self.push(label, Severity::Note, None, header);
+        }
+
+        if let Some(body) = body {
+            self.push("body", Severity::Note, None, body);
+        }
+
+        let Some(info) = info else {
+            return;
+        };
+
+        for fallback in &info.fallbacks {
+            let message = Some(fallback.to_string());
+            match fallback {
+                Fallback::ConflictingContentLength { .. } => {
+                    for header in headers.iter().filter(|header| {
+                        header.name.as_str().eq_ignore_ascii_case("content-length")
+                    }) {
+                        self.push("fallback", Severity::Warning, message.clone(), header);
+                    }
+                }
+                Fallback::TruncatedBody { .. } => {
+                    if let Some(body) = body {
+                        self.push("fallback", Severity::Warning, message, body);
+                    }
+                }
+            }
+        }
+        self.push_warnings(&info.warnings);
+    }
+
+    /// Adds a warning for each framing problem.
+    pub fn push_warnings(&mut self, warnings: &[FramingWarning]) {
+        for warning in warnings {
+            let range = match warning {
+                FramingWarning::UnexpectedBody { range, .. } => range.clone(),
+            };
+            self.push(
+                "framing warning",
+                Severity::Warning,
+                Some(warning.to_string()),
+                range,
+            );
+        }
+    }
+
+    /// Adds an error for each offending range of a parsing error.
+    pub fn push_error(&mut self, err: &ParseError) {
+        for range in err.ranges() {
+            self.push(
+                "parse error",
+                Severity::Error,
+                Some(err.to_string()),
+                range.clone(),
+            );
+        }
+    }
+
+    /// Adds an error for each violation of the HTTP message syntax.
+    #[cfg(feature = "strict")]
+    pub fn push_violations(&mut self, violations: &[crate::http::Violation]) {
+        for violation in violations {
+            self.push(
+                "violation",
+                Severity::Error,
+                Some(violation.kind.to_string()),
+                violation.range.clone(),
+            );
+        }
+    }
+
+    /// Returns the number of annotations.
+    pub fn len(&self) -> usize {
+        self.annotations.len()
+    }
+
+    /// Returns `true` if there are no annotations.
+    pub fn is_empty(&self) -> bool {
+        self.annotations.is_empty()
+    }
+
+    /// Returns an iterator over the annotations, in the order they were pushed.
+    pub fn iter(&self) -> impl Iterator<Item = &Annotation> {
+        self.annotations.iter()
+    }
+
+    /// Returns the annotations, in the order they were pushed.
+    pub fn into_inner(self) -> Vec<Annotation> {
+        self.annotations
+    }
+}
+
+impl Extend<Annotation> for Annotations {
+    fn extend<I: IntoIterator<Item = Annotation>>(&mut self, iter: I) {
+        self.annotations.extend(iter);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::{parse_response_with_config, ContentLengthPolicy, ParserConfig},
+        testutil::harness::assert_response,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_annotate_response() {
+        let src = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nContent-Length: 3\r\n\r\nabc";
+        let config = ParserConfig::default()
+            .content_length(ContentLengthPolicy::Largest)
+            .parse_info(true);
+        let response = parse_response_with_config(src, &config).unwrap();
+        assert_response(src, &response);
+
+        let mut annotations = Annotations::new();
+        annotations.push_response(&response);
+
+        let summary: Vec<_> = annotations
+            .iter()
+            .map(|a| (a.label.as_str(), a.severity, a.range()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("status line", Severity::Note, 0..17),
+                ("header content-length", Severity::Note, 17..36),
+                ("header content-length", Severity::Note, 36..55),
+                ("body", Severity::Note, 57..60),
+                ("fallback", Severity::Warning, 17..36),
+                ("fallback", Severity::Warning, 36..55),
+            ]
+        );
+        assert!(annotations.iter().all(|a| a.range().end <= src.len()));
+    }
+
+    #[test]
+    fn test_annotate_error() {
+        let err = crate::http::parse_request(
+            b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked, gzip\r\n\r\n",
+        )
+        .unwrap_err();
+
+        let mut annotations = Annotations::new();
+        annotations.push_error(&err);
+        annotations.push_policy("empty", Severity::Note, 5..5);
+
+        assert_eq!(annotations.len(), err.ranges().count());
+        assert!(annotations.iter().all(|a| a.severity == Severity::Error));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_annotations_format() {
+        use utils::range::RangeSet;
+
+        let mut annotations = Annotations::new();
+        annotations.push_policy("redacted", Severity::Warning, RangeSet::from([2..4, 6..7]));
+
+        assert_eq!(
+            serde_json::to_value(&annotations).unwrap(),
+            serde_json::json!({
+                "version": 1,
+                "annotations": [
+                    { "offset": 2, "length": 2, "label": "redacted", "severity": "warning", "message": null },
+                    { "offset": 6, "length": 1, "label": "redacted", "severity": "warning", "message": null },
+                ]
+            })
+        );
+    }
+}
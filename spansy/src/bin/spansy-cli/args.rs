@@ -0,0 +1,223 @@
+use std::path::PathBuf;
+
+/// The usage of the tool.
+pub(crate) const USAGE: &str = "\
+Usage: spansy-cli [OPTIONS] <SENT> [RECEIVED]
+       spansy-cli diff [--format <FORMAT>] <A> <B>
+
+Parses the HTTP requests sent and the responses received over a connection, and lists
+each message with the range it occupies.
+
+Arguments:
+  <SENT>      File containing the data sent by the client
+  [RECEIVED]  File containing the data received by the client
+
+Options:
+  -H, --header <NAME>     Print the headers with the given name (repeatable)
+  -j, --json <POINTER>    Print the value at a JSON pointer in each body (repeatable)
+      --reveal            Print the ranges of the selected headers and JSON values as JSON
+      --format <FORMAT>   Output format, either `text` or `json` [default: text]
+  -h, --help              Print this help
+
+Diff:
+  Compares the messages of two transcripts of the same direction component by component,
+  e.g. header by header, and lists the components which differ with their ranges in each.
+  Exits with status 1 if the transcripts differ.
+";
+
+/// The output format.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Format {
+    /// A human readable listing.
+    #[default]
+    Text,
+    /// A JSON document with the span tree of every message.
+    Json,
+}
+
+/// A command of the tool.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Command {
+    /// Lists the messages of a transcript.
+    Inspect(Args),
+    /// Compares two transcripts.
+    Diff(DiffArgs),
+}
+
+impl Command {
+    /// Parses the command line arguments, excluding the program name.
+    ///
+    /// Returns `None` if help was requested.
+    pub(crate) fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut args = args.into_iter().peekable();
+        if args.peek().is_some_and(|arg| arg == "diff") {
+            args.next();
+            return Ok(DiffArgs::parse(args)?.map(Command::Diff));
+        }
+
+        Ok(Args::parse(args)?.map(Command::Inspect))
+    }
+}
+
+impl Format {
+    fn parse(format: Option<&str>) -> Result<Self, String> {
+        match format {
+            Some("text") => Ok(Format::Text),
+            Some("json") => Ok(Format::Json),
+            Some(format) => Err(format!("unknown format {format}")),
+            None => Err("missing value for --format".to_string()),
+        }
+    }
+}
+
+/// The command line arguments of the `diff` command.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct DiffArgs {
+    /// The first transcript.
+    pub(crate) a: PathBuf,
+    /// The second transcript.
+    pub(crate) b: PathBuf,
+    /// The output format.
+    pub(crate) format: Format,
+}
+
+impl DiffArgs {
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut parsed = Self::default();
+        let mut paths = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "--format" => {
+                    parsed.format = Format::parse(args.next().as_deref())?;
+                }
+                _ if arg.starts_with('-') => return Err(format!("unknown option {arg}")),
+                _ => paths.push(PathBuf::from(arg)),
+            }
+        }
+
+        let [a, b]: [PathBuf; 2] = paths
+            .try_into()
+            .map_err(|_| "diff takes exactly two files".to_string())?;
+        parsed.a = a;
+        parsed.b = b;
+
+        Ok(Some(parsed))
+    }
+}
+
+/// The command line arguments of the default command, which lists the messages of a
+/// transcript.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Args {
+    /// The file containing the data sent by the client.
+    pub(crate) sent: PathBuf,
+    /// The file containing the data received by the client.
+    pub(crate) received: Option<PathBuf>,
+    /// The names of the headers to print.
+    pub(crate) headers: Vec<String>,
+    /// The JSON pointers of the body values to print.
+    pub(crate) json: Vec<String>,
+    /// Whether to print the ranges of the selected values as JSON.
+    pub(crate) reveal: bool,
+    /// The output format.
+    pub(crate) format: Format,
+}
+
+impl Args {
+    /// Parses the command line arguments, excluding the program name.
+    ///
+    /// Returns `None` if help was requested.
+    pub(crate) fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut parsed = Self::default();
+        let mut paths = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {name}"))
+            };
+
+            match arg.as_str() {
+                "-h" | "--help" => return Ok(None),
+                "-H" | "--header" => parsed.headers.push(value(&arg)?),
+                "-j" | "--json" => parsed.json.push(value(&arg)?),
+                "--reveal" => parsed.reveal = true,
+                "--format" => parsed.format = Format::parse(Some(&value(&arg)?))?,
+                _ if arg.starts_with('-') && arg != "-" => {
+                    return Err(format!("unknown option {arg}"))
+                }
+                _ => paths.push(PathBuf::from(arg)),
+            }
+        }
+
+        let mut paths = paths.into_iter();
+        parsed.sent = paths.next().ok_or("missing <SENT> file")?;
+        parsed.received = paths.next();
+        if let Some(path) = paths.next() {
+            return Err(format!("unexpected argument {}", path.display()));
+        }
+
+        Ok(Some(parsed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        Args::parse(args.iter().map(ToString::to_string))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = parse(&[
+            "sent.bin", "-H", "Cookie", "recv.bin", "--json", "/a/0", "--reveal", "--format",
+            "json",
+        ])
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            args,
+            Args {
+                sent: "sent.bin".into(),
+                received: Some("recv.bin".into()),
+                headers: vec!["Cookie".to_string()],
+                json: vec!["/a/0".to_string()],
+                reveal: true,
+                format: Format::Json,
+            }
+        );
+
+        assert_eq!(parse(&["a", "--help"]), Ok(None));
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["a", "--header"]).is_err());
+        assert!(parse(&["a", "--bogus"]).is_err());
+        assert!(parse(&["a", "b", "c"]).is_err());
+        assert!(parse(&["a", "--format", "yaml"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_diff_args() {
+        let command =
+            Command::parse(["diff", "a.bin", "--format", "json", "b.bin"].map(String::from));
+        assert_eq!(
+            command,
+            Ok(Some(Command::Diff(DiffArgs {
+                a: "a.bin".into(),
+                b: "b.bin".into(),
+                format: Format::Json,
+            })))
+        );
+
+        assert!(Command::parse(["diff", "a.bin"].map(String::from)).is_err());
+        assert!(matches!(
+            Command::parse(["a.bin"].map(String::from)),
+            Ok(Some(Command::Inspect(_)))
+        ));
+    }
+}
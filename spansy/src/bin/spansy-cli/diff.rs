@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use utils::range::RangeSet;
+
+use crate::tree::Node;
+
+/// How a component differs between two transcripts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Change {
+    /// The component is in both transcripts, with different bytes.
+    Changed,
+    /// The component is only in the second transcript.
+    Added,
+    /// The component is only in the first transcript.
+    Removed,
+}
+
+/// A component which differs between two transcripts.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub(crate) struct Difference {
+    /// The path of the component in the span tree.
+    pub(crate) path: String,
+    /// How the component differs.
+    pub(crate) change: Change,
+    /// The ranges of the component in the first transcript, unless it was added.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) a: Option<RangeSet<usize>>,
+    /// The ranges of the component in the second transcript, unless it was removed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) b: Option<RangeSet<usize>>,
+}
+
+/// Returns the components which differ between the span trees of two transcripts.
+///
+/// Components are matched by path, so a header is compared with the header of the same name
+/// in the other message. Only the innermost components which differ are reported, e.g. the
+/// value of a header rather than the header and its message.
+pub(crate) fn diff(a: &[Node], a_src: &[u8], b: &[Node], b_src: &[u8]) -> Vec<Difference> {
+    let mut diffs = Vec::new();
+    diff_nodes(a, a_src, b, b_src, &mut diffs);
+    diffs
+}
+
+fn diff_nodes(a: &[Node], a_src: &[u8], b: &[Node], b_src: &[u8], diffs: &mut Vec<Difference>) {
+    let mut b_nodes: HashMap<_, _> = keyed(b).into_iter().collect();
+    for (key, a_node) in keyed(a) {
+        match b_nodes.remove(&key) {
+            Some(b_node) => diff_node(a_node, a_src, b_node, b_src, diffs),
+            None => diffs.push(Difference {
+                path: a_node.path.clone(),
+                change: Change::Removed,
+                a: Some(a_node.ranges.clone()),
+                b: None,
+            }),
+        }
+    }
+
+    let mut added: Vec<_> = b_nodes.into_values().collect();
+    added.sort_by_key(|node| node.ranges.min());
+    diffs.extend(added.into_iter().map(|b_node| Difference {
+        path: b_node.path.clone(),
+        change: Change::Added,
+        a: None,
+        b: Some(b_node.ranges.clone()),
+    }));
+}
+
+fn diff_node(a: &Node, a_src: &[u8], b: &Node, b_src: &[u8], diffs: &mut Vec<Difference>) {
+    if content(a, a_src).eq(content(b, b_src)) {
+        return;
+    }
+
+    let len = diffs.len();
+    diff_nodes(&a.children, a_src, &b.children, b_src, diffs);
+
+    // The node is reported if its children are the same, e.g. if it has none, or if it only
+    // differs in the bytes between them.
+    if diffs.len() == len {
+        diffs.push(Difference {
+            path: a.path.clone(),
+            change: Change::Changed,
+            a: Some(a.ranges.clone()),
+            b: Some(b.ranges.clone()),
+        });
+    }
+}
+
+/// Returns the nodes keyed by path and by occurrence of the path, as a header may be repeated.
+fn keyed(nodes: &[Node]) -> Vec<((&str, usize), &Node)> {
+    let mut occurrences = HashMap::new();
+    nodes
+        .iter()
+        .map(|node| {
+            let occurrence = occurrences.entry(node.path.as_str()).or_insert(0);
+            *occurrence += 1;
+            ((node.path.as_str(), *occurrence - 1), node)
+        })
+        .collect()
+}
+
+/// Returns the bytes of a node.
+fn content<'a>(node: &'a Node, src: &'a [u8]) -> impl Iterator<Item = u8> + 'a {
+    node.ranges
+        .iter_ranges()
+        .flat_map(move |range| src[range].iter().copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use spansy::{http::parse_request, testutil::harness::assert_request};
+
+    use crate::{tree::message_tree, Message};
+
+    use super::*;
+
+    fn tree(src: &[u8]) -> Vec<Node> {
+        let request = parse_request(src).unwrap();
+        assert_request(src, &request);
+        let request = Message::Request(request);
+        vec![message_tree(&request, "request[0]".to_string())]
+    }
+
+    #[test]
+    fn test_diff() {
+        let a = b"GET / HTTP/1.1\r\nCookie: a\r\nCookie: b\r\nHost: x\r\n\r\n";
+        let b = b"GET / HTTP/1.1\r\nCookie: a\r\nCookie: c\r\nAccept: */*\r\n\r\n";
+
+        assert!(diff(&tree(a), a, &tree(a), a).is_empty());
+        assert_eq!(
+            diff(&tree(a), a, &tree(b), b),
+            [
+                Difference {
+                    path: "request[0]/header:cookie/value".to_string(),
+                    change: Change::Changed,
+                    a: Some(RangeSet::from(35..36)),
+                    b: Some(RangeSet::from(35..36)),
+                },
+                Difference {
+                    path: "request[0]/header:host".to_string(),
+                    change: Change::Removed,
+                    a: Some(RangeSet::from(38..47)),
+                    b: None,
+                },
+                Difference {
+                    path: "request[0]/header:accept".to_string(),
+                    change: Change::Added,
+                    a: None,
+                    b: Some(RangeSet::from(38..51)),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! Inspects the HTTP messages of a transcript, e.g. to debug a redaction policy.
+//!
+//! ```text
+//! cargo run -p spansy --features cli --bin spansy-cli -- sent.bin received.bin -H cookie --reveal
+//! cargo run -p spansy --features cli --bin spansy-cli -- diff a.bin b.bin
+//! ```
+
+mod args;
+mod diff;
+mod tree;
+
+use std::{error::Error, path::Path, process::ExitCode};
+
+use serde::Serialize;
+use spansy::{
+    http::{Header, Request, Requests, Response, Responses},
+    json::JsonValue,
+    Source, Span, Spanned,
+};
+use utils::range::{RangeSet, Union};
+
+use crate::{
+    args::{Args, Command, DiffArgs, Format, USAGE},
+    diff::{diff, Change},
+    tree::{message_tree, Node},
+};
+
+fn main() -> ExitCode {
+    let command = match Command::parse(std::env::args().skip(1)) {
+        Ok(Some(command)) => command,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match &command {
+        Command::Inspect(args) => run(args).map(|()| ExitCode::SUCCESS),
+        Command::Diff(args) => run_diff(args),
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &Args) -> Result<(), Box<dyn Error>> {
+    let sent = Requests::new(read(&args.sent)?)
+        .map(|request| request.map(Message::Request))
+        .collect::<Result<Vec<_>, _>>()?;
+    let received = match &args.received {
+        Some(path) => Responses::new(read(path)?)
+            .map(|response| response.map(Message::Response))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => Vec::new(),
+    };
+
+    match args.format {
+        Format::Text => print_text(args, &sent, &received),
+        Format::Json => print_json(args, &sent, &received),
+    }
+}
+
+/// Compares two transcripts, returning a failure if they differ.
+fn run_diff(args: &DiffArgs) -> Result<ExitCode, Box<dyn Error>> {
+    let (a_src, a) = read_messages(&args.a)?;
+    let (b_src, b) = read_messages(&args.b)?;
+    if let (Some(a), Some(b)) = (a.first(), b.first()) {
+        if a.kind() != b.kind() {
+            return Err("transcripts are not of the same direction".into());
+        }
+    }
+
+    let trees = |messages: &[Message]| -> Vec<Node> {
+        messages
+            .iter()
+            .enumerate()
+            .map(|(index, message)| message_tree(message, message.path(index)))
+            .collect()
+    };
+    let diffs = diff(&trees(&a), &a_src, &trees(&b), &b_src);
+
+    match args.format {
+        Format::Text => {
+            for diff in &diffs {
+                let change = match diff.change {
+                    Change::Changed => "changed",
+                    Change::Added => "added",
+                    Change::Removed => "removed",
+                };
+                let mut line = format!("{change:<7} {}", diff.path);
+                if let Some(a) = &diff.a {
+                    line += &format!(" a: {}", format_ranges(a));
+                }
+                if let Some(b) = &diff.b {
+                    line += &format!(" b: {}", format_ranges(b));
+                }
+                println!("{line}");
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(&diffs)?),
+    }
+
+    Ok(if diffs.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Reads the messages of a transcript, which are responses if it starts with a status line
+/// and requests otherwise.
+fn read_messages(path: &Path) -> Result<(Source, Vec<Message>), Box<dyn Error>> {
+    let src = read(path)?;
+    let messages = if src.starts_with(b"HTTP/") {
+        Responses::new(src.clone())
+            .map(|response| response.map(Message::Response))
+            .collect::<Result<_, _>>()?
+    } else {
+        Requests::new(src.clone())
+            .map(|request| request.map(Message::Request))
+            .collect::<Result<_, _>>()?
+    };
+
+    Ok((src, messages))
+}
+
+/// Prints each message with the selected values.
+fn print_text(args: &Args, sent: &[Message], received: &[Message]) -> Result<(), Box<dyn Error>> {
+    let mut reveal = Reveal::default();
+    for (messages, revealed) in [(sent, &mut reveal.sent), (received, &mut reveal.received)] {
+        for (index, message) in messages.iter().enumerate() {
+            println!(
+                "{} {index}: {} {:?}",
+                message.kind(),
+                format_ranges(message.span().indices()),
+                message.start_line()
+            );
+
+            for selection in select(args, message, index) {
+                println!(
+                    "  {}: {} {:?}",
+                    selection.label,
+                    format_ranges(&selection.ranges),
+                    selection.text
+                );
+                *revealed = revealed.union(&selection.ranges);
+            }
+        }
+    }
+
+    if args.reveal {
+        println!("{}", serde_json::to_string(&reveal)?);
+    }
+
+    Ok(())
+}
+
+/// Prints the span tree of every message and the selected values as a JSON document.
+fn print_json(args: &Args, sent: &[Message], received: &[Message]) -> Result<(), Box<dyn Error>> {
+    let mut output = JsonOutput::default();
+    let mut reveal = Reveal::default();
+    for (messages, tree, revealed) in [
+        (sent, &mut output.sent, &mut reveal.sent),
+        (received, &mut output.received, &mut reveal.received),
+    ] {
+        for (index, message) in messages.iter().enumerate() {
+            tree.push(message_tree(message, message.path(index)));
+
+            for selection in select(args, message, index) {
+                *revealed = revealed.union(&selection.ranges);
+                output.selected.push(selection);
+            }
+        }
+    }
+    output.reveal = args.reveal.then_some(reveal);
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+
+    Ok(())
+}
+
+/// Returns the headers and JSON values of a message selected by the arguments.
+fn select(args: &Args, message: &Message, index: usize) -> Vec<Selection> {
+    let path = message.path(index);
+    let mut selections = Vec::new();
+
+    for name in &args.headers {
+        for header in message.headers_with_name(name) {
+            selections.push(Selection {
+                path: format!("{path}/header:{}", name.to_ascii_lowercase()),
+                label: format!("header {}", header.name.as_str()),
+                ranges: header.span().indices().clone(),
+                text: String::from_utf8_lossy(header.value.as_bytes()).into_owned(),
+            });
+        }
+    }
+
+    for pointer in &args.json {
+        if let Some(value) = message.json(pointer) {
+            selections.push(Selection {
+                path: format!("{path}/body#{pointer}"),
+                label: format!("json {pointer}"),
+                ranges: value.span().indices().clone(),
+                text: value.span().as_str().to_string(),
+            });
+        }
+    }
+
+    selections
+}
+
+/// Reads a transcript file.
+fn read(path: &Path) -> Result<Source, Box<dyn Error>> {
+    std::fs::read(path)
+        .map(Source::from)
+        .map_err(|err| format!("failed to read {}: {err}", path.display()).into())
+}
+
+/// Formats ranges as a comma separated list, e.g. `0..5, 7..9`.
+fn format_ranges(ranges: &RangeSet<usize>) -> String {
+    let ranges: Vec<_> = ranges
+        .iter_ranges()
+        .map(|range| format!("{}..{}", range.start, range.end))
+        .collect();
+    ranges.join(", ")
+}
+
+/// A value selected by the arguments.
+#[derive(Serialize)]
+struct Selection {
+    /// The path of the value, as in the span tree.
+    path: String,
+    /// The label of the value in the text output.
+    #[serde(skip)]
+    label: String,
+    /// The ranges of the value in the source.
+    ranges: RangeSet<usize>,
+    /// The value, with any invalid UTF-8 replaced.
+    text: String,
+}
+
+/// The ranges of the selected values, in each direction.
+#[derive(Default, Serialize)]
+struct Reveal {
+    sent: RangeSet<usize>,
+    received: RangeSet<usize>,
+}
+
+/// The output of the `json` format.
+#[derive(Default, Serialize)]
+struct JsonOutput {
+    /// The span trees of the requests.
+    sent: Vec<Node>,
+    /// The span trees of the responses.
+    received: Vec<Node>,
+    /// The values selected by the arguments.
+    selected: Vec<Selection>,
+    /// The ranges of the selected values, if requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reveal: Option<Reveal>,
+}
+
+/// A message of the transcript.
+enum Message {
+    Request(Request),
+    Response(Response),
+}
+
+impl Message {
+    fn kind(&self) -> &'static str {
+        match self {
+            Message::Request(_) => "request",
+            Message::Response(_) => "response",
+        }
+    }
+
+    /// Returns the path of the message in the span tree, e.g. `request[0]`.
+    fn path(&self, index: usize) -> String {
+        format!("{}[{index}]", self.kind())
+    }
+
+    fn span(&self) -> &Span {
+        match self {
+            Message::Request(request) => request.span(),
+            Message::Response(response) => response.span(),
+        }
+    }
+
+    /// Returns the start line, without the line terminator.
+    fn start_line(&self) -> &str {
+        let line = match self {
+            Message::Request(request) => request.request.span().as_str(),
+            Message::Response(response) => response.status.span().as_str(),
+        };
+        line.trim_end()
+    }
+
+    fn headers_with_name<'a>(&'a self, name: &'a str) -> Box<dyn Iterator<Item = &'a Header> + 'a> {
+        match self {
+            Message::Request(request) => Box::new(request.headers_with_name(name)),
+            Message::Response(response) => Box::new(response.headers_with_name(name)),
+        }
+    }
+
+    fn json(&self, pointer: &str) -> Option<&JsonValue> {
+        match self {
+            Message::Request(request) => request.json(pointer),
+            Message::Response(response) => response.json(pointer),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use serde::Serialize;
+use spansy::{
+    http::{Body, BodyContent, Header, Request, Response},
+    json::JsonValue,
+    Spanned,
+};
+use utils::range::RangeSet;
+
+use crate::Message;
+
+/// A node of the span tree of a message.
+#[derive(Debug, Serialize)]
+pub(crate) struct Node {
+    /// The path of the node, e.g. `request[0]/header:host`, or `response[0]/body#/items/0`
+    /// for the value at a JSON pointer in a body.
+    pub(crate) path: String,
+    /// The kind of the node, e.g. `header` or `string`.
+    kind: &'static str,
+    /// The ranges of the node in the source.
+    pub(crate) ranges: RangeSet<usize>,
+    /// The length of the node in bytes.
+    len: usize,
+    /// The value of the `Content-Type` header, for a body.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    /// The nodes contained in this node.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) children: Vec<Node>,
+}
+
+impl Node {
+    fn new<T: ?Sized>(path: String, kind: &'static str, span: &impl Spanned<T>) -> Self {
+        let ranges = span.span().indices().clone();
+        Self {
+            path,
+            kind,
+            len: ranges.len(),
+            ranges,
+            content_type: None,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Returns the span tree of a message.
+pub(crate) fn message_tree(message: &Message, path: String) -> Node {
+    match message {
+        Message::Request(request) => request_tree(request, path),
+        Message::Response(response) => response_tree(response, path),
+    }
+}
+
+fn request_tree(request: &Request, path: String) -> Node {
+    let mut node = Node::new(path, "request", request);
+    node.children.push(Node::new(
+        format!("{}/request-line", node.path),
+        "request-line",
+        &request.request,
+    ));
+    node.children
+        .extend(headers_tree(&request.headers, &node.path));
+    if let Some(body) = &request.body {
+        node.children
+            .push(body_tree(body, &request.headers, &node.path));
+    }
+    node
+}
+
+fn response_tree(response: &Response, path: String) -> Node {
+    let mut node = Node::new(path, "response", response);
+    for (index, interim) in response.interim.iter().enumerate() {
+        let mut interim = response_tree(interim, format!("{}/interim[{index}]", node.path));
+        interim.kind = "interim";
+        node.children.push(interim);
+    }
+    node.children.push(Node::new(
+        format!("{}/status-line", node.path),
+        "status-line",
+        &response.status,
+    ));
+    node.children
+        .extend(headers_tree(&response.headers, &node.path));
+    if let Some(body) = &response.body {
+        node.children
+            .push(body_tree(body, &response.headers, &node.path));
+    }
+    node
+}
+
+fn headers_tree<'a>(headers: &'a [Header], path: &'a str) -> impl Iterator<Item = Node> + 'a {
+    headers.iter().map(move |header| {
+        let path = format!(
+            "{path}/header:{}",
+            header.name.as_str().to_ascii_lowercase()
+        );
+        let mut node = Node::new(path, "header", header);
+        node.children = vec![
+            Node::new(format!("{}/name", node.path), "header-name", &header.name),
+            Node::new(
+                format!("{}/value", node.path),
+                "header-value",
+                &header.value,
+            ),
+        ];
+        node
+    })
+}
+
+fn body_tree(body: &Body, headers: &[Header], path: &str) -> Node {
+    let mut node = Node::new(format!("{path}/body"), "body", body);
+    node.content_type = headers
+        .iter()
+        .find(|header| header.name.as_str().eq_ignore_ascii_case("content-type"))
+        .map(|header| String::from_utf8_lossy(header.value.as_bytes()).into_owned());
+
+    if let Some(chunked) = &body.chunked {
+        for (index, chunk) in chunked.chunks.iter().enumerate() {
+            let mut chunk_node = Node::new(format!("{}/chunk[{index}]", node.path), "chunk", chunk);
+            chunk_node.children.push(Node::new(
+                format!("{}/data", chunk_node.path),
+                "chunk-data",
+                &chunk.data,
+            ));
+            node.children.push(chunk_node);
+        }
+    }
+
+    if let BodyContent::Json(value) = &body.content {
+        node.children
+            .push(json_tree(value, format!("{}#", node.path)));
+    }
+    node
+}
+
+fn json_tree(value: &JsonValue, path: String) -> Node {
+    let kind = match value {
+        JsonValue::Null(_) => "null",
+        JsonValue::Bool(_) => "bool",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    };
+    let mut node = Node::new(path, kind, value);
+
+    match value {
+        JsonValue::Array(array) => {
+            for (index, elem) in array.elems.iter().enumerate() {
+                node.children
+                    .push(json_tree(elem, format!("{}/{index}", node.path)));
+            }
+        }
+        JsonValue::Object(object) => {
+            for kv in &object.elems {
+                let key = kv.key.unescape().map_or_else(
+                    |_| kv.key.span().as_str().to_string(),
+                    |key| key.into_owned(),
+                );
+                let key = key.replace('~', "~0").replace('/', "~1");
+                node.children
+                    .push(json_tree(&kv.value, format!("{}/{key}", node.path)));
+            }
+        }
+        _ => {}
+    }
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use spansy::{http::parse_response, testutil::harness::assert_response};
+
+    use super::*;
+
+    fn paths(node: &Node, paths: &mut Vec<String>) {
+        paths.push(format!("{} {} {}", node.path, node.kind, node.len));
+        for child in &node.children {
+            self::paths(child, paths);
+        }
+    }
+
+    #[test]
+    fn test_message_tree() {
+        let src = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+            Transfer-Encoding: chunked\r\n\r\n8\r\n{\"a/b\": \r\n5\r\n[1,2]\r\n1\r\n}\r\n0\r\n\r\n";
+        let response = parse_response(src).unwrap();
+        assert_response(src, &response);
+        let tree = message_tree(&Message::Response(response), "response[0]".to_string());
+
+        let mut all = Vec::new();
+        paths(&tree, &mut all);
+        assert_eq!(
+            all,
+            [
+                "response[0] response 113",
+                "response[0]/status-line status-line 17",
+                "response[0]/header:content-type header 32",
+                "response[0]/header:content-type/name header-name 12",
+                "response[0]/header:content-type/value header-value 16",
+                "response[0]/header:transfer-encoding header 28",
+                "response[0]/header:transfer-encoding/name header-name 17",
+                "response[0]/header:transfer-encoding/value header-value 7",
+                "response[0]/body body 34",
+                "response[0]/body/chunk[0] chunk 13",
+                "response[0]/body/chunk[0]/data chunk-data 8",
+                "response[0]/body/chunk[1] chunk 10",
+                "response[0]/body/chunk[1]/data chunk-data 5",
+                "response[0]/body/chunk[2] chunk 6",
+                "response[0]/body/chunk[2]/data chunk-data 1",
+                "response[0]/body# object 14",
+                "response[0]/body#/a~1b array 5",
+                "response[0]/body#/a~1b/0 number 1",
+                "response[0]/body#/a~1b/1 number 1",
+            ]
+        );
+
+        let body = tree.children.last().unwrap();
+        assert_eq!(body.content_type.as_deref(), Some("application/json"));
+        // The JSON object spans the chunk data, excluding the framing.
+        assert_eq!(body.children.last().unwrap().ranges.len_ranges(), 3);
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    json::{JsonValue, KeyValue},
+    xml::{Attribute, Element, Node, XmlDocument},
+    ParseError,
+};
+
+/// A budget of memory for the structures allocated while parsing, such as headers, chunks
+/// and JSON values.
+///
+/// A budget is shared by every parse which uses it, so it bounds the memory retained by a
+/// whole session of untrusted messages rather than by each message. The usage is an estimate
+/// from the sizes of the allocated structures, excluding the source itself.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use spansy::{
+///     http::{ParserConfig, Requests},
+///     MemoryBudget,
+/// };
+///
+/// let budget = Arc::new(MemoryBudget::new(1024));
+/// let config = ParserConfig::new().memory_budget(budget.clone());
+///
+/// let src = b"GET / HTTP/1.1\r\nHost: a\r\n\r\n".repeat(100);
+/// let err = Requests::new_from_slice(&src)
+///     .with_config(config)
+///     .find_map(Result::err)
+///     .unwrap();
+///
+/// let exceeded = err.budget_exceeded().unwrap();
+/// assert_eq!(exceeded.limit, 1024);
+/// assert!(budget.used() <= 1024);
+/// ```
+#[derive(Debug)]
+pub struct MemoryBudget {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+impl MemoryBudget {
+    /// Returns a new budget of `limit` bytes.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            used: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the limit of the budget in bytes.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Returns the number of bytes used so far.
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of bytes remaining.
+    pub fn remaining(&self) -> usize {
+        self.limit - self.used()
+    }
+
+    /// Resets the usage to zero, e.g. once the parsed messages are dropped.
+    pub fn reset(&self) {
+        self.used.store(0, Ordering::Relaxed);
+    }
+
+    /// Charges `bytes` to the budget for a structure parsed at `offset`.
+    pub(crate) fn charge(&self, bytes: usize, offset: usize) -> Result<(), ParseError> {
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                used.checked_add(bytes).filter(|used| *used <= self.limit)
+            })
+            .map(|_| ())
+            .map_err(|used| {
+                ParseError::with_source(
+                    "memory budget exceeded",
+                    BudgetExceeded {
+                        limit: self.limit,
+                        used,
+                        requested: bytes,
+                    },
+                )
+                .with_label(offset..offset, "memory budget exceeded")
+            })
+    }
+}
+
+/// The cause of a [`ParseError`] returned when a [`MemoryBudget`] is exceeded.
+///
+/// Returned by [`ParseError::budget_exceeded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BudgetExceeded {
+    /// The limit of the budget in bytes.
+    pub limit: usize,
+    /// The number of bytes used before the allocation.
+    pub used: usize,
+    /// The number of bytes requested by the allocation.
+    pub requested: usize,
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "allocating {} bytes exceeds the budget of {} bytes, of which {} are used",
+            self.requested, self.limit, self.used
+        )
+    }
+}
+
+impl std::error::Error for BudgetExceeded {}
+
+/// Returns the estimated size of the structures of a JSON value.
+pub(crate) fn json_size(value: &JsonValue) -> usize {
+    std::mem::size_of::<JsonValue>() + json_heap_size(value)
+}
+
+/// Returns the estimated size of the structures of a JSON value, excluding the value itself.
+fn json_heap_size(value: &JsonValue) -> usize {
+    match value {
+        JsonValue::Array(array) => array.elems.iter().map(json_size).sum(),
+        JsonValue::Object(object) => object
+            .elems
+            .iter()
+            .map(|kv| std::mem::size_of::<KeyValue>() + json_heap_size(&kv.value))
+            .sum(),
+        _ => 0,
+    }
+}
+
+/// Returns the estimated size of the structures of an XML document.
+pub(crate) fn xml_size(document: &XmlDocument) -> usize {
+    std::mem::size_of::<XmlDocument>() + element_heap_size(&document.root)
+}
+
+/// Returns the estimated size of the structures of an XML element, excluding the element
+/// itself.
+fn element_heap_size(element: &Element) -> usize {
+    element.attributes.len() * std::mem::size_of::<Attribute>()
+        + element
+            .children
+            .iter()
+            .map(|child| {
+                std::mem::size_of::<Node>()
+                    + match child {
+                        Node::Element(element) => element_heap_size(element),
+                        _ => 0,
+                    }
+            })
+            .sum::<usize>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_charge() {
+        let budget = MemoryBudget::new(100);
+
+        budget.charge(60, 0).unwrap();
+        budget.charge(40, 10).unwrap();
+        assert_eq!(budget.remaining(), 0);
+
+        let err = budget.charge(1, 20).unwrap_err();
+        assert_eq!(
+            err.budget_exceeded(),
+            Some(&BudgetExceeded {
+                limit: 100,
+                used: 100,
+                requested: 1
+            })
+        );
+        assert_eq!(err.ranges().collect::<Vec<_>>(), [&(20..20)]);
+        assert!(budget.charge(usize::MAX, 0).is_err());
+
+        budget.reset();
+        assert_eq!(budget.used(), 0);
+    }
+}
@@ -0,0 +1,332 @@
+//! Conversion of selected ranges into the commitment index expected by `tlsn-core`.
+//!
+//! `tlsn-core` commits to a transcript as a list of direction-tagged range sets, which must
+//! be non-empty, within the transcript, and are expected in a canonical order so that the
+//! prover and verifier derive identical commitments. [`CommitIndex`] collects the ranges of
+//! parsed values and maintains these guarantees, so that the conversion is not repeated by
+//! every application.
+//!
+//! A prover may pay a cost per committed range, so [`minimize`] merges selections which are
+//! separated only by bytes which a [`MergePolicy`] allows to be revealed.
+
+use utils::range::{RangeSet, ToRangeSet, Union};
+
+use crate::ParseError;
+
+/// The direction of data in a transcript.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// Data sent by the prover, such as requests.
+    Sent,
+    /// Data received by the prover, such as responses.
+    Received,
+}
+
+/// An ordered index of the ranges of a transcript to commit to.
+///
+/// Entries are ordered by direction, with sent data first, then by the start and end of their
+/// ranges. Empty range sets are ignored and duplicate entries are removed, so the index does
+/// not depend on the order in which ranges were pushed.
+///
+/// # Example
+///
+/// ```
+/// use spansy::{
+///     commit::{CommitIndex, Direction},
+///     http::Session,
+/// };
+///
+/// let session = Session::new_from_slice(
+///     b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n",
+///     b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok",
+/// )
+/// .unwrap();
+/// let exchange = &session.exchanges[0];
+/// let response = exchange.response.as_ref().unwrap();
+///
+/// let mut index = CommitIndex::new();
+/// index.push(Direction::Received, response.body.as_ref().unwrap());
+/// index.push(Direction::Sent, &exchange.request.headers[0]);
+///
+/// assert_eq!(index.directions().collect::<Vec<_>>(), [Direction::Sent, Direction::Received]);
+/// assert!(index.validate(37, 40).is_ok());
+/// ```
+// Not deserializable, as the entries of deserialized data would not be guaranteed to be in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CommitIndex {
+    entries: Vec<(Direction, RangeSet<usize>)>,
+}
+
+impl CommitIndex {
+    /// Returns an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the ranges of a value in the given direction.
+    ///
+    /// The ranges are ignored if they are empty or already in the index.
+    pub fn push(&mut self, direction: Direction, value: impl ToRangeSet<usize>) {
+        let ranges = value.to_range_set();
+        if ranges.is_empty() {
+            return;
+        }
+
+        let entry = (direction, ranges);
+        if let Err(pos) = self.entries.binary_search_by(|other| cmp(other, &entry)) {
+            self.entries.insert(pos, entry);
+        }
+    }
+
+    /// Returns the number of entries in the index.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the entries of the index, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (Direction, &RangeSet<usize>)> {
+        self.entries
+            .iter()
+            .map(|(direction, ranges)| (*direction, ranges))
+    }
+
+    /// Returns an iterator over the direction of each entry, in order.
+    pub fn directions(&self) -> impl Iterator<Item = Direction> + '_ {
+        self.entries.iter().map(|(direction, _)| *direction)
+    }
+
+    /// Returns the union of the ranges in the given direction.
+    pub fn ranges(&self, direction: Direction) -> RangeSet<usize> {
+        self.entries
+            .iter()
+            .filter(|(other, _)| *other == direction)
+            .fold(RangeSet::default(), |acc, (_, ranges)| acc.union(ranges))
+    }
+
+    /// Checks that every entry is within a transcript with the given lengths of sent and
+    /// received data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any range ends past the data of its direction.
+    pub fn validate(&self, sent_len: usize, received_len: usize) -> Result<(), ParseError> {
+        for (direction, ranges) in &self.entries {
+            let len = match direction {
+                Direction::Sent => sent_len,
+                Direction::Received => received_len,
+            };
+            if let Some(end) = ranges.end().filter(|end| *end > len) {
+                return Err(ParseError::new(format!(
+                    "{direction:?} range end {end} exceeds transcript length {len}"
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the entries of the index, in order.
+    pub fn into_inner(self) -> Vec<(Direction, RangeSet<usize>)> {
+        self.entries
+    }
+}
+
+impl<T: ToRangeSet<usize>> Extend<(Direction, T)> for CommitIndex {
+    fn extend<I: IntoIterator<Item = (Direction, T)>>(&mut self, iter: I) {
+        for (direction, value) in iter {
+            self.push(direction, value);
+        }
+    }
+}
+
+impl<T: ToRangeSet<usize>> FromIterator<(Direction, T)> for CommitIndex {
+    fn from_iter<I: IntoIterator<Item = (Direction, T)>>(iter: I) -> Self {
+        let mut index = Self::new();
+        index.extend(iter);
+        index
+    }
+}
+
+/// Which bytes may be revealed between adjacent selections to merge them.
+///
+/// A gap between two selections is merged only if it is at most [`max_gap`](Self::max_gap)
+/// bytes long and consists entirely of allowed bytes, so that merging does not reveal any
+/// data beyond insignificant separators and whitespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergePolicy {
+    allowed: [bool; 256],
+    max_gap: usize,
+}
+
+impl Default for MergePolicy {
+    fn default() -> Self {
+        Self {
+            allowed: [false; 256],
+            max_gap: usize::MAX,
+        }
+    }
+}
+
+impl MergePolicy {
+    /// Returns a policy which allows no bytes, so only touching selections are merged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a policy which allows spaces, tabs, carriage returns and line feeds.
+    pub fn whitespace() -> Self {
+        Self::new().allow(b" \t\r\n")
+    }
+
+    /// Returns a policy which allows JSON whitespace and the structural characters
+    /// `,`, `:`, `[`, `]`, `{` and `}`.
+    pub fn json() -> Self {
+        Self::whitespace().allow(b",:[]{}")
+    }
+
+    /// Allows the given bytes in a gap.
+    pub fn allow(mut self, bytes: &[u8]) -> Self {
+        for byte in bytes {
+            self.allowed[*byte as usize] = true;
+        }
+        self
+    }
+
+    /// Sets the maximum length of a gap which is merged. By default the length is unbounded.
+    pub fn max_gap(mut self, len: usize) -> Self {
+        self.max_gap = len;
+        self
+    }
+
+    /// Returns `true` if the given gap may be revealed.
+    pub fn allows(&self, gap: &[u8]) -> bool {
+        gap.len() <= self.max_gap && gap.iter().all(|byte| self.allowed[*byte as usize])
+    }
+}
+
+/// Returns the smallest set of ranges which covers the selected ranges of `src`, merging
+/// adjacent selections whose gap is allowed by `policy`.
+///
+/// The gaps before the first and after the last selection are never included, and a gap
+/// which is not within `src` is never merged.
+///
+/// # Example
+///
+/// ```
+/// use spansy::{
+///     commit::{minimize, MergePolicy},
+///     json::parse_slice,
+/// };
+/// use utils::range::RangeSet;
+///
+/// let src = b"{\"a\": 1, \"b\": [2, 3], \"c\": 4}";
+/// let value = parse_slice(src).unwrap();
+/// let selected = ["a", "b.0", "b.1"].map(|path| value.get(path).unwrap().clone());
+///
+/// let ranges = minimize(src, selected.as_slice(), &MergePolicy::json());
+/// assert_eq!(ranges, RangeSet::from([6..7, 15..19]));
+/// ```
+pub fn minimize(
+    src: &[u8],
+    selected: impl ToRangeSet<usize>,
+    policy: &MergePolicy,
+) -> RangeSet<usize> {
+    let selected = selected.to_range_set();
+
+    let gaps: Vec<_> = selected
+        .iter_ranges()
+        .zip(selected.iter_ranges().skip(1))
+        .map(|(prev, next)| prev.end..next.start)
+        .filter(|gap| src.get(gap.clone()).is_some_and(|gap| policy.allows(gap)))
+        .collect();
+
+    selected.union(&RangeSet::from(gaps))
+}
+
+/// Orders entries by direction, then by the start and end of their ranges, then by the
+/// ranges themselves so that distinct entries never compare equal.
+fn cmp(a: &(Direction, RangeSet<usize>), b: &(Direction, RangeSet<usize>)) -> std::cmp::Ordering {
+    a.0.cmp(&b.0)
+        .then_with(|| a.1.min().cmp(&b.1.min()))
+        .then_with(|| a.1.end().cmp(&b.1.end()))
+        .then_with(|| {
+            a.1.iter_ranges()
+                .map(|r| (r.start, r.end))
+                .cmp(b.1.iter_ranges().map(|r| (r.start, r.end)))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_index_order() {
+        let index: CommitIndex = [
+            (Direction::Received, RangeSet::from(5..9)),
+            (Direction::Sent, RangeSet::from([4..6, 8..10])),
+            (Direction::Received, RangeSet::from(0..2)),
+            (Direction::Sent, RangeSet::from(4..10)),
+            (Direction::Sent, RangeSet::from(4..6)),
+            (Direction::Received, RangeSet::from(5..9)),
+            (Direction::Sent, RangeSet::default()),
+        ]
+        .into_iter()
+        .collect();
+
+        let entries = index.clone().into_inner();
+        assert_eq!(
+            entries,
+            [
+                (Direction::Sent, RangeSet::from(4..6)),
+                (Direction::Sent, RangeSet::from([4..6, 8..10])),
+                (Direction::Sent, RangeSet::from(4..10)),
+                (Direction::Received, RangeSet::from(0..2)),
+                (Direction::Received, RangeSet::from(5..9)),
+            ]
+        );
+        assert_eq!(
+            index.ranges(Direction::Received),
+            RangeSet::from([0..2, 5..9])
+        );
+
+        assert!(index.validate(10, 9).is_ok());
+        assert!(index.validate(10, 8).is_err());
+    }
+
+    #[test]
+    fn test_minimize() {
+        let src = b"a=1&b=2 ,\r\n c";
+        let selected = RangeSet::from([0..1, 2..3, 4..5, 6..7, 12..13]);
+
+        // Touching selections are always merged.
+        assert_eq!(
+            minimize(src, [0..2, 2..3].as_slice(), &MergePolicy::new()),
+            RangeSet::from(0..3)
+        );
+        assert_eq!(minimize(src, &selected, &MergePolicy::new()), selected);
+
+        let policy = MergePolicy::whitespace().allow(b"=,");
+        assert_eq!(
+            minimize(src, &selected, &policy),
+            RangeSet::from([0..3, 4..13])
+        );
+        assert_eq!(
+            minimize(src, &selected, &policy.clone().max_gap(1)),
+            RangeSet::from([0..3, 4..7, 12..13])
+        );
+
+        // Gaps outside the source are not merged.
+        assert_eq!(
+            minimize(src, [12..13, 20..21].as_slice(), &policy),
+            RangeSet::from([12..13, 20..21])
+        );
+    }
+}
@@ -0,0 +1,353 @@
+//! Span parsing for delimiter-framed text protocols, such as SMTP or Redis RESP.
+//!
+//! A message is a sequence of lines, each terminated by a delimiter. A [`Framing`] decides
+//! where each message ends, and may request a number of raw bytes for length-prefixed
+//! payloads, which can contain the delimiter themselves.
+//!
+//! # Example
+//!
+//! A framing for SMTP replies, where every line of a multi-line reply but the last has a `-`
+//! after the reply code:
+//!
+//! ```
+//! use spansy::{
+//!     framed::{Framing, Line, Messages, Next},
+//!     ParseError,
+//! };
+//!
+//! #[derive(Clone)]
+//! struct SmtpReply;
+//!
+//! impl Framing for SmtpReply {
+//!     fn next(&mut self, line: &Line) -> Result<Next, ParseError> {
+//!         Ok(match line.content.as_bytes().get(3) {
+//!             Some(b'-') => Next::Line,
+//!             _ => Next::Complete,
+//!         })
+//!     }
+//! }
+//!
+//! let src = b"250-smtp.example.com\r\n250-SIZE 35882577\r\n250 HELP\r\n221 Bye\r\n";
+//! let replies = Messages::new_from_slice(src, b"\r\n", SmtpReply)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//!
+//! assert_eq!(replies.len(), 2);
+//! assert_eq!(replies[0].lines.len(), 3);
+//! assert_eq!(replies[1].lines[0].content, b"221 Bye".as_slice());
+//! ```
+
+use utils::range::{RangeSet, ToRangeSet};
+
+use crate::{helpers::find, offset::impl_offset, ParseError, Source, Span, Spanned};
+
+/// A line of a delimiter-framed message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Line {
+    /// The span of the line, including the delimiter.
+    pub(crate) span: Span,
+    /// The content of the line, excluding the delimiter.
+    pub content: Span,
+}
+
+impl_offset!(Line { span, content });
+
+impl Spanned for Line {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for Line {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+/// A delimiter-framed message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Message {
+    pub(crate) span: Span,
+    /// The lines of the message, in order.
+    pub lines: Vec<Line>,
+}
+
+impl_offset!(Message { span, lines });
+
+impl Spanned for Message {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for Message {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+/// What follows a line of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Next {
+    /// Another line.
+    Line,
+    /// A line whose content is the given number of raw bytes, followed by the delimiter.
+    Data(usize),
+    /// Nothing, the message is complete.
+    Complete,
+}
+
+/// The framing of the messages of a protocol.
+///
+/// A framing is cloned at the start of each message, so it can keep state for the message.
+pub trait Framing: Clone {
+    /// Returns what follows the given line of the message.
+    fn next(&mut self, line: &Line) -> Result<Next, ParseError>;
+}
+
+/// A framing where every line is a message, such as the commands of SMTP.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SingleLine;
+
+impl Framing for SingleLine {
+    fn next(&mut self, _line: &Line) -> Result<Next, ParseError> {
+        Ok(Next::Complete)
+    }
+}
+
+/// The framing of the Redis serialization protocol (RESP2).
+///
+/// Each message is a single value, which may be an array of nested values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resp {
+    /// The number of values remaining in each enclosing array.
+    remaining: Vec<usize>,
+    /// Whether the next line is the payload of a bulk string.
+    payload: bool,
+}
+
+impl Framing for Resp {
+    fn next(&mut self, line: &Line) -> Result<Next, ParseError> {
+        let content = line.content.as_bytes();
+        let start = line.content.indices.min().unwrap_or_default();
+        let invalid = || {
+            ParseError::new(format!(
+                "invalid RESP line: {:?}",
+                String::from_utf8_lossy(content)
+            ))
+            .with_label(start..start + content.len(), "invalid RESP line")
+        };
+        let len = || -> Result<i64, ParseError> {
+            std::str::from_utf8(&content[1..])
+                .ok()
+                .and_then(|len| len.parse().ok())
+                .ok_or_else(invalid)
+        };
+
+        if std::mem::take(&mut self.payload) {
+            return Ok(self.complete_value());
+        }
+
+        match content.first() {
+            Some(b'+' | b'-' | b':') => Ok(self.complete_value()),
+            Some(b'$') => match len()? {
+                -1 => Ok(self.complete_value()),
+                len => {
+                    self.payload = true;
+                    Ok(Next::Data(usize::try_from(len).map_err(|_| invalid())?))
+                }
+            },
+            Some(b'*') => match len()? {
+                -1 | 0 => Ok(self.complete_value()),
+                len => {
+                    self.remaining
+                        .push(usize::try_from(len).map_err(|_| invalid())?);
+                    Ok(Next::Line)
+                }
+            },
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl Resp {
+    /// Completes a value, and any arrays which it completes.
+    fn complete_value(&mut self) -> Next {
+        while let Some(remaining) = self.remaining.last_mut() {
+            *remaining -= 1;
+            if *remaining > 0 {
+                return Next::Line;
+            }
+            self.remaining.pop();
+        }
+
+        Next::Complete
+    }
+}
+
+/// An iterator yielding parsed delimiter-framed messages.
+#[derive(Debug)]
+pub struct Messages<F> {
+    src: Source,
+    /// The current position in the source string.
+    pos: usize,
+    delimiter: Vec<u8>,
+    framing: F,
+}
+
+impl<F: Framing> Messages<F> {
+    /// Returns a new `Messages` iterator.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The source to parse.
+    /// * `delimiter` - The delimiter which terminates each line, such as `\r\n`.
+    /// * `framing` - The framing of the messages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the delimiter is empty.
+    pub fn new(src: impl Into<Source>, delimiter: &[u8], framing: F) -> Self {
+        assert!(!delimiter.is_empty(), "delimiter is not empty");
+
+        Self {
+            src: src.into(),
+            pos: 0,
+            delimiter: delimiter.to_vec(),
+            framing,
+        }
+    }
+
+    /// Returns a new `Messages` iterator.
+    pub fn new_from_slice(src: &[u8], delimiter: &[u8], framing: F) -> Self {
+        Self::new(Source::copy_from_slice(src), delimiter, framing)
+    }
+
+    /// Returns the current position in the source.
+    ///
+    /// This is the position at which the next message will be parsed. It is not advanced if
+    /// a message fails to parse.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    fn parse_message(&self) -> Result<Message, ParseError> {
+        let mut framing = self.framing.clone();
+        let mut lines = Vec::new();
+        let mut pos = self.pos;
+        let mut data_len = None;
+
+        loop {
+            let content_end = match data_len {
+                Some(len) => pos
+                    .checked_add(len)
+                    .filter(|end| *end <= self.src.len())
+                    .ok_or_else(|| self.incomplete())?,
+                None => {
+                    pos + find(&self.src[pos..], &self.delimiter)
+                        .ok_or_else(|| self.incomplete())?
+                }
+            };
+            let end = content_end + self.delimiter.len();
+            if self.src.get(content_end..end) != Some(self.delimiter.as_slice()) {
+                return Err(ParseError::new("data is not terminated by the delimiter")
+                    .with_label(content_end..end.min(self.src.len()), "expected delimiter"));
+            }
+
+            let line = Line {
+                span: Span::new_bytes(&self.src, pos..end),
+                content: Span::new_bytes(&self.src, pos..content_end),
+            };
+            let next = framing.next(&line)?;
+            lines.push(line);
+            pos = end;
+
+            data_len = match next {
+                Next::Line => None,
+                Next::Data(len) => Some(len),
+                Next::Complete => break,
+            };
+        }
+
+        Ok(Message {
+            span: Span::new_bytes(&self.src, self.pos..pos),
+            lines,
+        })
+    }
+
+    fn incomplete(&self) -> ParseError {
+        ParseError::new("incomplete message")
+            .with_label(self.pos..self.src.len(), "incomplete message")
+    }
+}
+
+impl<F: Framing> Iterator for Messages<F> {
+    type Item = Result<Message, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.src.len() {
+            None
+        } else {
+            Some(self.parse_message().inspect(|message| {
+                self.pos += message.span.len();
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse<F: Framing>(src: &[u8], framing: F) -> Result<Vec<Message>, ParseError> {
+        Messages::new_from_slice(src, b"\r\n", framing).collect()
+    }
+
+    #[test]
+    fn test_single_line() {
+        let messages = parse(b"EHLO example.com\r\nQUIT\r\n", SingleLine).unwrap();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].span(), b"EHLO example.com\r\n".as_slice());
+        assert_eq!(messages[1].lines[0].content, b"QUIT".as_slice());
+        assert_eq!(
+            messages[1].lines[0].content.indices(),
+            &RangeSet::from(18..22)
+        );
+    }
+
+    #[test]
+    fn test_resp() {
+        let src = b"+OK\r\n*2\r\n$5\r\na\r\nbc\r\n*1\r\n:42\r\n$-1\r\n";
+        let messages = parse(src, Resp::default()).unwrap();
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].span(), b"+OK\r\n".as_slice());
+
+        // The bulk string payload may contain the delimiter.
+        let array = &messages[1];
+        assert_eq!(array.lines.len(), 5);
+        assert_eq!(array.lines[2].content, b"a\r\nbc".as_slice());
+        assert_eq!(array.lines[4].content, b":42".as_slice());
+
+        assert_eq!(messages[2].span(), b"$-1\r\n".as_slice());
+    }
+
+    #[test]
+    fn test_incomplete() {
+        for src in [
+            b"+OK".as_slice(),
+            b"*2\r\n+a\r\n",
+            b"$5\r\nabc\r\n",
+            b"$3\r\nabcd\r\n",
+            b"?\r\n",
+        ] {
+            assert!(parse(src, Resp::default()).is_err(), "{src:?}");
+        }
+
+        let err = parse(b"+OK\r\n$3\r\nab", Resp::default()).unwrap_err();
+        assert_eq!(err.ranges().collect::<Vec<_>>(), [&(5..11)]);
+    }
+}
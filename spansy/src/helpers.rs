@@ -19,6 +19,13 @@ pub(crate) fn get_span_range(src: &[u8], span: &[u8]) -> Range<usize> {
     span_start - src_start..span_end - src_start
 }
 
+/// Returns the position of the first occurrence of `needle` in `haystack`.
+pub(crate) fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -33,6 +40,13 @@ mod tests {
         assert_eq!(get_span_range(src, &src[3..6]), 3..6);
     }
 
+    #[test]
+    fn test_find() {
+        assert_eq!(find(b"foo\r\nbar\r\n", b"\r\n"), Some(3));
+        assert_eq!(find(b"foo\r", b"\r\n"), None);
+        assert_eq!(find(b"", b"\r\n"), None);
+    }
+
     #[test]
     #[should_panic]
     fn test_get_span_range_outside_src_begin() {
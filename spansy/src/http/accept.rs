@@ -0,0 +1,190 @@
+use utils::range::{RangeSet, ToRangeSet};
+
+use crate::{
+    http::{
+        value::{parse_qvalue, QValue},
+        HeaderValue, Request,
+    },
+    offset::impl_offset,
+    Offset, ParseError, Source, Span, Spanned,
+};
+
+/// An element of a header used for content negotiation, such as `gzip;q=0.8` in
+/// `Accept-Encoding` or `text/html` in `Accept` (RFC 9110, section 12.5).
+///
+/// # Example
+///
+/// ```
+/// use spansy::http::parse_request;
+///
+/// let request = parse_request(
+///     b"GET / HTTP/1.1\r\nAccept-Encoding: gzip;q=0.5, identity\r\n\r\n",
+/// )
+/// .unwrap();
+///
+/// let items = request.accept_items("accept-encoding").unwrap();
+/// assert_eq!(items[1].value.as_str(), "identity");
+/// assert_eq!(items[0].q.thousandths(), 500);
+/// assert_eq!(items[0].q_value.as_ref().unwrap().as_str(), "0.5");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AcceptItem {
+    pub(crate) span: Span<str>,
+
+    /// The negotiated value, such as `gzip` or `text/html`, excluding its parameters.
+    pub value: Span<str>,
+    /// The value of the `q` parameter, if present.
+    pub q_value: Option<Span<str>>,
+    /// The quality of the value, which is 1 if the `q` parameter is not present.
+    pub q: QValue,
+}
+
+impl AcceptItem {
+    /// Returns `true` if the value is acceptable, i.e. its quality is not 0.
+    pub fn is_acceptable(&self) -> bool {
+        self.q != QValue::ZERO
+    }
+}
+
+impl_offset!(AcceptItem { span, value, q_value; q });
+
+impl Spanned<str> for AcceptItem {
+    fn span(&self) -> &Span<str> {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for AcceptItem {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+impl Request {
+    /// Returns the elements of all headers of the request with the given name
+    /// (case-insensitive), such as `Accept` or `Accept-Encoding`, in the order they appear.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a header value is not valid UTF-8 or has an invalid `q` parameter.
+    pub fn accept_items(&self, name: &str) -> Result<Vec<AcceptItem>, ParseError> {
+        let mut items = Vec::new();
+        for header in self.headers_with_name(name) {
+            items.extend(parse_accept(&header.value)?);
+        }
+
+        Ok(items)
+    }
+}
+
+/// Parses the value of a header used for content negotiation, such as `Accept` or
+/// `Accept-Encoding`, into its elements.
+///
+/// Empty elements, e.g. in `gzip, , br`, are skipped. Parameters other than `q` are part of
+/// the span of an element but not of its value.
+pub fn parse_accept(value: &HeaderValue) -> Result<Vec<AcceptItem>, ParseError> {
+    let mut items = parse_accept_from_bytes(value.0.data.clone().into())?;
+    items.offset(value.0.indices.min().unwrap_or_default());
+
+    Ok(items)
+}
+
+/// Parses the elements of a header value, with spans relative to the start of `src`.
+fn parse_accept_from_bytes(src: Source) -> Result<Vec<AcceptItem>, ParseError> {
+    let src_str = std::str::from_utf8(&src)?;
+
+    split_unquoted(src_str, ',')
+        .map(trim_ows)
+        .filter(|elem| !elem.is_empty())
+        .map(|elem| {
+            let mut params = split_unquoted(elem, ';');
+            let value = trim_ows(params.next().unwrap_or_default());
+            if value.is_empty() {
+                return Err(ParseError::new(format!("element {elem:?} has no value")));
+            }
+
+            let q_value = params
+                .filter_map(|param| param.split_once('='))
+                .find(|(name, _)| trim_ows(name).eq_ignore_ascii_case("q"))
+                .map(|(_, q)| trim_ows(q));
+            let q = q_value.map_or(Ok(QValue::ONE), |q| parse_qvalue(q.as_bytes()))?;
+
+            Ok(AcceptItem {
+                span: Span::new_from_str(&src, elem),
+                value: Span::new_from_str(&src, value),
+                q_value: q_value.map(|q| Span::new_from_str(&src, q)),
+                q,
+            })
+        })
+        .collect()
+}
+
+/// Splits `s` at each `delim` which is not within a quoted string.
+fn split_unquoted(s: &str, delim: char) -> impl Iterator<Item = &str> {
+    let mut quoted = false;
+    let mut escaped = false;
+    s.split(move |c| {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            _ => return c == delim && !quoted,
+        }
+        false
+    })
+}
+
+fn trim_ows(s: &str) -> &str {
+    s.trim_matches([' ', '\t'])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::parse_request;
+    use crate::testutil::harness::assert_request;
+
+    use super::*;
+
+    #[test]
+    fn test_accept_items() {
+        let src = b"GET / HTTP/1.1\r\n\
+            Accept: text/html, application/json;charset=\"utf-8,x\";q=0.9 , */*;Q=0\r\n\
+            Accept-Encoding: gzip\r\naccept-encoding: ,identity;q=1.0\r\n\r\n";
+        let request = parse_request(src).unwrap();
+        assert_request(src, &request);
+
+        let accept = request.accept_items("Accept").unwrap();
+        let values: Vec<_> = accept.iter().map(|item| item.value.as_str()).collect();
+        assert_eq!(values, ["text/html", "application/json", "*/*"]);
+        assert_eq!(
+            accept[1].span().as_str(),
+            "application/json;charset=\"utf-8,x\";q=0.9"
+        );
+        assert_eq!(accept[1].q.thousandths(), 900);
+        assert!(accept[0].q_value.is_none() && accept[0].q == QValue::ONE);
+        assert!(!accept[2].is_acceptable());
+
+        let encodings = request.accept_items("accept-encoding").unwrap();
+        assert_eq!(encodings.len(), 2);
+        let identity = &encodings[1];
+        assert_eq!(identity.value.as_str(), "identity");
+        assert_eq!(
+            &src[identity.value.indices().min().unwrap()..identity.value.indices().end().unwrap()],
+            b"identity"
+        );
+        assert_eq!(identity.q_value.as_ref().unwrap().as_str(), "1.0");
+
+        assert!(request.accept_items("accept-language").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_accept_items_invalid() {
+        for value in ["gzip;q=2", "gzip;q=", ";q=1"] {
+            let src = format!("GET / HTTP/1.1\r\nAccept-Encoding: {value}\r\n\r\n");
+            let request = parse_request(src.as_bytes()).unwrap();
+            assert_request(src.as_bytes(), &request);
+            assert!(request.accept_items("accept-encoding").is_err(), "{value}");
+        }
+    }
+}
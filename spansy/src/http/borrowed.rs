@@ -0,0 +1,862 @@
+use std::ops::Range;
+
+use utils::range::{RangeSet, ToRangeSet};
+
+use crate::{
+    helpers::get_span_range,
+    http::{
+        chunked,
+        span::{parse_request_from_bytes, parse_response_from_bytes},
+        value, ConnectionBehavior, ContentLengthPolicy, Header, ParserConfig, Request, Response,
+    },
+    Offset, ParseError, Source,
+};
+
+pub(crate) const MAX_HEADERS: usize = 128;
+
+/// A span which borrows its source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanRef<'a> {
+    src: &'a [u8],
+    range: Range<usize>,
+}
+
+impl<'a> SpanRef<'a> {
+    pub(crate) fn new(src: &'a [u8], range: Range<usize>) -> Self {
+        debug_assert!(range.end <= src.len());
+
+        Self { src, range }
+    }
+
+    /// Returns the range of the span in the source.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns the length of the span in bytes.
+    pub fn len(&self) -> usize {
+        self.range.len()
+    }
+
+    /// Returns `true` if the span is empty.
+    pub fn is_empty(&self) -> bool {
+        self.range.is_empty()
+    }
+
+    /// Returns the span as a byte slice.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        &self.src[self.range.clone()]
+    }
+
+    /// Returns the span as a string slice.
+    ///
+    /// Returns an error if the span is not valid UTF-8.
+    pub fn as_str(&self) -> Result<&'a str, ParseError> {
+        Ok(std::str::from_utf8(self.as_bytes())?)
+    }
+}
+
+impl ToRangeSet<usize> for SpanRef<'_> {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        RangeSet::from(self.range.clone())
+    }
+}
+
+impl PartialEq<[u8]> for SpanRef<'_> {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_bytes() == other
+    }
+}
+
+impl PartialEq<str> for SpanRef<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for SpanRef<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+/// An HTTP header which borrows its source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HeaderRef<'a> {
+    /// The span of the header, including the line terminator.
+    pub span: SpanRef<'a>,
+    /// The header name.
+    pub name: SpanRef<'a>,
+    /// The header value.
+    pub value: SpanRef<'a>,
+}
+
+/// A chunk of a body with the chunked transfer coding, which borrows its source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChunkRef<'a> {
+    /// The span of the chunk, including the chunk size line and the CRLF following the data.
+    pub span: SpanRef<'a>,
+    /// The chunk size.
+    pub size: usize,
+    /// The chunk extension, including the leading `;`.
+    pub extension: Option<SpanRef<'a>>,
+    /// The chunk data.
+    pub data: SpanRef<'a>,
+}
+
+/// An HTTP request which borrows its source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RequestRef<'a> {
+    /// The span of the request.
+    pub span: SpanRef<'a>,
+    /// The request line, including the line terminator.
+    pub request_line: SpanRef<'a>,
+    /// The request method.
+    pub method: SpanRef<'a>,
+    /// The request target.
+    pub target: SpanRef<'a>,
+    /// Request headers.
+    pub headers: Vec<HeaderRef<'a>>,
+    /// Request body, including the chunk framing if the body uses the chunked transfer coding.
+    pub body: Option<SpanRef<'a>>,
+    /// The chunks of the body, if it uses the chunked transfer coding.
+    pub chunks: Option<Vec<ChunkRef<'a>>>,
+    /// The trailer fields of the body, if it uses the chunked transfer coding.
+    pub trailers: Vec<HeaderRef<'a>>,
+    /// The number of bytes missing from the body, if it is shorter than its `Content-Length`.
+    ///
+    /// This is only set if [`ParserConfig::truncated_body`] is enabled.
+    pub truncated: Option<usize>,
+}
+
+impl<'a> RequestRef<'a> {
+    /// Returns an iterator of request headers with the given name (case-insensitive), in the
+    /// order they appear in the request.
+    pub fn headers_with_name<'b>(
+        &'b self,
+        name: &'b str,
+    ) -> impl Iterator<Item = &'b HeaderRef<'a>> + 'b {
+        self.headers
+            .iter()
+            .filter(|h| h.name.as_bytes().eq_ignore_ascii_case(name.as_bytes()))
+    }
+
+    /// Converts the request into an owned [`Request`], copying the request bytes.
+    ///
+    /// The spans of the owned request have the same indices as the borrowed request.
+    pub fn to_request(&self) -> Result<Request, ParseError> {
+        let src = Source::copy_from_slice(self.span.as_bytes());
+        let mut request = parse_request_from_bytes(&src, 0, &ParserConfig::default())?;
+        request.offset(self.span.range.start);
+
+        Ok(request)
+    }
+}
+
+/// An HTTP response which borrows its source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ResponseRef<'a> {
+    /// The span of the response.
+    pub span: SpanRef<'a>,
+    /// The status line, including the line terminator.
+    pub status_line: SpanRef<'a>,
+    /// The status code.
+    pub code: SpanRef<'a>,
+    /// The reason phrase.
+    pub reason: SpanRef<'a>,
+    /// Response headers.
+    pub headers: Vec<HeaderRef<'a>>,
+    /// Response body, including the chunk framing if the body uses the chunked transfer coding.
+    pub body: Option<SpanRef<'a>>,
+    /// The chunks of the body, if it uses the chunked transfer coding.
+    pub chunks: Option<Vec<ChunkRef<'a>>>,
+    /// The trailer fields of the body, if it uses the chunked transfer coding.
+    pub trailers: Vec<HeaderRef<'a>>,
+    /// The number of bytes missing from the body, if it is shorter than its `Content-Length`.
+    ///
+    /// This is only set if [`ParserConfig::truncated_body`] is enabled.
+    pub truncated: Option<usize>,
+}
+
+impl<'a> ResponseRef<'a> {
+    /// Returns an iterator of response headers with the given name (case-insensitive), in the
+    /// order they appear in the response.
+    pub fn headers_with_name<'b>(
+        &'b self,
+        name: &'b str,
+    ) -> impl Iterator<Item = &'b HeaderRef<'a>> + 'b {
+        self.headers
+            .iter()
+            .filter(|h| h.name.as_bytes().eq_ignore_ascii_case(name.as_bytes()))
+    }
+
+    /// Converts the response into an owned [`Response`], copying the response bytes.
+    ///
+    /// The spans of the owned response have the same indices as the borrowed response.
+    pub fn to_response(&self) -> Result<Response, ParseError> {
+        let src = Source::copy_from_slice(self.span.as_bytes());
+        let mut response = parse_response_from_bytes(&src, 0, &ParserConfig::default())?;
+        response.offset(self.span.range.start);
+
+        Ok(response)
+    }
+}
+
+/// Parses an HTTP request without taking ownership of the source.
+pub fn parse_request_ref(src: &[u8]) -> Result<RequestRef<'_>, ParseError> {
+    parse_request_ref_at(src, 0, &ParserConfig::default())
+}
+
+/// Parses an HTTP response without taking ownership of the source.
+pub fn parse_response_ref(src: &[u8]) -> Result<ResponseRef<'_>, ParseError> {
+    parse_response_ref_at(src, 0, &ParserConfig::default())
+}
+
+/// Parses an HTTP request starting from the `offset`.
+pub(crate) fn parse_request_ref_at<'a>(
+    src: &'a [u8],
+    offset: usize,
+    config: &ParserConfig,
+) -> Result<RequestRef<'a>, ParseError> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+
+    let (method, path, head_end) = {
+        let mut request = httparse::Request::new(&mut headers);
+
+        let head_end = match request.parse(&src[offset..]) {
+            Ok(httparse::Status::Complete(head_end)) => head_end + offset,
+            Ok(httparse::Status::Partial) => {
+                return Err(ParseError::new(format!("incomplete request: {:?}", src))
+                    .with_label(offset..src.len(), "incomplete request"))
+            }
+            Err(err) => {
+                return Err(
+                    ParseError::from(err).with_label(head_range(src, offset), "invalid request")
+                )
+            }
+        };
+
+        let method = request
+            .method
+            .ok_or_else(|| ParseError::new("method missing from request".to_string()))?;
+
+        let path = request
+            .path
+            .ok_or_else(|| ParseError::new("path missing from request".to_string()))?;
+
+        (method, path, head_end)
+    };
+
+    let request_line_end = line_end(src, offset).ok_or_else(|| {
+        ParseError::new("request line is not terminated".to_string())
+            .with_label(offset..head_end, "invalid request")
+    })?;
+    let request_line_range = offset..request_line_end;
+
+    let headers = headers
+        .iter()
+        .take_while(|h| *h != &httparse::EMPTY_HEADER)
+        .map(|header| {
+            let header = from_header(src, header)?;
+            config.check_interrupted(header.span.range.start)?;
+            config.charge(
+                size_of::<HeaderRef<'_>>() + size_of::<Header>(),
+                header.span.range.start,
+            )?;
+            Ok(header)
+        })
+        .collect::<Result<_, ParseError>>()?;
+
+    // httparse allocates a new buffer to store the method for performance reasons,
+    // so we have to search for the span in the source. This is quick as the method
+    // is at the front.
+    let method = src[offset..]
+        .windows(method.len())
+        .find(|w| *w == method.as_bytes())
+        .expect("method is present");
+
+    let mut request = RequestRef {
+        span: SpanRef::new(src, offset..head_end),
+        request_line: SpanRef::new(src, request_line_range),
+        method: SpanRef::new(src, get_span_range(src, method)),
+        target: SpanRef::new(src, get_span_range(src, path.as_bytes())),
+        headers,
+        body: None,
+        chunks: None,
+        trailers: Vec::new(),
+        truncated: None,
+    };
+
+    let body_len = request_body_len(&request, config.content_length_policy())?;
+    let (body_len, truncated) = truncate(src, head_end, body_len, config);
+    request.truncated = truncated;
+    if let Some((chunks, trailers)) = set_body(
+        src,
+        offset..head_end,
+        body_len,
+        config,
+        &mut request.span,
+        &mut request.body,
+    )? {
+        request.chunks = Some(chunks);
+        request.trailers = trailers;
+    }
+
+    Ok(request)
+}
+
+/// Parses an HTTP response starting from the `offset`.
+pub(crate) fn parse_response_ref_at<'a>(
+    src: &'a [u8],
+    offset: usize,
+    config: &ParserConfig,
+) -> Result<ResponseRef<'a>, ParseError> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+
+    let (reason, code, head_end) = {
+        let mut response = httparse::Response::new(&mut headers);
+
+        let head_end = match response.parse(&src[offset..]) {
+            Ok(httparse::Status::Complete(head_end)) => head_end + offset,
+            Ok(httparse::Status::Partial) => {
+                return Err(ParseError::new(format!("incomplete response: {:?}", src))
+                    .with_label(offset..src.len(), "incomplete response"))
+            }
+            Err(err) => {
+                return Err(
+                    ParseError::from(err).with_label(head_range(src, offset), "invalid response")
+                )
+            }
+        };
+
+        let code = response
+            .code
+            .ok_or_else(|| ParseError::new("code missing from response".to_string()))
+            .map(|c| c.to_string())?;
+
+        // The reason phrase may be empty, e.g. `HTTP/1.1 200\r\n`.
+        let reason = response.reason.unwrap_or_default();
+
+        (reason, code, head_end)
+    };
+
+    let status_line_end = line_end(src, offset).ok_or_else(|| {
+        ParseError::new("status line is not terminated".to_string())
+            .with_label(offset..head_end, "invalid response")
+    })?;
+    let status_line_range = offset..status_line_end;
+
+    let headers = headers
+        .iter()
+        .take_while(|h| *h != &httparse::EMPTY_HEADER)
+        .map(|header| {
+            let header = from_header(src, header)?;
+            config.check_interrupted(header.span.range.start)?;
+            config.charge(
+                size_of::<HeaderRef<'_>>() + size_of::<Header>(),
+                header.span.range.start,
+            )?;
+            Ok(header)
+        })
+        .collect::<Result<_, ParseError>>()?;
+
+    // httparse doesn't preserve the response code span, so we find it.
+    let code = src[offset..]
+        .windows(3)
+        .find(|w| *w == code.as_bytes())
+        .expect("code is present");
+
+    // An empty reason phrase is not borrowed from the source, so it is placed after the
+    // code and the space which follows it, if any.
+    let reason_range = if reason.is_empty() {
+        let code_end = get_span_range(src, code).end;
+        let start = code_end + usize::from(src.get(code_end) == Some(&b' '));
+        start..start
+    } else {
+        get_span_range(src, reason.as_bytes())
+    };
+
+    let mut response = ResponseRef {
+        span: SpanRef::new(src, offset..head_end),
+        status_line: SpanRef::new(src, status_line_range),
+        code: SpanRef::new(src, get_span_range(src, code)),
+        reason: SpanRef::new(src, reason_range),
+        headers,
+        body: None,
+        chunks: None,
+        trailers: Vec::new(),
+        truncated: None,
+    };
+
+    let body_len = response_body_len(&response, config.content_length_policy())?;
+    let (body_len, truncated) = truncate(src, head_end, body_len, config);
+    response.truncated = truncated;
+    if let Some((chunks, trailers)) = set_body(
+        src,
+        offset..head_end,
+        body_len,
+        config,
+        &mut response.span,
+        &mut response.body,
+    )? {
+        response.chunks = Some(chunks);
+        response.trailers = trailers;
+    }
+
+    Ok(response)
+}
+
+/// Converts a `httparse::Header` to a `HeaderRef`.
+pub(crate) fn from_header<'a>(
+    src: &'a [u8],
+    header: &httparse::Header,
+) -> Result<HeaderRef<'a>, ParseError> {
+    let name_range = get_span_range(src, header.name.as_bytes());
+    let value_range = get_span_range(src, header.value);
+
+    // Capture the entire header including trailing whitespace and the line ending.
+    let header_end = line_end(src, value_range.end).ok_or_else(|| {
+        ParseError::new("header is not terminated".to_string())
+            .with_label(name_range.start..src.len(), "invalid header")
+    })?;
+    let header_range = name_range.start..header_end;
+
+    Ok(HeaderRef {
+        span: SpanRef::new(src, header_range),
+        name: SpanRef::new(src, name_range),
+        value: SpanRef::new(src, value_range),
+    })
+}
+
+/// Returns the end of the line containing `pos`, after its line ending.
+///
+/// httparse accepts lines ending with a bare LF as well as with CRLF, so a line ends at the
+/// first LF, including the CR which may precede it.
+fn line_end(src: &[u8], pos: usize) -> Option<usize> {
+    src[pos..]
+        .iter()
+        .position(|b| *b == b'\n')
+        .map(|len| pos + len + 1)
+}
+
+/// The length of a message body.
+enum BodyLength {
+    /// The body has a fixed length.
+    Fixed(usize),
+    /// The body uses the chunked transfer coding.
+    Chunked,
+    /// The body is shorter than its fixed length, and has the given length.
+    Truncated(usize),
+    /// The body extends until the connection is closed, i.e. to the end of the source.
+    UntilClose,
+}
+
+/// The chunks and the trailer fields of a body with the chunked transfer coding.
+type ChunkedRef<'a> = (Vec<ChunkRef<'a>>, Vec<HeaderRef<'a>>);
+
+/// Sets the body of the message with the given head and extends its span to the end of the
+/// body.
+///
+/// Returns the chunks and the trailer fields of the body, if it uses the chunked transfer
+/// coding.
+fn set_body<'a>(
+    src: &'a [u8],
+    head: Range<usize>,
+    body_len: BodyLength,
+    config: &ParserConfig,
+    span: &mut SpanRef<'a>,
+    body: &mut Option<SpanRef<'a>>,
+) -> Result<Option<ChunkedRef<'a>>, ParseError> {
+    let mut chunked = None;
+    let range = match body_len {
+        BodyLength::Fixed(0) => return Ok(None),
+        BodyLength::Fixed(len) => body_range(src, head.end, len)?,
+        BodyLength::Truncated(len) => head.end..head.end + len,
+        BodyLength::UntilClose if head.end == src.len() => return Ok(None),
+        BodyLength::UntilClose => head.end..src.len(),
+        BodyLength::Chunked => {
+            let (chunks, trailers, end) = chunked::parse_chunks(src, head.end, config)?;
+            chunked = Some((chunks, trailers));
+            head.end..end
+        }
+    };
+
+    *body = Some(SpanRef::new(src, range.clone()));
+    *span = SpanRef::new(src, head.start..range.end);
+
+    Ok(chunked)
+}
+
+/// Truncates a fixed length body to the end of the source if the configuration allows it,
+/// returning the length of the body and the number of missing bytes.
+fn truncate(
+    src: &[u8],
+    head_end: usize,
+    body_len: BodyLength,
+    config: &ParserConfig,
+) -> (BodyLength, Option<usize>) {
+    match body_len {
+        BodyLength::Fixed(len) if config.allows_truncated_body() && len > src.len() - head_end => {
+            let available = src.len() - head_end;
+            (BodyLength::Truncated(available), Some(len - available))
+        }
+        body_len => (body_len, None),
+    }
+}
+
+/// Returns the range of the head of the message starting at `offset`, or of the rest of the
+/// source if the head is not terminated.
+fn head_range(src: &[u8], offset: usize) -> Range<usize> {
+    let end = src[offset..]
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map_or(src.len(), |end| offset + end + 4);
+
+    offset..end
+}
+
+/// Returns the range of a body of the given length, checking that it is within the source.
+fn body_range(src: &[u8], head_end: usize, body_len: usize) -> Result<Range<usize>, ParseError> {
+    let range = head_end..head_end + body_len;
+
+    if range.end > src.len() {
+        return Err(ParseError::new(format!(
+            "body range {}..{} exceeds source {}",
+            range.start,
+            range.end,
+            src.len()
+        ))
+        .with_label(head_end..src.len(), "body is truncated"));
+    }
+
+    Ok(range)
+}
+
+/// Calculates the length of the request body according to RFC 9112, section 6.
+fn request_body_len(
+    request: &RequestRef<'_>,
+    policy: ContentLengthPolicy,
+) -> Result<BodyLength, ParseError> {
+    // The presence of a message body in a request is signaled by a Content-Length
+    // or Transfer-Encoding header field.
+
+    // A message with both a Transfer-Encoding and a Content-Length header field is rejected,
+    // see `transfer_coding`.
+    if request
+        .headers_with_name("Transfer-Encoding")
+        .next()
+        .is_some()
+    {
+        transfer_coding(&request.headers)
+    } else if let Some(len) = content_length(request.headers_with_name("Content-Length"), policy)? {
+        // If a valid Content-Length header field is present without Transfer-Encoding, its decimal value
+        // defines the expected message body length in octets.
+        Ok(BodyLength::Fixed(len))
+    } else {
+        // If this is a request message and none of the above are true, then the message body length is zero
+        Ok(BodyLength::Fixed(0))
+    }
+}
+
+/// Calculates the length of the response body according to RFC 9112, section 6.
+fn response_body_len(
+    response: &ResponseRef<'_>,
+    policy: ContentLengthPolicy,
+) -> Result<BodyLength, ParseError> {
+    // Any response to a HEAD request and any response with a 1xx (Informational), 204 (No Content), or 304 (Not Modified)
+    // status code is always terminated by the first empty line after the header fields, regardless of the header fields
+    // present in the message, and thus cannot contain a message body or trailer section.
+    match value::parse_integer(response.code.as_bytes()).expect("code is a number") {
+        100..=199 | 204 | 304 => return Ok(BodyLength::Fixed(0)),
+        _ => {}
+    }
+
+    if response
+        .headers_with_name("Transfer-Encoding")
+        .next()
+        .is_some()
+    {
+        transfer_coding(&response.headers)
+    } else if let Some(len) = content_length(response.headers_with_name("Content-Length"), policy)?
+    {
+        // If a valid Content-Length header field is present without Transfer-Encoding, its decimal value
+        // defines the expected message body length in octets.
+        Ok(BodyLength::Fixed(len))
+    } else if ConnectionBehavior::new(
+        response
+            .status_line
+            .as_bytes()
+            .split(|b| *b == b' ')
+            .next()
+            .unwrap_or_default(),
+        response
+            .headers_with_name("Connection")
+            .map(|header| header.value.as_bytes()),
+    )
+    .is_close()
+    {
+        // If this is a response message and none of the above are true, then there is no way to
+        // determine the length of the message body except by reading it until the connection is closed.
+        Ok(BodyLength::UntilClose)
+    } else {
+        // A persistent connection may be followed by more responses, so a body which is
+        // delimited by the connection closing is an error.
+        Err(ParseError::new(
+            "A response with a body must contain either a Content-Length or Transfer-Encoding header".to_string(),
+        )
+        .with_label(response.status_line.range(), "body length is unknown"))
+    }
+}
+
+/// Returns the body length of a message with a `Transfer-Encoding` header.
+///
+/// The chunked transfer coding must be applied last, and only once, so that it determines the
+/// length of the body (RFC 9112, section 6.3). Other codings applied before it, such as
+/// `gzip, chunked`, do not affect the framing, and are undone by
+/// [`Request::decoded_body`](crate::http::Request::decoded_body).
+///
+/// A message which also has a `Content-Length` header is rejected. RFC 9112 allows the
+/// `Transfer-Encoding` to override it, but recipients which disagree on the framing can be
+/// exploited for request smuggling.
+fn transfer_coding(headers: &[HeaderRef<'_>]) -> Result<BodyLength, ParseError> {
+    if let Some(header) = headers
+        .iter()
+        .find(|h| h.name.as_bytes().eq_ignore_ascii_case(b"Content-Length"))
+    {
+        return Err(ParseError::new(
+            "message has both Transfer-Encoding and Content-Length headers".to_string(),
+        )
+        .with_label(header.span.range(), "conflicting header"));
+    }
+
+    let headers = headers
+        .iter()
+        .filter(|h| h.name.as_bytes().eq_ignore_ascii_case(b"Transfer-Encoding"));
+
+    let mut chunked = false;
+    let mut empty = true;
+    for header in headers {
+        let codings = header
+            .value
+            .as_bytes()
+            .split(|b| *b == b',')
+            .map(<[u8]>::trim_ascii)
+            .filter(|coding| !coding.is_empty());
+
+        for coding in codings {
+            if chunked {
+                return Err(ParseError::new(format!(
+                    "unsupported transfer coding: {:?}",
+                    String::from_utf8_lossy(header.value.as_bytes())
+                ))
+                .with_label(header.value.range(), "coding applied after chunked"));
+            }
+            chunked = coding.eq_ignore_ascii_case(b"chunked");
+            empty = false;
+        }
+    }
+
+    if empty {
+        return Err(ParseError::new("Transfer-Encoding is empty".to_string()));
+    } else if !chunked {
+        return Err(ParseError::new(
+            "unsupported transfer coding: chunked is not the final coding".to_string(),
+        ));
+    }
+
+    Ok(BodyLength::Chunked)
+}
+
+/// Parses the values of the `Content-Length` headers, returning `None` if there are none.
+///
+/// A header may contain a list of values, e.g. `Content-Length: 42, 42`. If the values differ,
+/// the `policy` decides which is used.
+fn content_length<'a>(
+    headers: impl Iterator<Item = &'a HeaderRef<'a>>,
+    policy: ContentLengthPolicy,
+) -> Result<Option<usize>, ParseError> {
+    let mut len: Option<usize> = None;
+    for header in headers {
+        for value in header.value.as_bytes().split(|b| *b == b',') {
+            let value = value::parse_integer(value).map_err(|err| {
+                ParseError::with_source("failed to parse Content-Length value", err)
+                    .with_label(header.value.range(), "invalid Content-Length")
+            })?;
+            let value = usize::try_from(value).map_err(|_| {
+                ParseError::new(format!("Content-Length {value} is too large"))
+                    .with_label(header.value.range(), "Content-Length is too large")
+            })?;
+
+            len = match (len, policy) {
+                (None, _) => Some(value),
+                (Some(len), _) if len == value => Some(len),
+                (Some(len), ContentLengthPolicy::Reject) => {
+                    return Err(ParseError::new(format!(
+                        "conflicting Content-Length values: {len} and {value}"
+                    ))
+                    .with_label(header.value.range(), "conflicting Content-Length"))
+                }
+                (Some(len), ContentLengthPolicy::First) => Some(len),
+                (Some(len), ContentLengthPolicy::Largest) => Some(len.max(value)),
+            };
+        }
+    }
+
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        testutil::harness::{assert_request, assert_response},
+        Spanned,
+    };
+
+    use super::*;
+
+    const REQUEST: &[u8] = b"POST /hello HTTP/1.1\r\nHost: localhost\r\n\
+        Content-Length: 5\r\n\r\nhello";
+
+    const RESPONSE: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+        Content-Length: 14\r\n\r\n{\"foo\": \"bar\"}";
+
+    #[test]
+    fn test_parse_request_ref() {
+        let request = parse_request_ref(REQUEST).unwrap();
+
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.target, "/hello");
+        assert_eq!(request.target.range(), 5..11);
+        assert_eq!(request.request_line, "POST /hello HTTP/1.1\r\n");
+        assert_eq!(
+            request.headers_with_name("host").next().unwrap().value,
+            "localhost"
+        );
+        assert_eq!(request.body.as_ref().unwrap(), "hello");
+        assert_eq!(request.span.range(), 0..REQUEST.len());
+        assert_request(REQUEST, &request.to_request().unwrap());
+    }
+
+    #[test]
+    fn test_parse_response_ref() {
+        let response = parse_response_ref(RESPONSE).unwrap();
+
+        assert_eq!(response.code, "200");
+        assert_eq!(response.reason, "OK");
+        assert_eq!(response.headers.len(), 2);
+        assert_eq!(response.body.as_ref().unwrap().range(), 71..85);
+        assert_response(RESPONSE, &response.to_response().unwrap());
+    }
+
+    #[test]
+    fn test_parse_bare_lf() {
+        let src = b"GET / HTTP/1.1\nA: b \n\n";
+        let request = parse_request_ref(src).unwrap();
+        assert_eq!(request.request_line, "GET / HTTP/1.1\n");
+        assert_eq!(request.headers[0].span, "A: b \n");
+        assert_eq!(request.span.range(), 0..src.len());
+
+        let request = request.to_request().unwrap();
+        assert_request(src, &request);
+        assert_eq!(request.headers[0].trailing_ows(), b" ".as_slice());
+
+        // A bare LF line must not be extended to a CRLF which follows it.
+        let src = b"HTTP/1.1 200 OK\nContent-Length: 0\r\n\r\n";
+        let response = parse_response_ref(src).unwrap();
+        assert_eq!(response.status_line, "HTTP/1.1 200 OK\n");
+        assert_eq!(response.headers[0].span, "Content-Length: 0\r\n");
+        assert_response(src, &response.to_response().unwrap());
+
+        let src = b"HTTP/1.1 200 OK\nContent-Length: 0\n\n";
+        assert_response(src, &crate::http::parse_response(src).unwrap());
+
+        let src = b"HTTP/1.1 200 OK\nTransfer-Encoding: chunked\n\n0\r\nA: b\n\r\n";
+        let response = parse_response_ref(src).unwrap();
+        assert_eq!(response.trailers[0].span, "A: b\n");
+        assert_response(src, &response.to_response().unwrap());
+    }
+
+    #[test]
+    fn test_parse_response_empty_reason() {
+        for (src, pos) in [
+            (b"HTTP/1.1 200\r\nContent-Length: 0\r\n\r\n".as_slice(), 12),
+            (b"HTTP/1.1 200 \r\nContent-Length: 0\r\n\r\n".as_slice(), 13),
+        ] {
+            let response = parse_response_ref(src).unwrap();
+            assert_eq!(response.code, "200");
+            assert_eq!(response.reason, "");
+            assert_eq!(response.reason.range(), pos..pos);
+
+            let owned = response.to_response().unwrap();
+            assert_response(src, &owned);
+            assert_eq!(owned.status.reason.as_str(), "");
+            assert_eq!(owned.status.span().as_str().as_bytes(), &src[..pos + 2]);
+        }
+    }
+
+    #[test]
+    fn test_ref_to_owned() {
+        let src = [b"garbage".as_slice(), REQUEST].concat();
+        let request = parse_request_ref_at(&src, 7, &ParserConfig::default()).unwrap();
+        let owned = request.to_request().unwrap();
+        assert_request(&src, &owned);
+
+        assert_eq!(owned.span().indices().min(), Some(7));
+        assert_eq!(
+            owned.request.target.span().indices().min(),
+            Some(request.target.range().start)
+        );
+        assert_eq!(owned.body.unwrap().span(), b"hello".as_slice());
+
+        let response = parse_response_ref(RESPONSE).unwrap();
+        assert_eq!(response.to_response().unwrap(), {
+            crate::http::parse_response(RESPONSE).unwrap()
+        });
+    }
+
+    #[test]
+    fn test_error_source() {
+        use std::error::Error;
+
+        let err = parse_request_ref(b"GET / HTTP/1.1\r\nHo st: localhost\r\n\r\n").unwrap_err();
+        assert!(err.source().unwrap().is::<httparse::Error>());
+
+        let err = parse_request_ref(b"GET / HTTP/1.1\r\nContent-Length: x\r\n\r\n").unwrap_err();
+        assert!(err.source().unwrap().is::<value::ValueError>());
+
+        let err = SpanRef::new(b"\xff", 0..1).as_str().unwrap_err();
+        assert!(err.source().unwrap().is::<std::str::Utf8Error>());
+    }
+
+    #[test]
+    fn test_error_ranges() {
+        let src = b"GET / HTTP/1.1\r\nContent-Length: 1\r\nContent-Length: 2\r\n\r\nab";
+        let err = parse_request_ref(src).unwrap_err();
+        assert_eq!(err.ranges().collect::<Vec<_>>(), [&(51..52)]);
+
+        let src = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n\
+            2\r\nab\r\nzz\r\n";
+        let err = crate::http::parse_request(src).unwrap_err();
+        let range = err.ranges().next().unwrap();
+        assert_eq!(&src[range.start..], b"zz\r\n");
+    }
+
+    #[cfg(feature = "diagnostics")]
+    #[test]
+    fn test_diagnostic_labels() {
+        use miette::Diagnostic;
+
+        let src = b"HTTP/1.1 200 OK\r\nContent-Length: x\r\n\r\n";
+        let err = parse_response_ref(src).unwrap_err();
+        let labels = err.labels().unwrap().collect::<Vec<_>>();
+
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].label(), Some("invalid Content-Length"));
+        assert_eq!(labels[0].offset(), 33);
+        assert_eq!(labels[0].len(), 1);
+    }
+}
@@ -0,0 +1,527 @@
+//! The chunked transfer coding (RFC 9112, section 7.1).
+
+use std::{fmt, ops::Range};
+
+use utils::range::{RangeSet, ToRangeSet};
+
+#[cfg(feature = "strict")]
+use crate::http::strict;
+use crate::{
+    helpers::find,
+    http::{
+        borrowed::{from_header, MAX_HEADERS},
+        flags::is_tchar,
+        Chunk, ChunkRef, Header, HeaderRef, ParserConfig, SpanRef,
+    },
+    offset::impl_offset,
+    ParseError, Span, Spanned,
+};
+
+/// Parses a body with the chunked transfer coding starting from `start`.
+///
+/// Returns the chunks, excluding the last chunk which has a size of zero, the fields of the
+/// trailer section, and the end of the body.
+///
+/// In strict mode, the chunk size lines and the trailer section are also validated, and any
+/// [`Violation`](crate::http::Violation) rejects the body.
+pub(crate) fn parse_chunks<'a>(
+    src: &'a [u8],
+    start: usize,
+    config: &ParserConfig,
+) -> Result<(Vec<ChunkRef<'a>>, Vec<HeaderRef<'a>>, usize), ParseError> {
+    let mut chunks = Vec::new();
+    let mut pos = start;
+    #[cfg(feature = "strict")]
+    let mut violations = Vec::new();
+
+    loop {
+        config.check_interrupted(pos)?;
+        let line = line_at(src, start, pos)?;
+        let (size, extension) = parse_chunk_size_line(src, line.clone())?;
+        #[cfg(feature = "strict")]
+        if config.is_strict() {
+            strict::validate_chunk_size_line(src, line.clone(), &mut violations);
+        }
+        let data_start = line.end + 2;
+
+        if size == 0 {
+            pos = data_start;
+            break;
+        }
+
+        let data_end = data_start
+            .checked_add(size)
+            .filter(|end| *end <= src.len())
+            .ok_or_else(|| {
+                ParseError::new(format!(
+                    "chunk of size {size} at {data_start} exceeds source {}",
+                    src.len()
+                ))
+                .with_label(data_start..src.len(), "chunk is truncated")
+            })?;
+
+        if src.get(data_end..data_end + 2) != Some(b"\r\n") {
+            return Err(
+                ParseError::new("chunk data is not terminated by CRLF".to_string())
+                    .with_label(data_end..src.len().min(data_end + 2), "expected CRLF"),
+            );
+        }
+
+        // Both the borrowed chunk and the owned chunk converted from it are charged.
+        config.charge(size_of::<ChunkRef<'_>>() + size_of::<Chunk>(), pos)?;
+        chunks.push(ChunkRef {
+            span: SpanRef::new(src, pos..data_end + 2),
+            size,
+            extension: extension.map(|range| SpanRef::new(src, range)),
+            data: SpanRef::new(src, data_start..data_end),
+        });
+        pos = data_end + 2;
+    }
+
+    let (trailers, end) = parse_trailers(src, start, pos, config)?;
+
+    #[cfg(feature = "strict")]
+    if config.is_strict() {
+        violations.extend(strict::validate_trailer_section(src, pos));
+        if !violations.is_empty() {
+            return Err(strict::violations_error(&violations));
+        }
+    }
+
+    Ok((chunks, trailers, end))
+}
+
+/// Parses the trailer section starting at `pos`, which is terminated by an empty line.
+///
+/// Returns the trailer fields and the end of the section.
+fn parse_trailers<'a>(
+    src: &'a [u8],
+    start: usize,
+    pos: usize,
+    config: &ParserConfig,
+) -> Result<(Vec<HeaderRef<'a>>, usize), ParseError> {
+    config.check_interrupted(pos)?;
+
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let len = match httparse::parse_headers(&src[pos..], &mut headers) {
+        Ok(httparse::Status::Complete((len, _))) => len,
+        Ok(httparse::Status::Partial) => {
+            return Err(ParseError::new("incomplete chunked body".to_string())
+                .with_label(start..src.len(), "incomplete chunked body"))
+        }
+        Err(err) => {
+            let end = find(&src[pos..], b"\r\n\r\n").map_or(src.len(), |len| pos + len + 4);
+            return Err(ParseError::from(err).with_label(pos..end, "invalid trailer section"));
+        }
+    };
+
+    let trailers = headers
+        .iter()
+        .take_while(|h| *h != &httparse::EMPTY_HEADER)
+        .map(|header| {
+            let header = from_header(src, header)?;
+            config.check_interrupted(header.span.range().start)?;
+            config.charge(
+                size_of::<HeaderRef<'_>>() + size_of::<Header>(),
+                header.span.range().start,
+            )?;
+            Ok(header)
+        })
+        .collect::<Result<_, ParseError>>()?;
+
+    Ok((trailers, pos + len))
+}
+
+/// Returns the range of the line starting at `pos`, excluding the CRLF.
+fn line_at(src: &[u8], start: usize, pos: usize) -> Result<Range<usize>, ParseError> {
+    find(&src[pos..], b"\r\n")
+        .map(|len| pos..pos + len)
+        .ok_or_else(|| {
+            ParseError::new("incomplete chunked body".to_string())
+                .with_label(start..src.len(), "incomplete chunked body")
+        })
+}
+
+/// The maximum number of significant hexadecimal digits of a chunk size.
+///
+/// This is the number of digits of the largest `usize`, so a chunk size never overflows.
+/// Leading zeros are not counted, as the grammar allows a chunk size to be padded with any
+/// number of them.
+pub const MAX_CHUNK_SIZE_DIGITS: usize = 2 * size_of::<usize>();
+
+/// The cause of a [`ParseError`] returned for an invalid chunk size line.
+///
+/// Returned by [`ParseError::chunk_size_error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChunkSizeError {
+    /// The line does not start with a hexadecimal digit, e.g. `+5` or ` 5`.
+    MissingSize,
+    /// The chunk size has more than [`MAX_CHUNK_SIZE_DIGITS`] significant digits, excluding
+    /// leading zeros.
+    TooManyDigits(usize),
+    /// The chunk size is followed by something other than a chunk extension, e.g. `5 x` or
+    /// `5 5`.
+    TrailingData,
+    /// The chunk extension is malformed, e.g. `;=x` or `;a="x`.
+    InvalidExtension,
+}
+
+impl fmt::Display for ChunkSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChunkSizeError::MissingSize => write!(f, "chunk size is missing"),
+            ChunkSizeError::TooManyDigits(digits) => write!(
+                f,
+                "chunk size has {digits} significant digits, more than the maximum of \
+                {MAX_CHUNK_SIZE_DIGITS}"
+            ),
+            ChunkSizeError::TrailingData => write!(f, "chunk size is followed by invalid data"),
+            ChunkSizeError::InvalidExtension => write!(f, "chunk extension is invalid"),
+        }
+    }
+}
+
+impl std::error::Error for ChunkSizeError {}
+
+/// Parses a chunk size line, returning the chunk size and the range of the chunk extension.
+///
+/// ```text
+/// chunk-size = 1*HEXDIG
+/// chunk-ext  = *( BWS ";" BWS chunk-ext-name [ BWS "=" BWS chunk-ext-val ] )
+/// chunk-ext-name = token
+/// chunk-ext-val  = token / quoted-string
+/// ```
+///
+/// Whitespace following the chunk size without an extension is tolerated, except in strict
+/// mode.
+fn parse_chunk_size_line(
+    src: &[u8],
+    line: Range<usize>,
+) -> Result<(usize, Option<Range<usize>>), ParseError> {
+    let invalid = |cause: ChunkSizeError, range: Range<usize>| {
+        ParseError::with_source(
+            format!(
+                "invalid chunk size line: {:?}",
+                String::from_utf8_lossy(&src[line.clone()])
+            ),
+            cause,
+        )
+        .with_label(range, cause.to_string())
+    };
+
+    let digits = src[line.clone()]
+        .iter()
+        .take_while(|b| b.is_ascii_hexdigit())
+        .count();
+    let zeros = src[line.start..line.start + digits]
+        .iter()
+        .take_while(|b| **b == b'0')
+        .count();
+    if digits == 0 {
+        return Err(invalid(ChunkSizeError::MissingSize, line.start..line.start));
+    } else if digits - zeros > MAX_CHUNK_SIZE_DIGITS {
+        return Err(invalid(
+            ChunkSizeError::TooManyDigits(digits - zeros),
+            line.start + zeros..line.start + digits,
+        ));
+    }
+
+    let size = src[line.start + zeros..line.start + digits]
+        .iter()
+        .fold(0usize, |size, b| {
+            let digit = (*b as char).to_digit(16).expect("digit is hexadecimal") as usize;
+            (size << 4) | digit
+        });
+
+    let ext_start = skip_bws(src, line.start + digits, line.end);
+    if ext_start == line.end {
+        return Ok((size, None));
+    } else if src[ext_start] != b';' {
+        return Err(invalid(ChunkSizeError::TrailingData, ext_start..line.end));
+    }
+
+    parse_chunk_ext(src, ext_start, line.end)
+        .map_err(|pos| invalid(ChunkSizeError::InvalidExtension, pos..line.end))?;
+
+    Ok((size, Some(ext_start..line.end)))
+}
+
+/// The ranges of an entry of a chunk extension.
+struct ExtensionRanges {
+    /// The entry, from the `;` to the end of the value, or of the name if there is no value.
+    entry: Range<usize>,
+    name: Range<usize>,
+    value: Option<Range<usize>>,
+}
+
+/// Parses the chunk extension at `pos..end`, returning the position of the first invalid
+/// byte if it is invalid.
+fn parse_chunk_ext(src: &[u8], mut pos: usize, end: usize) -> Result<Vec<ExtensionRanges>, usize> {
+    let token = |pos: usize| pos + src[pos..end].iter().take_while(|b| is_tchar(**b)).count();
+
+    let mut entries = Vec::new();
+    loop {
+        pos = skip_bws(src, pos, end);
+        if pos == end {
+            return Ok(entries);
+        } else if src[pos] != b';' {
+            return Err(pos);
+        }
+        let start = pos;
+
+        let name = skip_bws(src, pos + 1, end);
+        pos = token(name);
+        if pos == name {
+            return Err(name);
+        }
+        let name = name..pos;
+
+        let eq = skip_bws(src, pos, end);
+        if eq == end || src[eq] != b'=' {
+            entries.push(ExtensionRanges {
+                entry: start..pos,
+                name,
+                value: None,
+            });
+            continue;
+        }
+
+        let value = skip_bws(src, eq + 1, end);
+        pos = if value < end && src[value] == b'"' {
+            quoted_string_end(src, value, end).ok_or(value)?
+        } else {
+            token(value)
+        };
+        if pos == value {
+            return Err(value);
+        }
+        entries.push(ExtensionRanges {
+            entry: start..pos,
+            name,
+            value: Some(value..pos),
+        });
+    }
+}
+
+/// An entry of a chunk extension, such as `;name=value`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkExtension {
+    pub(crate) span: Span,
+
+    /// The name of the entry.
+    pub name: Span,
+    /// The value of the entry, if present, including the quotes of a quoted string.
+    pub value: Option<Span>,
+}
+
+impl_offset!(ChunkExtension { span, name, value });
+
+impl Spanned for ChunkExtension {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for ChunkExtension {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+/// Returns the entries of a chunk extension, or no entries if it is invalid.
+pub(crate) fn parse_extensions(extension: &Span) -> Vec<ChunkExtension> {
+    let src = extension.as_bytes();
+    parse_chunk_ext(src, 0, src.len())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|entry| ChunkExtension {
+            span: extension.slice(entry.entry),
+            name: extension.slice(entry.name),
+            value: entry.value.map(|value| extension.slice(value)),
+        })
+        .collect()
+}
+
+/// Returns the end of the quoted string starting at `start`, if it is terminated before `end`.
+fn quoted_string_end(src: &[u8], start: usize, end: usize) -> Option<usize> {
+    let mut pos = start + 1;
+    while pos < end {
+        match src[pos] {
+            b'"' => return Some(pos + 1),
+            b'\\' if pos + 1 < end => pos += 2,
+            b'\t' | b' ' | 0x21..=0x7E | 0x80..=0xFF => pos += 1,
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+/// Returns the position of the first byte at or after `pos` which is not whitespace.
+fn skip_bws(src: &[u8], pos: usize, end: usize) -> usize {
+    pos + src[pos..end]
+        .iter()
+        .take_while(|b| matches!(b, b' ' | b'\t'))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chunks() {
+        let src = b"5\r\nhello\r\nA;name=value\r\n0123456789\r\n0\r\nTrailer: x\r\n\r\n";
+        let (chunks, trailers, end) = parse_chunks(src, 0, &ParserConfig::default()).unwrap();
+
+        assert_eq!(end, src.len());
+        assert_eq!(chunks.len(), 2);
+
+        assert_eq!(chunks[0].span.range(), 0..10);
+        assert_eq!(chunks[0].size, 5);
+        assert_eq!(chunks[0].extension, None);
+        assert_eq!(chunks[0].data, "hello");
+
+        assert_eq!(chunks[1].size, 10);
+        assert_eq!(chunks[1].extension.as_ref().unwrap(), ";name=value");
+        assert_eq!(chunks[1].data, "0123456789");
+
+        assert_eq!(trailers.len(), 1);
+        assert_eq!(trailers[0].span.range(), 39..51);
+        assert_eq!(trailers[0].name, "Trailer");
+        assert_eq!(trailers[0].value, "x");
+    }
+
+    #[test]
+    fn test_parse_chunks_empty() {
+        let (chunks, trailers, end) =
+            parse_chunks(b"0\r\n\r\n", 0, &ParserConfig::default()).unwrap();
+
+        assert!(chunks.is_empty());
+        assert!(trailers.is_empty());
+        assert_eq!(end, 5);
+    }
+
+    #[test]
+    fn test_parse_chunks_invalid() {
+        for src in [
+            b"".as_slice(),
+            b"0\r\n",
+            b"\r\n\r\n",
+            b"x\r\nhello\r\n0\r\n\r\n",
+            b"5 x\r\nhello\r\n0\r\n\r\n",
+            b"5\r\nhelloX\r\n0\r\n\r\n",
+            b"6\r\nhello\r\n0\r\n\r\n",
+            b"ffffffffffffffffffff\r\n",
+            b"5;\r\nhello\r\n0\r\n\r\n",
+            b"0\r\nTrailer: x\r\n",
+            b"0\r\nTrailer x\r\n\r\n",
+        ] {
+            assert!(
+                parse_chunks(src, 0, &ParserConfig::default()).is_err(),
+                "{src:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_chunk_extensions() {
+        let src = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+            2 ; a = b;c;d=\"x;y\"\r\nhi\r\n1\r\n!\r\n0\r\n\r\n";
+        let response = crate::http::parse_response(src).unwrap();
+        crate::testutil::harness::assert_response(src, &response);
+        let chunks = response.body().unwrap().chunked.as_ref().unwrap().chunks();
+
+        let extensions = chunks[0].extensions();
+        assert_eq!(extensions.len(), 3);
+        assert_eq!(extensions[0].span, b"; a = b".as_slice());
+        assert_eq!(extensions[0].name, b"a".as_slice());
+        assert_eq!(extensions[0].value.as_ref().unwrap(), b"b".as_slice());
+        assert_eq!(extensions[0].name.indices(), &RangeSet::from(51..52));
+        assert_eq!(extensions[1].span, b";c".as_slice());
+        assert!(extensions[1].value.is_none());
+        assert_eq!(extensions[2].value.as_ref().unwrap(), b"\"x;y\"".as_slice());
+
+        assert!(chunks[1].extensions().is_empty());
+    }
+
+    #[test]
+    fn test_parse_chunk_size_line() {
+        let parse = |line: &[u8]| parse_chunk_size_line(line, 0..line.len());
+        let cause = |line: &[u8]| *parse(line).unwrap_err().chunk_size_error().unwrap();
+
+        assert_eq!(parse(b"00aF").unwrap(), (0xaf, None));
+        assert_eq!(parse(b"5 \t").unwrap(), (5, None));
+        assert_eq!(parse(b"5 ; a = b ;c").unwrap(), (5, Some(2..12)));
+        assert_eq!(parse(b"5;a=\"x;\\\"y\"").unwrap(), (5, Some(1..11)));
+        // Leading zeros are not limited.
+        let padded = format!("{:0>1$}", "a", MAX_CHUNK_SIZE_DIGITS + 8);
+        assert_eq!(parse(padded.as_bytes()).unwrap(), (0xa, None));
+        assert_eq!(
+            parse(&[b'0'; MAX_CHUNK_SIZE_DIGITS + 1]).unwrap(),
+            (0, None)
+        );
+        let max = format!("00{:x}", usize::MAX);
+        assert_eq!(parse(max.as_bytes()).unwrap(), (usize::MAX, None));
+
+        assert_eq!(cause(b"+5"), ChunkSizeError::MissingSize);
+        assert_eq!(cause(b" 5"), ChunkSizeError::MissingSize);
+        let overflow = format!("0{:0<1$}", "1", MAX_CHUNK_SIZE_DIGITS + 1);
+        assert_eq!(
+            cause(overflow.as_bytes()),
+            ChunkSizeError::TooManyDigits(MAX_CHUNK_SIZE_DIGITS + 1)
+        );
+        let err = parse(overflow.as_bytes()).unwrap_err();
+        assert_eq!(err.ranges().collect::<Vec<_>>(), [&(1..overflow.len())]);
+        assert_eq!(cause(b"5 5"), ChunkSizeError::TrailingData);
+        assert_eq!(cause(b"5x"), ChunkSizeError::TrailingData);
+        for ext in [
+            &b"5;=x"[..],
+            b"5;a=",
+            b"5;a=\"x",
+            b"5;a b",
+            b"5;a;",
+            b"5;a=x y",
+        ] {
+            assert_eq!(cause(ext), ChunkSizeError::InvalidExtension, "{ext:?}");
+        }
+
+        let err = parse(b"5;a=,").unwrap_err();
+        assert_eq!(err.ranges().collect::<Vec<_>>(), [&(4..5)]);
+    }
+
+    #[cfg(feature = "strict")]
+    #[test]
+    fn test_parse_chunks_strict() {
+        let config = ParserConfig::new().strict(true);
+
+        let src = b"5 ;a=b\r\nhello\r\n0\r\nTrailer: x\r\n\r\n";
+        assert!(parse_chunks(src, 0, &config).is_ok());
+
+        for (src, violation) in [
+            (b"5 \r\nhello\r\n0\r\n\r\n".as_slice(), 1..2),
+            (b"5\r\nhello\r\n0\t\r\n\r\n", 11..12),
+            (b"0\r\nTrailer: x\n\r\n", 13..14),
+            (b"0\r\nTrailer: x\r\n\n", 15..16),
+        ] {
+            assert!(
+                parse_chunks(src, 0, &ParserConfig::default()).is_ok(),
+                "{src:?}"
+            );
+
+            let err = parse_chunks(src, 0, &config).unwrap_err();
+            assert_eq!(err.ranges().collect::<Vec<_>>(), [&violation], "{src:?}");
+        }
+    }
+
+    #[test]
+    fn test_parse_chunks_deadline() {
+        let src = b"5\r\nhello\r\n0\r\n\r\n";
+        let config = ParserConfig::new().deadline(std::time::Instant::now());
+
+        let err = parse_chunks(src, 0, &config).unwrap_err();
+        assert_eq!(err.ranges().collect::<Vec<_>>(), [&(0..0)]);
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{
+    http::{value, Header, HttpDate, Session},
+    Span,
+};
+
+/// The kind of validator used to revalidate a cached response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ValidatorKind {
+    /// An `ETag` validator, echoed in an `If-None-Match` header.
+    ETag,
+    /// A `Last-Modified` validator, echoed in an `If-Modified-Since` header.
+    LastModified,
+}
+
+/// A cache validator in a response, correlated with a conditional request which used it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Revalidation {
+    /// The kind of validator.
+    pub kind: ValidatorKind,
+    /// The index of the exchange whose response provided the validator.
+    pub origin: usize,
+    /// The index of the exchange whose request used the validator.
+    pub conditional: usize,
+    /// The `ETag` or `Last-Modified` header of the original response.
+    pub validator: Header,
+    /// The `If-None-Match` or `If-Modified-Since` header of the conditional request.
+    pub condition: Header,
+    /// The entity-tag within the `If-None-Match` header which matched the validator.
+    ///
+    /// This is `None` for `Last-Modified` validators.
+    pub matched: Option<Span>,
+}
+
+impl Session {
+    /// Returns the conditional requests in the session which revalidate a previously
+    /// received response.
+    ///
+    /// A request revalidates a response if it targets the same resource as an earlier
+    /// request, and one of its `If-None-Match` entity-tags weakly matches the `ETag` of the
+    /// earlier response, or its `If-Modified-Since` date equals the `Last-Modified` date of
+    /// the earlier response. If several earlier responses match, the most recent one is
+    /// used.
+    pub fn revalidations(&self) -> Vec<Revalidation> {
+        let mut revalidations = Vec::new();
+
+        for (idx, exchange) in self.exchanges.iter().enumerate() {
+            let request = &exchange.request;
+            let target = request.request.target.as_str();
+
+            let earlier = self.exchanges[..idx]
+                .iter()
+                .enumerate()
+                .rev()
+                .filter(|(_, earlier)| earlier.request.request.target.as_str() == target)
+                .filter_map(|(idx, earlier)| earlier.response.as_ref().map(|res| (idx, res)));
+
+            for condition in request.headers_with_name("If-None-Match") {
+                let found = earlier.clone().find_map(|(origin, response)| {
+                    let validator = response.headers_with_name("ETag").next()?;
+                    let matched = entity_tags(&condition.value.0)
+                        .find(|tag| weak_eq(tag.as_bytes(), validator.value.as_bytes()))?;
+
+                    Some((origin, validator, matched))
+                });
+
+                if let Some((origin, validator, matched)) = found {
+                    revalidations.push(Revalidation {
+                        kind: ValidatorKind::ETag,
+                        origin,
+                        conditional: idx,
+                        validator: validator.clone(),
+                        condition: condition.clone(),
+                        matched: Some(matched),
+                    });
+                }
+            }
+
+            for condition in request.headers_with_name("If-Modified-Since") {
+                let Some(date) = parse_date(&condition.value.0) else {
+                    continue;
+                };
+
+                let found = earlier.clone().find_map(|(origin, response)| {
+                    let validator = response
+                        .headers_with_name("Last-Modified")
+                        .find(|header| parse_date(&header.value.0) == Some(date))?;
+
+                    Some((origin, validator))
+                });
+
+                if let Some((origin, validator)) = found {
+                    revalidations.push(Revalidation {
+                        kind: ValidatorKind::LastModified,
+                        origin,
+                        conditional: idx,
+                        validator: validator.clone(),
+                        condition: condition.clone(),
+                        matched: None,
+                    });
+                }
+            }
+        }
+
+        revalidations
+    }
+}
+
+/// Parses an HTTP date from a header value.
+fn parse_date(value: &Span) -> Option<HttpDate> {
+    value::parse_http_date(value.as_bytes()).ok()
+}
+
+/// Returns an iterator over the entity-tags in an `If-None-Match` header value.
+///
+/// The wildcard `*` is not yielded, as it does not correspond to a specific validator.
+fn entity_tags(value: &Span) -> impl Iterator<Item = Span> + '_ {
+    let bytes = value.as_bytes();
+    let mut pos = 0;
+
+    std::iter::from_fn(move || loop {
+        while pos < bytes.len() && matches!(bytes[pos], b' ' | b'\t' | b',') {
+            pos += 1;
+        }
+
+        if pos >= bytes.len() {
+            return None;
+        }
+
+        let start = pos;
+        if bytes[pos..].starts_with(b"W/") {
+            pos += 2;
+        }
+
+        if bytes.get(pos) != Some(&b'"') {
+            // Skip anything which is not an entity-tag, such as the wildcard.
+            while pos < bytes.len() && bytes[pos] != b',' {
+                pos += 1;
+            }
+            continue;
+        }
+
+        let Some(len) = bytes[pos + 1..].iter().position(|b| *b == b'"') else {
+            pos = bytes.len();
+            continue;
+        };
+
+        pos += len + 2;
+
+        return Some(value.slice(start..pos));
+    })
+}
+
+/// Compares two entity-tags using the weak comparison function of RFC 9110, section 8.8.3.2.
+fn weak_eq(a: &[u8], b: &[u8]) -> bool {
+    let a = a.strip_prefix(b"W/").unwrap_or(a);
+    let b = b.strip_prefix(b"W/").unwrap_or(b);
+
+    a == b
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil::harness::assert_session;
+    use utils::range::IndexRanges;
+
+    use crate::{Source, Spanned};
+
+    use super::*;
+
+    const SENT: &[u8] = b"\
+        GET /a HTTP/1.1\r\nHost: localhost\r\n\r\n\
+        GET /b HTTP/1.1\r\nHost: localhost\r\n\r\n\
+        GET /a HTTP/1.1\r\nHost: localhost\r\nIf-None-Match: \"x\", W/\"abc\"\r\n\r\n\
+        GET /b HTTP/1.1\r\nHost: localhost\r\nIf-Modified-Since: Wed, 21 Oct 2015 07:28:00 GMT\r\n\r\n\
+        GET /b HTTP/1.1\r\nHost: localhost\r\nIf-None-Match: \"abc\"\r\n\r\n";
+
+    const RECEIVED: &[u8] = b"\
+        HTTP/1.1 200 OK\r\nETag: \"abc\"\r\nContent-Length: 0\r\n\r\n\
+        HTTP/1.1 200 OK\r\nLast-Modified: Wed, 21 Oct 2015 07:28:00 GMT\r\nContent-Length: 0\r\n\r\n\
+        HTTP/1.1 304 Not Modified\r\n\r\n\
+        HTTP/1.1 304 Not Modified\r\n\r\n\
+        HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+
+    #[test]
+    fn test_revalidations() {
+        let session = Session::new_from_slice(SENT, RECEIVED).unwrap();
+        assert_session(SENT, RECEIVED, &session);
+        let revalidations = session.revalidations();
+
+        assert_eq!(revalidations.len(), 2);
+
+        let etag = &revalidations[0];
+        assert_eq!(etag.kind, ValidatorKind::ETag);
+        assert_eq!((etag.origin, etag.conditional), (0, 2));
+        assert_eq!(etag.validator.value.as_bytes(), b"\"abc\"");
+        assert_eq!(etag.condition.value.as_bytes(), b"\"x\", W/\"abc\"");
+        assert_eq!(
+            SENT.index_ranges(etag.matched.as_ref().unwrap().indices()),
+            b"W/\"abc\""
+        );
+
+        let last_modified = &revalidations[1];
+        assert_eq!(last_modified.kind, ValidatorKind::LastModified);
+        assert_eq!((last_modified.origin, last_modified.conditional), (1, 3));
+        assert_eq!(
+            RECEIVED.index_ranges(last_modified.validator.span().indices()),
+            b"Last-Modified: Wed, 21 Oct 2015 07:28:00 GMT\r\n"
+        );
+    }
+
+    #[test]
+    fn test_entity_tags() {
+        let value = Span::new_bytes(
+            &Source::from_static(b"*, \"a,b\" ,W/\"c\", bogus, \"d"),
+            0..26,
+        );
+
+        let tags = entity_tags(&value).collect::<Vec<_>>();
+
+        assert_eq!(tags.len(), 2);
+        assert_eq!(tags[0].as_bytes(), b"\"a,b\"");
+        assert_eq!(tags[1].as_bytes(), b"W/\"c\"");
+    }
+}
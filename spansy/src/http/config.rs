@@ -0,0 +1,234 @@
+use std::{fmt, sync::Arc, time::Instant};
+
+use crate::{
+    http::{ContentDecoders, ParseMetrics, ParseObserver},
+    MemoryBudget, ParseError,
+};
+
+/// Configuration of the HTTP parser.
+///
+/// The default configuration parses messages leniently.
+#[derive(Clone, Default)]
+pub struct ParserConfig {
+    strict: bool,
+    content_length: ContentLengthPolicy,
+    truncated_body: bool,
+    deadline: Option<Instant>,
+    budget: Option<Arc<MemoryBudget>>,
+    metrics: Option<Arc<dyn ParseMetrics>>,
+    observer: Option<Arc<dyn ParseObserver>>,
+    parse_info: bool,
+    decoders: Option<Arc<ContentDecoders>>,
+}
+
+/// How a message with multiple, differing `Content-Length` values is handled.
+///
+/// Repeated identical values, e.g. `Content-Length: 42, 42`, are always accepted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ContentLengthPolicy {
+    /// The message is rejected, as required by RFC 9112, section 6.3.
+    #[default]
+    Reject,
+    /// The first value is used.
+    First,
+    /// The largest value is used.
+    Largest,
+}
+
+impl fmt::Debug for ParserConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParserConfig")
+            .field("strict", &self.strict)
+            .field("content_length", &self.content_length)
+            .field("truncated_body", &self.truncated_body)
+            .field("deadline", &self.deadline)
+            .field("budget", &self.budget)
+            .field("metrics", &self.metrics.is_some())
+            .field("observer", &self.observer.is_some())
+            .field("parse_info", &self.parse_info)
+            .field("decoders", &self.decoders)
+            .finish()
+    }
+}
+
+impl ParserConfig {
+    /// Returns a new parser configuration with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether messages are validated against the strict syntax of RFC 9110 and
+    /// RFC 9112.
+    ///
+    /// In strict mode, a message is rejected if its head, or the chunk size lines and trailer
+    /// section of a chunked body, contain any [`Violation`](crate::http::Violation).
+    #[cfg(feature = "strict")]
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets how a message with multiple, differing `Content-Length` values is handled.
+    pub fn content_length(mut self, policy: ContentLengthPolicy) -> Self {
+        self.content_length = policy;
+        self
+    }
+
+    /// Sets whether a body which is shorter than its `Content-Length` is accepted.
+    ///
+    /// Captures of a connection are often cut off, so the final message of a transcript may
+    /// be incomplete. If enabled, such a body spans the rest of the source, and the number
+    /// of missing bytes is recorded in [`Body::truncated`](crate::http::Body::truncated).
+    /// Otherwise the message is rejected. Chunked bodies must always be complete.
+    pub fn truncated_body(mut self, allow: bool) -> Self {
+        self.truncated_body = allow;
+        self
+    }
+
+    /// Sets the time by which parsing must complete.
+    ///
+    /// The deadline is checked before each message and within the loops scanning headers and
+    /// chunks, so that a hostile source can not occupy a thread indefinitely. A message which
+    /// is being parsed when the deadline passes is rejected.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the memory budget charged for the structures allocated while parsing.
+    ///
+    /// The budget is shared by every parse using this configuration, and a message which
+    /// exceeds it is rejected with an error whose cause is
+    /// [`BudgetExceeded`](crate::BudgetExceeded).
+    pub fn memory_budget(mut self, budget: Arc<MemoryBudget>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Sets the sink which is notified of every message parsed.
+    pub fn metrics(mut self, metrics: Arc<dyn ParseMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Sets the observer which is notified of the progress of every message parsed, and which
+    /// can cancel parsing.
+    pub fn observer(mut self, observer: Arc<dyn ParseObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Sets whether a [`ParseInfo`](crate::http::ParseInfo) is attached to every message
+    /// parsed, retrievable with [`Request::parse_info`](crate::http::Request::parse_info) and
+    /// [`Response::parse_info`](crate::http::Response::parse_info).
+    ///
+    /// This times every message, so it is disabled by default.
+    pub fn parse_info(mut self, enable: bool) -> Self {
+        self.parse_info = enable;
+        self
+    }
+
+    /// Sets the decoders of the content codings of message bodies.
+    ///
+    /// A body whose codings all have a decoder is decoded while parsing, and its content is
+    /// [`BodyContent::Compressed`](crate::http::BodyContent::Compressed), which is parsed as
+    /// JSON if it has an `application/json` content type. Otherwise, the content of a body with
+    /// a coding is [`BodyContent::Binary`](crate::http::BodyContent::Binary). The decoded
+    /// content is charged to the memory budget.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use spansy::{
+    ///     http::{parse_response_with_config, BodyContent, ContentDecoders, ParserConfig},
+    ///     Spanned,
+    /// };
+    ///
+    /// let config = ParserConfig::default().content_decoders(Arc::new(ContentDecoders::default()));
+    /// let src = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+    ///     Content-Encoding: identity\r\nContent-Length: 8\r\n\r\n{\"a\":42}";
+    /// let response = parse_response_with_config(src, &config).unwrap();
+    ///
+    /// assert_eq!(response.json("/a").unwrap().span(), "42");
+    /// ```
+    pub fn content_decoders(mut self, decoders: Arc<ContentDecoders>) -> Self {
+        self.decoders = Some(decoders);
+        self
+    }
+
+    /// Returns `true` if strict validation is enabled.
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Returns how a message with multiple, differing `Content-Length` values is handled.
+    pub fn content_length_policy(&self) -> ContentLengthPolicy {
+        self.content_length
+    }
+
+    /// Returns `true` if a body shorter than its `Content-Length` is accepted.
+    pub fn allows_truncated_body(&self) -> bool {
+        self.truncated_body
+    }
+
+    /// Returns `true` if a [`ParseInfo`](crate::http::ParseInfo) is attached to every message
+    /// parsed.
+    pub fn collects_parse_info(&self) -> bool {
+        self.parse_info
+    }
+
+    /// Returns the time by which parsing must complete, if one is set.
+    pub fn deadline_at(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns an error if parsing at `offset` is cancelled or past the deadline.
+    pub(crate) fn check_interrupted(&self, offset: usize) -> Result<(), ParseError> {
+        if self
+            .observer
+            .as_ref()
+            .is_some_and(|observer| observer.is_cancelled())
+        {
+            return Err(
+                ParseError::new("parsing was cancelled").with_label(offset..offset, "cancelled")
+            );
+        }
+
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(ParseError::new("parsing deadline exceeded")
+                .with_label(offset..offset, "deadline exceeded"));
+        }
+
+        Ok(())
+    }
+
+    /// Charges `bytes` to the memory budget, if one is set, for a structure parsed at
+    /// `offset`.
+    pub(crate) fn charge(&self, bytes: usize, offset: usize) -> Result<(), ParseError> {
+        match &self.budget {
+            Some(budget) => budget.charge(bytes, offset),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the metrics sink, if one is set.
+    pub(crate) fn metrics_sink(&self) -> Option<&dyn ParseMetrics> {
+        self.metrics.as_deref()
+    }
+
+    /// Returns the content decoders, if they are set.
+    pub(crate) fn decoders(&self) -> Option<&ContentDecoders> {
+        self.decoders.as_deref()
+    }
+
+    /// Returns the observer, if one is set.
+    pub(crate) fn observer_ref(&self) -> Option<&dyn ParseObserver> {
+        self.observer.as_deref()
+    }
+}
@@ -0,0 +1,173 @@
+use crate::http::{Exchange, Request, Response};
+
+/// Whether a connection persists after a message, following RFC 9112, section 9.3.
+///
+/// A connection is closed after a message with the `close` connection option. Otherwise,
+/// HTTP/1.1 connections persist, and HTTP/1.0 connections only persist if the message has
+/// the `keep-alive` connection option.
+///
+/// # Example
+///
+/// ```
+/// use spansy::http::{parse_request, ConnectionBehavior};
+///
+/// let request = parse_request(b"GET / HTTP/1.1\r\nConnection: close\r\n\r\n").unwrap();
+/// assert_eq!(request.connection(), ConnectionBehavior::Close);
+///
+/// let request = parse_request(b"GET / HTTP/1.0\r\nConnection: Keep-Alive\r\n\r\n").unwrap();
+/// assert_eq!(request.connection(), ConnectionBehavior::KeepAlive);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConnectionBehavior {
+    /// The connection persists, so more messages may follow.
+    KeepAlive,
+    /// The connection is closed after the message, so no more messages follow.
+    Close,
+}
+
+impl ConnectionBehavior {
+    /// Returns the behavior of a message with the given HTTP version, e.g. `HTTP/1.1`, and
+    /// `Connection` header values.
+    pub(crate) fn new<'a>(version: &[u8], connection: impl IntoIterator<Item = &'a [u8]>) -> Self {
+        let mut keep_alive = false;
+        for option in connection
+            .into_iter()
+            .flat_map(|value| value.split(|b| *b == b','))
+            .map(<[u8]>::trim_ascii)
+        {
+            if option.eq_ignore_ascii_case(b"close") {
+                return ConnectionBehavior::Close;
+            }
+            keep_alive |= option.eq_ignore_ascii_case(b"keep-alive");
+        }
+
+        if version == b"HTTP/1.0" && !keep_alive {
+            ConnectionBehavior::Close
+        } else {
+            ConnectionBehavior::KeepAlive
+        }
+    }
+
+    /// Returns `true` if the connection is closed after the message.
+    pub fn is_close(&self) -> bool {
+        *self == ConnectionBehavior::Close
+    }
+}
+
+impl Request {
+    /// Returns whether the connection persists after the request.
+    pub fn connection(&self) -> ConnectionBehavior {
+        ConnectionBehavior::new(
+            self.request.version().as_bytes(),
+            self.headers_with_name("Connection")
+                .map(|header| header.value.as_bytes()),
+        )
+    }
+}
+
+impl Response {
+    /// Returns whether the connection persists after the response.
+    pub fn connection(&self) -> ConnectionBehavior {
+        ConnectionBehavior::new(
+            self.status.version().as_bytes(),
+            self.headers_with_name("Connection")
+                .map(|header| header.value.as_bytes()),
+        )
+    }
+}
+
+impl Exchange {
+    /// Returns whether the connection persists after the exchange, which is closed if either
+    /// the request or the response closes it.
+    pub fn connection(&self) -> ConnectionBehavior {
+        let response = self.response.as_ref().map(Response::connection);
+        if self.request.connection().is_close() || response.is_some_and(|c| c.is_close()) {
+            ConnectionBehavior::Close
+        } else {
+            ConnectionBehavior::KeepAlive
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil::harness::{assert_request, assert_response};
+    use crate::{
+        http::{parse_request, parse_response, Session},
+        Spanned,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_connection_behavior() {
+        for (version, connection, expected) in [
+            ("HTTP/1.1", None, ConnectionBehavior::KeepAlive),
+            (
+                "HTTP/1.1",
+                Some("keep-alive, Close"),
+                ConnectionBehavior::Close,
+            ),
+            ("HTTP/1.1", Some("upgrade"), ConnectionBehavior::KeepAlive),
+            ("HTTP/1.0", None, ConnectionBehavior::Close),
+            (
+                "HTTP/1.0",
+                Some("Keep-Alive"),
+                ConnectionBehavior::KeepAlive,
+            ),
+            (
+                "HTTP/1.0",
+                Some("keep-alive, close"),
+                ConnectionBehavior::Close,
+            ),
+        ] {
+            let connection = connection
+                .map(|value| format!("Connection: {value}\r\n"))
+                .unwrap_or_default();
+
+            let src = format!("GET / {version}\r\n{connection}\r\n");
+            let request = parse_request(src.as_bytes()).unwrap();
+            assert_request(src.as_bytes(), &request);
+            assert_eq!(request.connection(), expected, "{version} {connection}");
+
+            let src = format!("{version} 204 No Content\r\n{connection}\r\n");
+            let response = parse_response(src.as_bytes()).unwrap();
+            assert_response(src.as_bytes(), &response);
+            assert_eq!(response.connection(), expected, "{version} {connection}");
+        }
+    }
+
+    #[test]
+    fn test_exchange_connection() {
+        let session = Session::new_from_slice(
+            b"GET / HTTP/1.1\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 0\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(
+            session.exchanges[0].request.connection(),
+            ConnectionBehavior::KeepAlive
+        );
+        assert!(session.exchanges[0].connection().is_close());
+
+        // No request can follow a request which closes the connection.
+        let sent = b"GET /a HTTP/1.1\r\nConnection: close\r\n\r\nGET /b HTTP/1.1\r\n\r\n";
+        assert!(Session::new_from_slice(sent, b"").is_err());
+        assert!(Session::new_from_slice(&sent[..38], b"").is_ok());
+    }
+
+    #[test]
+    fn test_close_delimited_body() {
+        let src = b"HTTP/1.0 200 OK\r\nContent-Type: text/plain\r\n\r\nhello\r\n";
+        let response = parse_response(src).unwrap();
+        assert_response(src, &response);
+        assert_eq!(response.body.unwrap().span(), b"hello\r\n".as_slice());
+        assert_eq!(response.span, 0..src.len());
+
+        // A body on a persistent connection must have a length.
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\n\r\nhello").is_err());
+        let response = parse_response(b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n").unwrap();
+        assert!(response.body.is_none());
+    }
+}
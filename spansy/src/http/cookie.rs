@@ -0,0 +1,432 @@
+use std::time::Duration;
+
+use utils::range::{Difference, RangeSet, ToRangeSet};
+
+use crate::{
+    http::{
+        value::{self, ValueError},
+        HeaderValue, HttpDate,
+    },
+    offset::impl_offset,
+    Offset, ParseError, Source, Span, Spanned,
+};
+
+/// A cookie name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CookieName(pub(crate) Span<str>);
+
+impl CookieName {
+    /// Returns the cookie name as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl_offset!(CookieName(_));
+
+impl Spanned<str> for CookieName {
+    fn span(&self) -> &Span<str> {
+        &self.0
+    }
+}
+
+impl ToRangeSet<usize> for CookieName {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.0.indices.clone()
+    }
+}
+
+/// A cookie value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CookieValue(pub(crate) Span<str>);
+
+impl CookieValue {
+    /// Returns the cookie value as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl_offset!(CookieValue(_));
+
+impl Spanned<str> for CookieValue {
+    fn span(&self) -> &Span<str> {
+        &self.0
+    }
+}
+
+impl ToRangeSet<usize> for CookieValue {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.0.indices.clone()
+    }
+}
+
+/// The value of the `SameSite` cookie attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum SameSite {
+    /// The cookie is only sent with same-site requests.
+    Strict,
+    /// The cookie is also sent with top-level cross-site navigations.
+    Lax,
+    /// The cookie is sent with all requests.
+    None,
+}
+
+/// The typed value of a cookie attribute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum CookieAttributeKind {
+    /// The `Expires` attribute.
+    Expires(HttpDate),
+    /// The `Max-Age` attribute.
+    ///
+    /// A value which is zero or negative is represented as a zero duration, meaning the
+    /// cookie expires immediately.
+    MaxAge(Duration),
+    /// The `Domain` attribute.
+    Domain,
+    /// The `Path` attribute.
+    Path,
+    /// The `Secure` attribute.
+    Secure,
+    /// The `HttpOnly` attribute.
+    HttpOnly,
+    /// The `SameSite` attribute.
+    SameSite(SameSite),
+    /// An attribute which is not recognized, or whose value is malformed.
+    ///
+    /// User agents ignore such attributes.
+    Other,
+}
+
+/// A cookie attribute, excluding the `;` separator and surrounding whitespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CookieAttribute {
+    pub(crate) span: Span<str>,
+
+    /// The attribute name.
+    pub name: Span<str>,
+    /// The attribute value, if present.
+    pub value: Option<Span<str>>,
+    /// The typed attribute.
+    pub kind: CookieAttributeKind,
+}
+
+impl_offset!(CookieAttribute { span, name, value; kind });
+
+impl Spanned<str> for CookieAttribute {
+    fn span(&self) -> &Span<str> {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for CookieAttribute {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+/// A `Set-Cookie` header value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetCookie {
+    pub(crate) span: Span<str>,
+
+    /// The cookie name.
+    pub name: CookieName,
+    /// The cookie value.
+    pub value: CookieValue,
+    /// The cookie attributes, in the order they appear.
+    pub attributes: Vec<CookieAttribute>,
+}
+
+impl SetCookie {
+    /// Returns the expiry date of the cookie, if an `Expires` attribute is present.
+    ///
+    /// If multiple are present, the last one takes effect.
+    pub fn expires(&self) -> Option<HttpDate> {
+        self.attributes
+            .iter()
+            .rev()
+            .find_map(|attr| match attr.kind {
+                CookieAttributeKind::Expires(date) => Some(date),
+                _ => None,
+            })
+    }
+
+    /// Returns the maximum age of the cookie, if a `Max-Age` attribute is present.
+    ///
+    /// If multiple are present, the last one takes effect. Note that `Max-Age` takes
+    /// precedence over `Expires`.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.attributes
+            .iter()
+            .rev()
+            .find_map(|attr| match attr.kind {
+                CookieAttributeKind::MaxAge(duration) => Some(duration),
+                _ => None,
+            })
+    }
+
+    /// Returns the `SameSite` policy of the cookie, if present.
+    ///
+    /// If multiple are present, the last one takes effect.
+    pub fn same_site(&self) -> Option<SameSite> {
+        self.attributes
+            .iter()
+            .rev()
+            .find_map(|attr| match attr.kind {
+                CookieAttributeKind::SameSite(same_site) => Some(same_site),
+                _ => None,
+            })
+    }
+
+    /// Returns the last attribute with the given name (case-insensitive).
+    pub fn attribute(&self, name: &str) -> Option<&CookieAttribute> {
+        self.attributes
+            .iter()
+            .rev()
+            .find(|attr| attr.name.as_str().eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the indices of the cookie excluding the value.
+    pub fn without_value(&self) -> RangeSet<usize> {
+        self.span.indices.difference(&self.value.0.indices)
+    }
+}
+
+impl_offset!(SetCookie {
+    span,
+    name,
+    value,
+    attributes
+});
+
+impl Spanned<str> for SetCookie {
+    fn span(&self) -> &Span<str> {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for SetCookie {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+/// Parses a `Set-Cookie` header value according to RFC 6265, section 5.2.
+pub fn parse_set_cookie(value: &HeaderValue) -> Result<SetCookie, ParseError> {
+    let mut cookie = parse_set_cookie_from_bytes(value.0.data.clone().into())?;
+    cookie.offset(value.0.indices.min().unwrap_or_default());
+
+    Ok(cookie)
+}
+
+/// Parses a `Set-Cookie` header value, with spans relative to the start of `src`.
+fn parse_set_cookie_from_bytes(src: Source) -> Result<SetCookie, ParseError> {
+    let src_str = std::str::from_utf8(&src)?;
+
+    let (pair, attributes) = match src_str.split_once(';') {
+        Some((pair, attributes)) => (pair, Some(attributes)),
+        None => (src_str, None),
+    };
+
+    let (name, value) = pair
+        .split_once('=')
+        .ok_or_else(|| ParseError::new("cookie name-value pair is missing '='".to_string()))?;
+
+    let name = trim_wsp(name);
+    if name.is_empty() {
+        return Err(ParseError::new("cookie name is empty".to_string()));
+    }
+
+    let attributes = attributes
+        .into_iter()
+        .flat_map(|attributes| attributes.split(';'))
+        .map(trim_wsp)
+        .filter(|attr| !attr.is_empty())
+        .map(|attr| parse_attribute(&src, attr))
+        .collect();
+
+    Ok(SetCookie {
+        span: Span::new_from_str(&src, trim_wsp(src_str)),
+        name: CookieName(Span::new_from_str(&src, name)),
+        value: CookieValue(Span::new_from_str(&src, trim_wsp(value))),
+        attributes,
+    })
+}
+
+/// Parses a cookie attribute, which must be a slice of `src`.
+fn parse_attribute(src: &Source, attr: &str) -> CookieAttribute {
+    let (name, value) = match attr.split_once('=') {
+        Some((name, value)) => (trim_wsp(name), Some(trim_wsp(value))),
+        None => (attr, None),
+    };
+
+    let kind = match (name.to_ascii_lowercase().as_str(), value) {
+        ("expires", Some(value)) => HttpDate::parse_cookie_date(value)
+            .map(CookieAttributeKind::Expires)
+            .unwrap_or(CookieAttributeKind::Other),
+        ("max-age", Some(value)) => parse_max_age(value)
+            .map(CookieAttributeKind::MaxAge)
+            .unwrap_or(CookieAttributeKind::Other),
+        ("domain", _) => CookieAttributeKind::Domain,
+        ("path", _) => CookieAttributeKind::Path,
+        ("secure", _) => CookieAttributeKind::Secure,
+        ("httponly", _) => CookieAttributeKind::HttpOnly,
+        ("samesite", Some(value)) => parse_same_site(value)
+            .map(CookieAttributeKind::SameSite)
+            .unwrap_or(CookieAttributeKind::Other),
+        _ => CookieAttributeKind::Other,
+    };
+
+    CookieAttribute {
+        span: Span::new_from_str(src, attr),
+        name: Span::new_from_str(src, name),
+        value: value.map(|value| Span::new_from_str(src, value)),
+        kind,
+    }
+}
+
+/// Parses the value of a `Max-Age` attribute according to RFC 6265, section 5.2.2.
+fn parse_max_age(value: &str) -> Option<Duration> {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    if value.starts_with('-') {
+        return Some(Duration::ZERO);
+    }
+
+    // Values too large to represent are clamped rather than rejected.
+    match value::parse_integer(value.as_bytes()) {
+        Ok(secs) => Some(Duration::from_secs(secs)),
+        Err(ValueError::IntegerOverflow(_)) => Some(Duration::from_secs(u64::MAX)),
+        Err(_) => None,
+    }
+}
+
+/// Parses the value of a `SameSite` attribute (case-insensitive).
+fn parse_same_site(value: &str) -> Option<SameSite> {
+    if value.eq_ignore_ascii_case("strict") {
+        Some(SameSite::Strict)
+    } else if value.eq_ignore_ascii_case("lax") {
+        Some(SameSite::Lax)
+    } else if value.eq_ignore_ascii_case("none") {
+        Some(SameSite::None)
+    } else {
+        None
+    }
+}
+
+/// Trims leading and trailing spaces and horizontal tabs.
+fn trim_wsp(s: &str) -> &str {
+    s.trim_matches([' ', '\t'])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::parse_response;
+    use crate::testutil::harness::assert_response;
+
+    use super::*;
+
+    const TEST_RESPONSE: &[u8] = b"\
+                        HTTP/1.1 200 OK\r\n\
+                        Set-Cookie: sessionid=38afes7a8; HttpOnly; Path=/\r\n\
+                        Set-Cookie: id=a3fWa; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=2592000; \
+                        SameSite=Lax; Secure\r\n\
+                        Content-Length: 0\r\n\r\n";
+
+    #[test]
+    fn test_parse_set_cookie() {
+        let res = parse_response(TEST_RESPONSE).unwrap();
+        assert_response(TEST_RESPONSE, &res);
+        let cookies = res.set_cookies().collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(cookies.len(), 2);
+
+        let cookie = &cookies[0];
+        assert_eq!(cookie.name.as_str(), "sessionid");
+        assert_eq!(cookie.value.as_str(), "38afes7a8");
+        assert_eq!(cookie.span(), "sessionid=38afes7a8; HttpOnly; Path=/");
+        assert_eq!(cookie.attributes.len(), 2);
+        assert_eq!(cookie.attributes[0].kind, CookieAttributeKind::HttpOnly);
+        assert_eq!(cookie.attributes[1].span(), "Path=/");
+        assert_eq!(cookie.attributes[1].value.as_ref().unwrap().as_str(), "/");
+        assert!(cookie.expires().is_none());
+        assert!(cookie.max_age().is_none());
+
+        let cookie = &cookies[1];
+        assert_eq!(cookie.name.as_str(), "id");
+        assert_eq!(cookie.expires().unwrap().unix_timestamp(), 1_445_412_480);
+        assert_eq!(cookie.max_age(), Some(Duration::from_secs(2_592_000)));
+        assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+        assert_eq!(
+            cookie.attribute("secure").unwrap().kind,
+            CookieAttributeKind::Secure
+        );
+    }
+
+    #[test]
+    fn test_set_cookie_spans() {
+        let res = parse_response(TEST_RESPONSE).unwrap();
+        assert_response(TEST_RESPONSE, &res);
+        let cookie = res.set_cookies().nth(1).unwrap().unwrap();
+
+        let expires = cookie.attribute("expires").unwrap();
+        let range = expires.value.as_ref().unwrap().indices().clone();
+        let range: std::ops::Range<usize> = range.try_into().unwrap();
+
+        assert_eq!(
+            &TEST_RESPONSE[range],
+            b"Wed, 21 Oct 2015 07:28:00 GMT".as_slice()
+        );
+
+        let range: std::ops::Range<usize> =
+            cookie.value.span().indices().clone().try_into().unwrap();
+
+        assert_eq!(&TEST_RESPONSE[range], b"a3fWa".as_slice());
+    }
+
+    #[test]
+    fn test_set_cookie_malformed_attributes() {
+        let src = Source::from_static(b"a=b; Max-Age=12x; Expires=never; SameSite=Sometimes");
+        let cookie = parse_set_cookie_from_bytes(src).unwrap();
+
+        assert!(cookie
+            .attributes
+            .iter()
+            .all(|attr| attr.kind == CookieAttributeKind::Other));
+    }
+
+    #[test]
+    fn test_set_cookie_negative_max_age() {
+        let src = Source::from_static(b"a=b; Max-Age=-1");
+        let cookie = parse_set_cookie_from_bytes(src).unwrap();
+
+        assert_eq!(cookie.max_age(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_set_cookie_empty_value() {
+        let src = Source::from_static(b"a=; Path=/");
+        let cookie = parse_set_cookie_from_bytes(src).unwrap();
+
+        assert_eq!(cookie.name.as_str(), "a");
+        assert_eq!(cookie.value.as_str(), "");
+    }
+
+    #[test]
+    fn test_set_cookie_invalid() {
+        assert!(parse_set_cookie_from_bytes(Source::from_static(b"novalue")).is_err());
+        assert!(parse_set_cookie_from_bytes(Source::from_static(b"=value")).is_err());
+    }
+}
@@ -0,0 +1,461 @@
+use std::time::{Duration, SystemTime};
+
+/// A calendar date and time in UTC, as found in HTTP date values.
+///
+/// Dates are ordered chronologically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HttpDate {
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+}
+
+impl HttpDate {
+    /// Returns the year.
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    /// Returns the month, starting from 1 for January.
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    /// Returns the day of the month, starting from 1.
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// Returns the hour of the day.
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    /// Returns the minute of the hour.
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// Returns the second of the minute.
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    /// Returns the number of seconds since the Unix epoch.
+    ///
+    /// Dates before the epoch produce a negative value.
+    pub fn unix_timestamp(&self) -> i64 {
+        days_from_civil(self.year as i64, self.month as i64, self.day as i64) * 86_400
+            + self.hour as i64 * 3_600
+            + self.minute as i64 * 60
+            + self.second as i64
+    }
+
+    /// Returns the date as a `SystemTime`.
+    pub fn to_system_time(&self) -> SystemTime {
+        let timestamp = self.unix_timestamp();
+        if timestamp >= 0 {
+            SystemTime::UNIX_EPOCH + Duration::from_secs(timestamp as u64)
+        } else {
+            SystemTime::UNIX_EPOCH - Duration::from_secs(timestamp.unsigned_abs())
+        }
+    }
+
+    /// Creates a new date, returning `None` if any of the fields are out of range.
+    fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Option<Self> {
+        if !(1..=12).contains(&month)
+            || day == 0
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 59
+        {
+            return None;
+        }
+
+        Some(Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        })
+    }
+
+    /// Parses an HTTP date in one of the formats of RFC 9110, section 5.6.7: the preferred
+    /// IMF-fixdate, or the obsolete RFC 850 and asctime formats.
+    ///
+    /// Unlike [`parse_cookie_date`](Self::parse_cookie_date), the grammar is followed exactly:
+    /// names are case-sensitive, fields have a fixed number of digits and are separated by a
+    /// single space, and the IMF-fixdate and RFC 850 formats must end with `GMT`.
+    pub(crate) fn parse_http_date(src: &str) -> Option<Self> {
+        let fields: Vec<&str> = src.split(' ').collect();
+
+        let (day_names, day, month, year, time) = match fields.as_slice() {
+            // IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+            [day_name, day, month, year, time, "GMT"] => (
+                (day_name.strip_suffix(',')?, DAY_NAMES),
+                parse_digits(day, 2)?,
+                *month,
+                parse_digits(year, 4)?,
+                time,
+            ),
+            // RFC 850, e.g. `Sunday, 06-Nov-94 08:49:37 GMT`.
+            [day_name, date, time, "GMT"] => {
+                let [day, month, year] = date.split('-').collect::<Vec<_>>()[..] else {
+                    return None;
+                };
+                (
+                    (day_name.strip_suffix(',')?, LONG_DAY_NAMES),
+                    parse_digits(day, 2)?,
+                    month,
+                    two_digit_year(parse_digits(year, 2)?),
+                    time,
+                )
+            }
+            // asctime, e.g. `Sun Nov  6 08:49:37 1994`, where days before the 10th are
+            // padded with a space.
+            [day_name, month, "", day, time, year] => (
+                (*day_name, DAY_NAMES),
+                parse_digits(day, 1)?,
+                *month,
+                parse_digits(year, 4)?,
+                time,
+            ),
+            [day_name, month, day, time, year] => (
+                (*day_name, DAY_NAMES),
+                parse_digits(day, 2)?,
+                *month,
+                parse_digits(year, 4)?,
+                time,
+            ),
+            _ => return None,
+        };
+
+        let (day_name, names) = day_names;
+        if !names.contains(&day_name) {
+            return None;
+        }
+
+        let month = MONTH_NAMES.iter().position(|name| *name == month)? as u8 + 1;
+        let [hour, minute, second] = time.split(':').collect::<Vec<_>>()[..] else {
+            return None;
+        };
+
+        Self::new(
+            year,
+            month,
+            day as u8,
+            parse_digits(hour, 2)? as u8,
+            parse_digits(minute, 2)? as u8,
+            parse_digits(second, 2)? as u8,
+        )
+    }
+
+    /// Parses a cookie date according to the algorithm in RFC 6265, section 5.1.1.
+    ///
+    /// This algorithm is intentionally lenient as servers emit dates in a wide variety of formats.
+    pub(crate) fn parse_cookie_date(src: &str) -> Option<Self> {
+        let mut time = None;
+        let mut day = None;
+        let mut month = None;
+        let mut year = None;
+
+        for token in src.split(is_cookie_delimiter).filter(|t| !t.is_empty()) {
+            if time.is_none() {
+                if let Some(value) = parse_cookie_time(token) {
+                    time = Some(value);
+                    continue;
+                }
+            }
+
+            if day.is_none() {
+                if let Some(value) = parse_leading_digits(token, 1, 2) {
+                    day = Some(value as u8);
+                    continue;
+                }
+            }
+
+            if month.is_none() {
+                if let Some(value) = parse_month(token) {
+                    month = Some(value);
+                    continue;
+                }
+            }
+
+            if year.is_none() {
+                if let Some(value) = parse_leading_digits(token, 2, 4) {
+                    year = Some(value);
+                    continue;
+                }
+            }
+        }
+
+        let (hour, minute, second) = time?;
+        let year = match year? {
+            year @ 70..=99 => year + 1900,
+            year @ 0..=69 => year + 2000,
+            year => year,
+        };
+
+        if year < 1601 {
+            return None;
+        }
+
+        Self::new(year as u16, month?, day?, hour, minute, second)
+    }
+}
+
+const DAY_NAMES: &[&str] = &["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+const LONG_DAY_NAMES: &[&str] = &[
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Parses a field of exactly `len` digits.
+fn parse_digits(field: &str, len: usize) -> Option<u16> {
+    if field.len() != len || !field.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    field.parse().ok()
+}
+
+/// Returns the year of a two-digit year in the RFC 850 format.
+///
+/// As required by RFC 9110, section 5.6.7, a year which would be more than 50 years in the
+/// future is the most recent year in the past with the same last two digits.
+fn two_digit_year(year: u16) -> u16 {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let current = year_from_days((now / 86_400) as i64) as u16;
+
+    let year = current - current % 100 + year;
+    if year > current + 50 {
+        year - 100
+    } else {
+        year
+    }
+}
+
+/// Returns `true` if the character is a cookie date delimiter.
+fn is_cookie_delimiter(c: char) -> bool {
+    matches!(c, '\x09' | '\x20'..='\x2F' | '\x3B'..='\x40' | '\x5B'..='\x60' | '\x7B'..='\x7E')
+}
+
+/// Parses between `min` and `max` leading digits of the token, which may only be
+/// followed by a non-digit.
+fn parse_leading_digits(token: &str, min: usize, max: usize) -> Option<u32> {
+    let digits = token.bytes().take_while(u8::is_ascii_digit).count();
+
+    if digits < min || digits > max {
+        return None;
+    }
+
+    token[..digits].parse().ok()
+}
+
+/// Parses a `hh:mm:ss` time token, where each field has 1 or 2 digits.
+fn parse_cookie_time(token: &str) -> Option<(u8, u8, u8)> {
+    let mut fields = token.splitn(3, ':');
+
+    let hour = fields.next()?;
+    let minute = fields.next()?;
+    let second = fields.next()?;
+
+    if !is_time_field(hour) || !is_time_field(minute) {
+        return None;
+    }
+
+    Some((
+        hour.parse().ok()?,
+        minute.parse().ok()?,
+        parse_leading_digits(second, 1, 2)? as u8,
+    ))
+}
+
+/// Returns `true` if the field consists of 1 or 2 digits.
+fn is_time_field(field: &str) -> bool {
+    (1..=2).contains(&field.len()) && field.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Parses a month from the first 3 characters of the token (case-insensitive).
+fn parse_month(token: &str) -> Option<u8> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+
+    let prefix = token.get(..3)?;
+
+    MONTHS
+        .iter()
+        .position(|month| month.eq_ignore_ascii_case(prefix))
+        .map(|idx| idx as u8 + 1)
+}
+
+fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the number of days since the Unix epoch for the given civil date.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#days_from_civil>.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146_097 + doe - 719_468
+}
+
+/// Returns the year of the given number of days since the Unix epoch.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn year_from_days(days: i64) -> i64 {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+
+    // The year starts in March, so January and February belong to the next year.
+    yoe + era * 400 + i64::from(mp >= 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cookie_date() {
+        let date = HttpDate::parse_cookie_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+
+        assert_eq!((date.year(), date.month(), date.day()), (2015, 10, 21));
+        assert_eq!((date.hour(), date.minute(), date.second()), (7, 28, 0));
+        assert_eq!(date.unix_timestamp(), 1_445_412_480);
+    }
+
+    #[test]
+    fn test_parse_cookie_date_lenient_formats() {
+        let expected = HttpDate::parse_cookie_date("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+
+        // RFC 850.
+        assert_eq!(
+            HttpDate::parse_cookie_date("Sunday, 06-Nov-94 08:49:37 GMT"),
+            Some(expected)
+        );
+        // ANSI C's asctime() format.
+        assert_eq!(
+            HttpDate::parse_cookie_date("Sun Nov  6 08:49:37 1994"),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_parse_cookie_date_invalid() {
+        assert!(HttpDate::parse_cookie_date("").is_none());
+        assert!(HttpDate::parse_cookie_date("Wed, 21 Oct 2015").is_none());
+        assert!(HttpDate::parse_cookie_date("Wed, 32 Oct 2015 07:28:00 GMT").is_none());
+        assert!(HttpDate::parse_cookie_date("Fri, 29 Feb 2019 07:28:00 GMT").is_none());
+        assert!(HttpDate::parse_cookie_date("Wed, 21 Oct 1600 07:28:00 GMT").is_none());
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        let expected = HttpDate::new(1994, 11, 6, 8, 49, 37);
+
+        for src in [
+            "Sun, 06 Nov 1994 08:49:37 GMT",
+            "Sunday, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov  6 08:49:37 1994",
+        ] {
+            assert_eq!(HttpDate::parse_http_date(src), expected, "{src:?}");
+        }
+        assert_eq!(
+            HttpDate::parse_http_date("Thu Nov 16 08:49:37 1994"),
+            HttpDate::new(1994, 11, 16, 8, 49, 37)
+        );
+    }
+
+    #[test]
+    fn test_parse_http_date_invalid() {
+        for src in [
+            "",
+            "xx 6 nov 94 8:49:37 trailing junk",
+            "1994 Nov 06 08:49:37",
+            "Sun, 06 Nov 1994 08:49:37 PST",
+            "Sun, 06 Nov 1994 08:49:37",
+            "sun, 06 nov 1994 08:49:37 GMT",
+            "Sun, 6 Nov 1994 08:49:37 GMT",
+            "Sun,  06 Nov 1994 08:49:37 GMT",
+            "Sun, 06 Nov 1994 8:49:37 GMT",
+            "Sunday, 06 Nov 1994 08:49:37 GMT",
+            "Sun, 06-Nov-94 08:49:37 GMT",
+            "Sun Nov 6 08:49:37 1994",
+            "Sun Nov  06 08:49:37 1994",
+            "Sun, 31 Nov 1994 08:49:37 GMT",
+        ] {
+            assert!(HttpDate::parse_http_date(src).is_none(), "{src:?}");
+        }
+    }
+
+    #[test]
+    fn test_two_digit_year() {
+        let current = year_from_days(
+            (SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+                / 86_400) as i64,
+        ) as u16;
+
+        for year in 0..100 {
+            let expanded = two_digit_year(year);
+            assert_eq!(expanded % 100, year);
+            assert!((current - 49..=current + 50).contains(&expanded));
+        }
+        assert_eq!(year_from_days(0), 1970);
+        assert_eq!(year_from_days(-1), 1969);
+        assert_eq!(year_from_days(days_from_civil(2024, 2, 29)), 2024);
+        assert_eq!(year_from_days(days_from_civil(2024, 12, 31)), 2024);
+    }
+
+    #[test]
+    fn test_unix_timestamp_before_epoch() {
+        let date = HttpDate::parse_cookie_date("Wed, 31 Dec 1969 23:59:59 GMT").unwrap();
+
+        assert_eq!(date.unix_timestamp(), -1);
+        assert_eq!(
+            date.to_system_time(),
+            SystemTime::UNIX_EPOCH - Duration::from_secs(1)
+        );
+    }
+}
@@ -0,0 +1,550 @@
+use std::{fmt, ops::Range, sync::Arc};
+
+use utils::range::{RangeSet, Union};
+
+use crate::{
+    http::{Body, Header, Request, Response},
+    multi::MultiSpanBuilder,
+    MultiSpan, ParseError, Spanned,
+};
+
+/// The default maximum length of decoded content, 16 MiB.
+const DEFAULT_LIMIT: usize = 16 << 20;
+
+/// A decoder of a content coding, such as `gzip` (RFC 9110, section 8.4.1).
+///
+/// Decoders are registered with [`ContentDecoders`], which applies them to the body of a
+/// message according to its `Content-Encoding` header.
+///
+/// # Back-mapping
+///
+/// A decoder returns its output as [`DecodedBlock`]s, each with the range of the encoded data
+/// it was decoded from. The blocks must be in order, and their encoded ranges must be
+/// non-overlapping and in order, so that positions in the decoded content can be mapped back
+/// to the indices of the source. A decoder which cannot attribute its output to parts of the
+/// input, such as a stream decompressor, returns a single block spanning all of the encoded
+/// data.
+pub trait ContentDecoder: Send + Sync {
+    /// Returns the names of the content codings handled by the decoder, such as `gzip` and
+    /// `x-gzip`, which are matched case-insensitively.
+    fn codings(&self) -> &'static [&'static str];
+
+    /// Decodes the encoded data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data is not validly encoded, or if the decoded data would be
+    /// longer than `limit` bytes.
+    fn decode(&self, data: &[u8], limit: usize) -> Result<Vec<DecodedBlock>, ParseError>;
+}
+
+/// A block of content decoded by a [`ContentDecoder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedBlock {
+    /// The range of the encoded data which the block was decoded from.
+    pub encoded: Range<usize>,
+    /// The decoded data.
+    pub data: Vec<u8>,
+}
+
+impl DecodedBlock {
+    /// Returns a block decoded from all of the encoded data.
+    pub fn whole(encoded: &[u8], data: Vec<u8>) -> Self {
+        Self {
+            encoded: 0..encoded.len(),
+            data,
+        }
+    }
+}
+
+/// A set of [`ContentDecoder`]s used to decode message bodies.
+///
+/// The default set contains the decoders of the enabled features: `gzip` and `deflate` with
+/// the `gzip` feature, `br` with the `brotli` feature and `zstd` with the `zstd` feature. The
+/// `gzip` and `brotli` decoders are pure Rust, so they can also be used on WebAssembly.
+///
+/// # Example
+///
+/// ```
+/// use spansy::http::{parse_response, ContentDecoders};
+///
+/// let response = parse_response(
+///     b"HTTP/1.1 200 OK\r\nContent-Encoding: identity\r\nContent-Length: 5\r\n\r\nhello",
+/// )
+/// .unwrap();
+///
+/// let content = response.decoded_body(&ContentDecoders::new()).unwrap().unwrap();
+/// assert_eq!(content.as_bytes(), b"hello");
+/// ```
+#[derive(Clone)]
+pub struct ContentDecoders {
+    decoders: Vec<Arc<dyn ContentDecoder>>,
+    limit: usize,
+}
+
+impl ContentDecoders {
+    /// Returns an empty set, which only supports the `identity` coding.
+    pub fn new() -> Self {
+        Self {
+            decoders: Vec::new(),
+            limit: DEFAULT_LIMIT,
+        }
+    }
+
+    /// Adds a decoder, which takes precedence over any decoder already added for the same
+    /// coding.
+    pub fn with_decoder(mut self, decoder: impl ContentDecoder + 'static) -> Self {
+        self.decoders.insert(0, Arc::new(decoder));
+        self
+    }
+
+    /// Sets the maximum length of the content after each decoding step.
+    ///
+    /// Defaults to 16 MiB, which protects against decompression bombs.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Returns the decoder of the given content coding, if any.
+    pub fn get(&self, coding: &str) -> Option<&dyn ContentDecoder> {
+        self.decoders
+            .iter()
+            .find(|decoder| {
+                decoder
+                    .codings()
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(coding))
+            })
+            .map(|decoder| decoder.as_ref())
+    }
+
+    /// Decodes content encoded with the given codings, in the order they were applied.
+    ///
+    /// The codings are undone in reverse order, and the indices of the decoded content map
+    /// back to the indices of `content`.
+    pub fn decode<'a>(
+        &self,
+        codings: impl IntoIterator<Item = &'a str>,
+        content: &MultiSpan,
+    ) -> Result<MultiSpan, ParseError> {
+        let codings: Vec<_> = codings
+            .into_iter()
+            .filter(|coding| !coding.eq_ignore_ascii_case("identity"))
+            .collect();
+
+        let mut content = content.clone();
+        for coding in codings.into_iter().rev() {
+            let decoder = self
+                .get(coding)
+                .ok_or_else(|| ParseError::new(format!("unsupported content coding {coding:?}")))?;
+            let blocks = decoder.decode(content.as_bytes(), self.limit)?;
+            content = map_blocks(&content, blocks)?;
+        }
+
+        Ok(content)
+    }
+
+    /// Decodes the body of a message with the given headers.
+    ///
+    /// Content codings are applied before any transfer codings other than `chunked`, which
+    /// has already been removed from the body.
+    fn decode_body(&self, headers: &[Header], body: &Body) -> Result<MultiSpan, ParseError> {
+        let content = match &body.chunked {
+            Some(chunked) => chunked.data().clone(),
+            None => MultiSpan::from(body.span()),
+        };
+
+        let mut codings = Vec::new();
+        for name in ["content-encoding", "transfer-encoding"] {
+            for header in headers
+                .iter()
+                .filter(|header| header.name.as_str().eq_ignore_ascii_case(name))
+            {
+                codings.extend(
+                    std::str::from_utf8(header.value.as_bytes())?
+                        .split(',')
+                        .map(|coding| coding.trim_matches([' ', '\t']))
+                        .filter(|coding| {
+                            !coding.is_empty() && !coding.eq_ignore_ascii_case("chunked")
+                        }),
+                );
+            }
+        }
+
+        self.decode(codings, &content)
+    }
+}
+
+impl Default for ContentDecoders {
+    fn default() -> Self {
+        #[allow(unused_mut)]
+        let mut decoders = Self::new();
+        #[cfg(feature = "zstd")]
+        {
+            decoders = decoders.with_decoder(ZstdDecoder);
+        }
+        #[cfg(feature = "brotli")]
+        {
+            decoders = decoders.with_decoder(BrotliDecoder);
+        }
+        #[cfg(feature = "gzip")]
+        {
+            decoders = decoders
+                .with_decoder(DeflateDecoder)
+                .with_decoder(GzipDecoder);
+        }
+        decoders
+    }
+}
+
+impl fmt::Debug for ContentDecoders {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContentDecoders")
+            .field(
+                "codings",
+                &self
+                    .decoders
+                    .iter()
+                    .flat_map(|decoder| decoder.codings())
+                    .collect::<Vec<_>>(),
+            )
+            .field("limit", &self.limit)
+            .finish()
+    }
+}
+
+impl Request {
+    /// Returns the body content of the request with its `Content-Encoding` and any
+    /// `Transfer-Encoding` other than `chunked` decoded, or `None` if the request has no body.
+    ///
+    /// The indices of the decoded content map back to the encoded bytes of the body they were
+    /// decoded from, excluding any chunk framing.
+    pub fn decoded_body(
+        &self,
+        decoders: &ContentDecoders,
+    ) -> Result<Option<MultiSpan>, ParseError> {
+        self.body
+            .as_ref()
+            .map(|body| decoders.decode_body(&self.headers, body))
+            .transpose()
+    }
+}
+
+impl Response {
+    /// Returns the body content of the response with its `Content-Encoding` and any
+    /// `Transfer-Encoding` other than `chunked` decoded, or `None` if the response has no
+    /// body.
+    ///
+    /// See [`Request::decoded_body`].
+    pub fn decoded_body(
+        &self,
+        decoders: &ContentDecoders,
+    ) -> Result<Option<MultiSpan>, ParseError> {
+        self.body
+            .as_ref()
+            .map(|body| decoders.decode_body(&self.headers, body))
+            .transpose()
+    }
+}
+
+/// Assembles the blocks decoded from `content`, mapping them back to its indices.
+fn map_blocks(content: &MultiSpan, blocks: Vec<DecodedBlock>) -> Result<MultiSpan, ParseError> {
+    let mut builder = MultiSpanBuilder::default();
+    let mut pending: Option<(RangeSet<usize>, Vec<u8>)> = None;
+    let mut pos = 0;
+    for block in blocks {
+        if block.encoded.start < pos || block.encoded.end > content.len() {
+            return Err(ParseError::new(format!(
+                "decoded block at {:?} is out of order or exceeds the content",
+                block.encoded
+            )));
+        }
+        pos = block.encoded.end;
+
+        let indices = content.indices_of(block.encoded);
+        // Blocks decoded from the same decoded segment of the content map to the same
+        // indices, so they are merged.
+        match &mut pending {
+            Some((pending_indices, data))
+                if pending_indices.end() > indices.min() && indices.min().is_some() =>
+            {
+                *pending_indices = pending_indices.union(&indices);
+                data.extend(block.data);
+            }
+            _ => {
+                if let Some((indices, data)) = pending.take() {
+                    builder.push_decoded(indices, &data);
+                }
+                pending = Some((indices, block.data));
+            }
+        }
+    }
+    if let Some((indices, data)) = pending {
+        builder.push_decoded(indices, &data);
+    }
+
+    Ok(builder.build())
+}
+
+/// Reads all of `reader`, returning an error if it yields more than `limit` bytes.
+#[cfg(any(feature = "gzip", feature = "brotli", feature = "zstd"))]
+fn read_limited(
+    coding: &str,
+    reader: impl std::io::Read,
+    limit: usize,
+) -> Result<Vec<u8>, ParseError> {
+    use std::io::Read;
+
+    let mut data = Vec::new();
+    reader
+        .take(limit as u64 + 1)
+        .read_to_end(&mut data)
+        .map_err(|err| ParseError::with_source(format!("invalid {coding} content"), err))?;
+
+    if data.len() > limit {
+        return Err(ParseError::new(format!(
+            "decoded {coding} content exceeds the limit of {limit} bytes"
+        )));
+    }
+
+    Ok(data)
+}
+
+/// A decoder of the `gzip` content coding (RFC 1952).
+#[cfg(feature = "gzip")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GzipDecoder;
+
+#[cfg(feature = "gzip")]
+impl ContentDecoder for GzipDecoder {
+    fn codings(&self) -> &'static [&'static str] {
+        &["gzip", "x-gzip"]
+    }
+
+    fn decode(&self, data: &[u8], limit: usize) -> Result<Vec<DecodedBlock>, ParseError> {
+        let decoded = read_limited("gzip", flate2::read::MultiGzDecoder::new(data), limit)?;
+        Ok(vec![DecodedBlock::whole(data, decoded)])
+    }
+}
+
+/// A decoder of the `deflate` content coding, which is the zlib format (RFC 1950).
+#[cfg(feature = "gzip")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeflateDecoder;
+
+#[cfg(feature = "gzip")]
+impl ContentDecoder for DeflateDecoder {
+    fn codings(&self) -> &'static [&'static str] {
+        &["deflate"]
+    }
+
+    fn decode(&self, data: &[u8], limit: usize) -> Result<Vec<DecodedBlock>, ParseError> {
+        let decoded = read_limited("deflate", flate2::read::ZlibDecoder::new(data), limit)?;
+        Ok(vec![DecodedBlock::whole(data, decoded)])
+    }
+}
+
+/// A decoder of the `br` content coding (RFC 7932).
+#[cfg(feature = "brotli")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BrotliDecoder;
+
+#[cfg(feature = "brotli")]
+impl ContentDecoder for BrotliDecoder {
+    fn codings(&self) -> &'static [&'static str] {
+        &["br"]
+    }
+
+    fn decode(&self, data: &[u8], limit: usize) -> Result<Vec<DecodedBlock>, ParseError> {
+        let decoder = brotli_decompressor::Decompressor::new(data, 4096);
+        let decoded = read_limited("br", decoder, limit)?;
+        Ok(vec![DecodedBlock::whole(data, decoded)])
+    }
+}
+
+/// A decoder of the `zstd` content coding (RFC 8878).
+///
+/// This decoder links the zstd C library, so it is not available on WebAssembly.
+#[cfg(feature = "zstd")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZstdDecoder;
+
+#[cfg(feature = "zstd")]
+impl ContentDecoder for ZstdDecoder {
+    fn codings(&self) -> &'static [&'static str] {
+        &["zstd"]
+    }
+
+    fn decode(&self, data: &[u8], limit: usize) -> Result<Vec<DecodedBlock>, ParseError> {
+        let decoder = zstd::stream::read::Decoder::new(data)
+            .map_err(|err| ParseError::with_source("invalid zstd content", err))?;
+        let decoded = read_limited("zstd", decoder, limit)?;
+        Ok(vec![DecodedBlock::whole(data, decoded)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::parse_response;
+    use crate::testutil::harness::assert_response;
+
+    use super::*;
+
+    /// A toy coding which doubles every byte, decoded in blocks of two encoded bytes.
+    struct Double;
+
+    impl ContentDecoder for Double {
+        fn codings(&self) -> &'static [&'static str] {
+            &["x-double"]
+        }
+
+        fn decode(&self, data: &[u8], _limit: usize) -> Result<Vec<DecodedBlock>, ParseError> {
+            Ok((0..data.len() / 2)
+                .map(|i| DecodedBlock {
+                    encoded: 2 * i..2 * i + 2,
+                    data: vec![data[2 * i]],
+                })
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_decoded_body_back_mapping() {
+        let src = b"HTTP/1.1 200 OK\r\nContent-Encoding: x-double, X-Double\r\n\
+            Transfer-Encoding: chunked\r\n\r\n3\r\naaa\r\n5\r\nabbbb\r\n0\r\n\r\n";
+        let response = parse_response(src).unwrap();
+        assert_response(src, &response);
+        let decoders = ContentDecoders::new().with_decoder(Double);
+
+        let content = response.decoded_body(&decoders).unwrap().unwrap();
+        assert_eq!(content.as_bytes(), b"ab");
+        // The first byte is decoded from "aaaa", which is split across the chunks.
+        assert_eq!(content.indices_of(0..1).into_inner(), [88..91, 96..97]);
+        assert_eq!(content.indices_of(1..2), RangeSet::from(97..101));
+
+        assert!(response.decoded_body(&ContentDecoders::new()).is_err());
+    }
+
+    #[test]
+    fn test_parse_compressed() {
+        use std::sync::Arc;
+
+        use crate::{
+            http::{parse_response_with_config, BodyContent, ParserConfig},
+            Spanned,
+        };
+
+        let src = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+            Content-Encoding: x-double\r\nContent-Length: 16\r\n\r\n{{\"\"aa\"\"::1122}}";
+        let head_len = src.len() - 16;
+
+        // Without a decoder, the content is not parsed.
+        let response = parse_response(src).unwrap();
+        assert_response(src, &response);
+        assert!(matches!(
+            response.body().unwrap().content,
+            BodyContent::Binary(_)
+        ));
+
+        let config = ParserConfig::default()
+            .content_decoders(Arc::new(ContentDecoders::new().with_decoder(Double)));
+        let response = parse_response_with_config(src, &config).unwrap();
+        assert_response(src, &response);
+        let BodyContent::Compressed { raw, decoded, json } = &response.body().unwrap().content
+        else {
+            panic!("content is decoded");
+        };
+        assert_eq!(raw.indices(), &RangeSet::from(head_len..src.len()));
+        assert_eq!(decoded.as_bytes(), b"{\"a\":12}");
+        assert!(json.is_some());
+
+        // The value maps back to the encoded bytes it was decoded from.
+        let value = response.json("/a").unwrap();
+        assert_eq!(value.span(), "12");
+        assert_eq!(
+            value.span().indices(),
+            &RangeSet::from(head_len + 10..head_len + 14)
+        );
+
+        // A redacted view can not be decoded.
+        let view = response.with_data(&src.to_vec().into()).unwrap();
+        assert!(matches!(
+            view.body().unwrap().content,
+            BodyContent::Binary(_)
+        ));
+    }
+
+    #[cfg(all(feature = "gzip", feature = "brotli", feature = "zstd"))]
+    #[test]
+    fn test_default_decoders() {
+        use std::io::Write;
+
+        let content = b"{\"hello\":\"world\"}";
+
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(content).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let zstd = zstd::encode_all(content.as_slice(), 0).unwrap();
+        let br = b"\x0b\x08\x80{\"hello\":\"world\"}\x03".to_vec();
+
+        let decoders = ContentDecoders::default();
+        for (coding, encoded) in [("gzip", gzip.clone()), ("br", br), ("zstd", zstd)] {
+            let mut src = format!(
+                "HTTP/1.1 200 OK\r\nContent-Encoding: {coding}\r\nContent-Length: {}\r\n\r\n",
+                encoded.len()
+            )
+            .into_bytes();
+            let head_len = src.len();
+            src.extend_from_slice(&encoded);
+
+            let response = parse_response(&src).unwrap();
+            let decoded = response.decoded_body(&decoders).unwrap().unwrap();
+            assert_eq!(decoded.as_bytes(), content, "{coding}");
+            assert_eq!(decoded.indices(), RangeSet::from(head_len..src.len()));
+
+            let limited = decoders.clone().limit(content.len() - 1);
+            assert!(response.decoded_body(&limited).is_err(), "{coding}");
+        }
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_decoded_body_transfer_coding() {
+        use std::io::Write;
+
+        let content = b"{\"hello\":\"world\"}";
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(content).unwrap();
+        let gzip = gzip.finish().unwrap();
+
+        // The gzip data is split across two chunks.
+        let (a, b) = gzip.split_at(10);
+        let mut src = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+            Transfer-Encoding: gzip, chunked\r\n\r\n"
+            .to_vec();
+        src.extend_from_slice(format!("{:x}\r\n", a.len()).as_bytes());
+        let a_start = src.len();
+        src.extend_from_slice(a);
+        src.extend_from_slice(format!("\r\n{:x}\r\n", b.len()).as_bytes());
+        let b_start = src.len();
+        src.extend_from_slice(b);
+        src.extend_from_slice(b"\r\n0\r\n\r\n");
+
+        let response = parse_response(&src).unwrap();
+        let body = response.body().unwrap();
+        assert!(matches!(body.content, crate::http::BodyContent::Binary(_)));
+        let chunked = body.chunked.as_ref().unwrap();
+        assert_eq!(chunked.data().as_bytes(), gzip);
+
+        let decoded = response
+            .decoded_body(&ContentDecoders::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(decoded.as_bytes(), content);
+        assert_eq!(
+            decoded.indices(),
+            RangeSet::from([a_start..a_start + a.len(), b_start..b_start + b.len()])
+        );
+    }
+}
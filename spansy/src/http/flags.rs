@@ -0,0 +1,223 @@
+use std::{fmt, ops};
+
+/// A set of flags raised by a suspicious header name.
+///
+/// Header names are not rejected for these, so strict consumers can reject any header with
+/// flags while lenient ones can proceed knowingly. Headers parsed from a message are always
+/// tokens, but headers deserialized from an untrusted source may contain any text.
+///
+/// # Example
+///
+/// ```
+/// use spansy::http::{parse_request, HeaderFlags};
+///
+/// let request = parse_request(b"GET / HTTP/1.1\r\nX_Forwarded_For: 1.2.3.4\r\n\r\n").unwrap();
+///
+/// let flags = request.headers[0].flags();
+/// assert_eq!(flags, HeaderFlags::UNDERSCORE);
+/// assert_eq!(flags.to_string(), "UNDERSCORE");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeaderFlags(u8);
+
+impl HeaderFlags {
+    /// The name is empty.
+    pub const EMPTY: Self = Self(1 << 0);
+    /// The name contains a byte which is not a token character (RFC 9110, section 5.6.2).
+    pub const INVALID_TOKEN: Self = Self(1 << 1);
+    /// The name contains a character which is not ASCII.
+    pub const NON_ASCII: Self = Self(1 << 2);
+    /// The name contains a character which looks like an ASCII character, such as a Cyrillic
+    /// `а` or a fullwidth `Ａ`, so it may impersonate another header.
+    pub const CONFUSABLE: Self = Self(1 << 3);
+    /// The name contains an invisible character, such as a zero-width space.
+    pub const INVISIBLE: Self = Self(1 << 4);
+    /// The name contains an underscore, which some proxies treat as a hyphen.
+    pub const UNDERSCORE: Self = Self(1 << 5);
+
+    const NAMES: [(Self, &'static str); 6] = [
+        (Self::EMPTY, "EMPTY"),
+        (Self::INVALID_TOKEN, "INVALID_TOKEN"),
+        (Self::NON_ASCII, "NON_ASCII"),
+        (Self::CONFUSABLE, "CONFUSABLE"),
+        (Self::INVISIBLE, "INVISIBLE"),
+        (Self::UNDERSCORE, "UNDERSCORE"),
+    ];
+
+    /// Returns an empty set of flags.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns the raw bits of the flags.
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    /// Returns `true` if no flags are set.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if all the flags in `other` are set.
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns `true` if any of the flags in `other` are set.
+    pub const fn intersects(&self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Returns the flags raised by a header name.
+    pub(crate) fn of_name(name: &str) -> Self {
+        let mut flags = Self::empty();
+        if name.is_empty() {
+            flags |= Self::EMPTY;
+        }
+
+        for c in name.chars() {
+            if c.is_ascii() {
+                if c == '_' {
+                    flags |= Self::UNDERSCORE;
+                } else if !is_tchar(c as u8) {
+                    flags |= Self::INVALID_TOKEN;
+                }
+                continue;
+            }
+
+            flags |= Self::NON_ASCII | Self::INVALID_TOKEN;
+            if is_invisible(c) {
+                flags |= Self::INVISIBLE;
+            } else if is_confusable(c) {
+                flags |= Self::CONFUSABLE;
+            }
+        }
+
+        flags
+    }
+}
+
+impl ops::BitOr for HeaderFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitOrAssign for HeaderFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl fmt::Display for HeaderFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names = Self::NAMES
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| name);
+
+        if let Some(name) = names.next() {
+            f.write_str(name)?;
+        }
+        for name in names {
+            write!(f, " | {name}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns `true` if the byte is a token character (RFC 9110, section 5.6.2).
+pub(crate) fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric()
+        || matches!(
+            b,
+            b'!' | b'#'
+                | b'$'
+                | b'%'
+                | b'&'
+                | b'\''
+                | b'*'
+                | b'+'
+                | b'-'
+                | b'.'
+                | b'^'
+                | b'_'
+                | b'`'
+                | b'|'
+                | b'~'
+        )
+}
+
+/// Returns `true` if the character is invisible when rendered.
+fn is_invisible(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{00AD}'
+                | '\u{034F}'
+                | '\u{180E}'
+                | '\u{200B}'..='\u{200F}'
+                | '\u{202A}'..='\u{202E}'
+                | '\u{2060}'..='\u{2064}'
+                | '\u{FEFF}'
+        )
+}
+
+/// Returns `true` if the character is commonly confused with an ASCII letter, digit or
+/// token symbol.
+fn is_confusable(c: char) -> bool {
+    matches!(
+        c,
+        // Fullwidth forms of ASCII.
+        '\u{FF01}'..='\u{FF5E}'
+            // Cyrillic letters which look like Latin letters.
+            | 'а' | 'в' | 'е' | 'к' | 'м' | 'н' | 'о' | 'р' | 'с' | 'т' | 'у' | 'х'
+            | 'А' | 'В' | 'Е' | 'К' | 'М' | 'Н' | 'О' | 'Р' | 'С' | 'Т' | 'У' | 'Х'
+            | 'і' | 'ј' | 'ѕ' | 'І' | 'Ј' | 'Ѕ'
+            // Greek letters which look like Latin letters.
+            | 'Α' | 'Β' | 'Ε' | 'Ζ' | 'Η' | 'Ι' | 'Κ' | 'Μ' | 'Ν' | 'Ο' | 'Ρ' | 'Τ' | 'Υ'
+            | 'Χ' | 'ο' | 'ν'
+            // Dashes and hyphens which look like `-`.
+            | '\u{2010}'..='\u{2015}' | '\u{2212}'
+            // Dotless i and Latin small capitals.
+            | 'ı' | 'ɪ'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_flags() {
+        assert!(HeaderFlags::of_name("Content-Type").is_empty());
+        assert_eq!(HeaderFlags::of_name(""), HeaderFlags::EMPTY);
+        assert_eq!(HeaderFlags::of_name("X_Api_Key"), HeaderFlags::UNDERSCORE);
+        assert_eq!(
+            HeaderFlags::of_name("Host Name"),
+            HeaderFlags::INVALID_TOKEN
+        );
+
+        // Cyrillic `о`.
+        let flags = HeaderFlags::of_name("Hоst");
+        assert_eq!(
+            flags,
+            HeaderFlags::INVALID_TOKEN | HeaderFlags::NON_ASCII | HeaderFlags::CONFUSABLE
+        );
+        assert_eq!(flags.to_string(), "INVALID_TOKEN | NON_ASCII | CONFUSABLE");
+
+        let flags = HeaderFlags::of_name("Cookie\u{200B}");
+        assert!(flags.contains(HeaderFlags::INVISIBLE));
+        assert!(!flags.intersects(HeaderFlags::CONFUSABLE | HeaderFlags::UNDERSCORE));
+
+        assert_eq!(
+            HeaderFlags::of_name("Überschrift"),
+            HeaderFlags::INVALID_TOKEN | HeaderFlags::NON_ASCII
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use utils::range::{Difference, RangeSet, ToRangeSet};
+
+use crate::{
+    http::{Body, BodyContent, Header, Request, Response},
+    Span, Spanned,
+};
+
+/// The media type of URL-encoded form bodies.
+const FORM_MEDIA_TYPE: &str = "application/x-www-form-urlencoded";
+
+/// A field of a body with the `application/x-www-form-urlencoded` content type, such as
+/// `password=hunter2`.
+///
+/// The name and value are spans of the encoded body, so percent-encoded and `+` characters
+/// are not decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormField {
+    span: Span,
+    /// The field name.
+    pub name: Span,
+    /// The field value, which is empty if the field has no `=`.
+    pub value: Span,
+}
+
+impl FormField {
+    /// Returns the indices of the field, excluding the value.
+    pub fn without_value(&self) -> RangeSet<usize> {
+        self.span.indices().difference(self.value.indices())
+    }
+}
+
+impl Spanned for FormField {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for FormField {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices().clone()
+    }
+}
+
+impl Request {
+    /// Returns the fields of the body of the request, if it has the
+    /// `application/x-www-form-urlencoded` content type.
+    ///
+    /// The fields are parsed from the body content, with any chunk framing removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spansy::http::parse_request;
+    ///
+    /// let request = parse_request(
+    ///     b"POST /login HTTP/1.1\r\nContent-Type: application/x-www-form-urlencoded\r\n\
+    ///     Content-Length: 31\r\n\r\nusername=alice&password=hunter2",
+    /// )
+    /// .unwrap();
+    ///
+    /// let password = request.form_field("password").unwrap();
+    /// assert_eq!(password.value, "hunter2".as_bytes());
+    /// ```
+    pub fn form(&self) -> Option<Vec<FormField>> {
+        form(&self.headers, self.body.as_ref())
+    }
+
+    /// Returns the first field of the body of the request with the given name, if it has the
+    /// `application/x-www-form-urlencoded` content type.
+    pub fn form_field(&self, name: &str) -> Option<FormField> {
+        find_field(self.form()?, name)
+    }
+}
+
+impl Response {
+    /// Returns the fields of the body of the response, if it has the
+    /// `application/x-www-form-urlencoded` content type.
+    ///
+    /// See [`Request::form`].
+    pub fn form(&self) -> Option<Vec<FormField>> {
+        form(&self.headers, self.body.as_ref())
+    }
+
+    /// Returns the first field of the body of the response with the given name, if it has
+    /// the `application/x-www-form-urlencoded` content type.
+    pub fn form_field(&self, name: &str) -> Option<FormField> {
+        find_field(self.form()?, name)
+    }
+}
+
+fn form(headers: &[Header], body: Option<&Body>) -> Option<Vec<FormField>> {
+    let content_type = headers
+        .iter()
+        .find(|header| header.name.as_str().eq_ignore_ascii_case("content-type"))?;
+    let media_type = content_type.value.as_bytes().split(|b| *b == b';').next()?;
+    if !media_type
+        .trim_ascii()
+        .eq_ignore_ascii_case(FORM_MEDIA_TYPE.as_bytes())
+    {
+        return None;
+    }
+
+    match &body?.content {
+        BodyContent::Binary(content) => Some(parse_form(content)),
+        BodyContent::Empty => Some(Vec::new()),
+        _ => None,
+    }
+}
+
+fn find_field(fields: Vec<FormField>, name: &str) -> Option<FormField> {
+    fields
+        .into_iter()
+        .find(|field| field.name.data() == name.as_bytes())
+}
+
+/// Parses the fields of a URL-encoded form, skipping empty fields such as in `a=1&&b=2`.
+pub(crate) fn parse_form(content: &Span) -> Vec<FormField> {
+    let data = content.data();
+    let mut fields = Vec::new();
+
+    let mut start = 0;
+    for field in data.split(|b| *b == b'&') {
+        let end = start + field.len();
+        if !field.is_empty() {
+            let name_end = field
+                .iter()
+                .position(|b| *b == b'=')
+                .map_or(end, |pos| start + pos);
+            let value_start = (name_end + 1).min(end);
+
+            fields.push(FormField {
+                span: content.slice(start..end),
+                name: content.slice(start..name_end),
+                value: content.slice(value_start..end),
+            });
+        }
+        start = end + 1;
+    }
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::{parse_request, parse_response, Requests};
+    use crate::testutil::harness::assert_request;
+
+    use super::*;
+
+    #[test]
+    fn test_request_form() {
+        let src = b"POST /login HTTP/1.1\r\n\
+            Content-Type: Application/X-WWW-Form-Urlencoded; charset=utf-8\r\n\
+            Content-Length: 35\r\n\r\nuser=alice&&token&password=hunt%20r";
+        let request = parse_request(src).unwrap();
+        assert_request(src, &request);
+
+        let fields = request.form().unwrap();
+        let names: Vec<_> = fields.iter().map(|field| field.name.data()).collect();
+        assert_eq!(names, [&b"user"[..], b"token", b"password"]);
+        assert!(fields[1].value.is_empty());
+
+        let password = request.form_field("password").unwrap();
+        assert_eq!(password.value, "hunt%20r".as_bytes());
+        assert_eq!(password.value, 135..143);
+        assert_eq!(password.without_value(), RangeSet::from(126..135));
+        assert!(request.form_field("missing").is_none());
+    }
+
+    #[test]
+    fn test_request_form_chunked() {
+        let src = b"POST /login HTTP/1.1\r\n\
+            Content-Type: application/x-www-form-urlencoded\r\n\
+            Transfer-Encoding: chunked\r\n\r\n\
+            8\r\na=1&pass\r\n6\r\nword=x\r\n0\r\n\r\n";
+        let request = parse_request(src).unwrap();
+        assert_request(src, &request);
+
+        // The field is split across chunks, so its span excludes the framing.
+        let password = request.form_field("password").unwrap();
+        assert_eq!(password.span().data(), b"password=x");
+        assert_eq!(password.span().indices().len_ranges(), 2);
+        assert_eq!(password.value, "x".as_bytes());
+    }
+
+    #[test]
+    fn test_request_json_chunked() {
+        let src = b"POST /token HTTP/1.1\r\nContent-Type: application/json\r\n\
+            Transfer-Encoding: chunked\r\n\r\n\
+            A\r\n{\"secret\":\r\n6\r\n\"abc\"}\r\n0\r\n\r\n";
+        let request = Requests::new_from_slice(src).next().unwrap().unwrap();
+
+        let secret = request.json("/secret").unwrap();
+        assert_eq!(secret.span(), "abc");
+        assert!(request.json("/missing").is_none());
+        assert!(request.form().is_none());
+    }
+
+    #[test]
+    fn test_response_form() {
+        let response = parse_response(
+            b"HTTP/1.1 200 OK\r\nContent-Type: application/x-www-form-urlencoded\r\n\
+            Content-Length: 20\r\n\r\naccess_token=abc&x=1",
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.form_field("access_token").unwrap().value,
+            "abc".as_bytes()
+        );
+        assert_eq!(response.form().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,388 @@
+//! Versioned serialization of HTTP messages.
+//!
+//! [`Request`] and [`Response`] are serialized with a `version` field, so that messages
+//! persisted by older versions of this crate can still be loaded. Messages serialized before the
+//! field was introduced are read as version 0.
+//!
+//! The format is supported by non-self-describing formats, such as bincode and postcard, as all
+//! fields are always serialized. However, as these formats do not encode the names of fields,
+//! the version is read first, and only messages with the current layout can be loaded, i.e.
+//! version 5 or later. Messages of older versions are rejected with an error naming their
+//! version, and can only be loaded from self-describing formats, such as JSON.
+
+use std::fmt;
+
+use serde::{
+    de::{self, value::MapAccessDeserializer, MapAccess, SeqAccess, Visitor},
+    ser::SerializeStruct,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{
+    http::{Body, Header, Request, RequestLine, Response, Status},
+    Span,
+};
+
+/// The version of the serialization format of [`Request`] and [`Response`].
+///
+/// Changes:
+///
+/// - 1: Added the `version` field, and the `interim` responses of a [`Response`].
+/// - 2: Added the chunks of a [`Body`] with the chunked transfer coding.
+/// - 3: Added the `truncated` length of a [`Body`]. Replaced the `Unknown` body content with
+///   `Binary`, `Empty` and `Unparsed`. `Unknown` content is read as `Binary`.
+/// - 4: Added the `Compressed` body content.
+/// - 5: Added the trailer fields of a [`ChunkedBody`](crate::http::ChunkedBody).
+/// - 6: Added the `Xml` body content.
+pub const FORMAT_VERSION: u32 = 6;
+
+/// The oldest version of the format which can be loaded from non-self-describing formats.
+///
+/// Version 6 only appended a variant to the body content, so version 5 messages have the same
+/// layout as the current version. This must be raised to the current version whenever a field
+/// is added or a variant is inserted.
+const MIN_BINARY_VERSION: u32 = 5;
+
+/// Returns an error if the format version is not supported.
+fn check_version<E: serde::de::Error>(version: u32) -> Result<(), E> {
+    if version > FORMAT_VERSION {
+        return Err(E::custom(format!(
+            "unsupported format version {version}, the latest supported version is {FORMAT_VERSION}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Returns an error if the format version can not be loaded from a non-self-describing format.
+fn check_binary_version<E: de::Error>(version: u32) -> Result<(), E> {
+    check_version(version)?;
+    if version < MIN_BINARY_VERSION {
+        return Err(E::custom(format!(
+            "format version {version} can only be loaded from self-describing formats, the \
+            oldest version supported by other formats is {MIN_BINARY_VERSION}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Returns the next element of a sequence, or an error if the sequence has ended.
+fn next_element<'de, A: SeqAccess<'de>, T: Deserialize<'de>>(
+    seq: &mut A,
+    index: usize,
+    expected: &dyn de::Expected,
+) -> Result<T, A::Error> {
+    seq.next_element()?
+        .ok_or_else(|| de::Error::invalid_length(index, expected))
+}
+
+impl Serialize for Request {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Request", 5)?;
+        state.serialize_field("version", &FORMAT_VERSION)?;
+        state.serialize_field("span", &self.span)?;
+        state.serialize_field("request", &self.request)?;
+        state.serialize_field("headers", &self.headers)?;
+        state.serialize_field("body", &self.body)?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Request")]
+struct RequestRepr {
+    #[serde(default)]
+    version: u32,
+    span: Span,
+    request: RequestLine,
+    headers: Vec<Header>,
+    body: Option<Body>,
+}
+
+/// Deserializes a request from a map, as in self-describing formats, or from a sequence of its
+/// fields, as in non-self-describing formats.
+struct RequestVisitor;
+
+impl<'de> Visitor<'de> for RequestVisitor {
+    type Value = RequestRepr;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("struct Request")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        RequestRepr::deserialize(MapAccessDeserializer::new(map))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let version = next_element(&mut seq, 0, &self)?;
+        check_binary_version(version)?;
+
+        Ok(RequestRepr {
+            version,
+            span: next_element(&mut seq, 1, &self)?,
+            request: next_element(&mut seq, 2, &self)?,
+            headers: next_element(&mut seq, 3, &self)?,
+            body: next_element(&mut seq, 4, &self)?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Request {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = deserializer.deserialize_struct(
+            "Request",
+            &["version", "span", "request", "headers", "body"],
+            RequestVisitor,
+        )?;
+        check_version(repr.version)?;
+
+        Ok(Request {
+            span: repr.span,
+            request: repr.request,
+            headers: repr.headers,
+            body: repr.body,
+            info: None,
+        })
+    }
+}
+
+impl Serialize for Response {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Response", 6)?;
+        state.serialize_field("version", &FORMAT_VERSION)?;
+        state.serialize_field("span", &self.span)?;
+        state.serialize_field("status", &self.status)?;
+        state.serialize_field("headers", &self.headers)?;
+        state.serialize_field("body", &self.body)?;
+        state.serialize_field("interim", &self.interim)?;
+        state.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Response")]
+struct ResponseRepr {
+    #[serde(default)]
+    version: u32,
+    span: Span,
+    status: Status,
+    headers: Vec<Header>,
+    body: Option<Body>,
+    // Added in version 1.
+    #[serde(default)]
+    interim: Vec<Response>,
+}
+
+/// Deserializes a response from a map, as in self-describing formats, or from a sequence of its
+/// fields, as in non-self-describing formats.
+struct ResponseVisitor;
+
+impl<'de> Visitor<'de> for ResponseVisitor {
+    type Value = ResponseRepr;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("struct Response")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        ResponseRepr::deserialize(MapAccessDeserializer::new(map))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let version = next_element(&mut seq, 0, &self)?;
+        check_binary_version(version)?;
+
+        Ok(ResponseRepr {
+            version,
+            span: next_element(&mut seq, 1, &self)?,
+            status: next_element(&mut seq, 2, &self)?,
+            headers: next_element(&mut seq, 3, &self)?,
+            body: next_element(&mut seq, 4, &self)?,
+            interim: next_element(&mut seq, 5, &self)?,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Response {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = deserializer.deserialize_struct(
+            "Response",
+            &["version", "span", "status", "headers", "body", "interim"],
+            ResponseVisitor,
+        )?;
+        check_version(repr.version)?;
+
+        Ok(Response {
+            span: repr.span,
+            status: repr.status,
+            headers: repr.headers,
+            body: repr.body,
+            interim: repr.interim,
+            info: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fmt::Debug;
+
+    use serde::de::DeserializeOwned;
+
+    use crate::{
+        http::{parse_request, parse_response, Responses, Session},
+        json,
+        testutil::harness::{assert_request, assert_response, assert_session},
+        Spanned,
+    };
+
+    use super::*;
+
+    /// Checks that values round trip through self-describing and non-self-describing formats.
+    fn roundtrip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: &T) {
+        let json = serde_json::to_string(value).unwrap();
+        assert_eq!(&serde_json::from_str::<T>(&json).unwrap(), value, "json");
+
+        let bytes = bincode::serialize(value).unwrap();
+        assert_eq!(
+            &bincode::deserialize::<T>(&bytes).unwrap(),
+            value,
+            "bincode"
+        );
+
+        let bytes = postcard::to_stdvec(value).unwrap();
+        assert_eq!(
+            &postcard::from_bytes::<T>(&bytes).unwrap(),
+            value,
+            "postcard"
+        );
+    }
+
+    const REQUEST: &[u8] =
+        b"POST /hello HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+        Content-Length: 14\r\n\r\n{\"foo\": \"bar\"}";
+
+    const RESPONSE: &[u8] = b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\n\
+        Set-Cookie: id=a3fWa; Max-Age=2592000\r\nTransfer-Encoding: chunked\r\n\r\n\
+        2\r\nhe\r\n3\r\nllo\r\n0\r\n\r\n";
+
+    #[test]
+    fn test_roundtrip() {
+        let request = parse_request(REQUEST).unwrap();
+        assert_request(REQUEST, &request);
+        roundtrip(&request);
+        roundtrip(request.span());
+        roundtrip(&request.request);
+        roundtrip(&request.headers);
+        roundtrip(request.body.as_ref().unwrap());
+        roundtrip(&request.size_breakdown());
+        roundtrip(&request.request.target.uri().unwrap());
+
+        let response = Responses::new_from_slice(RESPONSE).next().unwrap().unwrap();
+        assert_eq!(response.interim.len(), 1);
+        assert_response(RESPONSE, &response);
+        roundtrip(&response);
+        roundtrip(
+            &response
+                .set_cookies()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+        );
+
+        let session = Session::new_from_slice(REQUEST, RESPONSE).unwrap();
+        assert_session(REQUEST, RESPONSE, &session);
+        roundtrip(&session);
+
+        roundtrip(&json::parse_str("{\"a\": [1, 2.5e3, true, null, \"b\"]}").unwrap());
+    }
+
+    /// A response serialized before the format was versioned.
+    const RESPONSE_V0: &str = r#"{"span":{"data":[72,84,84,80,47,49,46,49,32,50,48,52,32,78,111,32,67,111,110,116,101,110,116,13,10,13,10],"indices":[{"start":0,"end":27}],"_pd":null},"status":{"span":{"data":[72,84,84,80,47,49,46,49,32,50,48,52,32,78,111,32,67,111,110,116,101,110,116,13,10],"indices":[{"start":0,"end":25}],"_pd":null},"code":{"data":[50,48,52],"indices":[{"start":9,"end":12}],"_pd":null},"reason":{"data":[78,111,32,67,111,110,116,101,110,116],"indices":[{"start":13,"end":23}],"_pd":null}},"headers":[],"body":null}"#;
+
+    /// A request serialized in version 2 of the format.
+    const REQUEST_V2: &str = r#"{"version":2,"span":{"data":[71,69,84,32,47,32,72,84,84,80,47,49,46,49,13,10,13,10],"indices":[{"start":0,"end":18}],"_pd":null},"request":{"span":{"data":[71,69,84,32,47,32,72,84,84,80,47,49,46,49,13,10],"indices":[{"start":0,"end":16}],"_pd":null},"method":{"data":[71,69,84],"indices":[{"start":0,"end":3}],"_pd":null},"target":{"data":[47],"indices":[{"start":4,"end":5}],"_pd":null}},"headers":[],"body":null}"#;
+
+    #[test]
+    fn test_golden_v0() {
+        let src = b"HTTP/1.1 204 No Content\r\n\r\n";
+        let response: Response = serde_json::from_str(RESPONSE_V0).unwrap();
+        assert_response(src, &response);
+        assert_eq!(response, parse_response(src).unwrap());
+    }
+
+    /// A body with unknown content serialized in version 2 of the format.
+    const BODY_V2: &str = r#"{"span":{"data":[104,105],"indices":[{"start":38,"end":40}],"_pd":null},"content":{"Unknown":{"data":[104,105],"indices":[{"start":38,"end":40}],"_pd":null}},"chunked":null,"truncated":null}"#;
+
+    #[test]
+    fn test_golden_v2() {
+        let src = b"GET / HTTP/1.1\r\n\r\n";
+        let request = parse_request(src).unwrap();
+        assert_request(src, &request);
+
+        assert_eq!(
+            serde_json::to_string(&request).unwrap(),
+            REQUEST_V2.replace(r#""version":2"#, &format!(r#""version":{FORMAT_VERSION}"#))
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(REQUEST_V2).unwrap(),
+            request
+        );
+
+        let src = b"POST / HTTP/1.1\r\nContent-Length: 2\r\n\r\nhi";
+        let request = parse_request(src).unwrap();
+        assert_request(src, &request);
+        assert_eq!(
+            &serde_json::from_str::<Body>(BODY_V2).unwrap(),
+            request.body().unwrap()
+        );
+    }
+
+    /// A response with a binary body serialized in version 5 of the format, before the `Xml`
+    /// body content was added.
+    const RESPONSE_V5_BINCODE: &[u8] =
+        include_bytes!("../../tests/fixtures/format/response_v5.bincode");
+    const RESPONSE_V5_POSTCARD: &[u8] =
+        include_bytes!("../../tests/fixtures/format/response_v5.postcard");
+
+    #[test]
+    fn test_golden_v5() {
+        let src = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nhi";
+        let response = parse_response(src).unwrap();
+
+        for loaded in [
+            bincode::deserialize::<Response>(RESPONSE_V5_BINCODE).unwrap(),
+            postcard::from_bytes::<Response>(RESPONSE_V5_POSTCARD).unwrap(),
+        ] {
+            assert_response(src, &loaded);
+            assert_eq!(loaded, response);
+        }
+    }
+
+    /// A chunked response serialized in version 4 of the format, before the trailer fields of a
+    /// chunked body were added.
+    const RESPONSE_V4_BINCODE: &[u8] =
+        include_bytes!("../../tests/fixtures/format/response_v4.bincode");
+    const RESPONSE_V4_POSTCARD: &[u8] =
+        include_bytes!("../../tests/fixtures/format/response_v4.postcard");
+
+    #[test]
+    fn test_golden_v4_binary() {
+        let err = bincode::deserialize::<Response>(RESPONSE_V4_BINCODE).unwrap_err();
+        assert!(err.to_string().contains("format version 4"), "{err}");
+
+        let err = postcard::from_bytes::<Response>(RESPONSE_V4_POSTCARD).unwrap_err();
+        assert!(matches!(err, postcard::Error::SerdeDeCustom));
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let mut json =
+            serde_json::to_value(parse_request(b"GET / HTTP/1.1\r\n\r\n").unwrap()).unwrap();
+        json["version"] = (FORMAT_VERSION + 1).into();
+
+        let err = serde_json::from_value::<Request>(json).unwrap_err();
+        assert!(err.to_string().contains("unsupported format version"));
+    }
+}
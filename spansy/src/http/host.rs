@@ -0,0 +1,248 @@
+use crate::{
+    http::{uri::split_host_port, Header, Request},
+    Offset, Source, Span,
+};
+
+/// An error returned when the `Host` header of a request is inconsistent.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum HostError {
+    /// The request has no `Host` header.
+    #[error("request has no Host header")]
+    Missing,
+    /// The request has more than one `Host` header.
+    #[error("request has {0} Host headers")]
+    Duplicate(usize),
+    /// The `Host` header value is not a valid authority.
+    #[error("invalid Host header value: {0:?}")]
+    Invalid(String),
+    /// The `Host` header does not match the authority of the request target.
+    #[error("Host header {host:?} does not match the target authority {target:?}")]
+    TargetMismatch {
+        /// The `Host` header value.
+        host: String,
+        /// The authority of the request target.
+        target: String,
+    },
+    /// The `Host` header does not match the identity of the server.
+    #[error("Host header {host:?} does not match the server name {server_name:?}")]
+    ServerMismatch {
+        /// The `Host` header value.
+        host: String,
+        /// The server name supplied by the caller.
+        server_name: String,
+    },
+}
+
+/// The `Host` header of a request, validated to be consistent with the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ValidatedHost {
+    /// The `Host` header.
+    pub header: Header,
+    /// The host component of the header value.
+    pub host: Span<str>,
+    /// The port component of the header value, if present.
+    pub port: Option<Span<str>>,
+}
+
+impl Request {
+    /// Validates that the `Host` header of the request identifies the server which was
+    /// contacted.
+    ///
+    /// According to RFC 9112, section 3.2, a request must contain exactly one `Host`
+    /// header, which must match the authority of the target if it is in absolute-form or
+    /// authority-form. If the server name is provided, such as the name of the server
+    /// identity which was authenticated during the TLS handshake, the host must also match
+    /// it (case-insensitive, ignoring the port).
+    ///
+    /// # Arguments
+    ///
+    /// * `server_name` - The name of the server the request was sent to, if known.
+    pub fn validate_host(&self, server_name: Option<&str>) -> Result<ValidatedHost, HostError> {
+        let mut headers = self.headers_with_name("Host");
+        let header = headers.next().ok_or(HostError::Missing)?;
+
+        let count = 1 + headers.count();
+        if count > 1 {
+            return Err(HostError::Duplicate(count));
+        }
+
+        let value = std::str::from_utf8(header.value.as_bytes())
+            .map_err(|_| HostError::Invalid(format!("{:?}", header.value.as_bytes())))?;
+
+        let (host, port) =
+            split_host_port(value).map_err(|_| HostError::Invalid(value.to_string()))?;
+
+        if host.is_empty() || host.contains(['/', '?', '#', '@', ' ']) {
+            return Err(HostError::Invalid(value.to_string()));
+        }
+
+        if let Some(target) = self.target_authority()? {
+            let default_port = match target.scheme.as_deref() {
+                Some(scheme) if scheme.eq_ignore_ascii_case("http") => Some("80"),
+                Some(scheme) if scheme.eq_ignore_ascii_case("https") => Some("443"),
+                _ => None,
+            };
+
+            let host_matches = host.eq_ignore_ascii_case(&target.host);
+            let port_matches = port.or(default_port) == target.port.as_deref().or(default_port);
+
+            if !host_matches || !port_matches {
+                return Err(HostError::TargetMismatch {
+                    host: value.to_string(),
+                    target: match target.port {
+                        Some(port) => format!("{}:{port}", target.host),
+                        None => target.host,
+                    },
+                });
+            }
+        }
+
+        if let Some(server_name) = server_name {
+            let normalize = |name: &str| name.trim_end_matches('.').to_ascii_lowercase();
+
+            if normalize(host) != normalize(server_name) {
+                return Err(HostError::ServerMismatch {
+                    host: value.to_string(),
+                    server_name: server_name.to_string(),
+                });
+            }
+        }
+
+        let src = Source::from(header.value.0.data.clone());
+        let offset = header.value.0.indices.min().unwrap_or_default();
+        let span = |s: &str| {
+            let mut span = Span::new_from_str(&src, s);
+            span.offset(offset);
+            span
+        };
+
+        Ok(ValidatedHost {
+            header: header.clone(),
+            host: span(host),
+            port: port.map(span),
+        })
+    }
+
+    /// Returns the authority of the request target if it is in absolute-form or
+    /// authority-form.
+    fn target_authority(&self) -> Result<Option<TargetAuthority>, HostError> {
+        let target = self.request.target.as_str();
+        let invalid = || HostError::Invalid(target.to_string());
+
+        // The authority-form is only used for CONNECT requests.
+        if self.request.method.as_str() == "CONNECT" {
+            let (host, port) = split_host_port(target).map_err(|_| invalid())?;
+            return Ok(Some(TargetAuthority {
+                scheme: None,
+                host: host.to_string(),
+                port: port.map(str::to_string),
+            }));
+        }
+
+        if target.starts_with('/') || target == "*" {
+            return Ok(None);
+        }
+
+        let uri = self.request.target.uri().map_err(|_| invalid())?;
+        let host = uri.host.as_ref().ok_or_else(invalid)?;
+
+        Ok(Some(TargetAuthority {
+            scheme: uri
+                .scheme
+                .as_ref()
+                .map(|scheme| scheme.as_str().to_string()),
+            host: host.as_str().to_string(),
+            port: uri.port.as_ref().map(|port| port.as_str().to_string()),
+        }))
+    }
+}
+
+/// The authority of a request target.
+struct TargetAuthority {
+    scheme: Option<String>,
+    host: String,
+    port: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{http::parse_request, testutil::harness::assert_request};
+
+    use super::*;
+
+    fn validate(src: &[u8], server_name: Option<&str>) -> Result<ValidatedHost, HostError> {
+        let request = parse_request(src).unwrap();
+        assert_request(src, &request);
+        request.validate_host(server_name)
+    }
+
+    #[test]
+    fn test_validate_host_origin_form() {
+        let src = b"GET / HTTP/1.1\r\nHost: Example.com:8443\r\n\r\n";
+        let host = validate(src, Some("example.com")).unwrap();
+
+        assert_eq!(host.host, "Example.com");
+        assert_eq!(host.host.indices(), 22..33);
+        assert_eq!(host.port.unwrap(), "8443");
+    }
+
+    #[test]
+    fn test_validate_host_absolute_form() {
+        let src = b"GET https://example.com/a HTTP/1.1\r\nHost: example.com:443\r\n\r\n";
+        assert!(validate(src, None).is_ok());
+
+        let src = b"GET https://example.com/a HTTP/1.1\r\nHost: other.com\r\n\r\n";
+        assert!(matches!(
+            validate(src, None),
+            Err(HostError::TargetMismatch { .. })
+        ));
+
+        let src = b"GET http://example.com:8080/a HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        assert!(matches!(
+            validate(src, None),
+            Err(HostError::TargetMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_host_authority_form() {
+        let src = b"CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:443\r\n\r\n";
+        assert!(validate(src, None).is_ok());
+
+        let src = b"CONNECT example.com:443 HTTP/1.1\r\nHost: example.com:80\r\n\r\n";
+        assert!(validate(src, None).is_err());
+    }
+
+    #[test]
+    fn test_validate_host_server_name() {
+        let src = b"GET / HTTP/1.1\r\nHost: example.com.\r\n\r\n";
+
+        assert!(validate(src, Some("EXAMPLE.com")).is_ok());
+        assert_eq!(
+            validate(src, Some("other.com")),
+            Err(HostError::ServerMismatch {
+                host: "example.com.".to_string(),
+                server_name: "other.com".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_host_invalid() {
+        assert_eq!(
+            validate(b"GET / HTTP/1.1\r\n\r\n", None),
+            Err(HostError::Missing)
+        );
+        assert_eq!(
+            validate(b"GET / HTTP/1.1\r\nHost: a\r\nHost: a\r\n\r\n", None),
+            Err(HostError::Duplicate(2))
+        );
+        assert!(matches!(
+            validate(b"GET / HTTP/1.1\r\nHost: a/b\r\n\r\n", None),
+            Err(HostError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+
+use crate::{
+    http::{Body, ContentLengthPolicy, FramingWarning, Header, MessageStats},
+    Offset,
+};
+
+/// Information about how a message was parsed.
+///
+/// This is only collected if [`ParserConfig::parse_info`](crate::http::ParserConfig::parse_info)
+/// is enabled, so that pipelines can log anomalies tied to a specific message. It is not part
+/// of the serialized format, and is ignored when comparing messages.
+///
+/// # Example
+///
+/// ```
+/// use spansy::http::{parse_request_with_config, Fallback, ParserConfig};
+///
+/// let config = ParserConfig::new().truncated_body(true).parse_info(true);
+/// let request = parse_request_with_config(
+///     b"POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nhello",
+///     &config,
+/// )
+/// .unwrap();
+///
+/// let info = request.parse_info().unwrap();
+/// assert_eq!(info.consumed, 44);
+/// assert_eq!(info.fallbacks, [Fallback::TruncatedBody { missing: 5 }]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseInfo {
+    /// Statistics of the message, including the time spent parsing it.
+    pub stats: MessageStats,
+    /// The number of source bytes consumed by the message, including any bytes skipped after
+    /// it by the [`Responses`](crate::http::Responses) iterator.
+    pub consumed: usize,
+    /// The framing problems which were recovered from after the message.
+    pub warnings: Vec<FramingWarning>,
+    /// The lenient behaviors which were needed to accept the message.
+    pub fallbacks: Vec<Fallback>,
+}
+
+impl ParseInfo {
+    /// Returns `true` if the message was parsed without warnings or fallbacks.
+    pub fn is_clean(&self) -> bool {
+        self.warnings.is_empty() && self.fallbacks.is_empty()
+    }
+
+    /// Returns the information of a parsed message.
+    pub(crate) fn new(
+        stats: MessageStats,
+        headers: &[Header],
+        body: Option<&Body>,
+        policy: ContentLengthPolicy,
+    ) -> Self {
+        Self {
+            consumed: stats.start_line_len + stats.headers_len + stats.body_len,
+            stats,
+            warnings: Vec::new(),
+            fallbacks: fallbacks(headers, body, policy),
+        }
+    }
+}
+
+impl Offset for ParseInfo {
+    fn offset(&mut self, offset: usize) {
+        for warning in &mut self.warnings {
+            match warning {
+                FramingWarning::UnexpectedBody { range, .. } => {
+                    range.start = range.start.checked_add(offset).expect("offset overflows");
+                    range.end = range.end.checked_add(offset).expect("offset overflows");
+                }
+            }
+        }
+    }
+}
+
+/// A lenient behavior of the parser which was needed to accept a message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Fallback {
+    /// The message has differing `Content-Length` values, which were resolved by the
+    /// configured [`ContentLengthPolicy`].
+    ConflictingContentLength {
+        /// The policy used to resolve the values.
+        policy: ContentLengthPolicy,
+        /// The values, in the order they appear in the message.
+        values: Vec<usize>,
+    },
+    /// The body is shorter than its `Content-Length`, which was accepted because
+    /// [`ParserConfig::truncated_body`](crate::http::ParserConfig::truncated_body) is enabled.
+    TruncatedBody {
+        /// The number of bytes missing from the body.
+        missing: usize,
+    },
+}
+
+impl fmt::Display for Fallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fallback::ConflictingContentLength { policy, values } => write!(
+                f,
+                "resolved conflicting Content-Length values {values:?} with the {policy:?} policy"
+            ),
+            Fallback::TruncatedBody { missing } => {
+                write!(f, "accepted a body truncated by {missing} bytes")
+            }
+        }
+    }
+}
+
+/// Returns the fallbacks which were needed to accept a message with the given headers and
+/// body, parsed with the given `Content-Length` policy.
+fn fallbacks(
+    headers: &[Header],
+    body: Option<&Body>,
+    policy: ContentLengthPolicy,
+) -> Vec<Fallback> {
+    let mut fallbacks = Vec::new();
+
+    // The values were already validated by the parser, so any which differ were resolved by
+    // a lenient policy.
+    let values: Vec<usize> = headers
+        .iter()
+        .filter(|header| header.name.as_str().eq_ignore_ascii_case("content-length"))
+        .flat_map(|header| header.value.as_bytes().split(|b| *b == b','))
+        .filter_map(|value| std::str::from_utf8(value).ok()?.trim().parse().ok())
+        .collect();
+    if values.windows(2).any(|pair| pair[0] != pair[1]) {
+        fallbacks.push(Fallback::ConflictingContentLength { policy, values });
+    }
+
+    if let Some(missing) = body.and_then(|body| body.truncated) {
+        fallbacks.push(Fallback::TruncatedBody { missing });
+    }
+
+    fallbacks
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::{parse_request, parse_response_with_config, ParserConfig, Responses};
+    use crate::testutil::harness::assert_response;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_info_disabled() {
+        let request = parse_request(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        assert!(request.parse_info().is_none());
+    }
+
+    #[test]
+    fn test_parse_info_conflicting_content_length() {
+        let config = ParserConfig::new()
+            .content_length(ContentLengthPolicy::Largest)
+            .parse_info(true);
+        let src = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nContent-Length: 4, 2\r\n\r\nabcd";
+        let response = parse_response_with_config(src, &config).unwrap();
+        assert_response(src, &response);
+
+        let info = response.parse_info().unwrap();
+        assert_eq!(info.consumed, src.len());
+        assert_eq!(info.stats.body_len, 4);
+        assert_eq!(
+            info.fallbacks,
+            [Fallback::ConflictingContentLength {
+                policy: ContentLengthPolicy::Largest,
+                values: vec![2, 4, 2],
+            }]
+        );
+        assert!(!info.is_clean());
+
+        // The info is ignored when comparing messages.
+        let plain = parse_response_with_config(src, &config.parse_info(false)).unwrap();
+        assert_eq!(response, plain);
+    }
+
+    #[test]
+    fn test_parse_info_unexpected_body() {
+        let src =
+            b"HTTP/1.1 204 No Content\r\n\r\njunkHTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+        let mut responses =
+            Responses::new_from_slice(src).with_config(ParserConfig::new().parse_info(true));
+
+        let mut response = responses.next().unwrap().unwrap();
+        let info = response.parse_info().unwrap();
+        assert_eq!(info.consumed, 31);
+        assert_eq!(
+            info.warnings,
+            [FramingWarning::UnexpectedBody {
+                code: 204,
+                range: 27..31,
+            }]
+        );
+
+        response.offset(10);
+        assert_eq!(
+            response.parse_info().unwrap().warnings,
+            [FramingWarning::UnexpectedBody {
+                code: 204,
+                range: 37..41,
+            }]
+        );
+
+        let info = responses.next().unwrap().unwrap().info.unwrap();
+        assert!(info.is_clean());
+        assert_eq!(info.consumed, src.len() - 31);
+    }
+}
@@ -0,0 +1,201 @@
+use std::{fmt, ops::Range};
+
+use crate::ParseError;
+
+/// Limits on the messages parsed by the [`Requests`](crate::http::Requests) and
+/// [`Responses`](crate::http::Responses) iterators.
+///
+/// The limits bound the work done for a single untrusted transcript. Once a limit is
+/// exceeded the iterator yields an error whose cause is [`LimitExceeded`], without advancing
+/// its position. By default there are no limits.
+///
+/// # Example
+///
+/// ```
+/// use spansy::http::{IteratorLimits, LimitExceeded, Requests};
+///
+/// let src = b"GET / HTTP/1.1\r\n\r\n".repeat(3);
+/// let mut requests =
+///     Requests::new_from_slice(&src).with_limits(IteratorLimits::new().max_messages(2));
+///
+/// assert!(requests.next().unwrap().is_ok());
+/// assert!(requests.next().unwrap().is_ok());
+///
+/// let err = requests.next().unwrap().unwrap_err();
+/// assert_eq!(
+///     err.limit_exceeded(),
+///     Some(&LimitExceeded::Messages { limit: 2 })
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IteratorLimits {
+    max_messages: usize,
+    max_bytes: usize,
+}
+
+impl Default for IteratorLimits {
+    fn default() -> Self {
+        Self {
+            max_messages: usize::MAX,
+            max_bytes: usize::MAX,
+        }
+    }
+}
+
+impl IteratorLimits {
+    /// Returns new limits, which do not limit anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum number of messages yielded.
+    ///
+    /// Interim responses are counted along with the final response they precede.
+    pub fn max_messages(mut self, max: usize) -> Self {
+        self.max_messages = max;
+        self
+    }
+
+    /// Sets the maximum number of bytes of the source processed, summed over all messages.
+    ///
+    /// A message is rejected once it is parsed if it would exceed the limit, so a limit below
+    /// the length of the source does not bound the work done to parse a single message.
+    pub fn max_bytes(mut self, max: usize) -> Self {
+        self.max_bytes = max;
+        self
+    }
+}
+
+/// The cause of a [`ParseError`] returned when an [`IteratorLimits`] is exceeded.
+///
+/// Returned by [`ParseError::limit_exceeded`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LimitExceeded {
+    /// The maximum number of messages was yielded.
+    Messages {
+        /// The maximum number of messages.
+        limit: usize,
+    },
+    /// The next message would exceed the maximum number of bytes processed.
+    Bytes {
+        /// The maximum number of bytes.
+        limit: usize,
+        /// The number of bytes processed, including the next message.
+        processed: usize,
+    },
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LimitExceeded::Messages { limit } => {
+                write!(f, "more than {limit} messages")
+            }
+            LimitExceeded::Bytes { limit, processed } => {
+                write!(
+                    f,
+                    "processing {processed} bytes exceeds the limit of {limit} bytes"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+/// The usage of an [`IteratorLimits`] by an iterator.
+#[derive(Debug, Default)]
+pub(crate) struct LimitUsage {
+    pub(crate) limits: IteratorLimits,
+    messages: usize,
+    bytes: usize,
+}
+
+impl LimitUsage {
+    /// Checks whether another message may be parsed at `pos`.
+    pub(crate) fn check_message(&self, pos: usize) -> Result<(), ParseError> {
+        if self.messages < self.limits.max_messages {
+            return Ok(());
+        }
+
+        let limit = self.limits.max_messages;
+        Err(
+            ParseError::with_source("message limit exceeded", LimitExceeded::Messages { limit })
+                .with_label(pos..pos, "message limit exceeded"),
+        )
+    }
+
+    /// Records a message occupying `range` of the source, unless it exceeds the byte limit.
+    pub(crate) fn record(&mut self, range: Range<usize>) -> Result<(), ParseError> {
+        let processed = self.bytes.saturating_add(range.len());
+        if processed > self.limits.max_bytes {
+            let limit = self.limits.max_bytes;
+            return Err(ParseError::with_source(
+                "byte limit exceeded",
+                LimitExceeded::Bytes { limit, processed },
+            )
+            .with_label(range, "byte limit exceeded"));
+        }
+
+        self.messages += 1;
+        self.bytes = processed;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::{Requests, Responses},
+        testutil::harness::assert_response,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_limits() {
+        let src = b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok\
+            HTTP/1.1 204 No Content\r\n\r\n";
+
+        let limits = IteratorLimits::new().max_messages(1);
+        let mut responses = Responses::new_from_slice(src).with_limits(limits);
+        let response = responses.next().unwrap().unwrap();
+        assert_response(src, &response);
+        assert_eq!(response.interim.len(), 1);
+        let err = responses.next().unwrap().unwrap_err();
+        assert_eq!(
+            err.limit_exceeded(),
+            Some(&LimitExceeded::Messages { limit: 1 })
+        );
+        assert_eq!(err.ranges().collect::<Vec<_>>(), [&(65..65)]);
+
+        let limits = IteratorLimits::new().max_bytes(70);
+        let mut responses = Responses::new_from_slice(src).with_limits(limits);
+        assert!(responses.next().unwrap().is_ok());
+        let err = responses.next().unwrap().unwrap_err();
+        assert_eq!(
+            err.limit_exceeded(),
+            Some(&LimitExceeded::Bytes {
+                limit: 70,
+                processed: src.len()
+            })
+        );
+        assert_eq!(responses.pos(), 65);
+
+        let limits = IteratorLimits::new().max_bytes(src.len());
+        assert_eq!(
+            Responses::new_from_slice(src).with_limits(limits).count(),
+            2
+        );
+
+        let requests = Requests::new_from_slice(b"GET / HTTP/1.1\r\n\r\n")
+            .with_limits(IteratorLimits::new().max_bytes(17));
+        assert!(requests
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_err()
+            .limit_exceeded()
+            .is_some());
+    }
+}
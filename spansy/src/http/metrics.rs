@@ -0,0 +1,192 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use crate::ParseError;
+
+/// The kind of an HTTP message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MessageKind {
+    /// An HTTP request.
+    Request,
+    /// An HTTP response.
+    Response,
+}
+
+/// Statistics about a parsed HTTP message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct MessageStats {
+    /// The length of the request line or status line, including the line terminator.
+    pub start_line_len: usize,
+    /// The length of the header section, including the empty line terminating it.
+    pub headers_len: usize,
+    /// The length of the message body.
+    pub body_len: usize,
+    /// The number of chunks, if the body uses the chunked transfer coding.
+    pub chunks: usize,
+    /// The time spent parsing the message.
+    pub duration: Duration,
+}
+
+/// A sink for metrics collected while parsing HTTP messages.
+///
+/// A sink is installed with [`ParserConfig::metrics`](crate::http::ParserConfig::metrics),
+/// after which it is notified of every message parsed with that configuration, including
+/// by the [`Requests`](crate::http::Requests) and [`Responses`](crate::http::Responses)
+/// iterators.
+pub trait ParseMetrics: Send + Sync {
+    /// Records a message which was parsed successfully.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of the message.
+    /// * `stats` - Statistics about the message.
+    fn record_message(&self, kind: MessageKind, stats: &MessageStats);
+
+    /// Records a message which failed to parse.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of the message.
+    /// * `error` - The error returned by the parser.
+    /// * `duration` - The time spent parsing before the error.
+    fn record_error(&self, kind: MessageKind, error: &ParseError, duration: Duration) {
+        let _ = (kind, error, duration);
+    }
+}
+
+/// A [`ParseMetrics`] sink which aggregates counters.
+#[derive(Debug, Default)]
+pub struct ParseCounters {
+    requests: AtomicU64,
+    responses: AtomicU64,
+    errors: AtomicU64,
+    start_line_bytes: AtomicU64,
+    header_bytes: AtomicU64,
+    body_bytes: AtomicU64,
+    chunks: AtomicU64,
+    nanos: AtomicU64,
+}
+
+impl ParseCounters {
+    /// Returns a new set of counters, all zero.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of requests parsed.
+    pub fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of responses parsed.
+    pub fn responses(&self) -> u64 {
+        self.responses.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of messages which failed to parse.
+    pub fn errors(&self) -> u64 {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total length of the start lines parsed.
+    pub fn start_line_bytes(&self) -> u64 {
+        self.start_line_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total length of the header sections parsed.
+    pub fn header_bytes(&self) -> u64 {
+        self.header_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total length of the bodies parsed.
+    pub fn body_bytes(&self) -> u64 {
+        self.body_bytes.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of chunks parsed.
+    pub fn chunks(&self) -> u64 {
+        self.chunks.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total time spent parsing, including messages which failed to parse.
+    pub fn duration(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+
+    fn add_duration(&self, duration: Duration) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+impl ParseMetrics for ParseCounters {
+    fn record_message(&self, kind: MessageKind, stats: &MessageStats) {
+        match kind {
+            MessageKind::Request => &self.requests,
+            MessageKind::Response => &self.responses,
+        }
+        .fetch_add(1, Ordering::Relaxed);
+
+        for (counter, value) in [
+            (&self.start_line_bytes, stats.start_line_len),
+            (&self.header_bytes, stats.headers_len),
+            (&self.body_bytes, stats.body_len),
+            (&self.chunks, stats.chunks),
+        ] {
+            counter.fetch_add(value as u64, Ordering::Relaxed);
+        }
+
+        self.add_duration(stats.duration);
+    }
+
+    fn record_error(&self, _kind: MessageKind, _error: &ParseError, duration: Duration) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        self.add_duration(duration);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        http::{ParserConfig, Requests, Responses},
+        testutil::harness::assert_request,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_parse_counters() {
+        let counters = Arc::new(ParseCounters::new());
+        let config = ParserConfig::new().metrics(counters.clone());
+
+        let src = b"GET / HTTP/1.1\r\nHost: a\r\n\r\n\
+            POST / HTTP/1.1\r\nContent-Length: 4\r\n\r\nping";
+        let requests = Requests::new_from_slice(src)
+            .with_config(config.clone())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(requests.len(), 2);
+        for request in &requests {
+            assert_request(src, request);
+        }
+
+        let mut responses =
+            Responses::new_from_slice(b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nabc")
+                .with_config(config);
+        assert!(responses.next().unwrap().is_err());
+
+        assert_eq!(counters.requests(), 2);
+        assert_eq!(counters.responses(), 0);
+        assert_eq!(counters.errors(), 1);
+        assert_eq!(counters.start_line_bytes(), 16 + 17);
+        assert_eq!(counters.header_bytes(), 11 + 21);
+        assert_eq!(counters.body_bytes(), 4);
+    }
+}
@@ -1,40 +1,177 @@
 //! HTTP span parsing.
 
+mod accept;
+mod borrowed;
+mod chunked;
+mod conditional;
+mod config;
+mod connection;
+mod cookie;
+mod date;
+mod encoding;
+mod flags;
+mod form;
+#[cfg(feature = "serde")]
+mod format;
+mod host;
+mod info;
+mod limits;
+mod metrics;
+mod oauth;
+mod progress;
+mod provenance;
+mod redaction;
+mod redirect;
+mod scan;
+mod session;
+mod size;
 mod span;
+#[cfg(feature = "strict")]
+mod strict;
+mod summary;
 mod types;
-
-use bytes::Bytes;
-
-pub use span::{parse_request, parse_response};
+mod uri;
+pub mod value;
+mod version;
+mod warning;
+
+pub use accept::{parse_accept, AcceptItem};
+pub use borrowed::{
+    parse_request_ref, parse_response_ref, ChunkRef, HeaderRef, RequestRef, ResponseRef, SpanRef,
+};
+pub use chunked::{ChunkExtension, ChunkSizeError, MAX_CHUNK_SIZE_DIGITS};
+pub use conditional::{Revalidation, ValidatorKind};
+pub use config::{ContentLengthPolicy, ParserConfig};
+pub use connection::ConnectionBehavior;
+pub use cookie::{
+    parse_set_cookie, CookieAttribute, CookieAttributeKind, CookieName, CookieValue, SameSite,
+    SetCookie,
+};
+pub use date::HttpDate;
+#[cfg(feature = "brotli")]
+pub use encoding::BrotliDecoder;
+#[cfg(feature = "zstd")]
+pub use encoding::ZstdDecoder;
+pub use encoding::{ContentDecoder, ContentDecoders, DecodedBlock};
+#[cfg(feature = "gzip")]
+pub use encoding::{DeflateDecoder, GzipDecoder};
+pub use flags::HeaderFlags;
+pub use form::FormField;
+#[cfg(feature = "serde")]
+pub use format::FORMAT_VERSION;
+pub use host::{HostError, ValidatedHost};
+pub use info::{Fallback, ParseInfo};
+pub use limits::{IteratorLimits, LimitExceeded};
+pub use metrics::{MessageKind, MessageStats, ParseCounters, ParseMetrics};
+pub use oauth::OAuthToken;
+pub use progress::{CancellationToken, ParseObserver};
+pub use provenance::Provenance;
+pub use redaction::compare_redacted;
+pub use redirect::{RedirectChain, RedirectLink};
+pub use scan::scan_message_bounds;
+pub use session::{Exchange, Session};
+pub use size::{HeaderSize, SizeBreakdown};
+pub use span::{
+    parse_request, parse_request_with_config, parse_response, parse_response_with_config,
+};
+#[cfg(feature = "strict")]
+pub use strict::{validate_request, validate_response, Violation, ViolationKind};
+pub use summary::TranscriptSummary;
 pub use types::{
-    Body, BodyContent, Code, Header, HeaderName, HeaderValue, Method, Reason, Request, RequestLine,
-    Response, Status, Target,
+    Body, BodyContent, Chunk, ChunkedBody, Code, Header, HeaderName, HeaderValue, Method,
+    MethodKind, Reason, Request, RequestLine, Response, Status, Target,
 };
+pub use uri::Uri;
+pub use version::VersionChange;
+pub use warning::FramingWarning;
 
-use crate::ParseError;
+use std::ops::Range;
+
+use crate::{ParseError, Source};
+
+use self::{
+    limits::LimitUsage,
+    span::{parse_request_from_bytes, parse_response_from_bytes},
+};
 
-use self::span::{parse_request_from_bytes, parse_response_from_bytes};
 /// An iterator yielding parsed HTTP requests.
 #[derive(Debug)]
 pub struct Requests {
-    src: Bytes,
+    src: Source,
     /// The current position in the source string.
     pos: usize,
+    config: ParserConfig,
+    limits: LimitUsage,
 }
 
 impl Requests {
     /// Returns a new `Requests` iterator.
-    pub fn new(src: Bytes) -> Self {
-        Self { src, pos: 0 }
+    pub fn new(src: impl Into<Source>) -> Self {
+        Self {
+            src: src.into(),
+            pos: 0,
+            config: ParserConfig::default(),
+            limits: LimitUsage::default(),
+        }
     }
 
     /// Returns a new `Requests` iterator.
     pub fn new_from_slice(src: &[u8]) -> Self {
+        Self::new(Source::copy_from_slice(src))
+    }
+
+    /// Returns a new `Requests` iterator which starts parsing at the given position.
+    ///
+    /// Spans of the parsed requests are relative to the start of `src`.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The source to parse.
+    /// * `pos` - The position in the source to start parsing from.
+    pub fn new_at(src: impl Into<Source>, pos: usize) -> Self {
         Self {
-            src: Bytes::copy_from_slice(src),
-            pos: 0,
+            pos,
+            ..Self::new(src)
         }
     }
+
+    /// Returns the current position in the source.
+    ///
+    /// This is the position at which the next request will be parsed. It is not advanced if
+    /// a request fails to parse.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Sets the position in the source at which the next request will be parsed.
+    ///
+    /// This can be used to skip over bytes which are not part of a request, for example to
+    /// resume parsing after an error. If the position is past the end of the source, the
+    /// iterator ends.
+    pub fn set_pos(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /// Sets the configuration used to parse each request.
+    pub fn with_config(mut self, config: ParserConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the limits on the requests parsed by the iterator.
+    pub fn with_limits(mut self, limits: IteratorLimits) -> Self {
+        self.limits.limits = limits;
+        self
+    }
+
+    /// Returns an iterator which also yields the range of the source occupied by each
+    /// request.
+    ///
+    /// If a request fails to parse, the error is yielded with the range of the remaining
+    /// source, after which the iterator ends.
+    pub fn with_ranges(self) -> WithRanges<Self> {
+        WithRanges { inner: self }
+    }
 }
 
 impl Iterator for Requests {
@@ -42,58 +179,235 @@ impl Iterator for Requests {
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.pos >= self.src.len() {
-            None
-        } else {
-            Some(
-                parse_request_from_bytes(&self.src, self.pos).inspect(|req| {
-                    self.pos += req.span.len();
-                }),
-            )
+            return None;
+        }
+
+        let result = self.limits.check_message(self.pos).and_then(|_| {
+            let req = parse_request_from_bytes(&self.src, self.pos, &self.config)?;
+            self.limits.record(self.pos..self.pos + req.span.len())?;
+            Ok(req)
+        });
+        if let Ok(req) = &result {
+            self.pos += req.span.len();
         }
+
+        Some(result)
     }
 }
 
 /// An iterator yielding parsed HTTP responses.
 #[derive(Debug)]
 pub struct Responses {
-    src: Bytes,
+    src: Source,
     /// The current position in the source string.
     pos: usize,
+    config: ParserConfig,
+    limits: LimitUsage,
+    warnings: Vec<FramingWarning>,
 }
 
 impl Responses {
     /// Returns a new `Responses` iterator.
-    pub fn new(src: Bytes) -> Self {
-        Self { src, pos: 0 }
+    pub fn new(src: impl Into<Source>) -> Self {
+        Self {
+            src: src.into(),
+            pos: 0,
+            config: ParserConfig::default(),
+            limits: LimitUsage::default(),
+            warnings: Vec::new(),
+        }
     }
 
     /// Returns a new `Responses` iterator.
     pub fn new_from_slice(src: &[u8]) -> Self {
+        Self::new(Source::copy_from_slice(src))
+    }
+
+    /// Returns a new `Responses` iterator which starts parsing at the given position.
+    ///
+    /// Spans of the parsed responses are relative to the start of `src`.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The source to parse.
+    /// * `pos` - The position in the source to start parsing from.
+    pub fn new_at(src: impl Into<Source>, pos: usize) -> Self {
         Self {
-            src: Bytes::copy_from_slice(src),
-            pos: 0,
+            pos,
+            ..Self::new(src)
         }
     }
+
+    /// Returns the current position in the source.
+    ///
+    /// This is the position at which the next response will be parsed. It is not advanced if
+    /// a response fails to parse.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Sets the position in the source at which the next response will be parsed.
+    ///
+    /// This can be used to skip over bytes which are not part of a response, for example to
+    /// resume parsing after an error. If the position is past the end of the source, the
+    /// iterator ends.
+    pub fn set_pos(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /// Sets the configuration used to parse each response.
+    pub fn with_config(mut self, config: ParserConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Sets the limits on the responses parsed by the iterator.
+    pub fn with_limits(mut self, limits: IteratorLimits) -> Self {
+        self.limits.limits = limits;
+        self
+    }
+
+    /// Returns the framing problems which were recovered from so far.
+    pub fn warnings(&self) -> &[FramingWarning] {
+        &self.warnings
+    }
+
+    /// Returns an iterator which also yields the range of the source occupied by each
+    /// response, including its interim responses.
+    ///
+    /// If a response fails to parse, the error is yielded with the range of the remaining
+    /// source, after which the iterator ends.
+    pub fn with_ranges(self) -> WithRanges<Self> {
+        WithRanges { inner: self }
+    }
+}
+
+impl Responses {
+    /// Skips any bytes following a response whose status forbids a body, up to the next
+    /// status line.
+    ///
+    /// A server may send a body with such a response regardless, which would otherwise be
+    /// parsed as the start of the next response. The warning is also recorded in the parse
+    /// info of the response, if it has one.
+    fn skip_unexpected_body(&mut self, response: &mut Response) {
+        let Ok(code) = value::parse_integer(response.status.code.as_str().as_bytes()) else {
+            return;
+        };
+
+        if !matches!(code, 100..=199 | 204 | 304)
+            || self.pos >= self.src.len()
+            || self.src[self.pos..].starts_with(b"HTTP/")
+        {
+            return;
+        }
+
+        let end = warning::find_status_line(&self.src, self.pos).unwrap_or(self.src.len());
+        let warning = FramingWarning::UnexpectedBody {
+            code: code as u16,
+            range: self.pos..end,
+        };
+        if let Some(info) = &mut response.info {
+            info.consumed += end - self.pos;
+            info.warnings.push(warning.clone());
+        }
+        self.warnings.push(warning);
+        self.pos = end;
+    }
 }
 
 impl Iterator for Responses {
     type Item = Result<Response, ParseError>;
 
+    /// Returns the next final response, with any interim responses preceding it attached.
+    ///
+    /// An interim response at the end of the source is returned by itself.
     fn next(&mut self) -> Option<Self::Item> {
-        if self.pos >= self.src.len() {
-            None
-        } else {
-            Some(
-                parse_response_from_bytes(&self.src, self.pos).inspect(|resp| {
-                    self.pos += resp.span.len();
-                }),
-            )
+        let start = self.pos;
+        if start < self.src.len() {
+            if let Err(err) = self.limits.check_message(start) {
+                return Some(Err(err));
+            }
         }
+
+        let mut interim = Vec::new();
+        while self.pos < self.src.len() {
+            let mut response = match parse_response_from_bytes(&self.src, self.pos, &self.config) {
+                Ok(response) => response,
+                Err(err) => {
+                    self.pos = start;
+                    return Some(Err(err));
+                }
+            };
+
+            self.pos += response.span.len();
+            self.skip_unexpected_body(&mut response);
+
+            if response.is_interim() && self.pos < self.src.len() {
+                interim.push(response);
+            } else {
+                if let Err(err) = self.limits.record(start..self.pos) {
+                    self.pos = start;
+                    return Some(Err(err));
+                }
+                response.interim = interim;
+                return Some(Ok(response));
+            }
+        }
+
+        None
+    }
+}
+
+/// An iterator adapter yielding parsed HTTP messages along with the range of the source
+/// they occupy.
+///
+/// Returned by [`Requests::with_ranges`] and [`Responses::with_ranges`].
+#[derive(Debug)]
+pub struct WithRanges<I> {
+    inner: I,
+}
+
+impl Iterator for WithRanges<Requests> {
+    type Item = (Range<usize>, Result<Request, ParseError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.inner.pos;
+        let result = self.inner.next()?;
+
+        if result.is_err() {
+            self.inner.pos = self.inner.src.len();
+        }
+
+        Some((start..self.inner.pos, result))
+    }
+}
+
+impl Iterator for WithRanges<Responses> {
+    type Item = (Range<usize>, Result<Response, ParseError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.inner.pos;
+        let result = self.inner.next()?;
+
+        // The iterator may have skipped bytes following the response, which are reported as a
+        // warning rather than as part of the response.
+        let end = match &result {
+            Ok(response) => response.span.indices.end().unwrap_or(start),
+            Err(_) => {
+                self.inner.pos = self.inner.src.len();
+                self.inner.pos
+            }
+        };
+
+        Some((start..end, result))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::testutil::harness::{assert_request, assert_response};
+    use utils::range::{RangeSet, ToRangeSet};
+
     use crate::Spanned;
 
     use super::*;
@@ -113,6 +427,9 @@ mod tests {
             .unwrap();
 
         assert_eq!(reqs.len(), 2);
+        for req in &reqs {
+            assert_request(MULTIPLE_REQUESTS, req);
+        }
 
         assert_eq!(reqs[0].request.method.as_str(), "GET");
         assert!(reqs[0].body.is_none());
@@ -158,6 +475,9 @@ mod tests {
             .unwrap();
 
         assert_eq!(resps.len(), 3);
+        for resp in &resps {
+            assert_response(MULTIPLE_RESPONSES, resp);
+        }
 
         assert_eq!(resps[0].status.code.as_str(), "200");
         assert_eq!(
@@ -199,6 +519,49 @@ mod tests {
         assert!(resps[2].body.is_none());
     }
 
+    #[test]
+    fn test_parse_with_ranges() {
+        let ranges = Requests::new_from_slice(MULTIPLE_REQUESTS)
+            .with_ranges()
+            .map(|(range, req)| (range, req.is_ok()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            ranges,
+            vec![(0..35, true), (35..MULTIPLE_REQUESTS.len(), true)]
+        );
+
+        let src = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n\
+            HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\ntruncated";
+        let ranges = Responses::new_from_slice(src)
+            .with_ranges()
+            .map(|(range, resp)| (range, resp.is_ok()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(ranges, vec![(0..38, true), (38..src.len(), false)]);
+    }
+
+    #[test]
+    fn test_parse_resume() {
+        let src = b"garbage\r\nGET / HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        let mut reqs = Requests::new_from_slice(src);
+
+        assert!(reqs.next().unwrap().is_err());
+        assert_eq!(reqs.pos(), 0);
+
+        reqs.set_pos(9);
+        let req = reqs.next().unwrap().unwrap();
+        assert_request(src, &req);
+        assert_eq!(req.span().indices().min(), Some(9));
+        assert_eq!(reqs.pos(), src.len());
+        assert!(reqs.next().is_none());
+
+        let mut resps = Responses::new_at(Source::from_static(MULTIPLE_RESPONSES), 38);
+        assert_eq!(resps.next().unwrap().unwrap().status.code.as_str(), "200");
+        assert_eq!(resps.next().unwrap().unwrap().status.code.as_str(), "204");
+        assert!(resps.next().is_none());
+    }
+
     #[test]
     fn test_parse_request_duplicate_headers() {
         let req_bytes = b"GET / HTTP/1.1\r\nHost: localhost\r\nAccept: application/json\r\n\
@@ -225,6 +588,174 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_header_order() {
+        let req = parse_request(
+            b"GET / HTTP/1.1\r\nHost: localhost\r\nAccept: application/json\r\n\
+            X-Id: 1\r\nAccept: application/xml\r\n\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            req.headers().map(|h| h.name.as_str()).collect::<Vec<_>>(),
+            ["Host", "Accept", "X-Id", "Accept"]
+        );
+        assert_eq!(req.headers().len(), 4);
+        assert_eq!(req.header_at(2).unwrap().value.as_bytes(), b"1");
+        assert!(req.header_at(4).is_none());
+
+        let indices: Vec<_> = req
+            .headers_with_name("accept")
+            .map(|h| req.header_index(h).unwrap())
+            .collect();
+        assert_eq!(indices, [1, 3]);
+
+        let other = parse_request(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n").unwrap();
+        assert!(req.header_index(other.header_at(0).unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_duplicate_header_matrix() {
+        for (headers, name, values) in [
+            ("X-A: 1\r\n", "x-a", &["1"][..]),
+            ("X-A: 1\r\nx-a: 2\r\nX-A: 3\r\n", "X-A", &["1", "2", "3"]),
+            (
+                "x-a: 1\r\nX-B: 2\r\nX-a: 3\r\nx-b: 4\r\n",
+                "x-A",
+                &["1", "3"],
+            ),
+            ("X-B: 1\r\nx-a: 2\r\nX-B: 3\r\n", "X-A", &["2"]),
+            ("X-B: 1\r\n", "x-a", &[]),
+        ] {
+            let req = parse_request(format!("GET / HTTP/1.1\r\n{headers}\r\n").as_bytes()).unwrap();
+            let res =
+                parse_response(format!("HTTP/1.1 204 OK\r\n{headers}\r\n").as_bytes()).unwrap();
+
+            for (found, first, single) in [
+                (
+                    req.headers_with_name(name).collect::<Vec<_>>(),
+                    req.first_header_value(name),
+                    req.single_header_value(name),
+                ),
+                (
+                    res.headers_with_name(name).collect(),
+                    res.first_header_value(name),
+                    res.single_header_value(name),
+                ),
+            ] {
+                let found: Vec<_> = found.iter().map(|h| h.value.as_bytes()).collect();
+                assert_eq!(
+                    found,
+                    values.iter().map(|v| v.as_bytes()).collect::<Vec<_>>(),
+                    "{headers:?}"
+                );
+                assert_eq!(
+                    first.map(|v| v.as_bytes()),
+                    values.first().map(|v| v.as_bytes())
+                );
+
+                match values {
+                    [] => assert!(single.unwrap().is_none()),
+                    [value] => assert_eq!(single.unwrap().unwrap().as_bytes(), value.as_bytes()),
+                    _ => assert!(single.is_err(), "{headers:?}"),
+                }
+            }
+        }
+
+        // The error labels the second header with the name.
+        let src = b"GET / HTTP/1.1\r\nHost: a\r\nX: 1\r\nhost: b\r\n\r\n";
+        let err = parse_request(src)
+            .unwrap()
+            .single_header_value("HOST")
+            .unwrap_err();
+        assert_eq!(err.ranges().collect::<Vec<_>>(), [&(31..40)]);
+    }
+
+    #[test]
+    fn test_find_header_value_containing() {
+        let src = b"GET / HTTP/1.1\r\nAuthorization: Bearer TOKEN\r\n\
+            X-Auth-Token: token, Token\r\n\r\n";
+        let req = parse_request(src).unwrap();
+        assert_request(src, &req);
+
+        let found: Vec<_> = req.find_header_value_containing("token").collect();
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0].0.name.as_str(), "Authorization");
+        assert_eq!(found[0].1, b"TOKEN".as_slice());
+        assert_eq!(found[0].1.indices(), &RangeSet::from(38..43));
+        assert_eq!(found[1].0.name.as_str(), "X-Auth-Token");
+        assert_eq!(found[2].1, b"Token".as_slice());
+        assert_eq!(&src[found[2].1.indices().min().unwrap()..][..5], b"Token");
+
+        // Header names are not searched, and overlapping occurrences are not repeated.
+        assert_eq!(req.find_header_value_containing("auth").count(), 0);
+        let res = parse_response(b"HTTP/1.1 204 OK\r\nX: aaaa\r\n\r\n").unwrap();
+        assert_eq!(res.find_header_value_containing("AA").count(), 2);
+        assert_eq!(res.find_header_value_containing("").count(), 0);
+    }
+
+    #[test]
+    fn test_method_kind() {
+        for (method, kind) in [
+            ("GET", MethodKind::Get),
+            ("HEAD", MethodKind::Head),
+            ("POST", MethodKind::Post),
+            ("PUT", MethodKind::Put),
+            ("DELETE", MethodKind::Delete),
+            ("OPTIONS", MethodKind::Options),
+            ("TRACE", MethodKind::Trace),
+            ("PATCH", MethodKind::Patch),
+        ] {
+            let req = parse_request(format!("{method} / HTTP/1.1\r\n\r\n").as_bytes()).unwrap();
+            assert_eq!(req.request.method.kind(), kind);
+        }
+
+        let req = parse_request(b"CONNECT example.com:443 HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!(req.request.method.kind(), MethodKind::Connect);
+
+        for method in ["PROPFIND", "get"] {
+            let req = parse_request(format!("{method} / HTTP/1.1\r\n\r\n").as_bytes()).unwrap();
+            let MethodKind::Other(span) = req.request.method.kind() else {
+                panic!("expected extension method");
+            };
+            assert_eq!(span.as_str(), method);
+            assert_eq!(span.indices(), &RangeSet::from(0..method.len()));
+        }
+    }
+
+    #[test]
+    fn test_header_ows() {
+        let req = parse_request(
+            b"GET / HTTP/1.1\r\nHost:  localhost \t\r\nX-Empty: \r\nX-Tight:a b\r\n\r\n",
+        )
+        .unwrap();
+
+        let host = &req.headers[0];
+        assert_eq!(host.leading_ows(), b"  ".as_slice());
+        assert_eq!(host.leading_ows().indices(), &RangeSet::from(21..23));
+        assert_eq!(host.trailing_ows(), b" \t".as_slice());
+        assert_eq!(host.trailing_ows().indices(), &RangeSet::from(32..34));
+
+        let empty = &req.headers[1];
+        assert_eq!(empty.leading_ows(), b" ".as_slice());
+        assert!(empty.trailing_ows().is_empty());
+
+        let tight = &req.headers[2];
+        assert!(tight.leading_ows().is_empty());
+        assert!(tight.trailing_ows().is_empty());
+
+        // Every byte of a header is accounted for.
+        for header in &req.headers {
+            let len = header.name.as_str().len()
+                + 1
+                + header.leading_ows().len()
+                + header.value.as_bytes().len()
+                + header.trailing_ows().len()
+                + 2;
+            assert_eq!(len, header.span().len());
+        }
+    }
+
     #[test]
     fn test_parse_response_duplicate_headers() {
         let resp_bytes = b"HTTP/1.1 200 OK\r\nSet-Cookie: lang=en; Path=/\r\n\
@@ -250,4 +781,107 @@ mod tests {
         assert_eq!(headers.len(), 1);
         assert_eq!(headers.first().unwrap().value.as_bytes(), b"14");
     }
+
+    #[test]
+    fn test_parse_responses_unexpected_body() {
+        let src = b"HTTP/1.1 204 No Content\r\n\r\nstray body\r\n\
+            HTTP/1.1 304 Not Modified\r\nContent-Length: 4\r\n\r\nbody\
+            HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+
+        let mut resps = Responses::new_from_slice(src).with_ranges();
+
+        let (range, resp) = resps.next().unwrap();
+        assert_eq!(range, 0..27);
+        assert_eq!(resp.unwrap().status.code.as_str(), "204");
+
+        let (range, resp) = resps.next().unwrap();
+        assert_eq!(range, 39..87);
+        assert_eq!(resp.unwrap().status.code.as_str(), "304");
+
+        let (_, resp) = resps.next().unwrap();
+        assert_eq!(resp.unwrap().body.unwrap().span(), b"ok".as_slice());
+        assert!(resps.next().is_none());
+
+        assert_eq!(
+            resps.inner.warnings(),
+            [
+                FramingWarning::UnexpectedBody {
+                    code: 204,
+                    range: 27..39,
+                },
+                FramingWarning::UnexpectedBody {
+                    code: 304,
+                    range: 87..91,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_responses_interim() {
+        let src = b"HTTP/1.1 100 Continue\r\n\r\n\
+            HTTP/1.1 103 Early Hints\r\nLink: </style.css>\r\n\r\n\
+            HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok\
+            HTTP/1.1 100 Continue\r\n\r\n";
+
+        let resps = Responses::new_from_slice(src)
+            .with_ranges()
+            .map(|(range, resp)| (range, resp.unwrap()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(resps.len(), 2);
+        for (_, resp) in &resps {
+            assert_response(src, resp);
+        }
+
+        let (range, resp) = &resps[0];
+        assert_eq!(*range, 0..113);
+        assert_eq!(resp.status.code.as_str(), "200");
+        assert_eq!(
+            resp.interim
+                .iter()
+                .map(|interim| interim.status.code.as_str())
+                .collect::<Vec<_>>(),
+            ["100", "103"]
+        );
+
+        // A trailing interim response has no final response to attach to.
+        let (range, resp) = &resps[1];
+        assert_eq!(*range, 113..src.len());
+        assert!(resp.is_interim());
+        assert!(resp.interim.is_empty());
+    }
+
+    #[test]
+    fn test_collect_spanned_components() {
+        let src = b"POST / HTTP/1.1\r\nContent-Type: application/json\r\n\
+            Transfer-Encoding: chunked\r\n\r\n5\r\n{\"a\":\r\n2\r\n1}\r\n0\r\n\r\n";
+        let request = parse_request(src).unwrap();
+        assert_request(src, &request);
+        let body = request.body.as_ref().unwrap();
+        let BodyContent::Json(value) = &body.content else {
+            panic!("expected JSON body");
+        };
+        let chunked = body.chunked.as_ref().unwrap();
+
+        let components: Vec<Box<dyn ToRangeSet<usize>>> = vec![
+            Box::new(&request.request.method),
+            Box::new(&request.headers[0]),
+            Box::new(value.get("a").unwrap()),
+            Box::new(&chunked.chunks[1]),
+        ];
+        assert_eq!(
+            components.to_range_set(),
+            RangeSet::from([(0..4), (17..49), (89..96)])
+        );
+
+        let spans: Vec<Box<dyn Spanned>> = vec![Box::new(&request.headers[1]), Box::new(body)];
+        assert_eq!(
+            spans
+                .iter()
+                .map(|span| span.to_range_set())
+                .collect::<Vec<_>>(),
+            [RangeSet::from(49..77), RangeSet::from(79..101)]
+        );
+    }
 }
@@ -0,0 +1,210 @@
+use utils::range::{Difference, RangeSet, ToRangeSet};
+
+use crate::{
+    http::{BodyContent, Response},
+    json::{JsonValue, Number, String},
+    ParseError, Spanned,
+};
+
+/// A successful OAuth 2.0 access token response (RFC 6749, section 5.1).
+///
+/// # Example
+///
+/// ```
+/// use spansy::{http::parse_response, Spanned};
+///
+/// let src = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 67\r\n\r\n\
+///     {\"access_token\":\"2YotnFZF\",\"token_type\":\"Bearer\",\"expires_in\":3600}";
+/// let response = parse_response(src).unwrap();
+/// let token = response.oauth_token().unwrap().unwrap();
+///
+/// assert_eq!(token.access_token.span().as_str(), "2YotnFZF");
+///
+/// // Everything but the access token can be revealed.
+/// let reveal = token.reveal_non_tokens();
+/// assert_eq!(reveal.len(), src.len() - "2YotnFZF".len());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OAuthToken {
+    /// The indices of the response.
+    indices: RangeSet<usize>,
+    /// The access token.
+    pub access_token: String,
+    /// The type of the access token, such as `Bearer`.
+    pub token_type: String,
+    /// The lifetime of the access token in seconds, if present.
+    pub expires_in: Option<Number>,
+    /// The refresh token, if present.
+    pub refresh_token: Option<String>,
+    /// The scope of the access token, if present.
+    pub scope: Option<String>,
+    /// The OpenID Connect ID token, if present.
+    pub id_token: Option<String>,
+}
+
+impl OAuthToken {
+    /// Returns the indices of the tokens: the access token, and the refresh token and ID
+    /// token if present.
+    ///
+    /// The indices exclude the quotes of each token.
+    pub fn tokens(&self) -> RangeSet<usize> {
+        [
+            Some(&self.access_token),
+            self.refresh_token.as_ref(),
+            self.id_token.as_ref(),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|token| token.span().indices().clone())
+        .collect::<Vec<_>>()
+        .to_range_set()
+    }
+
+    /// Returns the indices of the response excluding the tokens.
+    ///
+    /// This is a common redaction: the tokens are hidden, and the rest of the response,
+    /// including the token type, lifetime and scope, is revealed.
+    pub fn reveal_non_tokens(&self) -> RangeSet<usize> {
+        self.indices.difference(&self.tokens())
+    }
+}
+
+impl Response {
+    /// Returns the OAuth 2.0 access token of the response, if it is a token response.
+    ///
+    /// A response is recognized as a token response if it is successful (2xx) and its body
+    /// is a JSON object with an `access_token` member. Returns an error if the members of a
+    /// token response have unexpected types, or if `token_type` is missing.
+    pub fn oauth_token(&self) -> Option<Result<OAuthToken, ParseError>> {
+        if !self.status.code.as_str().starts_with('2') {
+            return None;
+        }
+
+        let BodyContent::Json(body @ JsonValue::Object(_)) = &self.body.as_ref()?.content else {
+            return None;
+        };
+        let access_token = body.get("access_token")?;
+
+        Some(parse_token(self, body, access_token))
+    }
+}
+
+fn parse_token(
+    response: &Response,
+    body: &JsonValue,
+    access_token: &JsonValue,
+) -> Result<OAuthToken, ParseError> {
+    let string = |name: &str, value: &JsonValue| match value {
+        JsonValue::String(value) => Ok(value.clone()),
+        _ => Err(ParseError::new(format!(
+            "OAuth token response member {name:?} is not a string"
+        ))
+        .with_label(range(value), "expected a string")),
+    };
+    let optional_string = |name: &str| body.get(name).map(|value| string(name, value)).transpose();
+
+    Ok(OAuthToken {
+        indices: response.span().indices().clone(),
+        access_token: string("access_token", access_token)?,
+        token_type: string(
+            "token_type",
+            body.get("token_type").ok_or_else(|| {
+                ParseError::new("OAuth token response is missing \"token_type\"")
+                    .with_label(range(body), "missing \"token_type\"")
+            })?,
+        )?,
+        expires_in: body
+            .get("expires_in")
+            .map(|value| match value {
+                JsonValue::Number(value) => Ok(value.clone()),
+                _ => Err(ParseError::new(
+                    "OAuth token response member \"expires_in\" is not a number",
+                )
+                .with_label(range(value), "expected a number")),
+            })
+            .transpose()?,
+        refresh_token: optional_string("refresh_token")?,
+        scope: optional_string("scope")?,
+        id_token: optional_string("id_token")?,
+    })
+}
+
+/// Returns the range spanned by a value.
+fn range(value: &JsonValue) -> std::ops::Range<usize> {
+    let indices = value.span().indices();
+    indices.min().unwrap_or_default()..indices.end().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use utils::range::{Disjoint, Subset};
+
+    use crate::{http::parse_response, testutil::harness::assert_response};
+
+    use super::*;
+
+    fn response(status: &str, body: &str) -> Response {
+        let src = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json;charset=UTF-8\r\n\
+            Cache-Control: no-store\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let response = parse_response(src.as_bytes()).unwrap();
+        assert_response(src.as_bytes(), &response);
+        response
+    }
+
+    #[test]
+    fn test_oauth_token() {
+        let response = response(
+            "200 OK",
+            r#"{"access_token":"2YotnFZFEjr1zCsicMWpAA","token_type":"example",
+            "expires_in":3600,"refresh_token":"tGzv3JOkF0XG5Qx2TlKWIA","scope":"read write"}"#,
+        );
+        let token = response.oauth_token().unwrap().unwrap();
+
+        assert_eq!(token.access_token.span().as_str(), "2YotnFZFEjr1zCsicMWpAA");
+        assert_eq!(token.token_type.span().as_str(), "example");
+        assert_eq!(token.expires_in.as_ref().unwrap().span(), "3600");
+        assert_eq!(
+            token.refresh_token.as_ref().unwrap().span().as_str(),
+            "tGzv3JOkF0XG5Qx2TlKWIA"
+        );
+        assert_eq!(token.scope.as_ref().unwrap().span().as_str(), "read write");
+        assert!(token.id_token.is_none());
+
+        let tokens = token.tokens();
+        assert_eq!(tokens.len_ranges(), 2);
+        assert_eq!(tokens.len(), 44);
+
+        let reveal = token.reveal_non_tokens();
+        assert_eq!(reveal.len(), response.span().len() - 44);
+        assert!(reveal.is_disjoint(token.access_token.span().indices()));
+        assert!(token.token_type.span().indices().is_subset(&reveal));
+    }
+
+    #[test]
+    fn test_oauth_token_not_recognized() {
+        for (status, body) in [
+            ("400 Bad Request", r#"{"error":"invalid_request"}"#),
+            ("200 OK", r#"{"token":"abc"}"#),
+            ("200 OK", r#"["access_token"]"#),
+        ] {
+            assert!(response(status, body).oauth_token().is_none(), "{body}");
+        }
+    }
+
+    #[test]
+    fn test_oauth_token_invalid() {
+        for body in [
+            r#"{"access_token":1,"token_type":"bearer"}"#,
+            r#"{"access_token":"abc"}"#,
+            r#"{"access_token":"abc","token_type":"bearer","expires_in":"3600"}"#,
+            r#"{"access_token":"abc","token_type":"bearer","refresh_token":null}"#,
+        ] {
+            let err = response("200 OK", body).oauth_token().unwrap().unwrap_err();
+            assert_eq!(err.ranges().count(), 1, "{body}");
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::http::{MessageKind, ParserConfig};
+
+/// An observer notified of the progress of the HTTP parser.
+///
+/// An observer is installed with [`ParserConfig::observer`], after which it is notified as
+/// each message parsed with that configuration progresses, including by the
+/// [`Requests`](crate::http::Requests) and [`Responses`](crate::http::Responses) iterators.
+/// All ranges are ranges of the source.
+///
+/// An observer can also cancel parsing, which is checked before each message and before
+/// each body is parsed.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::{
+///     atomic::{AtomicUsize, Ordering},
+///     Arc,
+/// };
+///
+/// use spansy::http::{MessageKind, ParseObserver, ParserConfig, Requests};
+///
+/// #[derive(Default)]
+/// struct Progress(AtomicUsize);
+///
+/// impl ParseObserver for Progress {
+///     fn message_parsed(&self, _kind: MessageKind, range: std::ops::Range<usize>) {
+///         self.0.store(range.end, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let progress = Arc::new(Progress::default());
+/// let src = b"GET / HTTP/1.1\r\n\r\nGET /a HTTP/1.1\r\n\r\n";
+/// let requests = Requests::new_from_slice(src)
+///     .with_config(ParserConfig::new().observer(progress.clone()));
+///
+/// assert_eq!(requests.count(), 2);
+/// assert_eq!(progress.0.load(Ordering::Relaxed), src.len());
+/// ```
+pub trait ParseObserver: Send + Sync {
+    /// Called when the parser starts parsing a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of the message.
+    /// * `offset` - The position of the message in the source.
+    fn message_started(&self, kind: MessageKind, offset: usize) {
+        let _ = (kind, offset);
+    }
+
+    /// Called when the head of a message, i.e. its start line and headers, is parsed.
+    fn head_parsed(&self, kind: MessageKind, range: Range<usize>) {
+        let _ = (kind, range);
+    }
+
+    /// Called when the body of a message is parsed.
+    fn body_parsed(&self, kind: MessageKind, range: Range<usize>) {
+        let _ = (kind, range);
+    }
+
+    /// Called when a message is parsed.
+    fn message_parsed(&self, kind: MessageKind, range: Range<usize>) {
+        let _ = (kind, range);
+    }
+
+    /// Returns `true` if parsing is cancelled.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// A [`ParseObserver`] which cancels parsing once it is cancelled.
+///
+/// The token is cheap to clone, and all clones share the same state, so it can be cancelled
+/// from another thread than the one parsing.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Returns a new token which is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels parsing.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+impl ParseObserver for CancellationToken {
+    fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Notifies the observer, if one is configured.
+pub(crate) fn notify(config: &ParserConfig, f: impl FnOnce(&dyn ParseObserver)) {
+    if let Some(observer) = config.observer_ref() {
+        f(observer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use crate::{
+        http::{Requests, Responses},
+        testutil::harness::{assert_request, assert_response},
+    };
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Events(Mutex<Vec<(&'static str, MessageKind, Range<usize>)>>);
+
+    impl ParseObserver for Events {
+        fn message_started(&self, kind: MessageKind, offset: usize) {
+            self.0.lock().unwrap().push(("start", kind, offset..offset));
+        }
+
+        fn head_parsed(&self, kind: MessageKind, range: Range<usize>) {
+            self.0.lock().unwrap().push(("head", kind, range));
+        }
+
+        fn body_parsed(&self, kind: MessageKind, range: Range<usize>) {
+            self.0.lock().unwrap().push(("body", kind, range));
+        }
+
+        fn message_parsed(&self, kind: MessageKind, range: Range<usize>) {
+            self.0.lock().unwrap().push(("message", kind, range));
+        }
+    }
+
+    #[test]
+    fn test_observer_events() {
+        let events = Arc::new(Events::default());
+        let config = ParserConfig::new().observer(events.clone());
+
+        let src = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+        let mut responses = Responses::new_from_slice(src).with_config(config);
+        assert_response(src, &responses.next().unwrap().unwrap());
+
+        let kind = MessageKind::Response;
+        assert_eq!(
+            *events.0.lock().unwrap(),
+            [
+                ("start", kind, 0..0),
+                ("head", kind, 0..38),
+                ("body", kind, 38..40),
+                ("message", kind, 0..40),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let token = CancellationToken::new();
+        let src = b"GET / HTTP/1.1\r\n\r\nGET /a HTTP/1.1\r\n\r\n";
+        let mut requests = Requests::new_from_slice(src)
+            .with_config(ParserConfig::new().observer(Arc::new(token.clone())));
+
+        assert_request(src, &requests.next().unwrap().unwrap());
+
+        token.cancel();
+        let err = requests.next().unwrap().unwrap_err();
+        assert_eq!(err.ranges().collect::<Vec<_>>(), [&(18..18)]);
+        assert_eq!(requests.pos(), 18);
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt;
+
+use utils::range::{RangeSet, RangeSetMap};
+
+use crate::{
+    http::{Header, MessageKind, Request, Response, Session},
+    Spanned,
+};
+
+/// The origin of a range of an HTTP session, such as a header of a request.
+///
+/// Provenances are ordered by message kind, then message index, then label, so a map keyed
+/// by provenance lists the ranges of each message together.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Provenance {
+    /// The kind of the message, which is a request if the range was sent and a response if
+    /// it was received.
+    pub kind: MessageKind,
+    /// The index of the exchange of the message in the session.
+    pub message: usize,
+    /// The label of the range within the message, such as `request-line`,
+    /// `header:authorization` or `body`.
+    pub label: std::string::String,
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            MessageKind::Request => "request",
+            MessageKind::Response => "response",
+        };
+        write!(f, "{kind}[{}]/{}", self.message, self.label)
+    }
+}
+
+impl Session {
+    /// Returns the ranges of the session labeled with their provenance.
+    ///
+    /// Each message is labeled with its start line (`request-line` or `status-line`), its
+    /// headers (`header:` followed by the lowercase header name, merging repeated headers)
+    /// and its `body`. The interim responses preceding a response are labeled `interim`.
+    /// The blank line which ends the headers is not labeled.
+    ///
+    /// Request ranges are relative to the sent data, and response ranges to the received
+    /// data, so the labels of a set of committed ranges of either direction can be found by
+    /// restricting the map to the ranges with [`RangeSetMap::restrict`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spansy::http::{MessageKind, Session};
+    /// use utils::range::RangeSet;
+    ///
+    /// let session = Session::new_from_slice(
+    ///     b"GET / HTTP/1.1\r\nAuthorization: Bearer abc\r\n\r\n",
+    ///     b"HTTP/1.1 204 No Content\r\n\r\n",
+    /// )
+    /// .unwrap();
+    /// let provenance = session.provenance();
+    ///
+    /// // Explain which parts of the request a commitment covers.
+    /// let committed = provenance.restrict(&RangeSet::from(30..40));
+    /// let labels: Vec<_> = committed
+    ///     .keys()
+    ///     .filter(|provenance| provenance.kind == MessageKind::Request)
+    ///     .map(ToString::to_string)
+    ///     .collect();
+    /// assert_eq!(labels, ["request[0]/header:authorization"]);
+    /// ```
+    pub fn provenance(&self) -> RangeSetMap<Provenance, usize> {
+        let mut map = RangeSetMap::new();
+
+        for (message, exchange) in self.exchanges.iter().enumerate() {
+            let mut label = |kind, label: std::string::String, indices: &RangeSet<usize>| {
+                map.union_mut(
+                    Provenance {
+                        kind,
+                        message,
+                        label,
+                    },
+                    indices.clone(),
+                );
+            };
+
+            let Request {
+                request,
+                headers,
+                body,
+                ..
+            } = &exchange.request;
+            label(
+                MessageKind::Request,
+                "request-line".to_string(),
+                request.span().indices(),
+            );
+            label_headers(headers, |name, header| {
+                label(MessageKind::Request, name, header.span().indices())
+            });
+            if let Some(body) = body {
+                label(
+                    MessageKind::Request,
+                    "body".to_string(),
+                    body.span().indices(),
+                );
+            }
+
+            let Some(Response {
+                status,
+                headers,
+                body,
+                interim,
+                ..
+            }) = &exchange.response
+            else {
+                continue;
+            };
+            for response in interim {
+                label(
+                    MessageKind::Response,
+                    "interim".to_string(),
+                    response.span().indices(),
+                );
+            }
+            label(
+                MessageKind::Response,
+                "status-line".to_string(),
+                status.span().indices(),
+            );
+            label_headers(headers, |name, header| {
+                label(MessageKind::Response, name, header.span().indices())
+            });
+            if let Some(body) = body {
+                label(
+                    MessageKind::Response,
+                    "body".to_string(),
+                    body.span().indices(),
+                );
+            }
+        }
+
+        map
+    }
+}
+
+fn label_headers(headers: &[Header], mut label: impl FnMut(std::string::String, &Header)) {
+    for header in headers {
+        label(
+            format!("header:{}", header.name.as_str().to_ascii_lowercase()),
+            header,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil::harness::assert_session;
+    use utils::range::Difference;
+
+    use super::*;
+
+    #[test]
+    fn test_session_provenance() {
+        let sent = b"POST /login HTTP/1.1\r\nHost: example.com\r\nCookie: a=1\r\n\
+            Cookie: b=2\r\nContent-Length: 5\r\n\r\nhello";
+        let received = b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\n\
+            Content-Length: 2\r\n\r\nok";
+        let session = Session::new_from_slice(sent, received).unwrap();
+        assert_session(sent, received, &session);
+        let provenance = session.provenance();
+
+        let labels: Vec<_> = provenance.keys().map(ToString::to_string).collect();
+        assert_eq!(
+            labels,
+            [
+                "request[0]/body",
+                "request[0]/header:content-length",
+                "request[0]/header:cookie",
+                "request[0]/header:host",
+                "request[0]/request-line",
+                "response[0]/body",
+                "response[0]/header:content-length",
+                "response[0]/interim",
+                "response[0]/status-line",
+            ]
+        );
+
+        let request = Provenance {
+            kind: MessageKind::Request,
+            message: 0,
+            label: "header:cookie".to_string(),
+        };
+        assert_eq!(
+            provenance.get(&request).unwrap().len(),
+            2 * "Cookie: a=1\r\n".len()
+        );
+
+        // Only the blank lines which end the headers are unlabeled.
+        let request_range = RangeSet::from(0..sent.len());
+        let labeled = provenance.union_of(
+            provenance
+                .keys()
+                .filter(|provenance| provenance.kind == MessageKind::Request),
+        );
+        assert_eq!(request_range.difference(&labeled).len(), 2);
+    }
+}
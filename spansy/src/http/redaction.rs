@@ -0,0 +1,136 @@
+use std::{cmp::Ordering, iter::Peekable};
+
+use utils::range::{Difference, RangeSet, RangeSetBuilder};
+
+use crate::Spanned;
+
+/// Compares two messages outside of the redacted ranges of the source.
+///
+/// The messages are compared by source index, so a byte differs if it is present in both
+/// messages with a different value, or if it is present in only one of them. This is useful
+/// to check that a claimed reconstruction of a message matches the revealed message, where
+/// only the redacted bytes may differ.
+///
+/// # Errors
+///
+/// Returns the ranges of the bytes which differ outside of `redacted`.
+///
+/// # Example
+///
+/// ```
+/// use spansy::http::{compare_redacted, parse_request};
+/// use utils::range::RangeSet;
+///
+/// let revealed = parse_request(b"GET / HTTP/1.1\r\nAuthorization: XXXX\r\n\r\n").unwrap();
+/// let claimed = parse_request(b"GET / HTTP/1.1\r\nAuthorization: abcd\r\n\r\n").unwrap();
+///
+/// assert_eq!(
+///     compare_redacted(&revealed, &claimed, &RangeSet::default()),
+///     Err(RangeSet::from(31..35))
+/// );
+/// assert!(compare_redacted(&revealed, &claimed, &RangeSet::from(31..35)).is_ok());
+/// ```
+pub fn compare_redacted<T: ?Sized>(
+    a: &impl Spanned<T>,
+    b: &impl Spanned<T>,
+    redacted: &RangeSet<usize>,
+) -> Result<(), RangeSet<usize>> {
+    let (a, b) = (a.span(), b.span());
+    let mut a = a.indices().iter().zip(a.data()).peekable();
+    let mut b = b.indices().iter().zip(b.data()).peekable();
+
+    let mut diff = RangeSetBuilder::new();
+    while let Some(idx) = next_diff(&mut a, &mut b) {
+        diff.push(idx..idx + 1);
+    }
+
+    let diff = diff.build().difference(redacted);
+    if diff.len_ranges() == 0 {
+        Ok(())
+    } else {
+        Err(diff)
+    }
+}
+
+/// Returns the next index at which the bytes differ.
+fn next_diff<'a>(
+    a: &mut Peekable<impl Iterator<Item = (usize, &'a u8)>>,
+    b: &mut Peekable<impl Iterator<Item = (usize, &'a u8)>>,
+) -> Option<usize> {
+    loop {
+        let order = match (a.peek(), b.peek()) {
+            (Some((a, _)), Some((b, _))) => a.cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return None,
+        };
+
+        match order {
+            Ordering::Less => return a.next().map(|(idx, _)| idx),
+            Ordering::Greater => return b.next().map(|(idx, _)| idx),
+            Ordering::Equal => {
+                let (idx, a) = a.next().expect("index is present");
+                let (_, b) = b.next().expect("index is present");
+                if a != b {
+                    return Some(idx);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::{parse_response, Response, Session},
+        testutil::harness::{assert_response, assert_session},
+    };
+
+    use super::*;
+
+    fn response(src: &[u8]) -> Response {
+        let response = parse_response(src).unwrap();
+        assert_response(src, &response);
+        response
+    }
+
+    #[test]
+    fn test_compare_redacted() {
+        let revealed = response(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\n****");
+        let claimed = response(b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nabcd");
+        let longer = response(b"HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\nabcdef");
+
+        assert!(compare_redacted(&revealed, &revealed, &RangeSet::default()).is_ok());
+        assert!(compare_redacted(&revealed, &claimed, &RangeSet::from(38..42)).is_ok());
+        assert_eq!(
+            compare_redacted(&revealed, &claimed, &RangeSet::from(38..40)),
+            Err(RangeSet::from(40..42))
+        );
+        assert_eq!(
+            compare_redacted(&claimed, &longer, &RangeSet::default()),
+            Err(RangeSet::from([(33..34), (42..44)]))
+        );
+    }
+
+    #[test]
+    fn test_compare_redacted_parts() {
+        let parse = |sent: &[u8]| {
+            let received = b"HTTP/1.1 204 No Content\r\n\r\n";
+            let session = Session::new_from_slice(sent, received).unwrap();
+            assert_session(sent, received, &session);
+            session
+        };
+        let session = parse(b"GET / HTTP/1.1\r\nCookie: a=1\r\n\r\n");
+        let other = parse(b"GET / HTTP/1.1\r\nCookie: b=2\r\n\r\n");
+
+        let header = &session.exchanges[0].request.headers[0];
+        let other_header = &other.exchanges[0].request.headers[0];
+        assert_eq!(
+            compare_redacted(header, other_header, &RangeSet::default()),
+            Err(RangeSet::from([(24..25), (26..27)]))
+        );
+        assert!(
+            compare_redacted(&header.value, &other_header.value, &RangeSet::from(24..27)).is_ok()
+        );
+    }
+}
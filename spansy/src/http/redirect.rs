@@ -0,0 +1,258 @@
+use crate::http::{Header, Request, Session, Target, Uri};
+
+/// A link in a redirect chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct RedirectLink {
+    /// The index of the exchange whose response redirected.
+    pub from: usize,
+    /// The index of the exchange whose request followed the redirect.
+    pub to: usize,
+    /// The `Location` header of the redirecting response.
+    pub location: Header,
+    /// The target of the request which followed the redirect.
+    pub target: Target,
+}
+
+/// A chain of redirects within a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RedirectChain {
+    /// The links of the chain, in order.
+    ///
+    /// A chain always contains at least one link.
+    pub links: Vec<RedirectLink>,
+}
+
+impl RedirectChain {
+    /// Returns the index of the exchange which started the chain.
+    pub fn start(&self) -> usize {
+        self.links.first().expect("chain is not empty").from
+    }
+
+    /// Returns the index of the exchange which ended the chain.
+    pub fn end(&self) -> usize {
+        self.links.last().expect("chain is not empty").to
+    }
+
+    /// Returns the target of the request which ended the chain.
+    pub fn final_target(&self) -> &Target {
+        &self.links.last().expect("chain is not empty").target
+    }
+}
+
+impl Session {
+    /// Reconstructs the redirect chains in the session.
+    ///
+    /// A redirect is followed if a `3xx` response has a `Location` header which resolves to
+    /// the target of a later request in the session. Relative references are resolved
+    /// against the target of the redirected request, and absolute URIs must match the `Host`
+    /// header of the following request.
+    pub fn redirect_chains(&self) -> Vec<RedirectChain> {
+        let mut links: Vec<RedirectLink> = Vec::new();
+
+        for (from, exchange) in self.exchanges.iter().enumerate() {
+            let Some(response) = &exchange.response else {
+                continue;
+            };
+
+            if !response.status.code.as_str().starts_with('3') {
+                continue;
+            }
+
+            let Some(location) = response.headers_with_name("Location").next() else {
+                continue;
+            };
+
+            let Ok(location_uri) = location.value.uri() else {
+                continue;
+            };
+
+            let followed = self
+                .exchanges
+                .iter()
+                .enumerate()
+                .skip(from + 1)
+                .filter(|(to, _)| !links.iter().any(|link| link.to == *to))
+                .find(|(_, next)| {
+                    follows_redirect(&exchange.request, &location_uri, &next.request)
+                });
+
+            if let Some((to, next)) = followed {
+                links.push(RedirectLink {
+                    from,
+                    to,
+                    location: location.clone(),
+                    target: next.request.request.target.clone(),
+                });
+            }
+        }
+
+        let mut chains = Vec::new();
+        for link in links
+            .iter()
+            .filter(|link| !links.iter().any(|l| l.to == link.from))
+        {
+            let mut chain = vec![link.clone()];
+            while let Some(next) = links
+                .iter()
+                .find(|next| next.from == chain.last().unwrap().to)
+            {
+                chain.push(next.clone());
+            }
+            chains.push(RedirectChain { links: chain });
+        }
+
+        chains
+    }
+}
+
+/// Returns `true` if `next` is a request for the resource referenced by `location`,
+/// received in response to `request`.
+fn follows_redirect(request: &Request, location: &Uri, next: &Request) -> bool {
+    let next_target = next.request.target.as_str();
+    let path = location.path.as_str();
+    // The fragment is never sent to the server.
+    let query = location
+        .query
+        .as_ref()
+        .map(|query| format!("?{}", query.as_str()))
+        .unwrap_or_default();
+
+    if let Some(authority) = location.authority() {
+        // Absolute-form targets must match exactly.
+        let uri = location.as_str().split('#').next().unwrap_or_default();
+        if next_target == uri {
+            return true;
+        }
+
+        let path = if path.is_empty() { "/" } else { path };
+        return host_matches(next, authority)
+            && remove_dot_segments(&format!("{path}{query}")) == next_target;
+    }
+
+    let base = request.request.target.as_str();
+    let base_path = base.split(['?', '#']).next().unwrap_or_default();
+
+    let resolved = if path.starts_with('/') {
+        remove_dot_segments(&format!("{path}{query}"))
+    } else if path.is_empty() && query.is_empty() {
+        base.to_string()
+    } else if path.is_empty() {
+        format!("{base_path}{query}")
+    } else {
+        let dir = &base_path[..base_path.rfind('/').map(|idx| idx + 1).unwrap_or(0)];
+        remove_dot_segments(&format!("{dir}{path}{query}"))
+    };
+
+    resolved == next_target
+}
+
+/// Returns `true` if the `Host` header of the request matches the authority.
+fn host_matches(request: &Request, authority: &str) -> bool {
+    request
+        .headers_with_name("Host")
+        .next()
+        .and_then(|host| std::str::from_utf8(host.value.as_bytes()).ok())
+        .is_some_and(|host| host.trim().eq_ignore_ascii_case(authority))
+}
+
+/// Removes dot segments from a path according to RFC 3986, section 5.2.4.
+///
+/// The query, if present, is left untouched.
+fn remove_dot_segments(path: &str) -> String {
+    let (path, query) = match path.find('?') {
+        Some(idx) => path.split_at(idx),
+        None => (path, ""),
+    };
+
+    let mut segments: Vec<&str> = Vec::new();
+    let mut parts = path.split('/').peekable();
+    // Skip the empty segment preceding the leading slash.
+    if path.starts_with('/') {
+        parts.next();
+    }
+
+    while let Some(segment) = parts.next() {
+        let is_last = parts.peek().is_none();
+        match segment {
+            "." => {
+                if is_last {
+                    segments.push("");
+                }
+            }
+            ".." => {
+                segments.pop();
+                if is_last {
+                    segments.push("");
+                }
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    format!("/{}{query}", segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::harness::assert_session;
+
+    const SENT: &[u8] = b"\
+        GET /old HTTP/1.1\r\nHost: example.com\r\n\r\n\
+        GET /new/ HTTP/1.1\r\nHost: example.com\r\n\r\n\
+        GET /new/final?x=1 HTTP/1.1\r\nHost: example.com\r\n\r\n\
+        GET /other HTTP/1.1\r\nHost: example.com\r\n\r\n\
+        GET /moved HTTP/1.1\r\nHost: example.com\r\n\r\n";
+
+    const RECEIVED: &[u8] = b"\
+        HTTP/1.1 301 Moved Permanently\r\nLocation: https://example.com/new/\r\nContent-Length: 0\r\n\r\n\
+        HTTP/1.1 302 Found\r\nLocation: ./final?x=1#frag\r\nContent-Length: 0\r\n\r\n\
+        HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n\
+        HTTP/1.1 307 Temporary Redirect\r\nLocation: /moved\r\nContent-Length: 0\r\n\r\n\
+        HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+
+    #[test]
+    fn test_redirect_chains() {
+        let session = Session::new_from_slice(SENT, RECEIVED).unwrap();
+        assert_session(SENT, RECEIVED, &session);
+        let chains = session.redirect_chains();
+
+        assert_eq!(chains.len(), 2);
+
+        let chain = &chains[0];
+        assert_eq!((chain.start(), chain.end()), (0, 2));
+        assert_eq!(chain.links.len(), 2);
+        assert_eq!(
+            chain.links[0].location.value.as_bytes(),
+            b"https://example.com/new/"
+        );
+        assert_eq!(chain.final_target().as_str(), "/new/final?x=1");
+
+        let chain = &chains[1];
+        assert_eq!((chain.start(), chain.end()), (3, 4));
+        assert_eq!(chain.final_target().as_str(), "/moved");
+    }
+
+    #[test]
+    fn test_redirect_to_other_host_is_not_followed() {
+        let sent = b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n\
+            GET /next HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let received = b"HTTP/1.1 302 Found\r\nLocation: https://evil.com/next\r\n\
+            Content-Length: 0\r\n\r\n";
+
+        let session = Session::new_from_slice(sent, received).unwrap();
+        assert_session(sent, received, &session);
+
+        assert!(session.redirect_chains().is_empty());
+    }
+
+    #[test]
+    fn test_remove_dot_segments() {
+        assert_eq!(remove_dot_segments("/a/b/c/./../../g"), "/a/g");
+        assert_eq!(remove_dot_segments("/a/b/.."), "/a/");
+        assert_eq!(remove_dot_segments("/../a?x=../b"), "/a?x=../b");
+    }
+}
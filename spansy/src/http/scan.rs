@@ -0,0 +1,101 @@
+use std::ops::Range;
+
+use crate::{
+    http::{
+        borrowed::{parse_request_ref_at, parse_response_ref_at},
+        ParserConfig,
+    },
+    ParseError,
+};
+
+/// Returns the range of each HTTP message in the source, in order, without building the spans
+/// of their parts.
+///
+/// Only the framing of each message is computed, i.e. the end of its head and the length of
+/// its body, which is cheaper than parsing the messages. This is useful to check that a
+/// transcript is well framed, or to split it into messages which are then parsed in parallel.
+///
+/// A message which starts with `HTTP/` is framed as a response and any other as a request.
+/// Interim (1xx) responses are returned as separate messages, and bytes following a message
+/// which can not have a body are not skipped, unlike [`Responses`](crate::http::Responses).
+///
+/// # Errors
+///
+/// Returns an error if a message is invalid or incomplete.
+///
+/// # Example
+///
+/// ```
+/// use spansy::http::scan_message_bounds;
+///
+/// let src = b"GET / HTTP/1.1\r\n\r\nPOST / HTTP/1.1\r\nContent-Length: 2\r\n\r\nhi";
+/// assert_eq!(scan_message_bounds(src).unwrap(), [0..18, 18..58]);
+/// ```
+pub fn scan_message_bounds(src: &[u8]) -> Result<Vec<Range<usize>>, ParseError> {
+    let config = ParserConfig::default();
+
+    let mut bounds = Vec::new();
+    let mut pos = 0;
+    while pos < src.len() {
+        let range = if src[pos..].starts_with(b"HTTP/") {
+            parse_response_ref_at(src, pos, &config)?.span.range()
+        } else {
+            parse_request_ref_at(src, pos, &config)?.span.range()
+        };
+
+        pos = range.end;
+        bounds.push(range);
+    }
+
+    Ok(bounds)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::{Requests, Responses};
+
+    use super::*;
+
+    #[test]
+    fn test_scan_message_bounds() {
+        let sent = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n\
+            POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n2\r\nhi\r\n0\r\n\r\n\
+            PUT / HTTP/1.1\r\nContent-Length: 3\r\n\r\nabc";
+        let received = b"HTTP/1.1 100 Continue\r\n\r\n\
+            HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok\
+            HTTP/1.1 204 No Content\r\n\r\n";
+
+        let requests: Vec<_> = Requests::new_from_slice(sent)
+            .with_ranges()
+            .map(|(range, _)| range)
+            .collect();
+        assert_eq!(scan_message_bounds(sent).unwrap(), requests);
+
+        // The interim response is part of the range of the final response.
+        let responses: Vec<_> = Responses::new_from_slice(received)
+            .with_ranges()
+            .map(|(range, _)| range)
+            .collect();
+        let bounds = scan_message_bounds(received).unwrap();
+        assert_eq!(
+            [bounds[0].start..bounds[1].end, bounds[2].clone()],
+            responses.as_slice()
+        );
+
+        assert!(scan_message_bounds(b"").unwrap().is_empty());
+        assert!(scan_message_bounds(&sent[..sent.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_scan_message_bounds_bare_lf() {
+        let src = b"GET / HTTP/1.1\nA: b\n\nHTTP/1.1 200 OK\nContent-Length: 2\n\nhi";
+        assert_eq!(scan_message_bounds(src).unwrap(), [0..21, 21..src.len()]);
+
+        // Every truncation of the source is rejected rather than panicking.
+        for len in 1..src.len() {
+            if len != 21 {
+                assert!(scan_message_bounds(&src[..len]).is_err(), "{len}");
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{
+    http::{Request, Requests, Response, Responses},
+    Offset, ParseError, Source,
+};
+
+/// An HTTP exchange, consisting of a request and its response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Exchange {
+    /// The request.
+    pub request: Request,
+    /// The response, if one was received.
+    pub response: Option<Response>,
+}
+
+impl Exchange {
+    /// Shifts the span range by the given offsets.
+    ///
+    /// # Arguments
+    ///
+    /// * `sent_offset` - The offset to apply to the request.
+    /// * `received_offset` - The offset to apply to the response.
+    pub fn offset(&mut self, sent_offset: usize, received_offset: usize) {
+        self.request.offset(sent_offset);
+        if let Some(response) = &mut self.response {
+            response.offset(received_offset);
+        }
+    }
+}
+
+/// An HTTP/1.1 session, parsed from the data sent and received over a single connection.
+///
+/// Requests are paired with responses in the order they appear, as required by HTTP/1.1.
+/// Interim (1xx) responses, such as `100 Continue`, are not paired with a request themselves,
+/// but are attached to the final response which follows them. An exchange which closes the
+/// connection, see [`Exchange::connection`], must be the last one.
+/// Request spans are relative to the sent data, and response spans are relative to the
+/// received data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Session {
+    /// The exchanges of the session, in order.
+    pub exchanges: Vec<Exchange>,
+}
+
+impl Session {
+    /// Parses a session from the data sent and received over a connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `sent` - The data sent by the client.
+    /// * `received` - The data received by the client.
+    pub fn new(sent: impl Into<Source>, received: impl Into<Source>) -> Result<Self, ParseError> {
+        let requests = Requests::new(sent).collect::<Result<Vec<_>, _>>()?;
+        let responses = Responses::new(received).collect::<Result<Vec<_>, _>>()?;
+
+        if responses.len() > requests.len() {
+            return Err(ParseError::new(format!(
+                "session contains more responses ({}) than requests ({})",
+                responses.len(),
+                requests.len()
+            )));
+        }
+
+        let mut responses = responses.into_iter();
+        let exchanges: Vec<_> = requests
+            .into_iter()
+            .map(|request| Exchange {
+                request,
+                response: responses.next(),
+            })
+            .collect();
+
+        // No messages can follow an exchange which closes the connection.
+        if let Some(pos) = exchanges[..exchanges.len().saturating_sub(1)]
+            .iter()
+            .position(|exchange| exchange.connection().is_close())
+        {
+            return Err(ParseError::new(format!(
+                "session continues after exchange {pos} closed the connection"
+            )));
+        }
+
+        Ok(Self { exchanges })
+    }
+
+    /// Parses a session from byte slices of the data sent and received over a connection.
+    pub fn new_from_slice(sent: &[u8], received: &[u8]) -> Result<Self, ParseError> {
+        Self::new(
+            Source::copy_from_slice(sent),
+            Source::copy_from_slice(received),
+        )
+    }
+
+    /// Returns an iterator over the requests of the session.
+    pub fn requests(&self) -> impl Iterator<Item = &Request> {
+        self.exchanges.iter().map(|exchange| &exchange.request)
+    }
+
+    /// Returns the response to the first request whose target has the given path, if it was
+    /// received.
+    ///
+    /// The query of the target is ignored, so `/api/planets/1/?format=json` matches the path
+    /// `/api/planets/1/`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spansy::{http::Session, Spanned};
+    ///
+    /// let session = Session::new_from_slice(
+    ///     b"GET /api/planets/1/ HTTP/1.1\r\nHost: swapi.dev\r\n\r\n",
+    ///     b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+    ///     Content-Length: 19\r\n\r\n{\"name\":\"Tatooine\"}",
+    /// )
+    /// .unwrap();
+    ///
+    /// let name = session
+    ///     .response_for("/api/planets/1/")
+    ///     .and_then(|response| response.json("/name"))
+    ///     .unwrap();
+    /// assert_eq!(name.span(), "Tatooine");
+    /// ```
+    pub fn response_for(&self, path: &str) -> Option<&Response> {
+        self.exchanges
+            .iter()
+            .find(|exchange| {
+                exchange
+                    .request
+                    .request
+                    .target
+                    .uri()
+                    .is_ok_and(|uri| uri.path.as_str() == path)
+            })?
+            .response
+            .as_ref()
+    }
+
+    /// Returns an iterator over the responses of the session.
+    pub fn responses(&self) -> impl Iterator<Item = &Response> {
+        self.exchanges
+            .iter()
+            .filter_map(|exchange| exchange.response.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil::harness::assert_session;
+    use crate::Spanned;
+
+    use super::*;
+
+    const SENT: &[u8] = b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n\
+        POST /hello HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nhello";
+
+    const RECEIVED: &[u8] = b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok\
+        HTTP/1.1 201 Created\r\nContent-Length: 0\r\n\r\n";
+
+    #[test]
+    fn test_session() {
+        let session = Session::new_from_slice(SENT, RECEIVED).unwrap();
+        assert_session(SENT, RECEIVED, &session);
+
+        assert_eq!(session.exchanges.len(), 2);
+
+        let exchange = &session.exchanges[1];
+        assert_eq!(exchange.request.request.target.as_str(), "/hello");
+        assert_eq!(
+            exchange.response.as_ref().unwrap().status.code.as_str(),
+            "201"
+        );
+        // Response spans are relative to the received data.
+        assert_eq!(
+            exchange.response.as_ref().unwrap().span().indices().min(),
+            Some(40)
+        );
+    }
+
+    #[test]
+    fn test_session_missing_response() {
+        let session = Session::new_from_slice(SENT, &RECEIVED[..40]).unwrap();
+
+        assert_eq!(session.requests().count(), 2);
+        assert_eq!(session.responses().count(), 1);
+        assert!(session.exchanges[1].response.is_none());
+    }
+
+    #[test]
+    fn test_session_too_many_responses() {
+        assert!(Session::new_from_slice(&SENT[..35], RECEIVED).is_err());
+    }
+
+    #[test]
+    fn test_session_interim_response() {
+        let sent = b"POST /upload HTTP/1.1\r\nExpect: 100-continue\r\nContent-Length: 2\r\n\r\nhi";
+        let received = b"HTTP/1.1 100 Continue\r\n\r\n\
+            HTTP/1.1 201 Created\r\nContent-Length: 0\r\n\r\n";
+
+        let session = Session::new_from_slice(sent, received).unwrap();
+        assert_session(sent, received, &session);
+
+        assert_eq!(session.exchanges.len(), 1);
+        let response = session.exchanges[0].response.as_ref().unwrap();
+        assert_eq!(response.status.code.as_str(), "201");
+        assert_eq!(response.interim.len(), 1);
+        assert_eq!(response.interim[0].status.code.as_str(), "100");
+        assert_eq!(response.interim[0].span().indices().min(), Some(0));
+        assert_eq!(response.span().indices().min(), Some(25));
+    }
+
+    #[test]
+    fn test_session_response_for() {
+        let sent = b"GET /a?x=1 HTTP/1.1\r\n\r\nGET /b HTTP/1.1\r\n\r\nGET /c HTTP/1.1\r\n\r\n";
+        let received = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+            Transfer-Encoding: chunked\r\n\r\n5\r\n{\"a\":\r\n5\r\n[1,2]\r\n1\r\n}\r\n0\r\n\r\n\
+            HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+        let session = Session::new_from_slice(sent, received).unwrap();
+        assert_session(sent, received, &session);
+
+        let response = session.response_for("/a").unwrap();
+        assert_eq!(response.json("/a/1").unwrap().span(), "2");
+        assert!(response.json("/b").is_none());
+
+        // The body of the second response is not JSON.
+        assert!(session.response_for("/b").unwrap().json("").is_none());
+        // The third request has no response.
+        assert!(session.response_for("/c").is_none());
+        assert!(session.response_for("/d").is_none());
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{
+    http::{Body, Header, Request, Response},
+    Spanned,
+};
+
+/// The number of bytes used by each part of an HTTP message.
+///
+/// This can be used to budget the size of a proof before deciding which parts of a message
+/// to reveal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct SizeBreakdown {
+    /// The length of the request or status line, including the CRLF.
+    pub start_line: usize,
+    /// The length of each header, in order.
+    pub headers: Vec<HeaderSize>,
+    /// The length of the framing which is not part of any other section, i.e. the empty line
+    /// terminating the header section.
+    pub framing: usize,
+    /// The length of the body.
+    pub body: usize,
+    /// The length of the chunk size lines and delimiters of a chunked body, which is included in
+    /// `body`.
+    pub chunk_overhead: usize,
+}
+
+/// The number of bytes used by a header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct HeaderSize {
+    /// The header name.
+    pub name: String,
+    /// The length of the header, including optional whitespace and the CRLF.
+    pub len: usize,
+}
+
+impl SizeBreakdown {
+    fn new(total: usize, start_line: usize, headers: &[Header], body: Option<&Body>) -> Self {
+        let headers: Vec<_> = headers
+            .iter()
+            .map(|header| HeaderSize {
+                name: header.name.as_str().to_string(),
+                len: header.span().len(),
+            })
+            .collect();
+        let headers_len: usize = headers.iter().map(|header| header.len).sum();
+        let (body, chunk_overhead) = body.map_or((0, 0), |body| {
+            let len = body.span().len();
+            let data_len = body
+                .chunked
+                .as_ref()
+                .map_or(len, |chunked| chunked.data().len());
+
+            (len, len - data_len)
+        });
+
+        Self {
+            start_line,
+            framing: total - start_line - headers_len - body,
+            headers,
+            body,
+            chunk_overhead,
+        }
+    }
+
+    /// Returns the total length of the message.
+    pub fn total(&self) -> usize {
+        self.start_line + self.headers_len() + self.framing + self.body
+    }
+
+    /// Returns the total length of the headers.
+    pub fn headers_len(&self) -> usize {
+        self.headers.iter().map(|header| header.len).sum()
+    }
+}
+
+impl Request {
+    /// Returns the number of bytes used by each part of the request.
+    pub fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new(
+            self.span.len(),
+            self.request.span.len(),
+            &self.headers,
+            self.body.as_ref(),
+        )
+    }
+}
+
+impl Response {
+    /// Returns the number of bytes used by each part of the response.
+    pub fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new(
+            self.span.len(),
+            self.status.span.len(),
+            &self.headers,
+            self.body.as_ref(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::{parse_request, parse_response},
+        testutil::harness::{assert_request, assert_response},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_request_size_breakdown() {
+        let src = b"POST /hello HTTP/1.1\r\nHost: localhost\r\nContent-Length: 5\r\n\r\nhello";
+        let request = parse_request(src).unwrap();
+        assert_request(src, &request);
+        let size = request.size_breakdown();
+
+        assert_eq!(size.start_line, 22);
+        assert_eq!(
+            size.headers,
+            [
+                HeaderSize {
+                    name: "Host".to_string(),
+                    len: 17,
+                },
+                HeaderSize {
+                    name: "Content-Length".to_string(),
+                    len: 19,
+                },
+            ]
+        );
+        assert_eq!(size.framing, 2);
+        assert_eq!(size.body, 5);
+        assert_eq!(size.total(), src.len());
+    }
+
+    #[test]
+    fn test_response_size_breakdown() {
+        let src = b"HTTP/1.1 204 No Content\r\n\r\n";
+        let response = parse_response(src).unwrap();
+        assert_response(src, &response);
+        let size = response.size_breakdown();
+
+        assert_eq!(size.start_line, 25);
+        assert!(size.headers.is_empty());
+        assert_eq!(size.framing, 2);
+        assert_eq!(size.body, 0);
+        assert_eq!(size.total(), src.len());
+    }
+
+    #[test]
+    fn test_chunked_size_breakdown() {
+        let src = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+            5\r\nhello\r\n6;x=y\r\n world\r\n0\r\n\r\n";
+        let response = parse_response(src).unwrap();
+        assert_response(src, &response);
+        let size = response.size_breakdown();
+
+        assert_eq!(size.body, 30);
+        assert_eq!(size.chunk_overhead, 19);
+        assert_eq!(size.total(), src.len());
+    }
+}
@@ -1,283 +1,364 @@
-use std::ops::Range;
-
-use bytes::Bytes;
+use std::{ops::Range, time::Instant};
 
 use crate::{
-    helpers::get_span_range,
+    budget,
     http::{
-        Body, BodyContent, Code, Header, HeaderName, HeaderValue, Method, Reason, Request,
+        borrowed::{parse_request_ref_at, parse_response_ref_at, ChunkRef, HeaderRef},
+        progress::notify,
+        Body, BodyContent, Chunk, ChunkedBody, Code, ContentDecoders, Header, HeaderName,
+        HeaderValue, MessageKind, MessageStats, Method, ParseInfo, ParserConfig, Reason, Request,
         RequestLine, Response, Status, Target,
     },
-    json, ParseError, Span,
+    json,
+    multi::MultiSpanBuilder,
+    xml, MultiSpan, ParseError, Source, Span,
 };
 
-const MAX_HEADERS: usize = 128;
+#[cfg(feature = "strict")]
+use crate::http::strict;
 
 /// Parses an HTTP request.
 pub fn parse_request(src: &[u8]) -> Result<Request, ParseError> {
-    parse_request_from_bytes(&Bytes::copy_from_slice(src), 0)
+    parse_request_with_config(src, &ParserConfig::default())
 }
 
-/// Parses an HTTP request from a `Bytes` buffer starting from the `offset`.
-pub(crate) fn parse_request_from_bytes(src: &Bytes, offset: usize) -> Result<Request, ParseError> {
-    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
-
-    let (method, path, head_end) = {
-        let mut request = httparse::Request::new(&mut headers);
-
-        let head_end = match request.parse(&src[offset..]) {
-            Ok(httparse::Status::Complete(head_end)) => head_end + offset,
-            Ok(httparse::Status::Partial) => {
-                return Err(ParseError(format!("incomplete request: {:?}", src)))
-            }
-            Err(err) => return Err(ParseError(err.to_string())),
-        };
-
-        let method = request
-            .method
-            .ok_or_else(|| ParseError("method missing from request".to_string()))?;
-
-        let path = request
-            .path
-            .ok_or_else(|| ParseError("path missing from request".to_string()))?;
-
-        (method, path, head_end)
-    };
-
-    let request_line_end = src[offset..]
-        .windows(2)
-        .position(|w| w == b"\r\n")
-        .expect("request line is terminated with CRLF");
-    let request_line_range = offset..offset + request_line_end + 2;
+/// Parses an HTTP request using the provided configuration.
+pub fn parse_request_with_config(src: &[u8], config: &ParserConfig) -> Result<Request, ParseError> {
+    parse_request_from_bytes(&Source::copy_from_slice(src), 0, config)
+}
 
-    let headers = headers
-        .iter()
-        .take_while(|h| *h != &httparse::EMPTY_HEADER)
-        .map(|header| from_header(src, header))
-        .collect();
-
-    // httparse allocates a new buffer to store the method for performance reasons,
-    // so we have to search for the span in the source. This is quick as the method
-    // is at the front.
-    let method = src[offset..]
-        .windows(method.len())
-        .find(|w| *w == method.as_bytes())
-        .expect("method is present");
-
-    let mut request = Request {
-        span: Span::new_bytes(src.clone(), offset..head_end),
-        request: RequestLine {
-            span: Span::new_str(src.clone(), request_line_range),
-            method: Method(Span::new_str(src.clone(), get_span_range(src, method))),
-            target: Target(Span::new_from_str(src.clone(), path)),
+/// Parses an HTTP request from a source starting from the `offset`.
+pub(crate) fn parse_request_from_bytes(
+    src: &Source,
+    offset: usize,
+    config: &ParserConfig,
+) -> Result<Request, ParseError> {
+    config.check_interrupted(offset)?;
+    notify(config, |observer| {
+        observer.message_started(MessageKind::Request, offset)
+    });
+
+    with_metrics(
+        config,
+        MessageKind::Request,
+        || parse_request_inner(src, offset, config),
+        |request| {
+            message_stats(
+                request.span.len(),
+                request.request.span.len(),
+                request.body.as_ref(),
+            )
         },
-        headers,
-        body: None,
-    };
-
-    let body_len = request_body_len(&request)?;
-
-    if body_len > 0 {
-        let range = head_end..head_end + body_len;
+        |request, stats| {
+            request.info = Some(Box::new(ParseInfo::new(
+                stats,
+                &request.headers,
+                request.body.as_ref(),
+                config.content_length_policy(),
+            )))
+        },
+    )
+}
 
-        if range.end > src.len() {
-            return Err(ParseError(format!(
-                "body range {}..{} exceeds source {}",
-                range.start,
-                range.end,
-                src.len()
-            )));
+fn parse_request_inner(
+    src: &Source,
+    offset: usize,
+    config: &ParserConfig,
+) -> Result<Request, ParseError> {
+    #[cfg(feature = "strict")]
+    if config.is_strict() {
+        let violations = strict::validate_request_head(src, offset);
+        if !violations.is_empty() {
+            return Err(strict::violations_error(&violations));
         }
-
-        let content_type = request
-            .headers_with_name("Content-Type")
-            .next()
-            .map(|header| header.value.as_bytes())
-            .unwrap_or_default();
-
-        request.body = Some(parse_body(src, range.clone(), content_type)?);
-        request.span = Span::new_bytes(src.clone(), offset..range.end);
     }
 
-    Ok(request)
+    let request = parse_request_ref_at(src, offset, config)?;
+    let head_end = request
+        .body
+        .as_ref()
+        .map_or(request.span.range().end, |body| body.range().start);
+    notify(config, |observer| {
+        observer.head_parsed(MessageKind::Request, offset..head_end)
+    });
+
+    let body = request
+        .body
+        .as_ref()
+        .map(|body| {
+            config.check_interrupted(head_end)?;
+
+            let content = ContentHeaders::new(&request.headers);
+
+            parse_body(
+                src,
+                body.range(),
+                request.chunks.as_deref(),
+                &request.trailers,
+                request.truncated,
+                &content,
+                config,
+            )
+            .inspect(|_| {
+                notify(config, |observer| {
+                    observer.body_parsed(MessageKind::Request, body.range())
+                })
+            })
+        })
+        .transpose()?;
+    notify(config, |observer| {
+        observer.message_parsed(MessageKind::Request, request.span.range())
+    });
+
+    Ok(Request {
+        span: Span::new_bytes(src, request.span.range()),
+        request: RequestLine {
+            span: Span::new_str(src, request.request_line.range()),
+            method: Method(Span::new_str(src, request.method.range())),
+            target: Target(Span::new_str(src, request.target.range())),
+        },
+        headers: from_headers(src, &request.headers),
+        body,
+        info: None,
+    })
 }
 
 /// Parses an HTTP response.
 pub fn parse_response(src: &[u8]) -> Result<Response, ParseError> {
-    parse_response_from_bytes(&Bytes::copy_from_slice(src), 0)
+    parse_response_with_config(src, &ParserConfig::default())
 }
 
-/// Parses an HTTP response from a `Bytes` buffer starting from the `offset`.
+/// Parses an HTTP response using the provided configuration.
+pub fn parse_response_with_config(
+    src: &[u8],
+    config: &ParserConfig,
+) -> Result<Response, ParseError> {
+    parse_response_from_bytes(&Source::copy_from_slice(src), 0, config)
+}
+
+/// Parses an HTTP response from a source starting from the `offset`.
 pub(crate) fn parse_response_from_bytes(
-    src: &Bytes,
+    src: &Source,
     offset: usize,
+    config: &ParserConfig,
 ) -> Result<Response, ParseError> {
-    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
-
-    let (reason, code, head_end) = {
-        let mut response = httparse::Response::new(&mut headers);
-
-        let head_end = match response.parse(&src[offset..]) {
-            Ok(httparse::Status::Complete(head_end)) => head_end + offset,
-            Ok(httparse::Status::Partial) => {
-                return Err(ParseError(format!("incomplete response: {:?}", src)))
-            }
-            Err(err) => return Err(ParseError(err.to_string())),
-        };
-
-        let code = response
-            .code
-            .ok_or_else(|| ParseError("code missing from response".to_string()))
-            .map(|c| c.to_string())?;
-
-        let reason = response
-            .reason
-            .ok_or_else(|| ParseError("reason missing from response".to_string()))?;
-
-        (reason, code, head_end)
-    };
+    config.check_interrupted(offset)?;
+    notify(config, |observer| {
+        observer.message_started(MessageKind::Response, offset)
+    });
+
+    with_metrics(
+        config,
+        MessageKind::Response,
+        || parse_response_inner(src, offset, config),
+        |response| {
+            message_stats(
+                response.span.len(),
+                response.status.span.len(),
+                response.body.as_ref(),
+            )
+        },
+        |response, stats| {
+            response.info = Some(Box::new(ParseInfo::new(
+                stats,
+                &response.headers,
+                response.body.as_ref(),
+                config.content_length_policy(),
+            )))
+        },
+    )
+}
 
-    let status_line_end = src[offset..]
-        .windows(2)
-        .position(|w| w == b"\r\n")
-        .expect("status line is terminated with CRLF");
-    let status_line_range = offset..offset + status_line_end + 2;
+fn parse_response_inner(
+    src: &Source,
+    offset: usize,
+    config: &ParserConfig,
+) -> Result<Response, ParseError> {
+    #[cfg(feature = "strict")]
+    if config.is_strict() {
+        let violations = strict::validate_response_head(src, offset);
+        if !violations.is_empty() {
+            return Err(strict::violations_error(&violations));
+        }
+    }
 
-    let headers = headers
-        .iter()
-        .take_while(|h| *h != &httparse::EMPTY_HEADER)
-        .map(|header| from_header(src, header))
-        .collect();
-
-    // httparse doesn't preserve the response code span, so we find it.
-    let code = src[offset..]
-        .windows(3)
-        .find(|w| *w == code.as_bytes())
-        .expect("code is present");
-
-    let mut response = Response {
-        span: Span::new_bytes(src.clone(), offset..head_end),
+    let response = parse_response_ref_at(src, offset, config)?;
+    let head_end = response
+        .body
+        .as_ref()
+        .map_or(response.span.range().end, |body| body.range().start);
+    notify(config, |observer| {
+        observer.head_parsed(MessageKind::Response, offset..head_end)
+    });
+
+    let body = response
+        .body
+        .as_ref()
+        .map(|body| {
+            config.check_interrupted(head_end)?;
+
+            let content = ContentHeaders::new(&response.headers);
+
+            parse_body(
+                src,
+                body.range(),
+                response.chunks.as_deref(),
+                &response.trailers,
+                response.truncated,
+                &content,
+                config,
+            )
+            .inspect(|_| {
+                notify(config, |observer| {
+                    observer.body_parsed(MessageKind::Response, body.range())
+                })
+            })
+        })
+        .transpose()?;
+    notify(config, |observer| {
+        observer.message_parsed(MessageKind::Response, response.span.range())
+    });
+
+    Ok(Response {
+        span: Span::new_bytes(src, response.span.range()),
         status: Status {
-            span: Span::new_str(src.clone(), status_line_range),
-            code: Code(Span::new_str(src.clone(), get_span_range(src, code))),
-            reason: Reason(Span::new_from_str(src.clone(), reason)),
+            span: Span::new_str(src, response.status_line.range()),
+            code: Code(Span::new_str(src, response.code.range())),
+            reason: Reason(Span::new_str(src, response.reason.range())),
         },
-        headers,
-        body: None,
-    };
-
-    let body_len = response_body_len(&response)?;
+        headers: from_headers(src, &response.headers),
+        body,
+        interim: Vec::new(),
+        info: None,
+    })
+}
 
-    if body_len > 0 {
-        let range = head_end..head_end + body_len;
+/// Runs the parser, notifying the metrics sink of the outcome and attaching the parse info to
+/// the message if either is configured.
+fn with_metrics<T>(
+    config: &ParserConfig,
+    kind: MessageKind,
+    parse: impl FnOnce() -> Result<T, ParseError>,
+    stats: impl FnOnce(&T) -> MessageStats,
+    attach_info: impl FnOnce(&mut T, MessageStats),
+) -> Result<T, ParseError> {
+    let metrics = config.metrics_sink();
+    if metrics.is_none() && !config.collects_parse_info() {
+        return parse();
+    }
 
-        if range.end > src.len() {
-            return Err(ParseError(format!(
-                "body range {}..{} exceeds source {}",
-                range.start,
-                range.end,
-                src.len()
-            )));
+    let start = Instant::now();
+    let mut result = parse();
+    let duration = start.elapsed();
+
+    match &mut result {
+        Ok(message) => {
+            let stats = MessageStats {
+                duration,
+                ..stats(message)
+            };
+            if let Some(metrics) = metrics {
+                metrics.record_message(kind, &stats);
+            }
+            if config.collects_parse_info() {
+                attach_info(message, stats);
+            }
+        }
+        Err(err) => {
+            if let Some(metrics) = metrics {
+                metrics.record_error(kind, err, duration);
+            }
         }
-
-        let content_type = response
-            .headers_with_name("Content-Type")
-            .next()
-            .map(|header| header.value.as_bytes())
-            .unwrap_or_default();
-
-        response.body = Some(parse_body(src, range.clone(), content_type)?);
-        response.span = Span::new_bytes(src.clone(), offset..range.end);
     }
 
-    Ok(response)
+    result
 }
 
-/// Converts a `httparse::Header` to a `Header`.
-fn from_header(src: &Bytes, header: &httparse::Header) -> Header {
-    let name_range = get_span_range(src, header.name.as_bytes());
-    let value_range = get_span_range(src, header.value);
-
-    let crlf_idx = src[value_range.end..]
-        .windows(2)
-        .position(|b| b == b"\r\n")
-        .expect("CRLF is present in a valid header");
+/// Returns the statistics of a message from the lengths of its sections.
+fn message_stats(len: usize, start_line_len: usize, body: Option<&Body>) -> MessageStats {
+    let body_len = body.map(|body| body.span.len()).unwrap_or_default();
+
+    MessageStats {
+        start_line_len,
+        headers_len: len - start_line_len - body_len,
+        body_len,
+        chunks: body
+            .and_then(|body| body.chunked.as_ref())
+            .map_or(0, |chunked| chunked.chunks.len()),
+        ..Default::default()
+    }
+}
 
-    // Capture the entire header including trailing whitespace and the CRLF.
-    let header_range = name_range.start..value_range.end + crlf_idx + 2;
+/// Converts borrowed headers to owned headers.
+fn from_headers(src: &Source, headers: &[HeaderRef<'_>]) -> Vec<Header> {
+    headers
+        .iter()
+        .map(|header| Header {
+            span: Span::new_bytes(src, header.span.range()),
+            name: HeaderName(Span::new_str(src, header.name.range())),
+            value: HeaderValue(Span::new_bytes(src, header.value.range())),
+        })
+        .collect()
+}
 
-    Header {
-        span: Span::new_bytes(src.clone(), header_range),
-        name: HeaderName(Span::new_str(src.clone(), name_range)),
-        value: HeaderValue(Span::new_bytes(src.clone(), value_range)),
-    }
+/// The headers of a message which describe its body content.
+struct ContentHeaders<'a> {
+    /// The value of the `Content-Type` header, or an empty value if there is none.
+    content_type: &'a [u8],
+    /// The content codings and the transfer codings other than `chunked`, in the order they
+    /// were applied, excluding `identity`.
+    codings: Vec<&'a [u8]>,
 }
 
-/// Calculates the length of the request body according to RFC 9112, section 6.
-fn request_body_len(request: &Request) -> Result<usize, ParseError> {
-    // The presence of a message body in a request is signaled by a Content-Length
-    // or Transfer-Encoding header field.
+impl<'a> ContentHeaders<'a> {
+    fn new(headers: &[HeaderRef<'a>]) -> Self {
+        fn header<'h, 'a>(
+            headers: &'h [HeaderRef<'a>],
+            name: &'static str,
+        ) -> impl Iterator<Item = &'h HeaderRef<'a>> {
+            headers
+                .iter()
+                .filter(move |h| h.name.as_bytes().eq_ignore_ascii_case(name.as_bytes()))
+        }
 
-    // If a message is received with both a Transfer-Encoding and a Content-Length header field,
-    // the Transfer-Encoding overrides the Content-Length
-    if request
-        .headers_with_name("Transfer-Encoding")
-        .next()
-        .is_some()
-    {
-        Err(ParseError(
-            "Transfer-Encoding not supported yet".to_string(),
-        ))
-    } else if let Some(h) = request.headers_with_name("Content-Length").next() {
-        // If a valid Content-Length header field is present without Transfer-Encoding, its decimal value
-        // defines the expected message body length in octets.
-        std::str::from_utf8(h.value.0.as_bytes())?
-            .parse::<usize>()
-            .map_err(|err| ParseError(format!("failed to parse Content-Length value: {err}")))
-    } else {
-        // If this is a request message and none of the above are true, then the message body length is zero
-        Ok(0)
+        let codings = header(headers, "Content-Encoding")
+            .chain(header(headers, "Transfer-Encoding"))
+            .flat_map(|h| h.value.as_bytes().split(|b| *b == b','))
+            .map(<[u8]>::trim_ascii)
+            .filter(|coding| {
+                !coding.is_empty()
+                    && !coding.eq_ignore_ascii_case(b"identity")
+                    && !coding.eq_ignore_ascii_case(b"chunked")
+            })
+            .collect();
+
+        Self {
+            content_type: header(headers, "Content-Type")
+                .next()
+                .map(|h| h.value.as_bytes())
+                .unwrap_or_default(),
+            codings,
+        }
     }
-}
 
-/// Calculates the length of the response body according to RFC 9112, section 6.
-fn response_body_len(response: &Response) -> Result<usize, ParseError> {
-    // Any response to a HEAD request and any response with a 1xx (Informational), 204 (No Content), or 304 (Not Modified)
-    // status code is always terminated by the first empty line after the header fields, regardless of the header fields
-    // present in the message, and thus cannot contain a message body or trailer section.
-    match response
-        .status
-        .code
-        .as_str()
-        .parse::<usize>()
-        .expect("code is valid utf-8")
-    {
-        100..=199 | 204 | 304 => return Ok(0),
-        _ => {}
+    /// Returns `true` if the content type is `application/json`.
+    fn is_json(&self) -> bool {
+        self.content_type.get(..16) == Some(b"application/json".as_slice())
     }
 
-    if response
-        .headers_with_name("Transfer-Encoding")
-        .next()
-        .is_some()
-    {
-        Err(ParseError(
-            "Transfer-Encoding not supported yet".to_string(),
-        ))
-    } else if let Some(h) = response.headers_with_name("Content-Length").next() {
-        // If a valid Content-Length header field is present without Transfer-Encoding, its decimal value
-        // defines the expected message body length in octets.
-        std::str::from_utf8(h.value.0.as_bytes())?
-            .parse::<usize>()
-            .map_err(|err| ParseError(format!("failed to parse Content-Length value: {err}")))
-    } else {
-        // If this is a response message and none of the above are true, then there is no way to
-        // determine the length of the message body except by reading it until the connection is closed.
+    /// Returns `true` if the content type is `application/xml`, `text/xml`, or an XML-based
+    /// media type such as `application/soap+xml`.
+    fn is_xml(&self) -> bool {
+        let media_type = self
+            .content_type
+            .split(|b| *b == b';')
+            .next()
+            .unwrap_or_default()
+            .trim_ascii()
+            .to_ascii_lowercase();
 
-        // We currently consider this an error because we have no outer context information.
-        Err(ParseError(
-            "A response with a body must contain either a Content-Length or Transfer-Encoding header".to_string(),
-        ))
+        matches!(media_type.as_slice(), b"application/xml" | b"text/xml")
+            || media_type.ends_with(b"+xml")
     }
 }
 
@@ -287,24 +368,156 @@ fn response_body_len(response: &Response) -> Result<usize, ParseError> {
 ///
 /// * `src` - The source bytes.
 /// * `range` - The range of the message body in the source bytes.
-/// * `content_type` - The value of the Content-Type header.
-fn parse_body(src: &Bytes, range: Range<usize>, content_type: &[u8]) -> Result<Body, ParseError> {
-    let span = Span::new_bytes(src.clone(), range.clone());
-    let content = if content_type.get(..16) == Some(b"application/json".as_slice()) {
-        let mut value = json::parse(span.data.clone())?;
-        value.offset(range.start);
-
-        BodyContent::Json(value)
+/// * `chunks` - The chunks of the body, if it uses the chunked transfer coding.
+/// * `trailers` - The trailer fields of the body, if it uses the chunked transfer coding.
+/// * `truncated` - The number of bytes missing from the body, if it is truncated.
+/// * `content` - The headers describing the body content.
+/// * `config` - The parser configuration.
+fn parse_body(
+    src: &Source,
+    range: Range<usize>,
+    chunks: Option<&[ChunkRef<'_>]>,
+    trailers: &[HeaderRef<'_>],
+    truncated: Option<usize>,
+    content: &ContentHeaders<'_>,
+    config: &ParserConfig,
+) -> Result<Body, ParseError> {
+    let span = Span::new_bytes(src, range.clone());
+    let chunked = chunks
+        .map(|chunks| -> Result<_, ParseError> {
+            Ok(ChunkedBody {
+                chunks: chunks
+                    .iter()
+                    .map(|chunk| Chunk {
+                        span: Span::new_bytes(src, chunk.span.range()),
+                        size: chunk.size,
+                        extension: chunk
+                            .extension
+                            .as_ref()
+                            .map(|extension| Span::new_bytes(src, extension.range())),
+                        data: Span::new_bytes(src, chunk.data.range()),
+                    })
+                    .collect(),
+                data: chunks
+                    .iter()
+                    .try_fold(MultiSpanBuilder::default(), |mut data, chunk| {
+                        data.push_verbatim(src, chunk.data.range())?;
+                        Ok::<_, ParseError>(data)
+                    })?
+                    .build(),
+                trailers: from_headers(src, trailers),
+            })
+        })
+        .transpose()?;
+
+    // The content is parsed from the chunk data if the body is chunked.
+    let data = chunked
+        .as_ref()
+        .map_or_else(|| MultiSpan::from(&span), |chunked| chunked.data.clone());
+    let decoders = config.decoders().filter(|decoders| {
+        content.codings.iter().all(|coding| {
+            std::str::from_utf8(coding).is_ok_and(|coding| decoders.get(coding).is_some())
+        })
+    });
+    let content = if data.is_empty() {
+        BodyContent::Empty
+    } else if !content.codings.is_empty() {
+        match decoders {
+            // An incomplete body can not be decoded.
+            Some(decoders) if truncated.is_none() => {
+                parse_compressed(decoders, &data, content, range.start, config)?
+            }
+            _ => BodyContent::Binary(data.to_span().expect("chunk data is verbatim")),
+        }
+    } else if !content.is_json() && !content.is_xml() {
+        BodyContent::Binary(data.to_span().expect("chunk data is verbatim"))
+    } else if truncated.is_some() {
+        // An incomplete JSON or XML body can not be parsed.
+        unparsed(content, &data)
+    } else if content.is_json() {
+        match json::parse(data.data.clone()) {
+            Ok(mut value) => {
+                value.rebase(&data);
+                config.charge(budget::json_size(&value), range.start)?;
+
+                BodyContent::Json(value)
+            }
+            Err(_) => unparsed(content, &data),
+        }
     } else {
-        BodyContent::Unknown(span.clone())
+        match xml::parse(data.data.clone()) {
+            Ok(mut document) => {
+                document.rebase(&data);
+                config.charge(budget::xml_size(&document), range.start)?;
+
+                BodyContent::Xml(document)
+            }
+            Err(_) => unparsed(content, &data),
+        }
     };
 
-    Ok(Body { span, content })
+    Ok(Body {
+        span,
+        content,
+        chunked,
+        truncated,
+    })
+}
+
+/// Returns the content of a body with a parsed media type whose content could not be parsed.
+fn unparsed(content: &ContentHeaders<'_>, data: &MultiSpan) -> BodyContent {
+    let media_type = content
+        .content_type
+        .split(|b| *b == b';')
+        .next()
+        .unwrap_or_default();
+
+    BodyContent::Unparsed {
+        media_type: String::from_utf8_lossy(media_type.trim_ascii()).to_ascii_lowercase(),
+        span: data.to_span().expect("chunk data is verbatim"),
+    }
+}
+
+/// Decodes the content of a body, parsing it as JSON if it has the `application/json` content
+/// type and the decoded content is valid JSON.
+fn parse_compressed(
+    decoders: &ContentDecoders,
+    data: &MultiSpan,
+    content: &ContentHeaders<'_>,
+    offset: usize,
+    config: &ParserConfig,
+) -> Result<BodyContent, ParseError> {
+    let codings = content
+        .codings
+        .iter()
+        .map(|coding| std::str::from_utf8(coding).expect("coding is valid UTF-8"));
+    let decoded = decoders.decode(codings, data)?;
+    config.charge(decoded.len(), offset)?;
+
+    let json = match content.is_json().then(|| json::parse(decoded.data.clone())) {
+        Some(Ok(mut value)) => {
+            value.rebase(&decoded);
+            config.charge(budget::json_size(&value), offset)?;
+            Some(value)
+        }
+        _ => None,
+    };
+
+    Ok(BodyContent::Compressed {
+        raw: data.to_span().expect("chunk data is verbatim"),
+        decoded,
+        json,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::Spanned;
+    use crate::testutil::harness::{assert_request, assert_response};
+    use utils::range::RangeSet;
+
+    use bytes::Bytes;
+
+    use crate::{http::ContentLengthPolicy, Offset, Spanned};
 
     use super::*;
 
@@ -366,6 +579,7 @@ mod tests {
     #[test]
     fn test_parse_request() {
         let req = parse_request(TEST_REQUEST).unwrap();
+        assert_request(TEST_REQUEST, &req);
 
         assert_eq!(req.span(), TEST_REQUEST);
         assert_eq!(req.request.method.as_str(), "GET");
@@ -385,9 +599,176 @@ mod tests {
         assert_eq!(req.body.unwrap().span(), b"Hello World!".as_slice());
     }
 
+    #[cfg(feature = "strict")]
+    #[test]
+    fn test_parse_strict() {
+        let config = ParserConfig::new().strict(true);
+
+        let src = "GET /caf\u{e9} HTTP/1.1\r\nHost: a\r\n\r\n".as_bytes();
+        assert_request(src, &parse_request(src).unwrap());
+        assert!(parse_request_with_config(src, &config).is_err());
+
+        assert_request(
+            TEST_REQUEST,
+            &parse_request_with_config(TEST_REQUEST, &config).unwrap(),
+        );
+        assert_response(
+            TEST_RESPONSE,
+            &parse_response_with_config(TEST_RESPONSE, &config).unwrap(),
+        );
+
+        // The chunked framing is validated as well as the head.
+        let src = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5 \r\nhello\r\n0\r\n\r\n";
+        assert_response(src, &parse_response(src).unwrap());
+        let err = parse_response_with_config(src, &config).unwrap_err();
+        assert!(err.to_string().contains("invalid chunk size line"));
+    }
+
+    #[test]
+    fn test_parse_content_length_policy() {
+        let src = b"POST / HTTP/1.1\r\nContent-Length: 3\r\nContent-Length: 5\r\n\r\nhello";
+
+        assert!(parse_request(src).is_err());
+
+        let first = ParserConfig::new().content_length(ContentLengthPolicy::First);
+        let req = parse_request_with_config(src, &first).unwrap();
+        assert_request(src, &req);
+        assert_eq!(req.body.unwrap().span(), b"hel".as_slice());
+
+        let largest = ParserConfig::new().content_length(ContentLengthPolicy::Largest);
+        let req = parse_request_with_config(src, &largest).unwrap();
+        assert_request(src, &req);
+        assert_eq!(req.body.unwrap().span(), b"hello".as_slice());
+
+        // Identical values are not a conflict.
+        let src = b"HTTP/1.1 200 OK\r\nContent-Length: 5, 5\r\nContent-Length: 5\r\n\r\nhello";
+        let res = parse_response(src).unwrap();
+        assert_response(src, &res);
+        assert_eq!(res.body.unwrap().span(), b"hello".as_slice());
+    }
+
+    #[test]
+    fn test_parse_truncated_body() {
+        let src = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+            Content-Length: 20\r\n\r\n{\"foo\": \"ba";
+        assert!(parse_response(src).is_err());
+
+        let config = ParserConfig::new().truncated_body(true);
+        let res = parse_response_with_config(src, &config).unwrap();
+        assert_response(src, &res);
+        let body = res.body.unwrap();
+        assert_eq!(body.span(), b"{\"foo\": \"ba".as_slice());
+        assert_eq!(body.truncated, Some(9));
+        assert!(matches!(
+            &body.content,
+            BodyContent::Unparsed { media_type, span }
+                if media_type == "application/json" && span == b"{\"foo\": \"ba".as_slice()
+        ));
+
+        // A complete body is not affected.
+        let res = parse_response_with_config(TEST_RESPONSE, &config).unwrap();
+        assert_response(TEST_RESPONSE, &res);
+        assert_eq!(res.body.unwrap().truncated, None);
+
+        // No bytes of the body are available.
+        let src = b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\n";
+        let req = parse_request_with_config(src, &config).unwrap();
+        assert_request(src, &req);
+        assert_eq!(req.body.as_ref().unwrap().truncated, Some(5));
+        assert_eq!(req.span(), src.as_slice());
+
+        // Chunked bodies must be complete.
+        let src = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nab";
+        assert!(parse_request_with_config(src, &config).is_err());
+    }
+
+    #[test]
+    fn test_parse_malformed_body() {
+        let src = b"POST / HTTP/1.1\r\nContent-Type: application/json; charset=utf-8\r\n\
+            Content-Length: 7\r\n\r\n{\"a\" 1}";
+        let req = parse_request(src).unwrap();
+        assert_request(src, &req);
+        assert!(matches!(
+            &req.body.unwrap().content,
+            BodyContent::Unparsed { media_type, span }
+                if media_type == "application/json" && span == b"{\"a\" 1}".as_slice()
+        ));
+    }
+
+    #[test]
+    fn test_parse_memory_budget() {
+        let body = format!("[{}0]", "0,".repeat(100));
+        let src = format!(
+            "POST / HTTP/1.1\r\nContent-Type: application/json\r\n\
+            Content-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+
+        let budget = std::sync::Arc::new(crate::MemoryBudget::new(4096));
+        let config = ParserConfig::new().memory_budget(budget.clone());
+
+        // The headers fit, but the JSON values do not.
+        let err = parse_request_with_config(src.as_bytes(), &config).unwrap_err();
+        assert_eq!(err.budget_exceeded().unwrap().limit, 4096);
+        assert!(budget.used() > 0);
+
+        let budget = std::sync::Arc::new(crate::MemoryBudget::new(usize::MAX));
+        let config = ParserConfig::new().memory_budget(budget.clone());
+        let req = parse_request_with_config(src.as_bytes(), &config).unwrap();
+        assert_request(src.as_bytes(), &req);
+        assert!(budget.used() > 101 * size_of::<json::JsonValue>());
+    }
+
+    #[test]
+    fn test_head_and_body_spans() {
+        let req = parse_request(TEST_REQUEST_JSON).unwrap();
+        assert_request(TEST_REQUEST_JSON, &req);
+        let head_len = TEST_REQUEST_JSON.len() - 14;
+
+        assert_eq!(req.head_span(), 0..head_len);
+        assert!(req.head_span().as_bytes().ends_with(b"\r\n\r\n"));
+        assert_eq!(req.body_span().unwrap(), head_len..TEST_REQUEST_JSON.len());
+
+        let src = [b"garbage".as_slice(), TEST_RESPONSE_JSON].concat();
+        let mut res = parse_response(TEST_RESPONSE_JSON).unwrap();
+        res.offset(7);
+        assert_response(&src, &res);
+        assert_eq!(res.head_span(), 7..src.len() - 14);
+        assert_eq!(res.body_span().unwrap(), src.len() - 14..src.len());
+
+        let src = b"HTTP/1.1 204 No Content\r\n\r\n";
+        let res = parse_response(src).unwrap();
+        assert_response(src, &res);
+        assert_eq!(res.head_span(), 0..27);
+        assert!(res.body_span().is_none());
+    }
+
+    #[test]
+    fn test_with_data() {
+        let src = [b"garbage".as_slice(), TEST_RESPONSE_JSON].concat();
+        let mut res = parse_response(TEST_RESPONSE_JSON).unwrap();
+        res.offset(7);
+        assert_response(&src, &res);
+
+        let masked_src = String::from_utf8(src).unwrap().replace("bar", "XXX");
+        let masked_src = Bytes::from(masked_src);
+        let masked = res.with_data(&masked_src).unwrap();
+        assert_response(&masked_src, &masked);
+
+        let BodyContent::Json(value) = &masked.body.as_ref().unwrap().content else {
+            panic!("expected JSON body");
+        };
+        assert_eq!(value.get("foo").unwrap(), "XXX");
+        assert_eq!(masked.span().indices(), res.span().indices());
+        assert_eq!(masked.status.code.as_str(), "200");
+        assert_eq!(res.span(), TEST_RESPONSE_JSON);
+    }
+
     #[test]
     fn test_parse_header_trailing_whitespace() {
-        let req = parse_request(b"GET / HTTP/1.1\r\nHost: example.com \r\n\r\n").unwrap();
+        let src = b"GET / HTTP/1.1\r\nHost: example.com \r\n\r\n";
+        let req = parse_request(src).unwrap();
+        assert_request(src, &req);
         let header = req.headers_with_name("Host").next().unwrap();
 
         assert_eq!(header.span.as_bytes(), b"Host: example.com \r\n".as_slice());
@@ -396,6 +777,7 @@ mod tests {
     #[test]
     fn test_parse_response() {
         let res = parse_response(TEST_RESPONSE).unwrap();
+        assert_response(TEST_RESPONSE, &res);
 
         assert_eq!(res.span(), TEST_RESPONSE);
         assert_eq!(res.status.code.as_str(), "200");
@@ -424,8 +806,10 @@ mod tests {
         let mut request = Vec::new();
         request.extend(TEST_REQUEST2);
         request.extend(TEST_REQUEST);
-        let request = Bytes::copy_from_slice(&request);
-        let req = parse_request_from_bytes(&request, TEST_REQUEST2.len()).unwrap();
+        let request = Source::from(request);
+        let req = parse_request_from_bytes(&request, TEST_REQUEST2.len(), &ParserConfig::default())
+            .unwrap();
+        assert_request(&request, &req);
 
         assert_eq!(req.span(), TEST_REQUEST);
         assert_eq!(req.request.method.as_str(), "GET");
@@ -451,8 +835,11 @@ mod tests {
         let mut response = Vec::new();
         response.extend(TEST_RESPONSE2);
         response.extend(TEST_RESPONSE);
-        let response = Bytes::copy_from_slice(&response);
-        let res = parse_response_from_bytes(&response, TEST_RESPONSE2.len()).unwrap();
+        let response = Source::from(response);
+        let res =
+            parse_response_from_bytes(&response, TEST_RESPONSE2.len(), &ParserConfig::default())
+                .unwrap();
+        assert_response(&response, &res);
 
         assert_eq!(res.span(), TEST_RESPONSE);
         assert_eq!(res.status.code.as_str(), "200");
@@ -478,6 +865,7 @@ mod tests {
     #[test]
     fn test_parse_request_json() {
         let req = parse_request(TEST_REQUEST_JSON).unwrap();
+        assert_request(TEST_REQUEST_JSON, &req);
 
         let BodyContent::Json(value) = req.body.unwrap().content else {
             panic!("body is not json");
@@ -489,6 +877,7 @@ mod tests {
     #[test]
     fn test_parse_response_json() {
         let res = parse_response(TEST_RESPONSE_JSON).unwrap();
+        assert_response(TEST_RESPONSE_JSON, &res);
 
         let BodyContent::Json(value) = res.body.unwrap().content else {
             panic!("body is not json");
@@ -496,4 +885,145 @@ mod tests {
 
         assert_eq!(value.span(), "{\"foo\": \"bar\"}");
     }
+
+    #[test]
+    fn test_parse_response_xml() {
+        let src = b"HTTP/1.1 200 OK\r\nContent-Type: application/soap+xml; charset=utf-8\r\n\
+            Transfer-Encoding: chunked\r\n\r\n9\r\n<a><b x=\"\r\n8\r\n1\">2</b>\r\n4\r\n</a>\r\n0\r\n\r\n";
+        let res = parse_response(src).unwrap();
+        assert_response(src, &res);
+
+        let b = res.xml("b").unwrap();
+        assert_eq!(b.text().unwrap(), "2");
+        assert_eq!(b.text().unwrap().indices(), 118..119);
+        // The element spans both chunks.
+        assert_eq!(b.span().indices(), &RangeSet::from([104..110, 115..123]));
+        assert_eq!(b.attribute("x").unwrap().value.indices(), 115..116);
+
+        // A malformed document is not parsed.
+        let src = b"HTTP/1.1 200 OK\r\nContent-Type: text/xml\r\nContent-Length: 4\r\n\r\n<a>b";
+        let res = parse_response(src).unwrap();
+        assert_response(src, &res);
+        assert!(matches!(
+            res.body.unwrap().content,
+            BodyContent::Unparsed { media_type, span }
+                if media_type == "text/xml" && span == b"<a>b".as_slice()
+        ));
+        let src = b"HTTP/1.1 200 OK\r\nContent-Type: image/svg\r\nContent-Length: 4\r\n\r\n<a>b";
+        let res = parse_response(src).unwrap();
+        assert_response(src, &res);
+        assert!(matches!(res.body.unwrap().content, BodyContent::Binary(_)));
+    }
+
+    #[test]
+    fn test_parse_response_chunked() {
+        let src = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+            Transfer-Encoding: chunked\r\n\r\n8\r\n{\"foo\": \r\n6;x=y\r\n\"bar\"}\r\n0\r\n\r\n";
+        let res = parse_response(src).unwrap();
+        assert_response(src, &res);
+        assert_eq!(res.body(), res.body.as_ref());
+        let body = res.body.unwrap();
+        let chunked = body.chunked.as_ref().unwrap();
+
+        assert_eq!(res.span, 0..src.len());
+        assert_eq!(body.span, 79..src.len());
+        assert_eq!(chunked.chunks().len(), 2);
+        assert_eq!(chunked.chunks()[0].data, b"{\"foo\": ".as_slice());
+        assert_eq!(
+            chunked.chunks[1].extension.as_ref().unwrap(),
+            b";x=y".as_slice()
+        );
+        assert_eq!(chunked.data(), b"{\"foo\": \"bar\"}".as_slice());
+        assert_eq!(chunked.data().indices(), RangeSet::from([82..90, 99..105]));
+
+        let BodyContent::Json(value) = body.content else {
+            panic!("body is not json");
+        };
+
+        assert_eq!(value.span(), "{\"foo\": \"bar\"}");
+        assert_eq!(value.span().indices(), &chunked.data().indices());
+
+        // The indices of the values correspond to their data, although the object spans both
+        // chunks.
+        let bar = value.get("foo").unwrap();
+        assert_eq!(bar.span(), "bar");
+        assert_eq!(bar.span().indices(), &RangeSet::from(100..103));
+        let src = Bytes::copy_from_slice(src);
+        assert_eq!(&value.span().with_data(&src).unwrap(), value.span());
+        assert_eq!(&bar.span().with_data(&src).unwrap(), bar.span());
+    }
+
+    #[test]
+    fn test_parse_request_chunked_empty() {
+        let src = b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n0\r\nA: b\r\n\r\n";
+        let req = parse_request(src).unwrap();
+        assert_request(src, &req);
+        let body = req.body.unwrap();
+
+        assert_eq!(req.span, 0..src.len());
+        assert!(body.chunked.unwrap().chunks.is_empty());
+        assert_eq!(body.content, BodyContent::Empty);
+        assert!(body.content.span().is_empty());
+    }
+
+    #[test]
+    fn test_parse_response_trailers() {
+        let src = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\nTrailer: Digest\r\n\r\n\
+            2\r\nhi\r\n0\r\nDigest: sha-256=abc\r\nX-Count:  2 \r\n\r\n";
+        let res = parse_response(src).unwrap();
+        assert_response(src, &res);
+
+        assert_eq!(res.span, 0..src.len());
+        let trailers = res.trailers();
+        assert_eq!(trailers.len(), 2);
+        assert_eq!(trailers[0].span, b"Digest: sha-256=abc\r\n".as_slice());
+        assert_eq!(trailers[0].name.as_str(), "Digest");
+        assert_eq!(trailers[0].value.as_bytes(), b"sha-256=abc");
+        assert_eq!(trailers[1].value.as_bytes(), b"2");
+        // The trailers are not merged into the headers.
+        assert!(res.headers_with_name("Digest").next().is_none());
+
+        let src = Bytes::copy_from_slice(src);
+        let mut body = res.body.clone().unwrap();
+        body.set_data(&src).unwrap();
+        assert_eq!(body.chunked.unwrap().trailers(), trailers);
+
+        let src = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+        let res = parse_response(src).unwrap();
+        assert_response(src, &res);
+        assert!(res.trailers().is_empty());
+    }
+
+    #[test]
+    fn test_parse_unsupported_transfer_coding() {
+        for coding in [
+            "gzip",
+            "chunked, gzip",
+            "chunked, chunked",
+            "",
+            "chunked\r\nContent-Length: 5",
+        ] {
+            let src = format!("POST / HTTP/1.1\r\nTransfer-Encoding: {coding}\r\n\r\n0\r\n\r\n");
+            assert!(parse_request(src.as_bytes()).is_err(), "{coding}");
+        }
+    }
+
+    #[test]
+    fn test_parse_transfer_coding_before_chunked() {
+        let src = b"POST / HTTP/1.1\r\nContent-Type: application/json\r\n\
+            Transfer-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n\
+            3\r\n{\"a\r\n2\r\n\":\r\n0\r\n\r\n";
+        let request = parse_request(src).unwrap();
+        assert_request(src, &request);
+        assert_eq!(request.span(), src.as_slice());
+
+        // The chunks are framed as usual, but the content is not parsed as it is encoded.
+        let body = request.body().unwrap();
+        let chunks = body.chunked.as_ref().unwrap().chunks();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].data, b"{\"a".as_slice());
+        assert!(
+            matches!(&body.content, BodyContent::Binary(span) if span.as_bytes() == b"{\"a\":")
+        );
+    }
 }
@@ -0,0 +1,450 @@
+use std::{fmt, ops::Range};
+
+use crate::{http::flags::is_tchar, ParseError};
+
+/// A violation of the HTTP/1.1 message syntax defined in RFC 9110 and RFC 9112.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Violation {
+    /// The kind of violation.
+    pub kind: ViolationKind,
+    /// The range of the offending bytes in the source.
+    pub range: Range<usize>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.kind, self.range.start, self.range.end
+        )
+    }
+}
+
+/// The kind of a [`Violation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ViolationKind {
+    /// A line is terminated by LF instead of CRLF.
+    BareLineFeed,
+    /// A CR is not followed by LF.
+    BareCarriageReturn,
+    /// The request line is not `method SP request-target SP HTTP-version`.
+    InvalidRequestLine,
+    /// The status line is not `HTTP-version SP status-code SP [ reason-phrase ]`.
+    InvalidStatusLine,
+    /// The method contains characters which are not token characters.
+    InvalidMethod,
+    /// The request target contains characters which are not visible ASCII characters.
+    InvalidTarget,
+    /// The HTTP version is not `HTTP/DIGIT.DIGIT`.
+    InvalidVersion,
+    /// The status code is not 3 digits.
+    InvalidStatusCode,
+    /// The reason phrase contains control characters.
+    InvalidReasonPhrase,
+    /// A header line does not contain a colon.
+    MissingColon,
+    /// A header name contains characters which are not token characters.
+    InvalidHeaderName,
+    /// A header name is followed by whitespace before the colon.
+    WhitespaceBeforeColon,
+    /// A header value contains control characters.
+    InvalidHeaderValue,
+    /// A header value is continued over multiple lines (obsolete line folding).
+    ObsFold,
+    /// A `Content-Length` value is not a sequence of digits.
+    InvalidContentLength,
+    /// A chunk size is followed by whitespace which does not precede a chunk extension,
+    /// e.g. `5 `.
+    InvalidChunkSize,
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ViolationKind::BareLineFeed => "bare LF line terminator",
+            ViolationKind::BareCarriageReturn => "bare CR",
+            ViolationKind::InvalidRequestLine => "invalid request line",
+            ViolationKind::InvalidStatusLine => "invalid status line",
+            ViolationKind::InvalidMethod => "invalid method",
+            ViolationKind::InvalidTarget => "invalid request target",
+            ViolationKind::InvalidVersion => "invalid HTTP version",
+            ViolationKind::InvalidStatusCode => "invalid status code",
+            ViolationKind::InvalidReasonPhrase => "invalid reason phrase",
+            ViolationKind::MissingColon => "header line without colon",
+            ViolationKind::InvalidHeaderName => "invalid header name",
+            ViolationKind::WhitespaceBeforeColon => "whitespace between header name and colon",
+            ViolationKind::InvalidHeaderValue => "invalid header value",
+            ViolationKind::ObsFold => "obsolete line folding",
+            ViolationKind::InvalidContentLength => "invalid Content-Length value",
+            ViolationKind::InvalidChunkSize => "invalid chunk size line",
+        })
+    }
+}
+
+/// Validates the head of an HTTP request against the strict syntax of RFC 9112.
+///
+/// Returns all the violations found in the request line and header section. The body is
+/// not validated.
+pub fn validate_request(src: &[u8]) -> Vec<Violation> {
+    validate_request_head(src, 0)
+}
+
+/// Validates the head of an HTTP response against the strict syntax of RFC 9112.
+///
+/// Returns all the violations found in the status line and header section. The body is
+/// not validated.
+pub fn validate_response(src: &[u8]) -> Vec<Violation> {
+    validate_response_head(src, 0)
+}
+
+/// Validates the head of a request starting at `offset`.
+pub(crate) fn validate_request_head(src: &[u8], offset: usize) -> Vec<Violation> {
+    validate_head(src, offset, validate_request_line)
+}
+
+/// Validates the head of a response starting at `offset`.
+pub(crate) fn validate_response_head(src: &[u8], offset: usize) -> Vec<Violation> {
+    validate_head(src, offset, validate_status_line)
+}
+
+/// Validates the chunk size line at `line`, excluding its CRLF.
+///
+/// The chunk size and extension are already validated by the parser, which tolerates
+/// whitespace after the chunk size.
+pub(crate) fn validate_chunk_size_line(
+    src: &[u8],
+    line: Range<usize>,
+    violations: &mut Vec<Violation>,
+) {
+    let size_end = line.start
+        + src[line.clone()]
+            .iter()
+            .take_while(|b| b.is_ascii_hexdigit())
+            .count();
+    let rest = trim_ows(src, size_end..line.end);
+
+    // Whitespace may only precede the `;` of an extension.
+    if rest.is_empty() && size_end < line.end {
+        violations.push(violation(
+            ViolationKind::InvalidChunkSize,
+            size_end..line.end,
+        ));
+    }
+}
+
+/// Validates the trailer section of a chunked body starting at `offset`.
+pub(crate) fn validate_trailer_section(src: &[u8], offset: usize) -> Vec<Violation> {
+    validate_lines(src, offset, None)
+}
+
+/// Returns a parse error for the violations, labeling each of them.
+pub(crate) fn violations_error(violations: &[Violation]) -> ParseError {
+    let message = violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    violations.iter().fold(
+        ParseError::new(format!("strict validation failed: {message}")),
+        |err, violation| err.with_label(violation.range.clone(), violation.kind.to_string()),
+    )
+}
+
+/// Validates a line of a message, excluding its line terminator.
+type ValidateLine = fn(&[u8], Range<usize>, &mut Vec<Violation>);
+
+fn validate_head(src: &[u8], offset: usize, validate_start_line: ValidateLine) -> Vec<Violation> {
+    validate_lines(src, offset, Some(validate_start_line))
+}
+
+/// Validates the lines of a field section starting at `offset`, preceded by a start line if
+/// a validator for it is given.
+fn validate_lines(
+    src: &[u8],
+    offset: usize,
+    mut validate_start_line: Option<ValidateLine>,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut pos = offset;
+
+    while let Some(lf) = src[pos..].iter().position(|b| *b == b'\n') {
+        let lf = pos + lf;
+        let end = if lf > pos && src[lf - 1] == b'\r' {
+            lf - 1
+        } else {
+            violations.push(violation(ViolationKind::BareLineFeed, lf..lf + 1));
+            lf
+        };
+
+        let line = pos..end;
+        for idx in line.clone().filter(|idx| src[*idx] == b'\r') {
+            violations.push(violation(ViolationKind::BareCarriageReturn, idx..idx + 1));
+        }
+
+        if let Some(validate_start_line) = validate_start_line.take() {
+            validate_start_line(src, line, &mut violations);
+        } else if line.is_empty() {
+            break;
+        } else {
+            validate_field_line(src, line, &mut violations);
+        }
+
+        pos = lf + 1;
+    }
+
+    violations
+}
+
+fn validate_request_line(src: &[u8], line: Range<usize>, violations: &mut Vec<Violation>) {
+    let parts = split_sp(src, line.clone());
+    let [method, target, version] = parts.as_slice() else {
+        violations.push(violation(ViolationKind::InvalidRequestLine, line));
+        return;
+    };
+
+    if method.is_empty() || !src[method.clone()].iter().all(|b| is_tchar(*b)) {
+        violations.push(violation(ViolationKind::InvalidMethod, method.clone()));
+    }
+
+    if target.is_empty() || !src[target.clone()].iter().all(u8::is_ascii_graphic) {
+        violations.push(violation(ViolationKind::InvalidTarget, target.clone()));
+    }
+
+    if !is_version(&src[version.clone()]) {
+        violations.push(violation(ViolationKind::InvalidVersion, version.clone()));
+    }
+}
+
+fn validate_status_line(src: &[u8], line: Range<usize>, violations: &mut Vec<Violation>) {
+    let Some(sp) = src[line.clone()].iter().position(|b| *b == b' ') else {
+        violations.push(violation(ViolationKind::InvalidStatusLine, line));
+        return;
+    };
+
+    let version = line.start..line.start + sp;
+    if !is_version(&src[version.clone()]) {
+        violations.push(violation(ViolationKind::InvalidVersion, version));
+    }
+
+    let code = line.start + sp + 1..(line.start + sp + 4).min(line.end);
+    if code.len() != 3 || !src[code.clone()].iter().all(u8::is_ascii_digit) {
+        violations.push(violation(ViolationKind::InvalidStatusCode, code));
+        return;
+    }
+
+    // The space following the status code is required, even if the reason is empty.
+    if src.get(code.end) != Some(&b' ') || code.end >= line.end {
+        violations.push(violation(ViolationKind::InvalidStatusLine, line));
+        return;
+    }
+
+    let reason = code.end + 1..line.end;
+    if let Some(idx) = reason.clone().find(|idx| !is_field_char(src[*idx])) {
+        violations.push(violation(ViolationKind::InvalidReasonPhrase, idx..idx + 1));
+    }
+}
+
+fn validate_field_line(src: &[u8], line: Range<usize>, violations: &mut Vec<Violation>) {
+    if matches!(src[line.start], b' ' | b'\t') {
+        violations.push(violation(ViolationKind::ObsFold, line));
+        return;
+    }
+
+    let Some(colon) = src[line.clone()].iter().position(|b| *b == b':') else {
+        violations.push(violation(ViolationKind::MissingColon, line));
+        return;
+    };
+    let colon = line.start + colon;
+
+    let name = line.start..colon;
+    let ws = src[name.clone()]
+        .iter()
+        .rev()
+        .take_while(|b| matches!(b, b' ' | b'\t'))
+        .count();
+    if ws > 0 {
+        violations.push(violation(
+            ViolationKind::WhitespaceBeforeColon,
+            colon - ws..colon,
+        ));
+    }
+
+    let name = name.start..colon - ws;
+    if name.is_empty() {
+        violations.push(violation(ViolationKind::InvalidHeaderName, name.clone()));
+    } else if let Some(idx) = name.clone().find(|idx| !is_tchar(src[*idx])) {
+        violations.push(violation(ViolationKind::InvalidHeaderName, idx..idx + 1));
+    }
+
+    let value = colon + 1..line.end;
+    if let Some(idx) = value.clone().find(|idx| !is_field_char(src[*idx])) {
+        violations.push(violation(ViolationKind::InvalidHeaderValue, idx..idx + 1));
+    }
+
+    if src[name].eq_ignore_ascii_case(b"content-length") {
+        let value = trim_ows(src, value);
+        if value.is_empty() || !src[value.clone()].iter().all(u8::is_ascii_digit) {
+            violations.push(violation(ViolationKind::InvalidContentLength, value));
+        }
+    }
+}
+
+fn violation(kind: ViolationKind, range: Range<usize>) -> Violation {
+    Violation { kind, range }
+}
+
+/// Splits the range on single spaces.
+fn split_sp(src: &[u8], range: Range<usize>) -> Vec<Range<usize>> {
+    let mut parts = Vec::new();
+    let mut start = range.start;
+    for idx in range.clone().filter(|idx| src[*idx] == b' ') {
+        parts.push(start..idx);
+        start = idx + 1;
+    }
+    parts.push(start..range.end);
+    parts
+}
+
+/// Trims optional whitespace from both ends of the range.
+fn trim_ows(src: &[u8], mut range: Range<usize>) -> Range<usize> {
+    while range.start < range.end && matches!(src[range.start], b' ' | b'\t') {
+        range.start += 1;
+    }
+    while range.end > range.start && matches!(src[range.end - 1], b' ' | b'\t') {
+        range.end -= 1;
+    }
+    range
+}
+
+/// Returns `true` if the bytes are an HTTP version, i.e. `HTTP/DIGIT.DIGIT`.
+fn is_version(version: &[u8]) -> bool {
+    matches!(version, [b'H', b'T', b'T', b'P', b'/', major, b'.', minor]
+        if major.is_ascii_digit() && minor.is_ascii_digit())
+}
+
+/// Returns `true` if the byte may appear in a field value or reason phrase.
+fn is_field_char(b: u8) -> bool {
+    matches!(b, b'\t' | b' ' | 0x21..=0x7E | 0x80..=0xFF)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(violations: Vec<Violation>) -> Vec<ViolationKind> {
+        violations.into_iter().map(|v| v.kind).collect()
+    }
+
+    #[test]
+    fn test_validate_valid_messages() {
+        assert!(validate_request(b"GET / HTTP/1.1\r\nHost: a\r\nX-A: b\tc \r\n\r\n").is_empty());
+        assert!(validate_response(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").is_empty());
+        assert!(validate_response(b"HTTP/1.1 200 \r\nContent-Length: 0\r\n\r\n").is_empty());
+    }
+
+    #[test]
+    fn test_validate_bare_line_feed() {
+        let violations = validate_request(b"GET / HTTP/1.1\nHost: a\r\n\r\n");
+
+        assert_eq!(
+            violations,
+            vec![Violation {
+                kind: ViolationKind::BareLineFeed,
+                range: 14..15
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_header_name() {
+        let src = b"GET / HTTP/1.1\r\nHo(st: a\r\nHost : a\r\n\r\n";
+        let violations = validate_request(src);
+
+        assert_eq!(
+            kinds(violations.clone()),
+            vec![
+                ViolationKind::InvalidHeaderName,
+                ViolationKind::WhitespaceBeforeColon
+            ]
+        );
+        assert_eq!(&src[violations[0].range.clone()], b"(");
+        assert_eq!(&src[violations[1].range.clone()], b" ");
+    }
+
+    #[test]
+    fn test_validate_obs_fold() {
+        let violations = validate_response(b"HTTP/1.1 200 OK\r\nX-A: a\r\n b\r\n\r\n");
+
+        assert_eq!(kinds(violations), vec![ViolationKind::ObsFold]);
+    }
+
+    #[test]
+    fn test_validate_start_lines() {
+        assert_eq!(
+            kinds(validate_request(b"GET  / HTTP/1.1\r\n\r\n")),
+            vec![ViolationKind::InvalidRequestLine]
+        );
+        assert_eq!(
+            kinds(validate_request(b"GET / HTTP/11\r\n\r\n")),
+            vec![ViolationKind::InvalidVersion]
+        );
+        assert_eq!(
+            kinds(validate_response(b"HTTP/1.1 200\r\n\r\n")),
+            vec![ViolationKind::InvalidStatusLine]
+        );
+        assert_eq!(
+            kinds(validate_response(b"HTTP/1.1 20x OK\r\n\r\n")),
+            vec![ViolationKind::InvalidStatusCode]
+        );
+    }
+
+    #[test]
+    fn test_validate_chunk_size_line() {
+        let validate = |line: &[u8]| {
+            let mut violations = Vec::new();
+            validate_chunk_size_line(line, 0..line.len(), &mut violations);
+            violations
+        };
+
+        assert!(validate(b"5").is_empty());
+        assert!(validate(b"5 ;a=b").is_empty());
+        assert_eq!(
+            validate(b"5 \t"),
+            vec![Violation {
+                kind: ViolationKind::InvalidChunkSize,
+                range: 1..3
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_trailer_section() {
+        let src = b"0\r\nX-A: a\r\n b\r\nX(B: c\nX-C: d\r\n\r\n";
+
+        assert_eq!(
+            kinds(validate_trailer_section(src, 3)),
+            vec![
+                ViolationKind::ObsFold,
+                ViolationKind::BareLineFeed,
+                ViolationKind::InvalidHeaderName
+            ]
+        );
+        assert!(validate_trailer_section(b"0\r\nX-A: a\r\n\r\n", 3).is_empty());
+    }
+
+    #[test]
+    fn test_validate_content_length() {
+        assert_eq!(
+            kinds(validate_request(
+                b"POST / HTTP/1.1\r\nContent-Length: +1\r\n\r\na"
+            )),
+            vec![ViolationKind::InvalidContentLength]
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use utils::range::{Intersection, RangeSet, ToRangeSet, Union};
+
+use crate::http::{Header, Request, Session};
+
+/// An overview of a session and of the bytes revealed from it, such as for a notarization
+/// report.
+///
+/// Names and values are normalized to lowercase, except for methods, which are
+/// case-sensitive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct TranscriptSummary {
+    /// The number of requests.
+    pub requests: usize,
+    /// The number of final responses, excluding interim (1xx) responses.
+    pub responses: usize,
+    /// The number of requests with each method.
+    pub methods: BTreeMap<String, usize>,
+    /// The hosts of the requests, from the `Host` header, or from the target if it is
+    /// absolute.
+    pub hosts: BTreeSet<String>,
+    /// The number of final responses with each status code.
+    pub status_codes: BTreeMap<String, usize>,
+    /// The number of messages with each media type, from the `Content-Type` header without
+    /// its parameters.
+    pub content_types: BTreeMap<String, usize>,
+    /// The length of the requests.
+    pub sent_bytes: usize,
+    /// The length of the responses, including interim responses.
+    pub received_bytes: usize,
+    /// The number of bytes of the requests which are revealed.
+    pub revealed_sent_bytes: usize,
+    /// The number of bytes of the responses which are revealed.
+    pub revealed_received_bytes: usize,
+}
+
+impl TranscriptSummary {
+    /// Returns the total length of the messages.
+    pub fn total_bytes(&self) -> usize {
+        self.sent_bytes + self.received_bytes
+    }
+
+    /// Returns the total number of bytes of the messages which are revealed.
+    pub fn revealed_bytes(&self) -> usize {
+        self.revealed_sent_bytes + self.revealed_received_bytes
+    }
+}
+
+impl Session {
+    /// Returns an overview of the session, and of the bytes revealed from it.
+    ///
+    /// Revealed bytes which are not part of a message are not counted.
+    ///
+    /// # Arguments
+    ///
+    /// * `revealed_sent` - The revealed ranges of the data sent by the client.
+    /// * `revealed_received` - The revealed ranges of the data received by the client.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spansy::http::Session;
+    /// use utils::range::RangeSet;
+    ///
+    /// let session = Session::new_from_slice(
+    ///     b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n",
+    ///     b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 2\r\n\r\nok",
+    /// )
+    /// .unwrap();
+    ///
+    /// let summary = session.summary(&RangeSet::from(0..16), &RangeSet::default());
+    /// assert_eq!(summary.hosts.first().unwrap(), "example.com");
+    /// assert_eq!(summary.status_codes["200"], 1);
+    /// assert_eq!(summary.revealed_bytes(), 16);
+    /// ```
+    pub fn summary(
+        &self,
+        revealed_sent: &RangeSet<usize>,
+        revealed_received: &RangeSet<usize>,
+    ) -> TranscriptSummary {
+        let mut summary = TranscriptSummary::default();
+        let mut sent = RangeSet::default();
+        let mut received = RangeSet::default();
+
+        for request in self.requests() {
+            summary.requests += 1;
+            *summary
+                .methods
+                .entry(request.request.method.as_str().to_string())
+                .or_default() += 1;
+            if let Some(host) = host(request) {
+                summary.hosts.insert(host);
+            }
+            count_content_type(&mut summary, &request.headers);
+            sent = sent.union(&request.to_range_set());
+        }
+
+        for response in self.responses() {
+            summary.responses += 1;
+            *summary
+                .status_codes
+                .entry(response.status.code.as_str().to_string())
+                .or_default() += 1;
+            count_content_type(&mut summary, &response.headers);
+            received = response
+                .interim
+                .iter()
+                .fold(received.union(&response.to_range_set()), |acc, interim| {
+                    acc.union(&interim.to_range_set())
+                });
+        }
+
+        summary.sent_bytes = sent.len();
+        summary.received_bytes = received.len();
+        summary.revealed_sent_bytes = sent.intersection(revealed_sent).len();
+        summary.revealed_received_bytes = received.intersection(revealed_received).len();
+
+        summary
+    }
+}
+
+/// Returns the host of a request in lowercase, without the port.
+fn host(request: &Request) -> Option<String> {
+    let uri = request.request.target.uri().ok();
+    let host = match uri.as_ref().and_then(|uri| uri.host.as_ref()) {
+        Some(host) => host.as_str().to_string(),
+        None => {
+            let value = request.first_header_value("Host")?;
+            let value = std::str::from_utf8(value.as_bytes()).ok()?.trim();
+            crate::http::uri::split_host_port(value).ok()?.0.to_string()
+        }
+    };
+
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+/// Counts the media type of a message, if it has one.
+fn count_content_type(summary: &mut TranscriptSummary, headers: &[Header]) {
+    let Some(header) = headers
+        .iter()
+        .find(|header| header.name.as_str().eq_ignore_ascii_case("Content-Type"))
+    else {
+        return;
+    };
+
+    let media_type = header
+        .value
+        .as_bytes()
+        .split(|b| *b == b';')
+        .next()
+        .unwrap_or_default()
+        .trim_ascii();
+    if !media_type.is_empty() {
+        *summary
+            .content_types
+            .entry(String::from_utf8_lossy(media_type).to_ascii_lowercase())
+            .or_default() += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::harness::assert_session;
+
+    #[test]
+    fn test_summary() {
+        let sent = b"GET / HTTP/1.1\r\nHost: Example.com:443\r\n\r\n\
+            POST http://api.example.com/v1 HTTP/1.1\r\nContent-Type: application/json\r\n\
+            Content-Length: 2\r\n\r\n{}\
+            GET /a HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let received = b"HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\n\
+            Content-Length: 0\r\n\r\n\
+            HTTP/1.1 100 Continue\r\n\r\n\
+            HTTP/1.1 201 Created\r\nContent-Type: Application/JSON\r\nContent-Length: 0\r\n\r\n";
+        let session = Session::new_from_slice(sent, received).unwrap();
+        assert_session(sent, received, &session);
+
+        let summary = session.summary(&RangeSet::from(0..1000), &RangeSet::from(0..10));
+        assert_eq!(summary.requests, 3);
+        assert_eq!(summary.responses, 2);
+        assert_eq!(
+            summary.methods,
+            BTreeMap::from([("GET".to_string(), 2), ("POST".to_string(), 1)])
+        );
+        assert_eq!(
+            summary.hosts,
+            BTreeSet::from(["api.example.com".to_string(), "example.com".to_string()])
+        );
+        assert_eq!(
+            summary.status_codes,
+            BTreeMap::from([("200".to_string(), 1), ("201".to_string(), 1)])
+        );
+        assert_eq!(
+            summary.content_types,
+            BTreeMap::from([
+                ("application/json".to_string(), 2),
+                ("text/html".to_string(), 1)
+            ])
+        );
+        assert_eq!(summary.sent_bytes, sent.len());
+        assert_eq!(summary.received_bytes, received.len());
+        // Revealed bytes outside of the messages are not counted.
+        assert_eq!(summary.revealed_sent_bytes, sent.len());
+        assert_eq!(summary.revealed_bytes(), sent.len() + 10);
+        assert_eq!(summary.total_bytes(), sent.len() + received.len());
+    }
+}
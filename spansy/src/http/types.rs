@@ -1,6 +1,16 @@
+use bytes::Bytes;
 use utils::range::{Difference, RangeSet, ToRangeSet};
 
-use crate::{json::JsonValue, Span, Spanned};
+use crate::{
+    http::{
+        chunked::parse_extensions, parse_set_cookie, uri::parse_uri, ChunkExtension, HeaderFlags,
+        ParseInfo, SetCookie, Uri,
+    },
+    json::JsonValue,
+    offset::impl_offset,
+    xml::{Element, XmlDocument},
+    MultiSpan, Offset, ParseError, Span, Spanned,
+};
 
 /// An HTTP header name.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -13,12 +23,14 @@ impl HeaderName {
         self.0.as_str()
     }
 
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.0.offset(offset);
+    /// Returns the flags raised by the header name, if it is suspicious.
+    pub fn flags(&self) -> HeaderFlags {
+        HeaderFlags::of_name(self.as_str())
     }
 }
 
+impl_offset!(HeaderName(_));
+
 impl Spanned<str> for HeaderName {
     fn span(&self) -> &Span<str> {
         &self.0
@@ -42,12 +54,14 @@ impl HeaderValue {
         self.0.as_bytes()
     }
 
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.0.offset(offset);
+    /// Parses the header value as a URI reference, e.g. for the `Location` header.
+    pub fn uri(&self) -> Result<Uri, ParseError> {
+        parse_uri(&self.0)
     }
 }
 
+impl_offset!(HeaderValue(_));
+
 impl Spanned for HeaderValue {
     fn span(&self) -> &Span {
         &self.0
@@ -72,6 +86,13 @@ pub struct Header {
 }
 
 impl Header {
+    /// Returns the flags raised by the header name, if it is suspicious.
+    ///
+    /// See [`HeaderFlags`] for details.
+    pub fn flags(&self) -> HeaderFlags {
+        self.name.flags()
+    }
+
     /// Returns the indices of the header excluding the value.
     ///
     /// The indices will include any optional whitespace and the CRLF.
@@ -79,12 +100,97 @@ impl Header {
         self.span.indices.difference(&self.value.span().indices)
     }
 
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.span.offset(offset);
-        self.name.offset(offset);
-        self.value.offset(offset);
+    /// Returns the optional whitespace between the colon and the value.
+    ///
+    /// Together with the name, the colon, the value, the trailing whitespace and the CRLF,
+    /// this covers every byte of the header.
+    pub fn leading_ows(&self) -> Span {
+        let start = self.name.0.len() + 1;
+        self.span
+            .slice(start..start + ows_len(self.span.data[start..].iter()))
+    }
+
+    /// Returns the optional whitespace between the value and the CRLF.
+    pub fn trailing_ows(&self) -> Span {
+        let leading = self.leading_ows();
+        // The header ends with a CRLF, or with a bare LF.
+        let end = self.span.len()
+            - if self.span.data.ends_with(b"\r\n") {
+                2
+            } else {
+                1
+            };
+        let start = self.name.0.len() + 1 + leading.len();
+        let len = ows_len(self.span.data[start..end].iter().rev());
+
+        self.span.slice(end - len..end)
+    }
+
+    pub(crate) fn set_data(&mut self, src: &Bytes) -> Result<(), ParseError> {
+        self.span = self.span.with_data(src)?;
+        self.name.0 = self.name.0.with_data(src)?;
+        self.value.0 = self.value.0.with_data(src)?;
+
+        Ok(())
+    }
+}
+
+impl_offset!(Header { span, name, value });
+
+/// Returns the length of the optional whitespace at the start of `bytes`.
+fn ows_len<'a>(bytes: impl Iterator<Item = &'a u8>) -> usize {
+    bytes.take_while(|b| matches!(b, b' ' | b'\t')).count()
+}
+
+/// Returns the value of the only header with the given name, or an error if there is more
+/// than one.
+fn single_header_value<'a>(
+    headers: &'a [Header],
+    name: &str,
+) -> Result<Option<&'a HeaderValue>, ParseError> {
+    let mut headers = headers
+        .iter()
+        .filter(|h| h.name.0.as_str().eq_ignore_ascii_case(name));
+    let Some(header) = headers.next() else {
+        return Ok(None);
+    };
+
+    if let Some(duplicate) = headers.next() {
+        let indices = &duplicate.span.indices;
+        return Err(
+            ParseError::new(format!("duplicate {name} header")).with_label(
+                indices.min().unwrap_or_default()..indices.end().unwrap_or_default(),
+                "duplicate header",
+            ),
+        );
     }
+
+    Ok(Some(&header.value))
+}
+
+/// Returns the non-overlapping occurrences of `needle` in the header values, ignoring ASCII
+/// case, together with the header containing each.
+fn find_header_value_containing<'a>(
+    headers: &'a [Header],
+    needle: &'a str,
+) -> impl Iterator<Item = (&'a Header, Span)> + 'a {
+    headers.iter().flat_map(move |header| {
+        let value = header.value.0.as_bytes();
+        let mut pos = 0;
+        std::iter::from_fn(move || {
+            if needle.is_empty() {
+                return None;
+            }
+
+            let start = pos
+                + value
+                    .get(pos..)?
+                    .windows(needle.len())
+                    .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))?;
+            pos = start + needle.len();
+            Some((header, header.value.0.slice(start..pos)))
+        })
+    })
 }
 
 impl Spanned for Header {
@@ -110,12 +216,58 @@ impl Method {
         self.0.as_str()
     }
 
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.0.offset(offset);
+    /// Returns the kind of the method.
+    ///
+    /// Methods are case-sensitive (RFC 9110, section 9.1), so e.g. `get` is an extension
+    /// method rather than [`MethodKind::Get`].
+    pub fn kind(&self) -> MethodKind {
+        match self.as_str() {
+            "GET" => MethodKind::Get,
+            "HEAD" => MethodKind::Head,
+            "POST" => MethodKind::Post,
+            "PUT" => MethodKind::Put,
+            "DELETE" => MethodKind::Delete,
+            "CONNECT" => MethodKind::Connect,
+            "OPTIONS" => MethodKind::Options,
+            "TRACE" => MethodKind::Trace,
+            "PATCH" => MethodKind::Patch,
+            _ => MethodKind::Other(self.0.clone()),
+        }
     }
 }
 
+/// The kind of an HTTP request method.
+///
+/// The kind only classifies the method, the exact bytes remain available from the span of
+/// the [`Method`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum MethodKind {
+    /// `GET`
+    Get,
+    /// `HEAD`
+    Head,
+    /// `POST`
+    Post,
+    /// `PUT`
+    Put,
+    /// `DELETE`
+    Delete,
+    /// `CONNECT`
+    Connect,
+    /// `OPTIONS`
+    Options,
+    /// `TRACE`
+    Trace,
+    /// `PATCH` (RFC 5789)
+    Patch,
+    /// An extension method.
+    Other(Span<str>),
+}
+
+impl_offset!(Method(_));
+
 impl Spanned<str> for Method {
     fn span(&self) -> &Span<str> {
         &self.0
@@ -139,12 +291,14 @@ impl Target {
         self.0.as_str()
     }
 
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.0.offset(offset);
+    /// Parses the target as a URI reference.
+    pub fn uri(&self) -> Result<Uri, ParseError> {
+        parse_uri(self.0.as_byte_span())
     }
 }
 
+impl_offset!(Target(_));
+
 impl Spanned<str> for Target {
     fn span(&self) -> &Span<str> {
         &self.0
@@ -175,14 +329,22 @@ impl RequestLine {
         self.span.indices.difference(&self.target.0.indices)
     }
 
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.span.offset(offset);
-        self.method.offset(offset);
-        self.target.offset(offset);
+    /// Returns the HTTP version, such as `HTTP/1.1`.
+    pub fn version(&self) -> Span {
+        // The version is the last element of the request line.
+        let line = self.span.as_str().trim_end();
+        let start = line.rfind(' ').map_or(0, |pos| pos + 1);
+
+        self.span.as_byte_span().slice(start..line.len())
     }
 }
 
+impl_offset!(RequestLine {
+    span,
+    method,
+    target
+});
+
 impl Spanned<str> for RequestLine {
     fn span(&self) -> &Span<str> {
         &self.span
@@ -196,29 +358,172 @@ impl ToRangeSet<usize> for RequestLine {
 }
 
 /// An HTTP request.
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///
+/// With the `serde` feature, the request is serialized in a versioned format, see
+/// `http::FORMAT_VERSION`.
+#[derive(Debug, Clone)]
 pub struct Request {
     pub(crate) span: Span,
     /// The request line.
     pub request: RequestLine,
-    /// Request headers.
+    /// Request headers, in the order they appear in the request.
+    ///
+    /// Prefer [`headers`](Self::headers). The field will be made private in the next breaking
+    /// release.
     pub headers: Vec<Header>,
     /// Request body.
+    ///
+    /// Prefer [`body`](Self::body). The field will be made private in the next breaking
+    /// release.
     pub body: Option<Body>,
+    pub(crate) info: Option<Box<ParseInfo>>,
 }
 
+impl PartialEq for Request {
+    /// Compares the requests, ignoring their [`ParseInfo`].
+    fn eq(&self, other: &Self) -> bool {
+        self.span == other.span
+            && self.request == other.request
+            && self.headers == other.headers
+            && self.body == other.body
+    }
+}
+
+impl Eq for Request {}
+
 impl Request {
     /// Returns an iterator of request headers with the given name (case-insensitive).
     ///
     /// This method returns an iterator because it is valid for HTTP records to contain
-    /// duplicate header names.
+    /// duplicate header names. The headers are yielded in the order they appear in the request,
+    /// which is significant when combining their values.
     pub fn headers_with_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Header> {
         self.headers
             .iter()
             .filter(|h| h.name.0.as_str().eq_ignore_ascii_case(name))
     }
 
+    /// Returns the value of the first request header with the given name (case-insensitive).
+    pub fn first_header_value(&self, name: &str) -> Option<&HeaderValue> {
+        self.headers
+            .iter()
+            .find(|h| h.name.0.as_str().eq_ignore_ascii_case(name))
+            .map(|h| &h.value)
+    }
+
+    /// Returns the value of the only request header with the given name (case-insensitive),
+    /// or `None` if there is no such header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error labeling the second header if the name is duplicated.
+    pub fn single_header_value(&self, name: &str) -> Result<Option<&HeaderValue>, ParseError> {
+        single_header_value(&self.headers, name)
+    }
+
+    /// Returns the occurrences of `needle` in the values of the request headers, ignoring ASCII
+    /// case, together with the header containing each.
+    ///
+    /// This locates a token regardless of the header which carries it, such as
+    /// `X-Auth-Token` or `Authorization`. An empty needle matches nothing.
+    pub fn find_header_value_containing<'a>(
+        &'a self,
+        needle: &'a str,
+    ) -> impl Iterator<Item = (&'a Header, Span)> + 'a {
+        find_header_value_containing(&self.headers, needle)
+    }
+
+    /// Returns an iterator of the request headers, in the order they appear in the request.
+    ///
+    /// The position of a header in the iterator is its index, as used by
+    /// [`header_at`](Self::header_at) and [`header_index`](Self::header_index).
+    pub fn headers(&self) -> impl DoubleEndedIterator<Item = &Header> + ExactSizeIterator {
+        self.headers.iter()
+    }
+
+    /// Returns the request body, if it has one.
+    pub fn body(&self) -> Option<&Body> {
+        self.body.as_ref()
+    }
+
+    /// Returns the trailer fields of the request body, which are only sent with the chunked
+    /// transfer coding.
+    pub fn trailers(&self) -> &[Header] {
+        self.body
+            .as_ref()
+            .and_then(|body| body.chunked.as_ref())
+            .map_or(&[], |chunked| chunked.trailers())
+    }
+
+    /// Returns the header at the given zero-based index, in the order the headers appear in
+    /// the request.
+    pub fn header_at(&self, index: usize) -> Option<&Header> {
+        self.headers.get(index)
+    }
+
+    /// Returns the zero-based index of a header of this request, such as one returned by
+    /// [`headers_with_name`](Self::headers_with_name).
+    ///
+    /// Returns `None` if the header is not part of this request.
+    pub fn header_index(&self, header: &Header) -> Option<usize> {
+        self.headers.iter().position(|h| h == header)
+    }
+
+    /// Returns the parsed `Referer` header of the request, if present.
+    pub fn referer(&self) -> Option<Result<Uri, ParseError>> {
+        self.headers_with_name("Referer")
+            .next()
+            .map(|header| header.value.uri())
+    }
+
+    /// Returns the parsed `Origin` header of the request, if present.
+    ///
+    /// Note that an opaque origin is serialized as `null`, which is parsed as a relative path.
+    pub fn origin(&self) -> Option<Result<Uri, ParseError>> {
+        self.headers_with_name("Origin")
+            .next()
+            .map(|header| header.value.uri())
+    }
+
+    /// Returns the value at a JSON pointer (RFC 6901) in the body of the request, if the
+    /// body is JSON.
+    ///
+    /// See [`Body::json`].
+    pub fn json(&self, pointer: &str) -> Option<&JsonValue> {
+        self.body.as_ref()?.json(pointer)
+    }
+
+    /// Returns the element at a path in the body of the request, if the body is XML.
+    ///
+    /// See [`Body::xml`].
+    pub fn xml(&self, path: &str) -> Option<&Element> {
+        self.body.as_ref()?.xml(path)
+    }
+
+    /// Returns information about how the request was parsed, if
+    /// [`ParserConfig::parse_info`](crate::http::ParserConfig::parse_info) was enabled.
+    pub fn parse_info(&self) -> Option<&ParseInfo> {
+        self.info.as_deref()
+    }
+
+    /// Returns the span of the request head, i.e. the request line, the headers and the empty
+    /// line terminating them.
+    pub fn head_span(&self) -> Span {
+        self.span.slice(0..self.head_len())
+    }
+
+    /// Returns the span of the region of the request following the head which holds the body,
+    /// including any framing, or `None` if the request has no body.
+    pub fn body_span(&self) -> Option<Span> {
+        self.body
+            .as_ref()
+            .map(|_| self.span.slice(self.head_len()..self.span.len()))
+    }
+
+    fn head_len(&self) -> usize {
+        self.span.len() - self.body.as_ref().map_or(0, |body| body.span.len())
+    }
+
     /// Returns the indices of the request excluding the target, headers and body.
     pub fn without_data(&self) -> RangeSet<usize> {
         let mut indices = self.span.indices.difference(&self.request.target.0.indices);
@@ -231,19 +536,41 @@ impl Request {
         indices
     }
 
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.span.offset(offset);
-        self.request.offset(offset);
-        for header in &mut self.headers {
-            header.offset(offset);
+    /// Returns a copy of the request whose spans view the bytes of `src` at the same
+    /// indices.
+    ///
+    /// This can be used to view the request in a modified copy of the source, such as a
+    /// masked transcript, without changing the original. The structure of the request is
+    /// preserved, so the new bytes are not parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The new source, which must contain the indices of the request.
+    pub fn with_data(&self, src: &Bytes) -> Result<Self, ParseError> {
+        let mut request = self.clone();
+        request.span = request.span.with_data(src)?;
+        request.request.span = request.request.span.with_data(src)?;
+        request.request.method.0 = request.request.method.0.with_data(src)?;
+        request.request.target.0 = request.request.target.0.with_data(src)?;
+        for header in &mut request.headers {
+            header.set_data(src)?;
         }
-        if let Some(body) = &mut self.body {
-            body.offset(offset);
+        if let Some(body) = &mut request.body {
+            body.set_data(src)?;
         }
+
+        Ok(request)
     }
 }
 
+impl_offset!(Request {
+    span,
+    request,
+    headers,
+    body,
+    info
+});
+
 impl Spanned for Request {
     fn span(&self) -> &Span {
         &self.span
@@ -266,13 +593,10 @@ impl Code {
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
-
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.0.offset(offset);
-    }
 }
 
+impl_offset!(Code(_));
+
 impl Spanned<str> for Code {
     fn span(&self) -> &Span<str> {
         &self.0
@@ -295,13 +619,10 @@ impl Reason {
     pub fn as_str(&self) -> &str {
         self.0.as_str()
     }
-
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.0.offset(offset);
-    }
 }
 
+impl_offset!(Reason(_));
+
 impl Spanned<str> for Reason {
     fn span(&self) -> &Span<str> {
         &self.0
@@ -327,14 +648,18 @@ pub struct Status {
 }
 
 impl Status {
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.span.offset(offset);
-        self.code.offset(offset);
-        self.reason.offset(offset);
+    /// Returns the HTTP version, such as `HTTP/1.1`.
+    pub fn version(&self) -> Span {
+        // The version is the first element of the status line.
+        let line = self.span.as_str();
+        let end = line.find(' ').unwrap_or(line.trim_end().len());
+
+        self.span.as_byte_span().slice(0..end)
     }
 }
 
+impl_offset!(Status { span, code, reason });
+
 impl Spanned<str> for Status {
     fn span(&self) -> &Span<str> {
         &self.span
@@ -348,29 +673,160 @@ impl ToRangeSet<usize> for Status {
 }
 
 /// An HTTP response.
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+///
+/// With the `serde` feature, the response is serialized in a versioned format, see
+/// `http::FORMAT_VERSION`.
+#[derive(Debug, Clone)]
 pub struct Response {
     pub(crate) span: Span,
     /// The response status.
     pub status: Status,
-    /// Response headers.
+    /// Response headers, in the order they appear in the response.
+    ///
+    /// Prefer [`headers`](Self::headers). The field will be made private in the next breaking
+    /// release.
     pub headers: Vec<Header>,
     /// Response body.
+    ///
+    /// Prefer [`body`](Self::body). The field will be made private in the next breaking
+    /// release.
     pub body: Option<Body>,
+    /// Interim (1xx) responses which preceded this response, such as `100 Continue`.
+    ///
+    /// These are only collected by [`Responses`](crate::http::Responses), and are not part
+    /// of the span of this response.
+    pub interim: Vec<Response>,
+    pub(crate) info: Option<Box<ParseInfo>>,
 }
 
+impl PartialEq for Response {
+    /// Compares the responses, ignoring their [`ParseInfo`].
+    fn eq(&self, other: &Self) -> bool {
+        self.span == other.span
+            && self.status == other.status
+            && self.headers == other.headers
+            && self.body == other.body
+            && self.interim == other.interim
+    }
+}
+
+impl Eq for Response {}
+
 impl Response {
     /// Returns an iterator of response headers with the given name (case-insensitive).
     ///
     /// This method returns an iterator because it is valid for HTTP records to contain
-    /// duplicate header names.
+    /// duplicate header names. The headers are yielded in the order they appear in the response,
+    /// which is significant when combining their values.
     pub fn headers_with_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Header> {
         self.headers
             .iter()
             .filter(|h| h.name.0.as_str().eq_ignore_ascii_case(name))
     }
 
+    /// Returns the value of the first response header with the given name (case-insensitive).
+    pub fn first_header_value(&self, name: &str) -> Option<&HeaderValue> {
+        self.headers
+            .iter()
+            .find(|h| h.name.0.as_str().eq_ignore_ascii_case(name))
+            .map(|h| &h.value)
+    }
+
+    /// Returns the value of the only response header with the given name (case-insensitive),
+    /// or `None` if there is no such header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error labeling the second header if the name is duplicated.
+    pub fn single_header_value(&self, name: &str) -> Result<Option<&HeaderValue>, ParseError> {
+        single_header_value(&self.headers, name)
+    }
+
+    /// Returns the occurrences of `needle` in the values of the response headers, ignoring ASCII
+    /// case, together with the header containing each.
+    ///
+    /// This locates a token regardless of the header which carries it, such as
+    /// `X-Auth-Token` or `Authorization`. An empty needle matches nothing.
+    pub fn find_header_value_containing<'a>(
+        &'a self,
+        needle: &'a str,
+    ) -> impl Iterator<Item = (&'a Header, Span)> + 'a {
+        find_header_value_containing(&self.headers, needle)
+    }
+
+    /// Returns an iterator of the response headers, in the order they appear in the response.
+    ///
+    /// The position of a header in the iterator is its index, as used by
+    /// [`header_at`](Self::header_at) and [`header_index`](Self::header_index).
+    pub fn headers(&self) -> impl DoubleEndedIterator<Item = &Header> + ExactSizeIterator {
+        self.headers.iter()
+    }
+
+    /// Returns the response body, if it has one.
+    pub fn body(&self) -> Option<&Body> {
+        self.body.as_ref()
+    }
+
+    /// Returns the trailer fields of the response body, which are only sent with the chunked
+    /// transfer coding.
+    pub fn trailers(&self) -> &[Header] {
+        self.body
+            .as_ref()
+            .and_then(|body| body.chunked.as_ref())
+            .map_or(&[], |chunked| chunked.trailers())
+    }
+
+    /// Returns the header at the given zero-based index, in the order the headers appear in
+    /// the response.
+    pub fn header_at(&self, index: usize) -> Option<&Header> {
+        self.headers.get(index)
+    }
+
+    /// Returns the zero-based index of a header of this response, such as one returned by
+    /// [`headers_with_name`](Self::headers_with_name).
+    ///
+    /// Returns `None` if the header is not part of this response.
+    pub fn header_index(&self, header: &Header) -> Option<usize> {
+        self.headers.iter().position(|h| h == header)
+    }
+
+    /// Returns an iterator of the parsed `Set-Cookie` headers of the response.
+    pub fn set_cookies(&self) -> impl Iterator<Item = Result<SetCookie, ParseError>> + '_ {
+        self.headers_with_name("Set-Cookie")
+            .map(|header| parse_set_cookie(&header.value))
+    }
+
+    /// Returns the parsed `Location` header of the response, if present.
+    pub fn location(&self) -> Option<Result<Uri, ParseError>> {
+        self.headers_with_name("Location")
+            .next()
+            .map(|header| header.value.uri())
+    }
+
+    /// Returns information about how the response was parsed, if
+    /// [`ParserConfig::parse_info`](crate::http::ParserConfig::parse_info) was enabled.
+    pub fn parse_info(&self) -> Option<&ParseInfo> {
+        self.info.as_deref()
+    }
+
+    /// Returns the span of the response head, i.e. the status line, the headers and the empty
+    /// line terminating them.
+    pub fn head_span(&self) -> Span {
+        self.span.slice(0..self.head_len())
+    }
+
+    /// Returns the span of the region of the response following the head which holds the body,
+    /// including any framing, or `None` if the response has no body.
+    pub fn body_span(&self) -> Option<Span> {
+        self.body
+            .as_ref()
+            .map(|_| self.span.slice(self.head_len()..self.span.len()))
+    }
+
+    fn head_len(&self) -> usize {
+        self.span.len() - self.body.as_ref().map_or(0, |body| body.span.len())
+    }
+
     /// Returns the indices of the response excluding the headers and body.
     pub fn without_data(&self) -> RangeSet<usize> {
         let mut indices = self.span.indices.clone();
@@ -383,19 +839,69 @@ impl Response {
         indices
     }
 
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.span.offset(offset);
-        self.status.offset(offset);
-        for header in &mut self.headers {
-            header.offset(offset);
+    /// Returns the value at a JSON pointer (RFC 6901) in the body of the response, if the
+    /// body is JSON.
+    ///
+    /// See [`Body::json`].
+    pub fn json(&self, pointer: &str) -> Option<&JsonValue> {
+        self.body.as_ref()?.json(pointer)
+    }
+
+    /// Returns the element at a path in the body of the response, if the body is XML.
+    ///
+    /// See [`Body::xml`].
+    pub fn xml(&self, path: &str) -> Option<&Element> {
+        self.body.as_ref()?.xml(path)
+    }
+
+    /// Returns `true` if the response is an interim (1xx) response.
+    ///
+    /// `101 Switching Protocols` is final, as no other response follows it.
+    pub fn is_interim(&self) -> bool {
+        let code = self.status.code.as_str();
+
+        code.starts_with('1') && code != "101"
+    }
+
+    /// Returns a copy of the response whose spans view the bytes of `src` at the same
+    /// indices.
+    ///
+    /// This can be used to view the response in a modified copy of the source, such as a
+    /// masked transcript, without changing the original. The structure of the response is
+    /// preserved, so the new bytes are not parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The new source, which must contain the indices of the response.
+    pub fn with_data(&self, src: &Bytes) -> Result<Self, ParseError> {
+        let mut response = self.clone();
+        response.span = response.span.with_data(src)?;
+        response.status.span = response.status.span.with_data(src)?;
+        response.status.code.0 = response.status.code.0.with_data(src)?;
+        response.status.reason.0 = response.status.reason.0.with_data(src)?;
+        for header in &mut response.headers {
+            header.set_data(src)?;
         }
-        if let Some(body) = &mut self.body {
-            body.offset(offset);
+        if let Some(body) = &mut response.body {
+            body.set_data(src)?;
         }
+        for interim in &mut response.interim {
+            *interim = interim.with_data(src)?;
+        }
+
+        Ok(response)
     }
 }
 
+impl_offset!(Response {
+    span,
+    status,
+    headers,
+    body,
+    interim,
+    info
+});
+
 impl Spanned for Response {
     fn span(&self) -> &Span {
         &self.span
@@ -412,10 +918,24 @@ impl ToRangeSet<usize> for Response {
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Body {
+    /// The span of the body, including the chunk framing if the body uses the chunked transfer
+    /// coding.
     pub(crate) span: Span,
 
     /// The body content.
+    ///
+    /// If the body uses the chunked transfer coding, the content spans only the chunk data.
     pub content: BodyContent,
+    /// The chunks of the body, if it uses the chunked transfer coding.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub chunked: Option<ChunkedBody>,
+    /// The number of bytes missing from the body, if it is shorter than its `Content-Length`.
+    ///
+    /// This is only set if [`ParserConfig::truncated_body`](crate::http::ParserConfig::truncated_body)
+    /// is enabled, in which case the content of a truncated body is not parsed.
+    // Added in version 3 of the serialization format.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub truncated: Option<usize>,
 }
 
 impl Body {
@@ -424,12 +944,61 @@ impl Body {
         self.span.as_bytes()
     }
 
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
-        self.span.offset(offset);
+    /// Returns the value at a JSON pointer (RFC 6901) in the body, if the body is JSON.
+    ///
+    /// The body is JSON if it has an `application/json` content type, in which case it was
+    /// parsed from the body content, with any chunk framing removed and any decoded content
+    /// coding undone.
+    pub fn json(&self, pointer: &str) -> Option<&JsonValue> {
+        match &self.content {
+            BodyContent::Json(value)
+            | BodyContent::Compressed {
+                json: Some(value), ..
+            } => value.pointer(pointer),
+            _ => None,
+        }
+    }
+
+    /// Returns the element at a path in the body, relative to the root element, if the body is
+    /// XML.
+    ///
+    /// See [`Element::get`] for the syntax of the path.
+    pub fn xml(&self, path: &str) -> Option<&Element> {
+        match &self.content {
+            BodyContent::Xml(document) => document.get(path),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn set_data(&mut self, src: &Bytes) -> Result<(), ParseError> {
+        self.span = self.span.with_data(src)?;
+        match &mut self.content {
+            BodyContent::Json(value) => value.set_data(src)?,
+            BodyContent::Xml(document) => document.set_data(src)?,
+            BodyContent::Empty => {}
+            BodyContent::Binary(span) | BodyContent::Unparsed { span, .. } => {
+                *span = span.with_data(src)?
+            }
+            // The new bytes are not decoded, so only the encoded content is kept.
+            BodyContent::Compressed { raw, .. } => {
+                self.content = BodyContent::Binary(raw.with_data(src)?)
+            }
+        }
+        if let Some(chunked) = &mut self.chunked {
+            chunked.set_data(src)?;
+        }
+
+        Ok(())
     }
 }
 
+impl_offset!(Body {
+    span,
+    content,
+    chunked;
+    truncated
+});
+
 impl Spanned for Body {
     fn span(&self) -> &Span {
         &self.span
@@ -442,22 +1011,189 @@ impl ToRangeSet<usize> for Body {
     }
 }
 
+/// The chunks of a body with the chunked transfer coding (RFC 9112, section 7.1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChunkedBody {
+    /// The chunks, excluding the last chunk which has a size of zero.
+    ///
+    /// Prefer [`chunks`](Self::chunks). The field will be made private in the next breaking
+    /// release.
+    pub chunks: Vec<Chunk>,
+    /// The data of all chunks, in order.
+    pub(crate) data: MultiSpan,
+    /// The fields of the trailer section, which follows the last chunk.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) trailers: Vec<Header>,
+}
+
+impl ChunkedBody {
+    /// Returns the chunks, excluding the last chunk which has a size of zero.
+    pub fn chunks(&self) -> &[Chunk] {
+        &self.chunks
+    }
+
+    /// Returns the data of all chunks, in order.
+    ///
+    /// The span has a segment for each chunk, as the chunks are separated by their framing.
+    pub fn data(&self) -> &MultiSpan {
+        &self.data
+    }
+
+    /// Returns the fields of the trailer section, in order.
+    ///
+    /// Trailer fields are sent after the body, so they may carry metadata computed from it,
+    /// such as a checksum. They are not merged into the headers of the message.
+    pub fn trailers(&self) -> &[Header] {
+        &self.trailers
+    }
+
+    pub(crate) fn set_data(&mut self, src: &Bytes) -> Result<(), ParseError> {
+        self.data = self.data.with_data(src)?;
+        for chunk in &mut self.chunks {
+            chunk.set_data(src)?;
+        }
+        for trailer in &mut self.trailers {
+            trailer.set_data(src)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl_offset!(ChunkedBody {
+    chunks,
+    data,
+    trailers
+});
+
+/// A chunk of a body with the chunked transfer coding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Chunk {
+    /// The span of the chunk, including the chunk size line and the CRLF following the data.
+    pub(crate) span: Span,
+    /// The chunk size.
+    pub size: usize,
+    /// The chunk extension, including the leading `;`.
+    pub extension: Option<Span>,
+    /// The chunk data.
+    pub data: Span,
+}
+
+impl Chunk {
+    /// Returns the entries of the chunk extension, in order.
+    pub fn extensions(&self) -> Vec<ChunkExtension> {
+        self.extension
+            .as_ref()
+            .map(parse_extensions)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn set_data(&mut self, src: &Bytes) -> Result<(), ParseError> {
+        self.span = self.span.with_data(src)?;
+        if let Some(extension) = &mut self.extension {
+            *extension = extension.with_data(src)?;
+        }
+        self.data = self.data.with_data(src)?;
+
+        Ok(())
+    }
+}
+
+impl_offset!(Chunk { span, extension, data; size });
+
+impl Spanned for Chunk {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for Chunk {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
 /// An HTTP request or response payload body content.
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
+// New variants must be appended, as non-self-describing formats such as bincode serialize the
+// index of the variant.
 pub enum BodyContent {
     /// Body with an `application/json` content type.
     Json(JsonValue),
-    /// Body with an unknown content type.
-    Unknown(Span),
+    /// Body with a content type which is not parsed, or with no content type.
+    ///
+    /// This includes bodies with a content coding or a transfer coding other than `chunked`
+    /// which is not decoded, as their content is encoded.
+    // Named `Unknown` before version 3 of the serialization format.
+    #[cfg_attr(feature = "serde", serde(alias = "Unknown"))]
+    Binary(Span),
+    /// Body with no content bytes, such as a chunked body with no chunks.
+    Empty,
+    /// Body with a content type which is parsed, but whose content could not be parsed,
+    /// either because it is malformed or because it is incomplete, i.e. the body is
+    /// truncated.
+    Unparsed {
+        /// The media type of the body, in lowercase and without parameters, such as
+        /// `application/json`.
+        media_type: std::string::String,
+        /// The content of the body.
+        span: Span,
+    },
+    /// Body with a content coding, such as `gzip`, or a transfer coding other than `chunked`,
+    /// which was decoded with the
+    /// [`content_decoders`](crate::http::ParserConfig::content_decoders) of the parser.
+    Compressed {
+        /// The encoded content of the body.
+        raw: Span,
+        /// The decoded content, whose positions map back to the indices of the encoded
+        /// content they were decoded from.
+        decoded: MultiSpan,
+        /// The decoded content parsed as JSON, if the body has an `application/json` content
+        /// type and the decoded content is valid JSON.
+        ///
+        /// The values view the decoded content, and their indices are the indices of the
+        /// encoded content they were decoded from. As compressed data generally can not be
+        /// attributed to parts of the decoded content, a value usually maps to all of the
+        /// encoded content.
+        json: Option<JsonValue>,
+    },
+    /// Body with an `application/xml` or `text/xml` content type, or an XML-based media type
+    /// such as `application/soap+xml`.
+    Xml(XmlDocument),
+}
+
+/// The span of [`BodyContent::Empty`].
+static EMPTY_SPAN: Span = Span::empty();
+
+impl Offset for BodyContent {
+    fn offset(&mut self, offset: usize) {
+        match self {
+            BodyContent::Json(value) => value.offset(offset),
+            BodyContent::Xml(document) => document.offset(offset),
+            BodyContent::Empty => {}
+            BodyContent::Binary(span) | BodyContent::Unparsed { span, .. } => span.offset(offset),
+            BodyContent::Compressed { raw, decoded, json } => {
+                raw.offset(offset);
+                decoded.offset(offset);
+                json.offset(offset);
+            }
+        }
+    }
 }
 
 impl Spanned for BodyContent {
     fn span(&self) -> &Span {
         match self {
             BodyContent::Json(json) => json.span().as_ref(),
-            BodyContent::Unknown(span) => span,
+            BodyContent::Xml(document) => document.span().as_ref(),
+            BodyContent::Empty => &EMPTY_SPAN,
+            BodyContent::Binary(span)
+            | BodyContent::Unparsed { span, .. }
+            | BodyContent::Compressed { raw: span, .. } => span,
         }
     }
 }
@@ -466,7 +1202,11 @@ impl ToRangeSet<usize> for BodyContent {
     fn to_range_set(&self) -> RangeSet<usize> {
         match self {
             BodyContent::Json(json) => json.span().indices.clone(),
-            BodyContent::Unknown(span) => span.indices.clone(),
+            BodyContent::Xml(document) => document.span().indices.clone(),
+            BodyContent::Empty => RangeSet::default(),
+            BodyContent::Binary(span)
+            | BodyContent::Unparsed { span, .. }
+            | BodyContent::Compressed { raw: span, .. } => span.indices.clone(),
         }
     }
 }
@@ -0,0 +1,356 @@
+use utils::range::{RangeSet, ToRangeSet};
+
+use crate::{
+    http::{form::parse_form, FormField},
+    offset::impl_offset,
+    Offset, ParseError, Source, Span, Spanned,
+};
+
+/// A URI reference, as found in request targets and URI-valued headers such as `Location`.
+///
+/// The reference is split into its components according to RFC 3986, appendix B. Each
+/// component excludes its delimiters, e.g. the query does not include the leading `?`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Uri {
+    pub(crate) span: Span<str>,
+
+    /// The scheme, e.g. `https`.
+    pub scheme: Option<Span<str>>,
+    /// The user information preceding the host.
+    pub userinfo: Option<Span<str>>,
+    /// The host, e.g. `example.com`.
+    ///
+    /// IP literals include the enclosing brackets.
+    pub host: Option<Span<str>>,
+    /// The port.
+    pub port: Option<Span<str>>,
+    /// The path, which may be empty.
+    pub path: Span<str>,
+    /// The query.
+    pub query: Option<Span<str>>,
+    /// The fragment.
+    pub fragment: Option<Span<str>>,
+}
+
+impl Uri {
+    /// Returns the URI as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.span.as_str()
+    }
+
+    /// Returns `true` if the URI has a scheme.
+    pub fn is_absolute(&self) -> bool {
+        self.scheme.is_some()
+    }
+
+    /// Returns the authority of the URI, excluding the user information.
+    ///
+    /// This is the host, followed by the port if present, e.g. `example.com:8080`.
+    pub fn authority(&self) -> Option<&str> {
+        let host = self.host.as_ref()?;
+        let uri = self.span.as_str();
+        let start = if !host.is_empty() {
+            uri_offset(&self.span, host)
+        } else if let Some(port) = &self.port {
+            // Account for the colon preceding the port.
+            uri_offset(&self.span, port) - 1
+        } else {
+            return Some("");
+        };
+        let end = match &self.port {
+            Some(port) => uri_offset(&self.span, port) + port.len(),
+            None => start + host.len(),
+        };
+
+        Some(&uri[start..end])
+    }
+
+    /// Returns the segments of the path, in order.
+    ///
+    /// The segments are separated by `/`, and the empty segment preceding the leading `/` of
+    /// an absolute path is skipped, so `/a/b/` has the segments `a`, `b` and an empty segment.
+    /// Percent-encoded characters are not decoded.
+    pub fn path_segments(&self) -> Vec<Span<str>> {
+        let path = self.path.as_str();
+        if path.is_empty() {
+            return Vec::new();
+        }
+
+        let mut start = usize::from(path.starts_with('/'));
+        let mut segments = Vec::new();
+        for segment in path[start..].split('/') {
+            segments.push(self.path.slice_str(start..start + segment.len()));
+            start += segment.len() + 1;
+        }
+
+        segments
+    }
+
+    /// Returns the parameters of the query, in order, such as `key=secret` in
+    /// `/search?q=rust&key=secret`.
+    ///
+    /// The query is parsed as a URL-encoded form, so empty parameters are skipped and
+    /// percent-encoded characters are not decoded.
+    pub fn query_params(&self) -> Vec<FormField> {
+        self.query
+            .as_ref()
+            .map(|query| parse_form(query.as_byte_span()))
+            .unwrap_or_default()
+    }
+
+    /// Returns the first query parameter with the given name.
+    pub fn query_param(&self, name: &str) -> Option<FormField> {
+        self.query_params()
+            .into_iter()
+            .find(|param| param.name.data() == name.as_bytes())
+    }
+}
+
+impl_offset!(Uri {
+    span,
+    scheme,
+    userinfo,
+    host,
+    port,
+    path,
+    query,
+    fragment
+});
+
+impl Spanned<str> for Uri {
+    fn span(&self) -> &Span<str> {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for Uri {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+/// Returns the offset of a non-empty component relative to the start of the URI.
+fn uri_offset(uri: &Span<str>, component: &Span<str>) -> usize {
+    component.indices.min().unwrap_or_default() - uri.indices.min().unwrap_or_default()
+}
+
+/// Parses a URI reference from a span.
+pub(crate) fn parse_uri(span: &Span) -> Result<Uri, ParseError> {
+    let mut uri = parse_uri_from_bytes(span.data.clone().into())?;
+    uri.offset(span.indices.min().unwrap_or_default());
+
+    Ok(uri)
+}
+
+/// Parses a URI reference, with spans relative to the start of `src`.
+fn parse_uri_from_bytes(src: Source) -> Result<Uri, ParseError> {
+    let src_str = std::str::from_utf8(&src)?;
+    let uri = src_str.trim_matches([' ', '\t']);
+
+    if uri
+        .bytes()
+        .any(|b| b.is_ascii_whitespace() || b.is_ascii_control())
+    {
+        return Err(ParseError::new(format!("invalid URI: {uri:?}")));
+    }
+
+    let span = |s: &str| Span::new_from_str(&src, s);
+
+    let (rest, fragment) = match uri.split_once('#') {
+        Some((rest, fragment)) => (rest, Some(fragment)),
+        None => (uri, None),
+    };
+
+    let (rest, query) = match rest.split_once('?') {
+        Some((rest, query)) => (rest, Some(query)),
+        None => (rest, None),
+    };
+
+    let (scheme, rest) = match rest.split_once(':') {
+        Some((scheme, rest)) if is_scheme(scheme) => (Some(scheme), rest),
+        _ => (None, rest),
+    };
+
+    let (authority, path) = match rest.strip_prefix("//") {
+        Some(rest) => {
+            let idx = rest.find('/').unwrap_or(rest.len());
+            let (authority, path) = rest.split_at(idx);
+            (Some(authority), path)
+        }
+        None => (None, rest),
+    };
+
+    let (userinfo, host, port) = match authority {
+        Some(authority) => {
+            let (userinfo, host_port) = match authority.rsplit_once('@') {
+                Some((userinfo, host_port)) => (Some(userinfo), host_port),
+                None => (None, authority),
+            };
+
+            let (host, port) = split_host_port(host_port)?;
+
+            (userinfo, Some(host), port)
+        }
+        None => (None, None, None),
+    };
+
+    Ok(Uri {
+        span: span(uri),
+        scheme: scheme.map(span),
+        userinfo: userinfo.map(span),
+        host: host.map(span),
+        port: port.map(span),
+        path: span(path),
+        query: query.map(span),
+        fragment: fragment.map(span),
+    })
+}
+
+/// Splits the host and the port of an authority, excluding user information.
+///
+/// An empty port is treated as absent.
+pub(crate) fn split_host_port(host_port: &str) -> Result<(&str, Option<&str>), ParseError> {
+    // The port follows the last colon, unless it is within an IP literal.
+    match host_port.rfind(':') {
+        Some(idx) if !host_port[idx..].contains(']') => {
+            let (host, port) = host_port.split_at(idx);
+            let port = &port[1..];
+            if !port.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(ParseError::new(format!("invalid port: {port:?}")));
+            }
+            Ok((host, (!port.is_empty()).then_some(port)))
+        }
+        _ => Ok((host_port, None)),
+    }
+}
+
+/// Returns `true` if the string is a valid URI scheme.
+fn is_scheme(s: &str) -> bool {
+    let mut bytes = s.bytes();
+    bytes.next().is_some_and(|b| b.is_ascii_alphabetic())
+        && bytes.all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil::harness::{assert_request, assert_response};
+    use std::ops::Range;
+
+    use crate::http::{parse_request, parse_response};
+
+    use super::*;
+
+    fn parse(src: &'static str) -> Uri {
+        parse_uri_from_bytes(Source::from_static(src.as_bytes())).unwrap()
+    }
+
+    #[test]
+    fn test_parse_absolute_uri() {
+        let uri = parse("https://user:pw@example.com:8080/a/b?x=1&y=2#frag");
+
+        assert!(uri.is_absolute());
+        assert_eq!(uri.scheme.unwrap(), "https");
+        assert_eq!(uri.userinfo.unwrap(), "user:pw");
+        assert_eq!(uri.host.as_ref().unwrap(), "example.com");
+        assert_eq!(uri.port.as_ref().unwrap(), "8080");
+        assert_eq!(uri.path, "/a/b");
+        assert_eq!(uri.query.unwrap(), "x=1&y=2");
+        assert_eq!(uri.fragment.unwrap(), "frag");
+        assert_eq!(uri.host.unwrap().indices(), 16..27);
+    }
+
+    #[test]
+    fn test_parse_authority() {
+        let uri = parse("https://example.com:8080/");
+        assert_eq!(uri.authority(), Some("example.com:8080"));
+
+        let uri = parse("http://[::1]/");
+        assert_eq!(uri.host.as_ref().unwrap(), "[::1]");
+        assert!(uri.port.is_none());
+        assert_eq!(uri.authority(), Some("[::1]"));
+
+        let uri = parse("https://example.com");
+        assert_eq!(uri.authority(), Some("example.com"));
+        assert_eq!(uri.path, "");
+    }
+
+    #[test]
+    fn test_parse_origin_form() {
+        let uri = parse("/search?q=spansy");
+
+        assert!(!uri.is_absolute());
+        assert!(uri.host.is_none());
+        assert_eq!(uri.path, "/search");
+        assert_eq!(uri.query.unwrap(), "q=spansy");
+    }
+
+    #[test]
+    fn test_parse_relative_reference() {
+        let uri = parse("../a:b");
+
+        assert!(uri.scheme.is_none());
+        assert_eq!(uri.path, "../a:b");
+    }
+
+    #[test]
+    fn test_uri_valued_headers() {
+        let src = b"HTTP/1.1 302 Found\r\nLocation: https://example.com/next?id=1\r\n\
+            Content-Length: 0\r\n\r\n";
+        let res = parse_response(src).unwrap();
+        assert_response(src, &res);
+        let location = res.location().unwrap().unwrap();
+
+        let host: Range<usize> = location.host.unwrap().indices().clone().try_into().unwrap();
+        assert_eq!(&src[host], b"example.com");
+
+        let req = parse_request(
+            b"GET /a?b=c HTTP/1.1\r\nReferer: https://example.com/\r\n\
+            Origin: https://example.com\r\n\r\n",
+        )
+        .unwrap();
+
+        assert_eq!(req.request.target.uri().unwrap().path, "/a");
+        assert_eq!(
+            req.request.target.uri().unwrap().query.unwrap().indices(),
+            7..10
+        );
+        assert_eq!(req.referer().unwrap().unwrap().path, "/");
+        assert_eq!(
+            req.origin().unwrap().unwrap().authority(),
+            Some("example.com")
+        );
+    }
+
+    #[test]
+    fn test_path_segments_and_query_params() {
+        let src = b"GET /v1/users/42/?q=r%20s&&key=secret&flag HTTP/1.1\r\n\r\n";
+        let req = parse_request(src).unwrap();
+        assert_request(src, &req);
+        let uri = req.request.target.uri().unwrap();
+
+        let segments = uri.path_segments();
+        assert_eq!(
+            segments.iter().map(Span::as_str).collect::<Vec<_>>(),
+            ["v1", "users", "42", ""]
+        );
+        assert_eq!(segments[2].indices(), 14..16);
+        assert_eq!(parse("a/b").path_segments().len(), 2);
+        assert!(parse("?q").path_segments().is_empty());
+
+        let params = uri.query_params();
+        assert_eq!(params.len(), 3);
+        assert_eq!(params[0].value, b"r%20s".as_slice());
+        let key = uri.query_param("key").unwrap();
+        assert_eq!(key.value.indices(), 31..37);
+        assert_eq!(&src[31..37], b"secret");
+        assert!(uri.query_param("flag").unwrap().value.is_empty());
+        assert!(parse("/").query_params().is_empty());
+    }
+
+    #[test]
+    fn test_parse_invalid_uri() {
+        assert!(parse_uri_from_bytes(Source::from_static(b"/a b")).is_err());
+        assert!(parse_uri_from_bytes(Source::from_static(b"http://a:b/")).is_err());
+    }
+}
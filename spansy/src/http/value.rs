@@ -0,0 +1,203 @@
+//! Parsers for common header field values.
+//!
+//! The parsers are locale-independent and strictly follow the grammar of RFC 9110, except
+//! that optional whitespace surrounding the value is ignored.
+//!
+//! # Example
+//!
+//! ```
+//! use spansy::http::value::{parse_integer, parse_qvalue};
+//!
+//! assert_eq!(parse_integer(b" 42 ").unwrap(), 42);
+//! assert!(parse_integer(b"+42").is_err());
+//!
+//! assert_eq!(parse_qvalue(b"0.8").unwrap().thousandths(), 800);
+//! ```
+
+use crate::{http::HttpDate, ParseError};
+
+/// An error returned when a header field value is invalid.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[non_exhaustive]
+pub enum ValueError {
+    /// The value is empty.
+    #[error("value is empty")]
+    Empty,
+    /// The value is not a sequence of digits.
+    #[error("invalid integer: {0:?}")]
+    InvalidInteger(String),
+    /// The value is an integer which is too large.
+    #[error("integer out of range: {0:?}")]
+    IntegerOverflow(String),
+    /// The value is not a valid quality value.
+    #[error("invalid q-value: {0:?}")]
+    InvalidQValue(String),
+    /// The value is not a valid HTTP date.
+    #[error("invalid HTTP date: {0:?}")]
+    InvalidDate(String),
+}
+
+impl From<ValueError> for ParseError {
+    fn from(value: ValueError) -> Self {
+        ParseError::with_source("invalid header value", value)
+    }
+}
+
+/// A quality value, used to weight preferences in content negotiation (RFC 9110,
+/// section 12.4.2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QValue(u16);
+
+impl QValue {
+    /// The quality value `0`, meaning "not acceptable".
+    pub const ZERO: Self = Self(0);
+    /// The quality value `1`, the default.
+    pub const ONE: Self = Self(1000);
+
+    /// Returns the quality value in thousandths, from 0 to 1000.
+    pub fn thousandths(&self) -> u16 {
+        self.0
+    }
+
+    /// Returns the quality value as a float, from 0 to 1.
+    pub fn as_f32(&self) -> f32 {
+        f32::from(self.0) / 1000.0
+    }
+}
+
+/// Parses a non-negative decimal integer, such as the value of `Content-Length`.
+///
+/// Unlike `str::parse`, a leading `+` sign is rejected.
+pub fn parse_integer(value: &[u8]) -> Result<u64, ValueError> {
+    let value = trim_ows(value);
+    if value.is_empty() {
+        return Err(ValueError::Empty);
+    }
+
+    if !value.iter().all(u8::is_ascii_digit) {
+        return Err(ValueError::InvalidInteger(lossy(value)));
+    }
+
+    value.iter().try_fold(0u64, |acc, digit| {
+        acc.checked_mul(10)
+            .and_then(|acc| acc.checked_add(u64::from(digit - b'0')))
+            .ok_or_else(|| ValueError::IntegerOverflow(lossy(value)))
+    })
+}
+
+/// Parses a quality value, e.g. the `q` parameter of an `Accept` header element.
+///
+/// The grammar is `( "0" [ "." 0*3DIGIT ] ) / ( "1" [ "." 0*3("0") ] )`.
+pub fn parse_qvalue(value: &[u8]) -> Result<QValue, ValueError> {
+    let value = trim_ows(value);
+    let invalid = || ValueError::InvalidQValue(lossy(value));
+
+    let (int, frac) = match value.iter().position(|b| *b == b'.') {
+        Some(idx) => (&value[..idx], &value[idx + 1..]),
+        None => (value, [].as_slice()),
+    };
+
+    if frac.len() > 3 || !frac.iter().all(u8::is_ascii_digit) {
+        return Err(invalid());
+    }
+
+    let thousandths = frac
+        .iter()
+        .chain(std::iter::repeat(&b'0'))
+        .take(3)
+        .fold(0u16, |acc, digit| acc * 10 + u16::from(digit - b'0'));
+
+    match int {
+        b"0" => Ok(QValue(thousandths)),
+        b"1" if thousandths == 0 => Ok(QValue::ONE),
+        b"" => Err(ValueError::Empty),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses an HTTP date, such as the value of `Date` or `Last-Modified`.
+///
+/// All three formats of RFC 9110, section 5.6.7 are accepted: the IMF-fixdate, e.g.
+/// `Sun, 06 Nov 1994 08:49:37 GMT`, and the obsolete RFC 850 and asctime formats. Dates in any
+/// other format or time zone are rejected, unlike the lenient parsing of cookie dates.
+pub fn parse_http_date(value: &[u8]) -> Result<HttpDate, ValueError> {
+    let value = trim_ows(value);
+    if value.is_empty() {
+        return Err(ValueError::Empty);
+    }
+
+    std::str::from_utf8(value)
+        .ok()
+        .and_then(HttpDate::parse_http_date)
+        .ok_or_else(|| ValueError::InvalidDate(lossy(value)))
+}
+
+/// Trims optional whitespace from both ends of the value.
+fn trim_ows(value: &[u8]) -> &[u8] {
+    let is_ows = |b: &u8| matches!(b, b' ' | b'\t');
+    let start = value.iter().position(|b| !is_ows(b)).unwrap_or(value.len());
+    let end = value
+        .iter()
+        .rposition(|b| !is_ows(b))
+        .map_or(start, |idx| idx + 1);
+
+    &value[start..end]
+}
+
+fn lossy(value: &[u8]) -> String {
+    String::from_utf8_lossy(value).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_integer() {
+        assert_eq!(parse_integer(b"0"), Ok(0));
+        assert_eq!(parse_integer(b"\t123 "), Ok(123));
+        assert_eq!(parse_integer(b"18446744073709551615"), Ok(u64::MAX));
+        assert!(matches!(
+            parse_integer(b"18446744073709551616"),
+            Err(ValueError::IntegerOverflow(_))
+        ));
+        assert_eq!(parse_integer(b"  "), Err(ValueError::Empty));
+        assert!(parse_integer(b"-1").is_err());
+        assert!(parse_integer(b"1 2").is_err());
+        assert!(parse_integer("１".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_parse_qvalue() {
+        assert_eq!(parse_qvalue(b"1"), Ok(QValue::ONE));
+        assert_eq!(parse_qvalue(b"1.000"), Ok(QValue::ONE));
+        assert_eq!(parse_qvalue(b"0"), Ok(QValue::ZERO));
+        assert_eq!(parse_qvalue(b"0.5").unwrap().thousandths(), 500);
+        assert_eq!(parse_qvalue(b"0.125").unwrap().as_f32(), 0.125);
+        assert!(parse_qvalue(b"1.001").is_err());
+        assert!(parse_qvalue(b"0.1234").is_err());
+        assert!(parse_qvalue(b"2").is_err());
+        assert!(parse_qvalue(b".5").is_err());
+    }
+
+    #[test]
+    fn test_parse_http_date() {
+        let date = parse_http_date(b" Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+        assert_eq!(date.unix_timestamp(), 784111777);
+
+        assert_eq!(parse_http_date(b"Sun Nov  6 08:49:37 1994").unwrap(), date);
+
+        for value in [
+            b"yesterday".as_slice(),
+            b"xx 6 nov 94 8:49:37 trailing junk",
+            b"1994 Nov 06 08:49:37",
+            b"Sun, 06 Nov 1994 08:49:37 PST",
+        ] {
+            assert!(matches!(
+                parse_http_date(value),
+                Err(ValueError::InvalidDate(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::{
+    http::{Header, Session},
+    Span,
+};
+
+/// A change of the protocol or its version within a session.
+///
+/// The framing of the messages, and therefore what a range of the transcript proves, depends
+/// on the protocol version, so these are the points at which a verifier should not assume the
+/// semantics of HTTP/1.1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum VersionChange {
+    /// A response has a different HTTP version than its request, such as an `HTTP/1.0`
+    /// response to an `HTTP/1.1` request, which closes the connection unless kept alive.
+    Response {
+        /// The index of the exchange.
+        exchange: usize,
+        /// The version of the request.
+        request: Span,
+        /// The version of the response.
+        response: Span,
+    },
+    /// A request has a different HTTP version than the request before it.
+    Request {
+        /// The index of the exchange of the request.
+        exchange: usize,
+        /// The version of the previous request.
+        previous: Span,
+        /// The version of the request.
+        current: Span,
+    },
+    /// A `101 Switching Protocols` response switches the connection to another protocol,
+    /// such as WebSocket, so the data which follows it is not HTTP/1.1.
+    Upgrade {
+        /// The index of the exchange.
+        exchange: usize,
+        /// The `Upgrade` header of the response, which names the new protocol.
+        protocol: Option<Header>,
+    },
+}
+
+impl VersionChange {
+    /// Returns the index of the exchange at which the change occurs.
+    pub fn exchange(&self) -> usize {
+        match self {
+            VersionChange::Response { exchange, .. }
+            | VersionChange::Request { exchange, .. }
+            | VersionChange::Upgrade { exchange, .. } => *exchange,
+        }
+    }
+}
+
+impl Session {
+    /// Returns the changes of the protocol or its version within the session, in the order
+    /// of their exchanges.
+    ///
+    /// Within an exchange, a change of the request version is listed before a change of the
+    /// response version, which is listed before an upgrade.
+    pub fn version_changes(&self) -> Vec<VersionChange> {
+        let mut changes = Vec::new();
+
+        let mut previous: Option<Span> = None;
+        for (idx, exchange) in self.exchanges.iter().enumerate() {
+            let request = exchange.request.request.version();
+            if let Some(previous) = previous.filter(|previous| previous != request.as_bytes()) {
+                changes.push(VersionChange::Request {
+                    exchange: idx,
+                    previous,
+                    current: request.clone(),
+                });
+            }
+
+            if let Some(response) = &exchange.response {
+                let version = response.status.version();
+                if version != request.as_bytes() {
+                    changes.push(VersionChange::Response {
+                        exchange: idx,
+                        request: request.clone(),
+                        response: version,
+                    });
+                }
+
+                if response.status.code.as_str() == "101" {
+                    changes.push(VersionChange::Upgrade {
+                        exchange: idx,
+                        protocol: response.headers_with_name("Upgrade").next().cloned(),
+                    });
+                }
+            }
+
+            previous = Some(request);
+        }
+
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::harness::assert_session;
+
+    #[test]
+    fn test_version_changes() {
+        let sent = b"GET /a HTTP/1.1\r\nHost: a\r\n\r\n\
+            GET /b HTTP/1.0\r\nConnection: keep-alive\r\n\r\n\
+            GET /c HTTP/1.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\r\n";
+        let received = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n\
+            HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: keep-alive\r\n\r\n\
+            HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n";
+        let session = Session::new_from_slice(sent, received).unwrap();
+        assert_session(sent, received, &session);
+
+        let changes = session.version_changes();
+        assert_eq!(
+            changes
+                .iter()
+                .map(VersionChange::exchange)
+                .collect::<Vec<_>>(),
+            [1, 1, 2, 2]
+        );
+
+        let VersionChange::Request {
+            previous, current, ..
+        } = &changes[0]
+        else {
+            panic!("expected a request version change");
+        };
+        assert_eq!(previous, b"HTTP/1.1".as_slice());
+        assert_eq!(current, b"HTTP/1.0".as_slice());
+        assert_eq!(&sent[current.indices().min().unwrap()..][..8], b"HTTP/1.0");
+
+        let VersionChange::Response {
+            request, response, ..
+        } = &changes[1]
+        else {
+            panic!("expected a response version change");
+        };
+        assert_eq!(request, b"HTTP/1.0".as_slice());
+        assert_eq!(response, b"HTTP/1.1".as_slice());
+
+        assert!(
+            matches!(&changes[2], VersionChange::Request { current, .. } if current == b"HTTP/1.1".as_slice())
+        );
+
+        let VersionChange::Upgrade { protocol, .. } = &changes[3] else {
+            panic!("expected an upgrade");
+        };
+        assert_eq!(protocol.as_ref().unwrap().value.as_bytes(), b"websocket");
+    }
+
+    #[test]
+    fn test_version_changes_none() {
+        let session = Session::new_from_slice(
+            b"GET / HTTP/1.1\r\n\r\nGET / HTTP/1.1\r\n\r\n",
+            b"HTTP/1.1 204 No Content\r\n\r\nHTTP/1.1 204 No Content\r\n\r\n",
+        )
+        .unwrap();
+
+        assert!(session.version_changes().is_empty());
+    }
+}
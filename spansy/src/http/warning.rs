@@ -0,0 +1,43 @@
+use std::{fmt, ops::Range};
+
+/// A recoverable framing problem detected while iterating over HTTP messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum FramingWarning {
+    /// A response whose status forbids a body, i.e. 1xx, 204 or 304, was followed by bytes
+    /// which are not the start of another response.
+    ///
+    /// The bytes were skipped up to the next status line.
+    UnexpectedBody {
+        /// The status code of the response.
+        code: u16,
+        /// The range of the skipped bytes in the source.
+        range: Range<usize>,
+    },
+}
+
+impl fmt::Display for FramingWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FramingWarning::UnexpectedBody { code, range } => write!(
+                f,
+                "skipped {} unexpected bytes at {}..{} after a {code} response",
+                range.len(),
+                range.start,
+                range.end
+            ),
+        }
+    }
+}
+
+/// Returns the position of the next status line at or after `pos`.
+pub(crate) fn find_status_line(src: &[u8], pos: usize) -> Option<usize> {
+    src.get(pos..)?
+        .windows(STATUS_LINE_PREFIX.len())
+        .position(|window| window == STATUS_LINE_PREFIX)
+        .map(|idx| pos + idx)
+}
+
+/// The prefix of every HTTP/1.x status line.
+const STATUS_LINE_PREFIX: &[u8] = b"HTTP/1.";
@@ -0,0 +1,447 @@
+use std::{collections::HashMap, ops::Range};
+
+use utils::range::RangeSet;
+
+use crate::{
+    http2::{
+        frame::{parse_frames, SETTINGS_HEADER_TABLE_SIZE},
+        hpack::{Decoder, DEFAULT_TABLE_SIZE},
+        Frame, FrameKind, HeaderField, Request, Response, ACK, END_HEADERS, END_STREAM, PRIORITY,
+    },
+    multi::MultiSpanBuilder,
+    ParseError, Source, Span,
+};
+
+/// The connection preface sent by the client (RFC 9113, section 3.4).
+pub const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+/// An HTTP/2 connection, parsed from the data sent and received by the client.
+///
+/// The header blocks sent in each direction are decompressed with a separate HPACK context,
+/// in the order they appear. Request spans are relative to the sent data, and response spans
+/// are relative to the received data.
+///
+/// The maximum size of the dynamic table of each context may be raised by its encoder up to
+/// the largest `SETTINGS_HEADER_TABLE_SIZE` advertised by the decoding peer anywhere in the
+/// transcript, as the interleaving of the directions is not known.
+///
+/// # Example
+///
+/// ```
+/// use spansy::http2::{Connection, PREFACE};
+///
+/// let mut sent = PREFACE.to_vec();
+/// // An empty SETTINGS frame, and a HEADERS frame on stream 1 with `:method: GET`,
+/// // `:scheme: https`, `:path: /` and `:authority: example.com`.
+/// sent.extend_from_slice(b"\x00\x00\x00\x04\x00\x00\x00\x00\x00");
+/// sent.extend_from_slice(b"\x00\x00\x10\x01\x05\x00\x00\x00\x01\x82\x87\x84\x41\x0bexample.com");
+///
+/// // A HEADERS frame with `:status: 200` and a DATA frame which ends the stream.
+/// let received = b"\x00\x00\x01\x01\x04\x00\x00\x00\x01\x88\
+///     \x00\x00\x02\x00\x01\x00\x00\x00\x01hi";
+///
+/// let connection = Connection::new_from_slice(&sent, received).unwrap();
+/// let request = &connection.requests[0];
+/// assert_eq!(request.authority().unwrap().value, b"example.com".as_slice());
+///
+/// let response = connection.response_to(request).unwrap();
+/// assert_eq!(response.status().unwrap().value, b"200".as_slice());
+/// assert_eq!(response.body.as_ref().unwrap(), b"hi".as_slice());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Connection {
+    /// The connection preface sent by the client.
+    pub preface: Span,
+    /// The frames sent by the client, in order.
+    pub sent: Vec<Frame>,
+    /// The frames received by the client, in order.
+    pub received: Vec<Frame>,
+    /// The requests, in the order their streams were opened.
+    pub requests: Vec<Request>,
+    /// The responses, in the order their streams were first received.
+    pub responses: Vec<Response>,
+}
+
+impl Connection {
+    /// Parses a connection from the data sent and received by the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `sent` - The data sent by the client, starting with the connection preface.
+    /// * `received` - The data received by the client.
+    pub fn new(sent: impl Into<Source>, received: impl Into<Source>) -> Result<Self, ParseError> {
+        let (sent, received) = (sent.into(), received.into());
+
+        if !sent.starts_with(PREFACE) {
+            return Err(ParseError::new("missing HTTP/2 connection preface")
+                .with_label(0..PREFACE.len().min(sent.len()), "expected preface"));
+        }
+        let preface = Span::new_bytes(&sent, 0..PREFACE.len());
+
+        let sent_frames = parse_frames(&sent, PREFACE.len())?;
+        let received_frames = parse_frames(&received, 0)?;
+
+        // Each peer decodes the header blocks of the other.
+        let requests = parse_streams(
+            &sent,
+            &sent_frames,
+            Decoder::new(table_size_limit(&received_frames)?),
+        )?
+        .into_iter()
+        .map(Stream::into_request)
+        .collect::<Result<_, _>>()?;
+        let responses = parse_streams(
+            &received,
+            &received_frames,
+            Decoder::new(table_size_limit(&sent_frames)?),
+        )?
+        .into_iter()
+        .map(Stream::into_response)
+        .collect();
+
+        Ok(Self {
+            preface,
+            sent: sent_frames,
+            received: received_frames,
+            requests,
+            responses,
+        })
+    }
+
+    /// Parses a connection from byte slices of the data sent and received by the client.
+    pub fn new_from_slice(sent: &[u8], received: &[u8]) -> Result<Self, ParseError> {
+        Self::new(
+            Source::copy_from_slice(sent),
+            Source::copy_from_slice(received),
+        )
+    }
+
+    /// Returns the response to a request, if it was received.
+    pub fn response_to(&self, request: &Request) -> Option<&Response> {
+        self.responses
+            .iter()
+            .find(|response| response.stream_id == request.stream_id)
+    }
+}
+
+/// Returns the largest dynamic table size which a peer allows in the given frames.
+fn table_size_limit(frames: &[Frame]) -> Result<usize, ParseError> {
+    let mut limit = DEFAULT_TABLE_SIZE;
+    for frame in frames.iter().filter(|frame| {
+        frame.kind == FrameKind::Settings && frame.stream_id == 0 && !frame.has_flag(ACK)
+    }) {
+        for setting in frame.settings()? {
+            if setting.id == SETTINGS_HEADER_TABLE_SIZE {
+                limit = limit.max(setting.value as usize);
+            }
+        }
+    }
+
+    Ok(limit)
+}
+
+/// The frames of a stream sent in one direction.
+struct Stream {
+    id: u32,
+    blocks: Vec<Vec<HeaderField>>,
+    data: Option<MultiSpanBuilder>,
+    indices: Vec<Range<usize>>,
+    complete: bool,
+}
+
+impl Stream {
+    fn into_request(self) -> Result<Request, ParseError> {
+        let mut blocks = self.blocks.into_iter();
+        let headers = blocks.next().unwrap_or_default();
+        let trailers = blocks.next().unwrap_or_default();
+        if blocks.next().is_some() {
+            return Err(ParseError::new(format!(
+                "stream {} has more than two header blocks",
+                self.id
+            )));
+        }
+
+        Ok(Request {
+            indices: RangeSet::from(self.indices),
+            stream_id: self.id,
+            headers,
+            body: self.data.map(MultiSpanBuilder::build),
+            trailers,
+            complete: self.complete,
+        })
+    }
+
+    fn into_response(self) -> Response {
+        let is_interim = |block: &Vec<HeaderField>| {
+            block.iter().any(|field| {
+                field.name.as_bytes() == b":status" && field.value.as_bytes().starts_with(b"1")
+            })
+        };
+
+        let mut blocks = self.blocks.into_iter().peekable();
+        let mut interim = Vec::new();
+        while let Some(block) = blocks.next_if(is_interim) {
+            interim.push(block);
+        }
+
+        Response {
+            indices: RangeSet::from(self.indices),
+            stream_id: self.id,
+            interim,
+            headers: blocks.next().unwrap_or_default(),
+            body: self.data.map(MultiSpanBuilder::build),
+            trailers: blocks.flatten().collect(),
+            complete: self.complete,
+        }
+    }
+}
+
+/// A header block which is continued by `CONTINUATION` frames.
+struct PendingBlock {
+    stream_id: u32,
+    /// The stream of the block, or `None` for the block of a `PUSH_PROMISE` frame.
+    stream: Option<usize>,
+    fragments: MultiSpanBuilder,
+}
+
+/// Assembles the streams of the frames sent in one direction, in the order they were opened.
+fn parse_streams(
+    src: &Source,
+    frames: &[Frame],
+    mut decoder: Decoder,
+) -> Result<Vec<Stream>, ParseError> {
+    let mut streams: Vec<Stream> = Vec::new();
+    let mut ids = HashMap::new();
+    let mut pending: Option<PendingBlock> = None;
+
+    for frame in frames {
+        let frame_range = frame.range();
+        let invalid = |msg: String| -> ParseError {
+            ParseError::new(msg).with_label(frame_range.clone(), "invalid frame")
+        };
+
+        if let Some(block) = &mut pending {
+            if frame.kind != FrameKind::Continuation || frame.stream_id != block.stream_id {
+                return Err(invalid(format!(
+                    "expected CONTINUATION frame on stream {}",
+                    block.stream_id
+                )));
+            }
+        }
+
+        let fragment = match frame.kind {
+            FrameKind::Headers => {
+                let skip = if frame.has_flag(PRIORITY) { 5 } else { 0 };
+                Some(frame.unpadded(skip)?)
+            }
+            FrameKind::PushPromise => Some(frame.unpadded(4)?),
+            FrameKind::Continuation => {
+                if pending.is_none() {
+                    return Err(invalid("unexpected CONTINUATION frame".to_string()));
+                }
+                Some(frame.payload_range())
+            }
+            FrameKind::Data => {
+                let stream = ids
+                    .get(&frame.stream_id)
+                    .map(|idx: &usize| &mut streams[*idx])
+                    .ok_or_else(|| {
+                        invalid(format!(
+                            "DATA frame on stream {} precedes its headers",
+                            frame.stream_id
+                        ))
+                    })?;
+                let data = frame.unpadded(0)?;
+                stream
+                    .data
+                    .get_or_insert_with(MultiSpanBuilder::default)
+                    .push_verbatim(src, data)?;
+                stream.indices.push(frame_range.clone());
+                stream.complete |= frame.has_flag(END_STREAM);
+                None
+            }
+            _ => None,
+        };
+        let Some(fragment) = fragment else {
+            continue;
+        };
+
+        if frame.stream_id == 0 {
+            return Err(invalid(format!("{:?} frame on stream 0", frame.kind)));
+        }
+
+        let mut block = match pending.take() {
+            Some(block) => block,
+            None => {
+                let stream = (frame.kind == FrameKind::Headers).then(|| {
+                    *ids.entry(frame.stream_id).or_insert_with(|| {
+                        streams.push(Stream {
+                            id: frame.stream_id,
+                            blocks: Vec::new(),
+                            data: None,
+                            indices: Vec::new(),
+                            complete: false,
+                        });
+                        streams.len() - 1
+                    })
+                });
+                if let Some(stream) = stream {
+                    streams[stream].complete |= frame.has_flag(END_STREAM);
+                }
+
+                PendingBlock {
+                    stream_id: frame.stream_id,
+                    stream,
+                    fragments: MultiSpanBuilder::default(),
+                }
+            }
+        };
+        block.fragments.push_verbatim(src, fragment)?;
+        if let Some(stream) = block.stream {
+            streams[stream].indices.push(frame_range.clone());
+        }
+
+        if frame.has_flag(END_HEADERS) {
+            let fields = decoder.decode(src, &block.fragments.build())?;
+            // The header block of a pushed request is only decoded to keep the context in
+            // sync.
+            if let Some(stream) = block.stream {
+                streams[stream].blocks.push(fields);
+            }
+        } else {
+            pending = Some(block);
+        }
+    }
+
+    if let Some(block) = pending {
+        return Err(ParseError::new(format!(
+            "header block of stream {} is incomplete",
+            block.stream_id
+        )));
+    }
+
+    Ok(streams)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::range::ToRangeSet;
+
+    use crate::http2::PADDED;
+
+    fn frame(kind: u8, flags: u8, stream_id: u32, payload: &[u8]) -> Vec<u8> {
+        let mut frame = (payload.len() as u32).to_be_bytes()[1..].to_vec();
+        frame.extend_from_slice(&[kind, flags]);
+        frame.extend_from_slice(&stream_id.to_be_bytes());
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn transcript() -> (Vec<u8>, Vec<u8>) {
+        let sent = [
+            PREFACE.to_vec(),
+            frame(0x4, 0, 0, b"\x00\x01\x00\x00\x10\x00"),
+            // GET / on stream 1.
+            frame(
+                0x1,
+                END_HEADERS | END_STREAM,
+                1,
+                b"\x82\x86\x84\x41\x0fwww.example.com",
+            ),
+            // A header block continued by a CONTINUATION frame, and a padded body.
+            frame(0x1, 0, 3, b"\x83\x86"),
+            frame(0x9, END_HEADERS, 3, b"\x84\xbe"),
+            frame(0x0, PADDED | END_STREAM, 3, b"\x02hello\x00\x00"),
+        ]
+        .concat();
+
+        let received = [
+            frame(0x4, 0, 0, b""),
+            frame(0x4, ACK, 0, b""),
+            // An interim response, then the response on stream 1.
+            frame(0x1, END_HEADERS, 1, b"\x08\x03103"),
+            frame(0x1, END_HEADERS, 1, b"\x88"),
+            frame(0x0, END_STREAM, 1, b"ok"),
+            // A response on stream 3 with trailers.
+            frame(0x1, END_HEADERS, 3, b"\x8d"),
+            frame(0x1, END_HEADERS | END_STREAM, 3, b"\x40\x05x-end\x011"),
+        ]
+        .concat();
+
+        (sent, received)
+    }
+
+    #[test]
+    fn test_parse_connection() {
+        let (sent, received) = transcript();
+        let connection = Connection::new_from_slice(&sent, &received).unwrap();
+
+        assert_eq!(connection.preface, PREFACE);
+        assert_eq!(connection.sent.len(), 5);
+        assert_eq!(connection.received.len(), 7);
+
+        let [get, post] = connection.requests.as_slice() else {
+            panic!("expected two requests");
+        };
+        assert_eq!(get.method().unwrap().value, b"GET".as_slice());
+        assert_eq!(
+            get.authority().unwrap().value,
+            b"www.example.com".as_slice()
+        );
+        assert!(get.body.is_none());
+        assert!(get.complete);
+
+        assert_eq!(post.stream_id, 3);
+        assert_eq!(post.method().unwrap().value, b"POST".as_slice());
+        assert_eq!(post.path().unwrap().value, b"/".as_slice());
+        // The authority is taken from the dynamic table.
+        assert_eq!(
+            post.authority().unwrap().value,
+            b"www.example.com".as_slice()
+        );
+        let body = post.body.as_ref().unwrap();
+        assert_eq!(body, b"hello".as_slice());
+        let body_start = sent.len() - 7;
+        assert_eq!(body.indices(), RangeSet::from(body_start..body_start + 5));
+        assert_eq!(post.to_range_set().end(), Some(sent.len()));
+        assert!(post.complete);
+
+        let response = connection.response_to(get).unwrap();
+        assert_eq!(response.interim.len(), 1);
+        assert_eq!(response.status().unwrap().value, b"200".as_slice());
+        assert_eq!(response.body.as_ref().unwrap(), b"ok".as_slice());
+        assert!(response.trailers.is_empty());
+        assert!(response.complete);
+
+        let response = connection.response_to(post).unwrap();
+        assert_eq!(response.status().unwrap().value, b"404".as_slice());
+        assert!(response.body.is_none());
+        assert_eq!(response.trailers[0].name, b"x-end".as_slice());
+        assert!(response.complete);
+    }
+
+    #[test]
+    fn test_parse_connection_invalid() {
+        let (sent, received) = transcript();
+
+        // The preface is missing.
+        assert!(Connection::new_from_slice(&sent[PREFACE.len()..], &received).is_err());
+
+        // A DATA frame precedes the headers of its stream.
+        let data = frame(0x0, 0, 5, b"data");
+        assert!(Connection::new_from_slice(&[sent.as_slice(), &data].concat(), &received).is_err());
+
+        // A header block is interrupted before its CONTINUATION frame.
+        let headers = frame(0x1, 0, 5, b"\x82");
+        let interrupted = [sent.as_slice(), &headers, &data].concat();
+        assert!(Connection::new_from_slice(&interrupted, &received).is_err());
+        assert!(
+            Connection::new_from_slice(&[sent.as_slice(), &headers].concat(), &received).is_err()
+        );
+
+        // A frame is truncated.
+        assert!(Connection::new_from_slice(&sent, &received[..received.len() - 1]).is_err());
+    }
+}
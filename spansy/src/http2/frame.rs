@@ -0,0 +1,218 @@
+use std::ops::Range;
+
+use utils::range::{RangeSet, ToRangeSet};
+
+use crate::{ParseError, Source, Span, Spanned};
+
+/// The length of a frame header.
+const HEADER_LEN: usize = 9;
+
+/// The `END_STREAM` flag of `DATA` and `HEADERS` frames.
+pub const END_STREAM: u8 = 0x1;
+/// The `ACK` flag of `SETTINGS` and `PING` frames.
+pub const ACK: u8 = 0x1;
+/// The `END_HEADERS` flag of `HEADERS`, `PUSH_PROMISE` and `CONTINUATION` frames.
+pub const END_HEADERS: u8 = 0x4;
+/// The `PADDED` flag of `DATA`, `HEADERS` and `PUSH_PROMISE` frames.
+pub const PADDED: u8 = 0x8;
+/// The `PRIORITY` flag of `HEADERS` frames.
+pub const PRIORITY: u8 = 0x20;
+
+/// The `SETTINGS_HEADER_TABLE_SIZE` setting.
+pub(crate) const SETTINGS_HEADER_TABLE_SIZE: u16 = 0x1;
+
+/// The type of an HTTP/2 frame (RFC 9113, section 6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrameKind {
+    /// A `DATA` frame, which carries a part of a message body.
+    Data,
+    /// A `HEADERS` frame, which opens a stream and carries a header block fragment.
+    Headers,
+    /// A `PRIORITY` frame.
+    Priority,
+    /// A `RST_STREAM` frame, which terminates a stream.
+    RstStream,
+    /// A `SETTINGS` frame.
+    Settings,
+    /// A `PUSH_PROMISE` frame, which carries the header block of a pushed request.
+    PushPromise,
+    /// A `PING` frame.
+    Ping,
+    /// A `GOAWAY` frame, which initiates the shutdown of the connection.
+    GoAway,
+    /// A `WINDOW_UPDATE` frame.
+    WindowUpdate,
+    /// A `CONTINUATION` frame, which continues a header block.
+    Continuation,
+    /// A frame of an extension type, which is ignored.
+    Unknown(u8),
+}
+
+impl From<u8> for FrameKind {
+    fn from(kind: u8) -> Self {
+        match kind {
+            0x0 => FrameKind::Data,
+            0x1 => FrameKind::Headers,
+            0x2 => FrameKind::Priority,
+            0x3 => FrameKind::RstStream,
+            0x4 => FrameKind::Settings,
+            0x5 => FrameKind::PushPromise,
+            0x6 => FrameKind::Ping,
+            0x7 => FrameKind::GoAway,
+            0x8 => FrameKind::WindowUpdate,
+            0x9 => FrameKind::Continuation,
+            kind => FrameKind::Unknown(kind),
+        }
+    }
+}
+
+/// An HTTP/2 frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frame {
+    pub(crate) span: Span,
+
+    /// The type of the frame.
+    pub kind: FrameKind,
+    /// The flags of the frame.
+    pub flags: u8,
+    /// The stream identifier, which is 0 for frames of the connection.
+    pub stream_id: u32,
+    /// The payload of the frame, including any padding.
+    pub payload: Span,
+}
+
+impl Frame {
+    /// Returns `true` if the given flag is set.
+    pub fn has_flag(&self, flag: u8) -> bool {
+        self.flags & flag != 0
+    }
+
+    /// Returns the settings of a `SETTINGS` frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the frame is not a `SETTINGS` frame or its payload is not a
+    /// sequence of settings.
+    pub fn settings(&self) -> Result<Vec<Setting>, ParseError> {
+        let range = self.payload_range();
+        if self.kind != FrameKind::Settings || !range.len().is_multiple_of(6) {
+            return Err(ParseError::new("invalid SETTINGS frame")
+                .with_label(self.range(), "invalid SETTINGS frame"));
+        }
+
+        let payload = self.payload.as_bytes();
+        Ok(payload
+            .chunks_exact(6)
+            .enumerate()
+            .map(|(i, setting)| Setting {
+                span: self.payload.slice(6 * i..6 * i + 6),
+                id: u16::from_be_bytes([setting[0], setting[1]]),
+                value: u32::from_be_bytes([setting[2], setting[3], setting[4], setting[5]]),
+            })
+            .collect())
+    }
+
+    /// Returns the range of the frame in the source.
+    pub(crate) fn range(&self) -> Range<usize> {
+        self.span.indices.min().unwrap_or_default()..self.span.indices.end().unwrap_or_default()
+    }
+
+    /// Returns the range of the payload in the source.
+    pub(crate) fn payload_range(&self) -> Range<usize> {
+        let end = self.range().end;
+        end - self.payload.len()..end
+    }
+
+    /// Returns the range of the data of a frame whose payload may be padded, skipping
+    /// `skip` bytes which follow the pad length.
+    pub(crate) fn unpadded(&self, skip: usize) -> Result<Range<usize>, ParseError> {
+        let payload = self.payload.as_bytes();
+        let range = self.payload_range();
+
+        let (pad_len, start) = if self.has_flag(PADDED) {
+            (*payload.first().unwrap_or(&0) as usize, 1)
+        } else {
+            (0, 0)
+        };
+
+        if start + skip + pad_len > payload.len() || (self.has_flag(PADDED) && payload.is_empty()) {
+            return Err(
+                ParseError::new(format!("invalid padding of {:?} frame", self.kind))
+                    .with_label(self.range(), "invalid padding"),
+            );
+        }
+
+        Ok(range.start + start + skip..range.end - pad_len)
+    }
+}
+
+impl Spanned for Frame {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for Frame {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+/// A setting of a `SETTINGS` frame (RFC 9113, section 6.5.1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Setting {
+    pub(crate) span: Span,
+
+    /// The identifier of the setting, such as 0x1 for `SETTINGS_HEADER_TABLE_SIZE`.
+    pub id: u16,
+    /// The value of the setting.
+    pub value: u32,
+}
+
+impl Spanned for Setting {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for Setting {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+/// Parses the frames of `src` starting at `offset`, until the end of the source.
+pub(crate) fn parse_frames(src: &Source, mut offset: usize) -> Result<Vec<Frame>, ParseError> {
+    let mut frames = Vec::new();
+    while offset < src.len() {
+        let header = src.get(offset..offset + HEADER_LEN).ok_or_else(|| {
+            ParseError::new("incomplete frame header")
+                .with_label(offset..src.len(), "incomplete frame header")
+        })?;
+
+        let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+        let payload_start = offset + HEADER_LEN;
+        let end = payload_start + len;
+        if end > src.len() {
+            return Err(ParseError::new(format!(
+                "frame payload of {len} bytes exceeds the source"
+            ))
+            .with_label(offset..src.len(), "incomplete frame"));
+        }
+
+        frames.push(Frame {
+            span: Span::new_bytes(src, offset..end),
+            kind: FrameKind::from(header[3]),
+            flags: header[4],
+            stream_id: u32::from_be_bytes([header[5], header[6], header[7], header[8]])
+                & 0x7fff_ffff,
+            payload: Span::new_bytes(src, payload_start..end),
+        });
+        offset = end;
+    }
+
+    Ok(frames)
+}
@@ -0,0 +1,496 @@
+//! HPACK header decompression (RFC 7541).
+
+use std::{collections::VecDeque, ops::Range};
+
+use bytes::Bytes;
+use utils::range::{RangeSet, ToRangeSet};
+
+use crate::{http2::huffman, multi::MultiSpanBuilder, MultiSpan, ParseError};
+
+/// The default maximum size of the dynamic table, in octets.
+pub(crate) const DEFAULT_TABLE_SIZE: usize = 4096;
+
+/// The overhead of an entry of the dynamic table, in octets.
+const ENTRY_OVERHEAD: usize = 32;
+
+/// The static table (RFC 7541, appendix A).
+const STATIC_TABLE: [(&str, &str); 61] = [
+    (":authority", ""),
+    (":method", "GET"),
+    (":method", "POST"),
+    (":path", "/"),
+    (":path", "/index.html"),
+    (":scheme", "http"),
+    (":scheme", "https"),
+    (":status", "200"),
+    (":status", "204"),
+    (":status", "206"),
+    (":status", "304"),
+    (":status", "400"),
+    (":status", "404"),
+    (":status", "500"),
+    ("accept-charset", ""),
+    ("accept-encoding", "gzip, deflate"),
+    ("accept-language", ""),
+    ("accept-ranges", ""),
+    ("accept", ""),
+    ("access-control-allow-origin", ""),
+    ("age", ""),
+    ("allow", ""),
+    ("authorization", ""),
+    ("cache-control", ""),
+    ("content-disposition", ""),
+    ("content-encoding", ""),
+    ("content-language", ""),
+    ("content-length", ""),
+    ("content-location", ""),
+    ("content-range", ""),
+    ("content-type", ""),
+    ("cookie", ""),
+    ("date", ""),
+    ("etag", ""),
+    ("expect", ""),
+    ("expires", ""),
+    ("from", ""),
+    ("host", ""),
+    ("if-match", ""),
+    ("if-modified-since", ""),
+    ("if-none-match", ""),
+    ("if-range", ""),
+    ("if-unmodified-since", ""),
+    ("last-modified", ""),
+    ("link", ""),
+    ("location", ""),
+    ("max-forwards", ""),
+    ("proxy-authenticate", ""),
+    ("proxy-authorization", ""),
+    ("range", ""),
+    ("referer", ""),
+    ("refresh", ""),
+    ("retry-after", ""),
+    ("server", ""),
+    ("set-cookie", ""),
+    ("strict-transport-security", ""),
+    ("transfer-encoding", ""),
+    ("user-agent", ""),
+    ("vary", ""),
+    ("via", ""),
+    ("www-authenticate", ""),
+];
+
+/// How a header field is represented in a header block (RFC 7541, section 6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Representation {
+    /// The field is an entry of the static or dynamic table.
+    Indexed,
+    /// The field is a literal which is added to the dynamic table.
+    IncrementalIndexing,
+    /// The field is a literal which is not added to the dynamic table.
+    WithoutIndexing,
+    /// The field is a literal which must never be added to a table by an intermediary, which
+    /// is used for sensitive values such as credentials.
+    NeverIndexed,
+}
+
+/// A header field of an HTTP/2 header block.
+///
+/// The name and value are decoded, and their positions map back to the indices of the header
+/// block they were decoded from:
+///
+/// - A literal string which is not Huffman-encoded views its bytes in the source.
+/// - A Huffman-encoded string is decoded from its encoded bytes.
+/// - A name or value taken from the static or dynamic table is decoded from the bytes of the
+///   index which refers to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeaderField {
+    indices: RangeSet<usize>,
+
+    /// The name of the field, in lowercase for a valid message.
+    pub name: MultiSpan,
+    /// The value of the field.
+    pub value: MultiSpan,
+    /// How the field is represented.
+    pub representation: Representation,
+}
+
+impl HeaderField {
+    /// Returns the indices of the representation of the field in the source.
+    pub fn indices(&self) -> &RangeSet<usize> {
+        &self.indices
+    }
+
+    /// Returns `true` if the field is a pseudo-header field, such as `:method`.
+    pub fn is_pseudo(&self) -> bool {
+        self.name.as_bytes().starts_with(b":")
+    }
+}
+
+impl ToRangeSet<usize> for HeaderField {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.indices.clone()
+    }
+}
+
+/// The decoding context of the header blocks sent in one direction of a connection.
+#[derive(Debug)]
+pub(crate) struct Decoder {
+    /// The entries of the dynamic table, the newest first.
+    table: VecDeque<(Bytes, Bytes)>,
+    /// The size of the dynamic table.
+    size: usize,
+    /// The maximum size of the dynamic table.
+    max_size: usize,
+    /// The largest maximum size the encoder may set.
+    max_size_limit: usize,
+}
+
+impl Decoder {
+    /// Returns a decoder whose encoder may set the maximum size of the dynamic table up to
+    /// `max_size_limit`.
+    pub(crate) fn new(max_size_limit: usize) -> Self {
+        Self {
+            table: VecDeque::new(),
+            size: 0,
+            max_size: DEFAULT_TABLE_SIZE.min(max_size_limit),
+            max_size_limit,
+        }
+    }
+
+    /// Decodes a header block of `src`, whose bytes are the data of `block`.
+    pub(crate) fn decode(
+        &mut self,
+        src: &[u8],
+        block: &MultiSpan,
+    ) -> Result<Vec<HeaderField>, ParseError> {
+        let mut reader = Reader {
+            src,
+            block,
+            data: block.as_bytes(),
+            pos: 0,
+        };
+
+        let mut fields = Vec::new();
+        while !reader.is_empty() {
+            let start = reader.pos;
+            let byte = reader.data[start];
+            let (representation, index) = if byte & 0x80 != 0 {
+                (Representation::Indexed, reader.integer(7)?)
+            } else if byte & 0x40 != 0 {
+                (Representation::IncrementalIndexing, reader.integer(6)?)
+            } else if byte & 0x20 != 0 {
+                let size = reader.integer(5)?;
+                if size > self.max_size_limit {
+                    return Err(reader.error(
+                        start,
+                        format!(
+                            "dynamic table size {size} exceeds the limit {}",
+                            self.max_size_limit
+                        ),
+                    ));
+                }
+                self.max_size = size;
+                self.evict(0);
+                continue;
+            } else if byte & 0x10 != 0 {
+                (Representation::NeverIndexed, reader.integer(4)?)
+            } else {
+                (Representation::WithoutIndexing, reader.integer(4)?)
+            };
+            let index_indices = block.indices_of(start..reader.pos);
+
+            let field = if representation == Representation::Indexed {
+                let (name, value) = self.get(index).ok_or_else(|| {
+                    reader.error(start, format!("invalid header table index {index}"))
+                })?;
+                HeaderField {
+                    indices: index_indices.clone(),
+                    name: decoded(index_indices.clone(), &name),
+                    value: decoded(index_indices, &value),
+                    representation,
+                }
+            } else {
+                let name = if index == 0 {
+                    reader.string()?
+                } else {
+                    let (name, _) = self.get(index).ok_or_else(|| {
+                        reader.error(start, format!("invalid header table index {index}"))
+                    })?;
+                    decoded(index_indices, &name)
+                };
+                let value = reader.string()?;
+                HeaderField {
+                    indices: block.indices_of(start..reader.pos),
+                    name,
+                    value,
+                    representation,
+                }
+            };
+
+            if representation == Representation::IncrementalIndexing {
+                self.insert(
+                    Bytes::copy_from_slice(field.name.as_bytes()),
+                    Bytes::copy_from_slice(field.value.as_bytes()),
+                );
+            }
+            fields.push(field);
+        }
+
+        Ok(fields)
+    }
+
+    /// Returns the entry at the given index of the static and dynamic tables.
+    fn get(&self, index: usize) -> Option<(Bytes, Bytes)> {
+        match index {
+            0 => None,
+            1..=61 => {
+                let (name, value) = STATIC_TABLE[index - 1];
+                Some((Bytes::from_static(name.as_bytes()), Bytes::from(value)))
+            }
+            _ => self.table.get(index - 62).cloned(),
+        }
+    }
+
+    fn insert(&mut self, name: Bytes, value: Bytes) {
+        let size = name.len() + value.len() + ENTRY_OVERHEAD;
+        self.evict(size);
+
+        // An entry larger than the table empties it.
+        if size <= self.max_size {
+            self.size += size;
+            self.table.push_front((name, value));
+        }
+    }
+
+    /// Evicts entries until an entry of `size` octets fits in the table.
+    fn evict(&mut self, size: usize) {
+        while self.size + size > self.max_size {
+            let Some((name, value)) = self.table.pop_back() else {
+                break;
+            };
+            self.size -= name.len() + value.len() + ENTRY_OVERHEAD;
+        }
+    }
+}
+
+/// Returns a span of `data` decoded from `indices`.
+fn decoded(indices: RangeSet<usize>, data: &[u8]) -> MultiSpan {
+    let mut builder = MultiSpanBuilder::default();
+    builder.push_decoded(indices, data);
+    builder.build()
+}
+
+/// A reader of the primitive types of a header block (RFC 7541, section 5).
+struct Reader<'a> {
+    src: &'a [u8],
+    block: &'a MultiSpan,
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    /// Returns an error labeled from `start` to the current position.
+    fn error(&self, start: usize, msg: impl Into<String>) -> ParseError {
+        let mut err = ParseError::new(msg);
+        if let Some(range) = self.source_range(start..self.pos.max(start + 1)) {
+            err = err.with_label(range, "invalid header block");
+        }
+        err
+    }
+
+    fn source_range(&self, range: Range<usize>) -> Option<Range<usize>> {
+        let range = range.start.min(self.data.len())..range.end.min(self.data.len());
+        let indices = self.block.indices_of(range);
+        Some(indices.min()?..indices.end()?)
+    }
+
+    fn byte(&mut self, start: usize) -> Result<u8, ParseError> {
+        let byte = *self
+            .data
+            .get(self.pos)
+            .ok_or_else(|| self.error(start, "header block is incomplete"))?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    /// Reads an integer with an `n`-bit prefix.
+    fn integer(&mut self, n: u32) -> Result<usize, ParseError> {
+        let start = self.pos;
+        let max = (1 << n) - 1;
+        let mut value = (self.byte(start)? & max) as usize;
+        if value < max as usize {
+            return Ok(value);
+        }
+
+        let mut shift = 0;
+        loop {
+            let byte = self.byte(start)?;
+            let add = ((byte & 0x7f) as usize)
+                .checked_shl(shift)
+                .filter(|add| add >> shift == (byte & 0x7f) as usize)
+                .and_then(|add| value.checked_add(add))
+                .ok_or_else(|| self.error(start, "integer overflows"))?;
+            value = add;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Reads a string literal.
+    fn string(&mut self) -> Result<MultiSpan, ParseError> {
+        let start = self.pos;
+        let huffman = self.data.get(start).is_some_and(|byte| byte & 0x80 != 0);
+        let len = self.integer(7)?;
+
+        let data_start = self.pos;
+        let data_end = data_start
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| self.error(start, "header block is incomplete"))?;
+        self.pos = data_end;
+
+        let data = &self.data[data_start..data_end];
+        if huffman {
+            let decoded_data =
+                huffman::decode(data).map_err(|err| self.error(start, err.to_string()))?;
+            Ok(decoded(
+                self.block.indices_of(data_start..data_end),
+                &decoded_data,
+            ))
+        } else {
+            // The string may span several frames, so it views a range of each.
+            let mut builder = MultiSpanBuilder::default();
+            for range in self.block.indices_of(data_start..data_end).iter_ranges() {
+                builder.push_verbatim(self.src, range)?;
+            }
+            Ok(builder.build())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_block(decoder: &mut Decoder, src: &[u8]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        let mut block = MultiSpanBuilder::default();
+        block.push_verbatim(src, 0..src.len()).unwrap();
+        decoder
+            .decode(src, &block.build())
+            .unwrap()
+            .into_iter()
+            .map(|field| {
+                (
+                    field.name.as_bytes().to_vec(),
+                    field.value.as_bytes().to_vec(),
+                )
+            })
+            .collect()
+    }
+
+    fn fields(fields: &[(&str, &str)]) -> Vec<(Vec<u8>, Vec<u8>)> {
+        fields
+            .iter()
+            .map(|(name, value)| (name.as_bytes().to_vec(), value.as_bytes().to_vec()))
+            .collect()
+    }
+
+    // RFC 7541, appendix C.3 and C.4.
+    #[test]
+    fn test_decode_requests() {
+        for blocks in [
+            [
+                b"\x82\x86\x84\x41\x0fwww.example.com".as_slice(),
+                b"\x82\x86\x84\xbe\x58\x08no-cache",
+                b"\x82\x87\x85\xbf\x40\x0acustom-key\x0ccustom-value",
+            ],
+            [
+                b"\x82\x86\x84\x41\x8c\xf1\xe3\xc2\xe5\xf2\x3a\x6b\xa0\xab\x90\xf4\xff",
+                b"\x82\x86\x84\xbe\x58\x86\xa8\xeb\x10\x64\x9c\xbf",
+                b"\x82\x87\x85\xbf\x40\x88\x25\xa8\x49\xe9\x5b\xa9\x7d\x7f\
+                  \x89\x25\xa8\x49\xe9\x5b\xb8\xe8\xb4\xbf",
+            ],
+        ] {
+            let mut decoder = Decoder::new(DEFAULT_TABLE_SIZE);
+            let base = [
+                (":method", "GET"),
+                (":scheme", "http"),
+                (":path", "/"),
+                (":authority", "www.example.com"),
+            ];
+
+            assert_eq!(decode_block(&mut decoder, blocks[0]), fields(&base));
+            assert_eq!(decoder.size, 57);
+
+            let mut expected = base.to_vec();
+            expected.push(("cache-control", "no-cache"));
+            assert_eq!(decode_block(&mut decoder, blocks[1]), fields(&expected));
+            assert_eq!(decoder.size, 110);
+
+            assert_eq!(
+                decode_block(&mut decoder, blocks[2]),
+                fields(&[
+                    (":method", "GET"),
+                    (":scheme", "https"),
+                    (":path", "/index.html"),
+                    (":authority", "www.example.com"),
+                    ("custom-key", "custom-value"),
+                ])
+            );
+            assert_eq!(decoder.size, 164);
+            assert_eq!(decoder.table.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_decode_indices() {
+        // A literal split across two fragments, then an indexed field.
+        let src = b"\x40\x03ab..c\x04abcd\xbe";
+        let mut block = MultiSpanBuilder::default();
+        block.push_verbatim(src, 0..4).unwrap();
+        block.push_verbatim(src, 6..src.len()).unwrap();
+
+        let fields = Decoder::new(DEFAULT_TABLE_SIZE)
+            .decode(src, &block.build())
+            .unwrap();
+
+        assert_eq!(fields[0].name, b"abc".as_slice());
+        assert_eq!(fields[0].name.indices().into_inner(), [2..4, 6..7]);
+        assert_eq!(fields[0].value.indices(), RangeSet::from(8..12));
+        assert_eq!(fields[0].indices().clone().into_inner(), [0..4, 6..12]);
+        assert_eq!(
+            fields[0].representation,
+            Representation::IncrementalIndexing
+        );
+
+        // The indexed field maps to the index which refers to it.
+        assert_eq!(fields[1].value, b"abcd".as_slice());
+        assert_eq!(fields[1].value.indices(), RangeSet::from(12..13));
+        assert_eq!(fields[1].representation, Representation::Indexed);
+    }
+
+    #[test]
+    fn test_decode_table_size_update() {
+        let mut decoder = Decoder::new(DEFAULT_TABLE_SIZE);
+        decode_block(&mut decoder, b"\x40\x01a\x01b");
+        assert_eq!(decoder.table.len(), 1);
+
+        // A size update to 0 empties the table.
+        decode_block(&mut decoder, b"\x20");
+        assert!(decoder.table.is_empty());
+
+        // An update above the limit, and an index past the table, are invalid.
+        for src in [b"\x3f\xe2\x1f".as_slice(), b"\xbe"] {
+            let mut block = MultiSpanBuilder::default();
+            block.push_verbatim(src, 0..src.len()).unwrap();
+            assert!(decoder.decode(src, &block.build()).is_err());
+        }
+    }
+}
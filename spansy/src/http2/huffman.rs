@@ -0,0 +1,144 @@
+//! The Huffman code of HPACK (RFC 7541, appendix B).
+
+use std::sync::OnceLock;
+
+use crate::ParseError;
+
+/// The end-of-string symbol, which must not appear in encoded data.
+const EOS: u16 = 256;
+
+/// The code length in bits of each symbol, with the end-of-string symbol last.
+///
+/// The code is canonical, so the codes are assigned in order of length, then of symbol, and
+/// are fully determined by their lengths.
+#[rustfmt::skip]
+const LENGTHS: [u8; 257] = [
+    13, 23, 28, 28, 28, 28, 28, 28, 28, 24, 30, 28, 28, 30, 28, 28,
+    28, 28, 28, 28, 28, 28, 30, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+    6, 10, 10, 12, 13, 6, 8, 11, 10, 10, 8, 11, 8, 6, 6, 6,
+    5, 5, 5, 6, 6, 6, 6, 6, 6, 6, 7, 8, 15, 6, 12, 10,
+    13, 6, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7, 7,
+    7, 7, 7, 7, 7, 7, 7, 7, 8, 7, 8, 13, 19, 13, 14, 6,
+    15, 5, 6, 5, 6, 5, 6, 6, 6, 5, 7, 7, 6, 6, 6, 5,
+    6, 7, 6, 5, 5, 6, 7, 7, 7, 7, 7, 15, 11, 14, 13, 28,
+    20, 22, 20, 20, 22, 22, 22, 23, 22, 23, 23, 23, 23, 23, 24, 23,
+    24, 24, 22, 23, 24, 23, 23, 23, 23, 21, 22, 23, 22, 23, 23, 24,
+    22, 21, 20, 22, 22, 23, 23, 21, 23, 22, 22, 24, 21, 22, 23, 23,
+    21, 21, 22, 21, 23, 22, 23, 23, 20, 22, 22, 22, 23, 22, 22, 23,
+    26, 26, 20, 19, 22, 23, 22, 25, 26, 26, 26, 27, 27, 26, 24, 25,
+    19, 21, 26, 27, 27, 26, 27, 24, 21, 21, 26, 26, 28, 27, 27, 27,
+    20, 24, 20, 21, 22, 21, 21, 23, 22, 22, 25, 25, 24, 24, 26, 23,
+    26, 27, 26, 26, 27, 27, 27, 27, 27, 28, 27, 27, 27, 27, 27, 26,
+    30,
+];
+
+/// The longest code length.
+const MAX_LEN: usize = 30;
+
+/// The tables of canonical decoding.
+struct Table {
+    /// The first code of each length.
+    first: [u32; MAX_LEN + 1],
+    /// The number of codes of each length.
+    count: [u32; MAX_LEN + 1],
+    /// The position in `symbols` of the first symbol of each length.
+    offset: [u16; MAX_LEN + 1],
+    /// The symbols, in order of code.
+    symbols: Vec<u16>,
+}
+
+fn table() -> &'static Table {
+    static TABLE: OnceLock<Table> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut symbols: Vec<u16> = (0..=EOS).collect();
+        symbols.sort_by_key(|sym| (LENGTHS[*sym as usize], *sym));
+
+        let mut table = Table {
+            first: [0; MAX_LEN + 1],
+            count: [0; MAX_LEN + 1],
+            offset: [0; MAX_LEN + 1],
+            symbols,
+        };
+        for len in LENGTHS {
+            table.count[len as usize] += 1;
+        }
+
+        let (mut code, mut offset) = (0, 0);
+        for len in 1..=MAX_LEN {
+            code <<= 1;
+            table.first[len] = code;
+            table.offset[len] = offset;
+            code += table.count[len];
+            offset += table.count[len] as u16;
+        }
+
+        table
+    })
+}
+
+/// Decodes Huffman-encoded data.
+///
+/// # Errors
+///
+/// Returns an error if the data contains the end-of-string symbol, or if its padding is
+/// longer than 7 bits or is not the most significant bits of the end-of-string code.
+pub(crate) fn decode(data: &[u8]) -> Result<Vec<u8>, ParseError> {
+    let table = table();
+
+    let mut decoded = Vec::with_capacity(data.len() * 8 / 5);
+    let (mut code, mut len) = (0u32, 0usize);
+    for bit in data
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1))
+    {
+        code = (code << 1) | bit as u32;
+        len += 1;
+
+        let index = code.wrapping_sub(table.first[len]);
+        if index < table.count[len] {
+            let sym = table.symbols[table.offset[len] as usize + index as usize];
+            if sym == EOS {
+                return Err(ParseError::new("Huffman-encoded string contains EOS"));
+            }
+            decoded.push(sym as u8);
+            (code, len) = (0, 0);
+        } else if len == MAX_LEN {
+            unreachable!("code is complete");
+        }
+    }
+
+    // The padding is a prefix of the EOS code, which is all ones.
+    if len > 7 || code != (1 << len) - 1 {
+        return Err(ParseError::new("invalid Huffman padding"));
+    }
+
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        // RFC 7541, appendix C.4.1 and C.6.1.
+        for (encoded, decoded) in [
+            (
+                b"\xf1\xe3\xc2\xe5\xf2\x3a\x6b\xa0\xab\x90\xf4\xff".as_slice(),
+                b"www.example.com".as_slice(),
+            ),
+            (b"\xa8\xeb\x10\x64\x9c\xbf", b"no-cache"),
+            (b"\x64\x02", b"302"),
+            (b"\xae\xc3\x77\x1a\x4b", b"private"),
+            (b"", b""),
+        ] {
+            assert_eq!(decode(encoded).unwrap(), decoded);
+        }
+
+        // The padding must be at most 7 bits of ones.
+        assert!(decode(b"\xa8\xeb\x10\x64\x9c\xbe").is_err());
+        assert!(decode(b"\xff").is_err());
+        // The EOS symbol.
+        assert!(decode(b"\xff\xff\xff\xff").is_err());
+    }
+}
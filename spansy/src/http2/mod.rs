@@ -0,0 +1,19 @@
+//! HTTP/2 span parsing.
+//!
+//! A [`Connection`] is parsed from the data sent and received by the client, starting with the
+//! connection preface. Its frames are split into streams, whose header blocks are decompressed
+//! with HPACK into [`Request`]s and [`Response`]s.
+//!
+//! As HPACK may encode a header field by reference to a table, the positions of a
+//! [`HeaderField`] map back to the bytes which encode it rather than to a verbatim copy.
+
+mod connection;
+mod frame;
+mod hpack;
+mod huffman;
+mod types;
+
+pub use connection::{Connection, PREFACE};
+pub use frame::{Frame, FrameKind, Setting, ACK, END_HEADERS, END_STREAM, PADDED, PRIORITY};
+pub use hpack::{HeaderField, Representation};
+pub use types::{Request, Response};
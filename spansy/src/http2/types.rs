@@ -0,0 +1,101 @@
+use utils::range::{RangeSet, ToRangeSet};
+
+use crate::{http2::HeaderField, MultiSpan};
+
+/// Returns the first field with the given name (case-insensitive).
+fn field<'a>(fields: &'a [HeaderField], name: &str) -> Option<&'a HeaderField> {
+    fields
+        .iter()
+        .find(|field| field.name.as_bytes().eq_ignore_ascii_case(name.as_bytes()))
+}
+
+/// An HTTP/2 request, sent on a stream opened by the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Request {
+    pub(crate) indices: RangeSet<usize>,
+
+    /// The identifier of the stream of the request.
+    pub stream_id: u32,
+    /// The header fields, including the pseudo-header fields such as `:method`, in order.
+    pub headers: Vec<HeaderField>,
+    /// The data of the `DATA` frames of the stream, in order, if any.
+    pub body: Option<MultiSpan>,
+    /// The trailer fields, which follow the body.
+    pub trailers: Vec<HeaderField>,
+    /// Whether the stream was ended by the client, i.e. the request is complete.
+    pub complete: bool,
+}
+
+impl Request {
+    /// Returns the first header field with the given name (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&HeaderField> {
+        field(&self.headers, name)
+    }
+
+    /// Returns the `:method` pseudo-header field.
+    pub fn method(&self) -> Option<&HeaderField> {
+        self.header(":method")
+    }
+
+    /// Returns the `:path` pseudo-header field.
+    pub fn path(&self) -> Option<&HeaderField> {
+        self.header(":path")
+    }
+
+    /// Returns the `:authority` pseudo-header field.
+    pub fn authority(&self) -> Option<&HeaderField> {
+        self.header(":authority")
+    }
+
+    /// Returns the `:scheme` pseudo-header field.
+    pub fn scheme(&self) -> Option<&HeaderField> {
+        self.header(":scheme")
+    }
+}
+
+impl ToRangeSet<usize> for Request {
+    /// Returns the indices of the frames of the request.
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.indices.clone()
+    }
+}
+
+/// An HTTP/2 response, received on the stream of its request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Response {
+    pub(crate) indices: RangeSet<usize>,
+
+    /// The identifier of the stream of the response.
+    pub stream_id: u32,
+    /// The header fields of the interim (1xx) responses which precede the response, in order.
+    pub interim: Vec<Vec<HeaderField>>,
+    /// The header fields, including the `:status` pseudo-header field, in order.
+    pub headers: Vec<HeaderField>,
+    /// The data of the `DATA` frames of the stream, in order, if any.
+    pub body: Option<MultiSpan>,
+    /// The trailer fields, which follow the body.
+    pub trailers: Vec<HeaderField>,
+    /// Whether the stream was ended by the server, i.e. the response is complete.
+    pub complete: bool,
+}
+
+impl Response {
+    /// Returns the first header field with the given name (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&HeaderField> {
+        field(&self.headers, name)
+    }
+
+    /// Returns the `:status` pseudo-header field.
+    pub fn status(&self) -> Option<&HeaderField> {
+        self.header(":status")
+    }
+}
+
+impl ToRangeSet<usize> for Response {
+    /// Returns the indices of the frames of the response.
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.indices.clone()
+    }
+}
@@ -0,0 +1,314 @@
+use std::{borrow::Cow, ops::Range};
+
+use crate::{
+    json::{JsonKey, String},
+    multi::MultiSpanBuilder,
+    MultiSpan, ParseError, Span,
+};
+
+/// Options for comparing JSON string values.
+///
+/// By default, strings are compared exactly after decoding escape sequences.
+///
+/// # Example
+///
+/// ```
+/// use spansy::json::{parse_str, JsonValue, StringComparison};
+///
+/// let value = parse_str("{\"email\": \"Alice@Example.com\"}").unwrap();
+/// let JsonValue::String(email) = value.get("email").unwrap() else {
+///     panic!("expected string");
+/// };
+///
+/// let comparison = StringComparison::new().case_insensitive(true);
+/// let span = comparison.matches(email, "alice@example.com").unwrap();
+///
+/// assert_eq!(span, "Alice@Example.com");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StringComparison {
+    case_insensitive: bool,
+    #[cfg(feature = "unicode")]
+    nfc: bool,
+}
+
+impl StringComparison {
+    /// Returns a new comparison which compares strings exactly.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether strings are compared case-insensitively.
+    ///
+    /// With the `unicode` feature, strings are compared with canonical caseless matching: they
+    /// are decomposed to Normalization Form D, case folded with the full Unicode case folding,
+    /// and recomposed to Normalization Form C. For example, `STRASSE` matches `straße`.
+    ///
+    /// Without the `unicode` feature, strings are only compared after converting them to
+    /// lowercase, which is not a case folding and does not match `STRASSE` with `straße`.
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Sets whether strings are normalized to Unicode Normalization Form C before they
+    /// are compared.
+    ///
+    /// This allows, for example, `é` written as a single code point to match `e` followed by
+    /// a combining acute accent.
+    #[cfg(feature = "unicode")]
+    pub fn nfc(mut self, nfc: bool) -> Self {
+        self.nfc = nfc;
+        self
+    }
+
+    /// Compares a JSON string value with the expected string.
+    ///
+    /// Returns the span of the value in the source if it matches, or `None` if it does not
+    /// match or contains an invalid escape sequence.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The JSON string value.
+    /// * `expected` - The expected string.
+    pub fn matches<'a>(&self, value: &'a String, expected: &str) -> Option<&'a Span<str>> {
+        let actual = value.unescape().ok()?;
+
+        (self.canonicalize(&actual) == self.canonicalize(expected)).then_some(&value.0)
+    }
+
+    fn canonicalize<'a>(&self, s: &'a str) -> Cow<'a, str> {
+        let mut s = Cow::Borrowed(s);
+
+        #[cfg(feature = "unicode")]
+        {
+            use caseless::Caseless;
+            use unicode_normalization::UnicodeNormalization;
+
+            if self.case_insensitive {
+                s = Cow::Owned(s.nfd().default_case_fold().nfc().collect());
+            } else if self.nfc {
+                s = Cow::Owned(s.nfc().collect());
+            }
+        }
+
+        #[cfg(not(feature = "unicode"))]
+        if self.case_insensitive {
+            s = Cow::Owned(s.to_lowercase());
+        }
+
+        s
+    }
+}
+
+impl String {
+    /// Returns the string with its escape sequences decoded.
+    ///
+    /// Returns an error if the string contains an invalid escape sequence.
+    pub fn unescape(&self) -> Result<Cow<'_, str>, ParseError> {
+        unescape(self.0.as_str())
+    }
+
+    /// Returns the string with its escape sequences decoded, as a span of the source.
+    ///
+    /// Each escape sequence is a decoded segment of the span, so positions in the decoded
+    /// string can be mapped to the indices of the source.
+    ///
+    /// Returns an error if the string contains an invalid escape sequence.
+    pub fn unescaped_span(&self) -> Result<MultiSpan, ParseError> {
+        let span = self.0.as_byte_span();
+        let mut builder = MultiSpanBuilder::default();
+        decode(self.0.as_str(), |range, c| match c {
+            Some(c) => builder.push_decoded(
+                span.indices_of(range),
+                c.encode_utf8(&mut [0; 4]).as_bytes(),
+            ),
+            None => builder.push_span(&span.slice(range)),
+        })?;
+
+        Ok(builder.build())
+    }
+}
+
+impl JsonKey {
+    /// Returns the key with its escape sequences decoded.
+    ///
+    /// Returns an error if the key contains an invalid escape sequence.
+    pub fn unescape(&self) -> Result<Cow<'_, str>, ParseError> {
+        unescape(self.0.as_str())
+    }
+}
+
+/// Decodes the escape sequences of a JSON string.
+fn unescape(s: &str) -> Result<Cow<'_, str>, ParseError> {
+    if !s.contains('\\') {
+        return Ok(Cow::Borrowed(s));
+    }
+
+    let mut out = std::string::String::with_capacity(s.len());
+    decode(s, |range, c| match c {
+        Some(c) => out.push(c),
+        None => out.push_str(&s[range]),
+    })?;
+
+    Ok(Cow::Owned(out))
+}
+
+/// Decodes a JSON string, calling `f` with the range of each run of literal characters and
+/// with the range and the decoded character of each escape sequence, in order.
+fn decode(s: &str, mut f: impl FnMut(Range<usize>, Option<char>)) -> Result<(), ParseError> {
+    let invalid = || ParseError::new(format!("invalid escape sequence in JSON string: {s:?}"));
+
+    let mut pos = 0;
+    while let Some(len) = s[pos..].find('\\') {
+        let start = pos + len;
+        if len > 0 {
+            f(pos..start, None);
+        }
+
+        let (c, end) = match s.as_bytes().get(start + 1).ok_or_else(invalid)? {
+            b'"' => ('"', start + 2),
+            b'\\' => ('\\', start + 2),
+            b'/' => ('/', start + 2),
+            b'b' => ('\u{8}', start + 2),
+            b'f' => ('\u{c}', start + 2),
+            b'n' => ('\n', start + 2),
+            b'r' => ('\r', start + 2),
+            b't' => ('\t', start + 2),
+            b'u' => {
+                let high = parse_hex4(s, start + 2).ok_or_else(invalid)?;
+                let (code, end) = if (0xD800..0xDC00).contains(&high) {
+                    // A high surrogate must be followed by an escaped low surrogate.
+                    if s.get(start + 6..start + 8) != Some("\\u") {
+                        return Err(invalid());
+                    }
+                    let low = parse_hex4(s, start + 8).ok_or_else(invalid)?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(invalid());
+                    }
+                    (
+                        0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00),
+                        start + 12,
+                    )
+                } else {
+                    (high, start + 6)
+                };
+                (char::from_u32(code).ok_or_else(invalid)?, end)
+            }
+            _ => return Err(invalid()),
+        };
+        f(start..end, Some(c));
+        pos = end;
+    }
+
+    if pos < s.len() {
+        f(pos..s.len(), None);
+    }
+
+    Ok(())
+}
+
+/// Parses the 4 hexadecimal digits at `pos`.
+fn parse_hex4(s: &str, pos: usize) -> Option<u32> {
+    let hex = s.get(pos..pos + 4)?;
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    u32::from_str_radix(hex, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use utils::range::RangeSet;
+
+    use crate::json::{parse_str, JsonValue};
+
+    use super::*;
+
+    fn string(src: &str) -> String {
+        match parse_str(&format!("[{src}]")).unwrap() {
+            JsonValue::Array(array) => match &array.elems[0] {
+                JsonValue::String(s) => s.clone(),
+                _ => panic!("expected string"),
+            },
+            _ => panic!("expected array"),
+        }
+    }
+
+    #[test]
+    fn test_unescape() {
+        assert_eq!(string("\"plain\"").unescape().unwrap(), "plain");
+        assert_eq!(
+            string(r#""a\"b\\c\/d\n\u00e9\ud83d\ude00""#)
+                .unescape()
+                .unwrap(),
+            "a\"b\\c/d\n\u{e9}\u{1f600}"
+        );
+        assert!(unescape(r"\ud83d").is_err());
+        assert!(unescape(r"\u12").is_err());
+        assert!(unescape(r"\u+123").is_err());
+        assert!(unescape("\\").is_err());
+    }
+
+    #[test]
+    fn test_unescaped_span() {
+        // The string starts at index 2 of the source `["a\n\u00e9b"]`.
+        let span = string(r#""a\n\u00e9b""#).unescaped_span().unwrap();
+
+        assert_eq!(span.as_bytes(), "a\n\u{e9}b".as_bytes());
+        assert_eq!(span.len_segments(), 4);
+        assert_eq!(span.indices_of(0..1), RangeSet::from(2..3));
+        assert_eq!(span.indices_of(1..2), RangeSet::from(3..5));
+        assert_eq!(span.indices_of(2..3), RangeSet::from(5..11));
+        assert_eq!(span.indices_of(4..5), RangeSet::from(11..12));
+        assert_eq!(span.to_span(), None);
+
+        let span = string("\"plain\"").unescaped_span().unwrap();
+        assert_eq!(span.to_span().unwrap(), b"plain".as_slice());
+    }
+
+    #[test]
+    fn test_string_comparison() {
+        let value = string(r#""Jürgen""#);
+
+        let exact = StringComparison::new();
+        assert_eq!(exact.matches(&value, "Jürgen").unwrap().indices(), 2..9);
+        assert!(exact.matches(&value, "jürgen").is_none());
+
+        let case_insensitive = StringComparison::new().case_insensitive(true);
+        assert!(case_insensitive.matches(&value, "JÜRGEN").is_some());
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_string_comparison_nfc() {
+        let value = string("\"Ju\u{308}rgen\"");
+
+        assert!(StringComparison::new().matches(&value, "Jürgen").is_none());
+        assert!(StringComparison::new()
+            .nfc(true)
+            .matches(&value, "Jürgen")
+            .is_some());
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn test_string_comparison_case_folding() {
+        let case_insensitive = StringComparison::new().case_insensitive(true);
+
+        assert!(case_insensitive
+            .matches(&string("\"STRASSE\""), "straße")
+            .is_some());
+        // The final sigma folds to the same letter as the medial sigma.
+        assert!(case_insensitive
+            .matches(&string("\"ΣΑΣ\""), "σασ")
+            .is_some());
+        assert!(case_insensitive
+            .matches(&string("\"Ju\u{308}RGEN\""), "jürgen")
+            .is_some());
+        assert!(case_insensitive
+            .matches(&string("\"STRASSE\""), "strase")
+            .is_none());
+    }
+}
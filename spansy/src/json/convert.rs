@@ -0,0 +1,83 @@
+use serde_json::{Map, Value};
+
+use super::types::JsonValue;
+
+use crate::{ParseError, Spanned};
+
+impl JsonValue {
+    /// Returns the value as an owned [`serde_json::Value`].
+    ///
+    /// The value is converted as `serde_json` would parse its source, so application logic can
+    /// use the converted value while the spans of this value are used to select the ranges to
+    /// reveal, without parsing the source twice. As with `serde_json`, the last member of an
+    /// object with a repeated key is kept.
+    ///
+    /// Returns an error if a string contains an invalid escape sequence, or if a number is out
+    /// of the range of `serde_json`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spansy::json::parse_str;
+    ///
+    /// let value = parse_str(r#"{"name": "café", "balance": [42, 1.5]}"#).unwrap();
+    ///
+    /// assert_eq!(
+    ///     value.to_serde().unwrap(),
+    ///     serde_json::json!({"name": "café", "balance": [42, 1.5]})
+    /// );
+    /// ```
+    pub fn to_serde(&self) -> Result<Value, ParseError> {
+        Ok(match self {
+            JsonValue::Null(_) => Value::Null,
+            JsonValue::Bool(v) => Value::Bool(v.span().as_str() == "true"),
+            JsonValue::Number(v) => {
+                let number = v.span().as_str();
+                serde_json::from_str(number).map_err(|err| {
+                    let range = v.span().indices().min().unwrap_or_default()
+                        ..v.span().indices().end().unwrap_or_default();
+                    ParseError::with_source(format!("invalid number: {number}"), err)
+                        .with_label(range, "number out of range")
+                })?
+            }
+            JsonValue::String(v) => Value::String(v.unescape()?.into_owned()),
+            JsonValue::Array(v) => Value::Array(
+                v.elems
+                    .iter()
+                    .map(JsonValue::to_serde)
+                    .collect::<Result<_, _>>()?,
+            ),
+            JsonValue::Object(v) => {
+                let mut map = Map::new();
+                for kv in &v.elems {
+                    map.insert(kv.key.unescape()?.into_owned(), kv.value.to_serde()?);
+                }
+                Value::Object(map)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::parse_str;
+
+    use super::*;
+
+    #[test]
+    fn test_to_serde() {
+        let src = r#"{"a": [null, true, false, -0, 1e2, 18446744073709551615, -12],
+            "b": {"c\n": "😀 \"x\""}, "a": "last", "d": []}"#;
+
+        let value = parse_str(src).unwrap();
+        assert_eq!(
+            value.to_serde().unwrap(),
+            serde_json::from_str::<Value>(src).unwrap()
+        );
+        assert_eq!(value.to_serde().unwrap()["a"], "last");
+
+        let value = parse_str("[1, 1e400]").unwrap();
+        let err = value.to_serde().unwrap_err();
+        assert_eq!(err.ranges().collect::<Vec<_>>(), [&(4..9)]);
+    }
+}
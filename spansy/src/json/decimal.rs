@@ -0,0 +1,280 @@
+use std::{cmp::Ordering, fmt};
+
+/// Exponents with a greater magnitude are displayed in scientific notation.
+const MAX_PLAIN_EXPONENT: i64 = 64;
+
+/// An arbitrary precision decimal number, parsed losslessly from a JSON number.
+///
+/// The number is normalized, so equal numbers compare equal regardless of how they are
+/// written, e.g. `1.50`, `15e-1` and `0.15E1` are all equal.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Decimal {
+    negative: bool,
+    /// The significant digits, without leading or trailing zeros.
+    digits: std::string::String,
+    exponent: i64,
+}
+
+impl Decimal {
+    /// Parses a decimal from the text of a JSON number.
+    ///
+    /// Returns `None` if the text is not a JSON number, or if its exponent overflows.
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+
+        let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => {
+                let digits = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+                if !is_digits(digits) {
+                    return None;
+                }
+                (mantissa, exponent.parse::<i64>().ok()?)
+            }
+            None => (s, 0),
+        };
+
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        if !is_digits(int) || (mantissa.contains('.') && !is_digits(frac)) {
+            return None;
+        }
+
+        let digits = format!("{int}{frac}");
+        let digits = digits.trim_start_matches('0');
+        if digits.is_empty() {
+            return Some(Self {
+                negative: false,
+                digits: "0".to_string(),
+                exponent: 0,
+            });
+        }
+
+        let significant = digits.trim_end_matches('0');
+        let trailing = digits.len() - significant.len();
+        let exponent = exponent
+            .checked_sub(i64::try_from(frac.len()).ok()?)?
+            .checked_add(i64::try_from(trailing).ok()?)?;
+
+        Some(Self {
+            negative,
+            digits: significant.to_string(),
+            exponent,
+        })
+    }
+
+    /// Returns `true` if the number is negative.
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// Returns the significant digits of the number, without leading or trailing zeros.
+    ///
+    /// The digits of zero are `"0"`.
+    pub fn digits(&self) -> &str {
+        &self.digits
+    }
+
+    /// Returns the exponent of the number.
+    ///
+    /// The value of the number is `digits * 10^exponent`.
+    pub fn exponent(&self) -> i64 {
+        self.exponent
+    }
+
+    /// Returns `true` if the number is an integer.
+    pub fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+
+    /// Returns the number as an `i128`, if it is an integer within range.
+    pub fn to_i128(&self) -> Option<i128> {
+        let magnitude = i128::try_from(self.to_u128_magnitude()?).ok();
+
+        match (self.negative, magnitude) {
+            (false, magnitude) => magnitude,
+            // The magnitude of `i128::MIN` does not fit in an `i128`.
+            (true, None) if self.to_u128_magnitude()? == i128::MIN.unsigned_abs() => {
+                Some(i128::MIN)
+            }
+            (true, magnitude) => magnitude.map(|magnitude| -magnitude),
+        }
+    }
+
+    /// Returns the number as a `u128`, if it is a non-negative integer within range.
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.negative {
+            return None;
+        }
+
+        self.to_u128_magnitude()
+    }
+
+    fn to_u128_magnitude(&self) -> Option<u128> {
+        if !self.is_integer() {
+            return None;
+        }
+
+        let mut value = self.digits.parse::<u128>().ok()?;
+        for _ in 0..self.exponent {
+            value = value.checked_mul(10)?;
+        }
+
+        Some(value)
+    }
+
+    /// Compares the absolute values of two numbers.
+    fn cmp_magnitude(&self, other: &Self) -> Ordering {
+        match (self.digits == "0", other.digits == "0") {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => {
+                // The position of the most significant digit, then the digits themselves, as
+                // trailing zeros are trimmed.
+                let magnitude = |d: &Self| d.digits.len() as i128 + d.exponent as i128;
+                magnitude(self)
+                    .cmp(&magnitude(other))
+                    .then_with(|| self.digits.cmp(&other.digits))
+            }
+        }
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.cmp_magnitude(other),
+            (true, true) => other.cmp_magnitude(self),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    /// Formats the number in plain decimal notation, or in scientific notation if the
+    /// exponent is very large or very small.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+
+        let len = self.digits.len() as i64;
+        if !(-MAX_PLAIN_EXPONENT..=MAX_PLAIN_EXPONENT).contains(&self.exponent) {
+            let (first, rest) = self.digits.split_at(1);
+            // The exponent of the first digit may not fit in an `i64`.
+            let exponent = i128::from(self.exponent) + i128::from(len) - 1;
+            return match rest {
+                "" => write!(f, "{first}e{exponent}"),
+                rest => write!(f, "{first}.{rest}e{exponent}"),
+            };
+        }
+
+        if self.exponent >= 0 {
+            write!(f, "{}{}", self.digits, "0".repeat(self.exponent as usize))
+        } else if -self.exponent < len {
+            let (int, frac) = self.digits.split_at((len + self.exponent) as usize);
+            write!(f, "{int}.{frac}")
+        } else {
+            let zeros = "0".repeat((-self.exponent - len) as usize);
+            write!(f, "0.{zeros}{}", self.digits)
+        }
+    }
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decimal(s: &str) -> Decimal {
+        Decimal::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_decimal_normalized() {
+        assert_eq!(decimal("1.50"), decimal("15e-1"));
+        assert_eq!(decimal("1.50"), decimal("0.15E1"));
+        assert_eq!(decimal("-0"), decimal("0.000"));
+        assert_eq!(decimal("1200").digits(), "12");
+        assert_eq!(decimal("1200").exponent(), 2);
+    }
+
+    #[test]
+    fn test_decimal_big_integer() {
+        let wei = decimal("115792089237316195423570985008687907853269984665640564039457");
+
+        assert!(wei.is_integer());
+        assert_eq!(wei.to_u128(), None);
+        assert_eq!(
+            wei.to_string(),
+            "115792089237316195423570985008687907853269984665640564039457"
+        );
+
+        assert_eq!(
+            decimal("170141183460469231731687303715884105727").to_i128(),
+            Some(i128::MAX)
+        );
+        assert_eq!(
+            decimal("-170141183460469231731687303715884105728").to_i128(),
+            Some(i128::MIN)
+        );
+        assert_eq!(decimal("1e3").to_u128(), Some(1000));
+        assert_eq!(decimal("-1").to_u128(), None);
+        assert_eq!(decimal("1.5").to_i128(), None);
+    }
+
+    #[test]
+    fn test_decimal_ord() {
+        let mut numbers: Vec<_> = ["10", "-2.5", "0", "9.99", "1e1", "-10", "0.001", "-0.01"]
+            .into_iter()
+            .map(decimal)
+            .collect();
+        numbers.sort();
+
+        let sorted: Vec<_> = numbers.iter().map(Decimal::to_string).collect();
+        assert_eq!(
+            sorted,
+            ["-10", "-2.5", "-0.01", "0", "0.001", "9.99", "10", "10"]
+        );
+        assert!(decimal("123") < decimal("1234"));
+        assert!(decimal("1.23") > decimal("1.2"));
+    }
+
+    #[test]
+    fn test_decimal_display() {
+        assert_eq!(decimal("-12.340").to_string(), "-12.34");
+        assert_eq!(decimal("0.0012").to_string(), "0.0012");
+        assert_eq!(decimal("1e100").to_string(), "1e100");
+        assert_eq!(decimal("-1.25e-100").to_string(), "-1.25e-100");
+        assert_eq!(
+            decimal("1e-9223372036854775808").to_string(),
+            "1e-9223372036854775808"
+        );
+        assert_eq!(
+            decimal("12e9223372036854775807").to_string(),
+            "1.2e9223372036854775808"
+        );
+    }
+
+    #[test]
+    fn test_decimal_invalid() {
+        assert!(Decimal::parse("").is_none());
+        assert!(Decimal::parse("1.").is_none());
+        assert!(Decimal::parse("1e").is_none());
+        assert!(Decimal::parse("XX").is_none());
+        assert!(Decimal::parse("1e99999999999999999999").is_none());
+    }
+}
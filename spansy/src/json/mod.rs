@@ -25,10 +25,25 @@
 //! assert_eq!(bar.span().indices(), 16..24);
 //! ```
 
+mod compare;
+#[cfg(feature = "serde_json")]
+mod convert;
+mod decimal;
+mod query;
+#[cfg(feature = "schema")]
+mod schema;
 mod span;
 mod types;
 mod visit;
 
+pub use compare::StringComparison;
+pub use decimal::Decimal;
+#[cfg(feature = "schema")]
+pub use schema::{
+    MappedField, Schema, SchemaMapping, SchemaViolation, SchemaViolationKind, UnexpectedField,
+};
 pub use span::{parse, parse_slice, parse_str};
-pub use types::{Array, Bool, JsonKey, JsonValue, KeyValue, Null, Number, Object, String};
+pub use types::{
+    Array, Bool, JsonKey, JsonKind, JsonValue, KeyValue, Null, Number, Object, String,
+};
 pub use visit::JsonVisit;
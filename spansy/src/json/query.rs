@@ -0,0 +1,257 @@
+use super::types::JsonValue;
+
+use crate::ParseError;
+
+/// A step of a query path.
+#[derive(Debug, PartialEq, Eq)]
+struct Segment {
+    /// Whether the selector applies to all descendants, i.e. `..`, rather than to the children.
+    recursive: bool,
+    selector: Selector,
+}
+
+/// Selects children of a value.
+#[derive(Debug, PartialEq, Eq)]
+enum Selector {
+    /// The member of an object with the given key.
+    Name(String),
+    /// The element of an array at the given index, counted from the end if negative.
+    Index(i64),
+    /// All members of an object or elements of an array.
+    Wildcard,
+}
+
+impl JsonValue {
+    /// Returns the values matching a JSONPath query, in document order.
+    ///
+    /// A subset of JSONPath (RFC 9535) is supported:
+    ///
+    /// - `$` is the value itself, and may be omitted, e.g. `a.b`.
+    /// - `.name` and `['name']` select the member of an object with the given key. Keys are
+    ///   compared with their escape sequences decoded.
+    /// - `[2]` selects the element of an array at an index, and `[-1]` the last element.
+    /// - `.*` and `[*]` select all members of an object or elements of an array.
+    /// - `..` applies the following selector to the value and all of its descendants, e.g.
+    ///   `$..id` selects every member with the key `id`.
+    ///
+    /// Returns an error if the query is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spansy::json::parse_str;
+    /// use spansy::Spanned;
+    ///
+    /// let src = r#"{"accounts": [{"id": 1, "owner": {"id": 7}}, {"id": 2}]}"#;
+    /// let value = parse_str(src).unwrap();
+    ///
+    /// let ids = value.query("$.accounts[*].id").unwrap();
+    /// assert_eq!(ids.iter().map(|id| id.span().as_str()).collect::<Vec<_>>(), ["1", "2"]);
+    ///
+    /// let ids = value.query("$..id").unwrap();
+    /// assert_eq!(ids.len(), 3);
+    ///
+    /// let last = value.query("$.accounts[-1].id").unwrap();
+    /// assert_eq!(last[0].span().indices(), 52..53);
+    /// ```
+    pub fn query(&self, path: &str) -> Result<Vec<&JsonValue>, ParseError> {
+        let mut values = vec![self];
+        for segment in parse_query(path)? {
+            let candidates = if segment.recursive {
+                values.into_iter().flat_map(descendants).collect()
+            } else {
+                values
+            };
+
+            values = candidates
+                .into_iter()
+                .flat_map(|value| select(value, &segment.selector))
+                .collect();
+        }
+
+        Ok(values)
+    }
+}
+
+/// Returns the value and all of its descendants, in document order.
+fn descendants(value: &JsonValue) -> Vec<&JsonValue> {
+    let mut values = vec![value];
+    for child in select(value, &Selector::Wildcard) {
+        values.extend(descendants(child));
+    }
+    values
+}
+
+/// Returns the children of a value selected by a selector.
+fn select<'a>(value: &'a JsonValue, selector: &Selector) -> Vec<&'a JsonValue> {
+    match (value, selector) {
+        (JsonValue::Object(object), Selector::Name(name)) => object
+            .elems
+            .iter()
+            .filter(|kv| kv.key.unescape().is_ok_and(|key| key == name.as_str()))
+            .map(|kv| &kv.value)
+            .collect(),
+        (JsonValue::Object(object), Selector::Wildcard) => {
+            object.elems.iter().map(|kv| &kv.value).collect()
+        }
+        (JsonValue::Array(array), Selector::Index(index)) => {
+            let index = if *index < 0 {
+                array.elems.len().checked_sub(index.unsigned_abs() as usize)
+            } else {
+                Some(*index as usize)
+            };
+            index
+                .and_then(|index| array.elems.get(index))
+                .into_iter()
+                .collect()
+        }
+        (JsonValue::Array(array), Selector::Wildcard) => array.elems.iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Parses a query into its segments.
+fn parse_query(path: &str) -> Result<Vec<Segment>, ParseError> {
+    let invalid = |pos: usize, label: &str| {
+        ParseError::new(format!("invalid JSONPath query: {path:?}")).with_label(pos..pos, label)
+    };
+
+    // Without the leading `$`, the path may start with a name, e.g. `a.b`.
+    let bare_name = !path.starts_with(['$', '.', '[']);
+
+    let mut segments = Vec::new();
+    let mut pos = usize::from(path.starts_with('$'));
+    while pos < path.len() {
+        let rest = &path[pos..];
+        let (recursive, selector_start) = if pos == 0 && bare_name {
+            (false, 0)
+        } else if rest.starts_with("..") {
+            (true, pos + 2)
+        } else if rest.starts_with('.') {
+            (false, pos + 1)
+        } else if rest.starts_with('[') {
+            (false, pos)
+        } else {
+            return Err(invalid(pos, "expected `.` or `[`"));
+        };
+
+        let rest = &path[selector_start..];
+        let (selector, len) = if let Some(bracketed) = rest.strip_prefix('[') {
+            let end =
+                bracketed_end(bracketed).ok_or_else(|| invalid(path.len(), "expected `]`"))?;
+            let selector = parse_bracketed(bracketed[..end].trim())
+                .ok_or_else(|| invalid(selector_start + 1, "invalid selector"))?;
+            (selector, end + 2)
+        } else if rest.starts_with('*') {
+            (Selector::Wildcard, 1)
+        } else {
+            let len = rest.find(['.', '[']).unwrap_or(rest.len());
+            if len == 0 {
+                return Err(invalid(selector_start, "expected a name"));
+            }
+            (Selector::Name(rest[..len].to_string()), len)
+        };
+
+        segments.push(Segment {
+            recursive,
+            selector,
+        });
+        pos = selector_start + len;
+    }
+
+    Ok(segments)
+}
+
+/// Returns the position of the `]` closing a bracketed selector, skipping a quoted name which
+/// may itself contain `]`.
+fn bracketed_end(bracketed: &str) -> Option<usize> {
+    let selector = bracketed.trim_start();
+    let start = bracketed.len() - selector.len();
+    let name_end = match selector.chars().next() {
+        Some(quote @ ('\'' | '"')) => start + 1 + selector[1..].find(quote)? + 1,
+        _ => start,
+    };
+
+    bracketed[name_end..].find(']').map(|end| name_end + end)
+}
+
+/// Parses the selector within brackets.
+fn parse_bracketed(selector: &str) -> Option<Selector> {
+    if selector == "*" {
+        return Some(Selector::Wildcard);
+    }
+
+    for quote in ['\'', '"'] {
+        if let Some(name) = selector
+            .strip_prefix(quote)
+            .and_then(|selector| selector.strip_suffix(quote))
+        {
+            return Some(Selector::Name(name.to_string()));
+        }
+    }
+
+    selector.parse().ok().map(Selector::Index)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{json::parse_str, Spanned};
+
+    use super::*;
+
+    fn query<'a>(value: &'a JsonValue, path: &str) -> Vec<&'a str> {
+        value
+            .query(path)
+            .unwrap()
+            .into_iter()
+            .map(|value| value.span().as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_query() {
+        let value = parse_str(
+            r#"{"a": {"b": [10, 11, {"c": "d"}]}, "a.b": 1, "e": [{"c": "f"}], "gh": 2}"#,
+        )
+        .unwrap();
+
+        assert_eq!(query(&value, "$"), [value.span().as_str()]);
+        assert_eq!(query(&value, "$.a.b[2].c"), ["d"]);
+        assert_eq!(query(&value, "a.b[-3]"), ["10"]);
+        assert_eq!(query(&value, "$['a.b']"), ["1"]);
+        assert_eq!(query(&value, "$[\"gh\"]"), ["2"]);
+        assert_eq!(query(&value, "$.a.b[*]").len(), 3);
+        assert_eq!(query(&value, "$.*").len(), 4);
+        assert_eq!(query(&value, "$..c"), ["d", "f"]);
+        assert_eq!(query(&value, "$..[0]"), ["10", "{\"c\": \"f\"}"]);
+        assert_eq!(query(&value, "$..*").len(), 11);
+
+        assert!(query(&value, "$.a.b[3]").is_empty());
+        assert!(query(&value, "$.a.b[-4]").is_empty());
+        assert!(query(&value, "$.missing.b").is_empty());
+        assert!(query(&value, "$.a[0]").is_empty());
+    }
+
+    #[test]
+    fn test_query_quoted_name() {
+        let value = parse_str(r#"{"a]b": 1, "c.d": {"e[0]": 2}}"#).unwrap();
+
+        assert_eq!(query(&value, "$['a]b']"), ["1"]);
+        assert_eq!(query(&value, "$[ \"c.d\" ]['e[0]']"), ["2"]);
+        assert_eq!(query(&value, "$..['e[0]']"), ["2"]);
+    }
+
+    #[test]
+    fn test_query_invalid() {
+        let value = parse_str("{}").unwrap();
+
+        for path in [
+            "$a", "$.", "$..", "$[0", "$[x]", "$.a[]", "$.a..", "$['a]", "$['a'x]",
+        ] {
+            assert!(value.query(path).is_err(), "{path:?}");
+        }
+
+        let err = value.query("$.a[x]").unwrap_err();
+        assert_eq!(err.ranges().collect::<Vec<_>>(), [&(4..4)]);
+    }
+}
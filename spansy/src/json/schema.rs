@@ -0,0 +1,573 @@
+//! Mapping of JSON values against a JSON Schema.
+
+use std::borrow::Cow;
+
+use utils::range::RangeSet;
+
+use crate::{
+    json::{parse_str, Decimal, JsonValue, KeyValue, Object},
+    ParseError, Spanned,
+};
+
+/// The maximum number of `$ref`s followed to resolve a schema, which bounds cyclic references.
+const MAX_REF_DEPTH: usize = 32;
+
+/// A JSON Schema, such as the response schema of an OpenAPI document.
+///
+/// The following keywords are used to map values: `$ref` (within the same document),
+/// `title`, `properties`, `additionalProperties` and `items`. Values are validated against
+/// the `type`, `required`, `minimum`, `maximum`, `exclusiveMinimum` and `exclusiveMaximum`
+/// keywords.
+///
+/// # Example
+///
+/// ```
+/// use spansy::json::{parse_str, Schema};
+///
+/// let schema = Schema::parse(
+///     r#"{"type": "object", "properties": {
+///         "balance": {"title": "Account balance", "type": "number"}
+///     }}"#,
+/// )
+/// .unwrap();
+///
+/// let value = parse_str(r#"{"balance": 1250.5, "internal": true}"#).unwrap();
+/// let mapping = schema.map(&value);
+///
+/// let balance = mapping.with_title("Account balance").next().unwrap();
+/// assert_eq!(balance.value, "1250.5");
+/// assert_eq!(mapping.unexpected[0].path, "internal");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    doc: JsonValue,
+    pointer: std::string::String,
+}
+
+impl Schema {
+    /// Parses a JSON Schema.
+    pub fn parse(src: &str) -> Result<Self, ParseError> {
+        Self::parse_at(src, "")
+    }
+
+    /// Parses the schema at a JSON pointer (RFC 6901) in a document.
+    ///
+    /// References are resolved against the whole document, so this can be used for the
+    /// schema of a response in an OpenAPI document, e.g.
+    /// `/paths/~1accounts/get/responses/200/content/application~1json/schema`.
+    pub fn parse_at(src: &str, pointer: &str) -> Result<Self, ParseError> {
+        let doc = parse_str(src)?;
+        let schema = Self {
+            doc,
+            pointer: pointer.to_string(),
+        };
+        match schema.lookup(pointer) {
+            Some(JsonValue::Object(_)) => Ok(schema),
+            Some(_) => Err(ParseError::new(format!(
+                "schema at {pointer:?} is not an object"
+            ))),
+            None => Err(ParseError::new(format!("no schema at {pointer:?}"))),
+        }
+    }
+
+    /// Maps a value against the schema, validating it.
+    pub fn map(&self, value: &JsonValue) -> SchemaMapping {
+        let mut mapping = SchemaMapping::default();
+        let root = self.lookup(&self.pointer).expect("schema is present");
+        self.map_value(root, value, "", "", &mut mapping);
+
+        mapping
+    }
+
+    fn map_value(
+        &self,
+        schema: &JsonValue,
+        value: &JsonValue,
+        path: &str,
+        pointer: &str,
+        mapping: &mut SchemaMapping,
+    ) {
+        let Some(schema) = self.resolve(schema) else {
+            return;
+        };
+
+        mapping.fields.push(MappedField {
+            path: path.to_string(),
+            pointer: pointer.to_string(),
+            title: keyword_str(schema, "title").map(Cow::into_owned),
+            value: value.clone(),
+        });
+        validate(schema, value, path, pointer, &mut mapping.violations);
+
+        match value {
+            JsonValue::Object(object) => {
+                let properties = match member(schema, "properties") {
+                    Some(JsonValue::Object(properties)) => Some(properties),
+                    _ => None,
+                };
+                let additional = member(schema, "additionalProperties");
+
+                for kv in &object.elems {
+                    let key = kv.key.0.as_str();
+                    let child_path = join(path, key);
+                    let child_pointer = format!("{pointer}/{}", escape_pointer(key));
+
+                    match properties
+                        .and_then(|properties| member(properties, key))
+                        .or(additional
+                            .filter(|additional| matches!(additional, JsonValue::Object(_))))
+                    {
+                        Some(child) => {
+                            self.map_value(child, &kv.value, &child_path, &child_pointer, mapping)
+                        }
+                        None if additional.is_some_and(|additional| additional == "true") => {}
+                        None => mapping.unexpected.push(UnexpectedField {
+                            path: child_path,
+                            pointer: child_pointer,
+                            field: kv.clone(),
+                        }),
+                    }
+                }
+            }
+            JsonValue::Array(array) => {
+                if let Some(items) = member(schema, "items") {
+                    let child_path = format!("{path}[]");
+                    for (i, elem) in array.elems.iter().enumerate() {
+                        self.map_value(
+                            items,
+                            elem,
+                            &child_path,
+                            &format!("{pointer}/{i}"),
+                            mapping,
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Resolves the references of a schema, returning the schema object.
+    fn resolve<'a>(&'a self, mut schema: &'a JsonValue) -> Option<&'a Object> {
+        for _ in 0..MAX_REF_DEPTH {
+            let JsonValue::Object(object) = schema else {
+                return None;
+            };
+            match keyword_str(object, "$ref") {
+                Some(reference) => schema = self.lookup(reference.strip_prefix('#')?)?,
+                None => return Some(object),
+            }
+        }
+
+        None
+    }
+
+    /// Returns the value at a JSON pointer in the document.
+    fn lookup(&self, pointer: &str) -> Option<&JsonValue> {
+        self.doc.pointer(pointer)
+    }
+}
+
+/// The result of mapping a value against a [`Schema`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SchemaMapping {
+    /// The values described by the schema, in document order.
+    pub fields: Vec<MappedField>,
+    /// The object members which are not described by the schema.
+    pub unexpected: Vec<UnexpectedField>,
+    /// The values which violate the schema, in document order.
+    pub violations: Vec<SchemaViolation>,
+}
+
+impl SchemaMapping {
+    /// Returns an iterator of the values at the given schema path, such as
+    /// `accounts[].balance`.
+    pub fn get<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a MappedField> {
+        self.fields.iter().filter(move |field| field.path == path)
+    }
+
+    /// Returns an iterator of the values whose schema has the given title.
+    pub fn with_title<'a>(&'a self, title: &'a str) -> impl Iterator<Item = &'a MappedField> {
+        self.fields
+            .iter()
+            .filter(move |field| field.title.as_deref() == Some(title))
+    }
+
+    /// Returns `true` if the value conforms to the schema.
+    ///
+    /// Unexpected members do not make a value invalid.
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// A value described by a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MappedField {
+    /// The schema path of the value, with `.` separating object members and `[]` denoting
+    /// array items, e.g. `accounts[].balance`. The root value has an empty path.
+    pub path: std::string::String,
+    /// The JSON pointer of the value, e.g. `/accounts/0/balance`.
+    pub pointer: std::string::String,
+    /// The title of the schema, if present.
+    pub title: Option<std::string::String>,
+    /// The value.
+    pub value: JsonValue,
+}
+
+/// An object member which is not described by a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UnexpectedField {
+    /// The schema path of the member.
+    pub path: std::string::String,
+    /// The JSON pointer of the member.
+    pub pointer: std::string::String,
+    /// The member.
+    pub field: KeyValue,
+}
+
+/// A value which violates a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SchemaViolation {
+    /// The schema path of the value.
+    pub path: std::string::String,
+    /// The JSON pointer of the value.
+    pub pointer: std::string::String,
+    /// The indices of the offending value.
+    ///
+    /// For a missing required member, these are the indices of the enclosing object.
+    pub indices: RangeSet<usize>,
+    /// The kind of violation.
+    pub kind: SchemaViolationKind,
+}
+
+/// The kind of a [`SchemaViolation`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SchemaViolationKind {
+    /// The value is not of any of the types allowed by the `type` keyword.
+    Type {
+        /// The allowed types, such as `string` or `integer`.
+        expected: Vec<std::string::String>,
+    },
+    /// A member listed by the `required` keyword is missing.
+    MissingRequired {
+        /// The key of the missing member.
+        key: std::string::String,
+    },
+    /// The number is less than the `minimum` or `exclusiveMinimum` keyword.
+    BelowMinimum {
+        /// The minimum.
+        limit: Decimal,
+        /// Whether the minimum is exclusive.
+        exclusive: bool,
+    },
+    /// The number is greater than the `maximum` or `exclusiveMaximum` keyword.
+    AboveMaximum {
+        /// The maximum.
+        limit: Decimal,
+        /// Whether the maximum is exclusive.
+        exclusive: bool,
+    },
+}
+
+/// Validates a value against the keywords of a schema, excluding those of its members and
+/// items.
+fn validate(
+    schema: &Object,
+    value: &JsonValue,
+    path: &str,
+    pointer: &str,
+    violations: &mut Vec<SchemaViolation>,
+) {
+    let mut violation = |kind| {
+        violations.push(SchemaViolation {
+            path: path.to_string(),
+            pointer: pointer.to_string(),
+            indices: value.span().indices().clone(),
+            kind,
+        })
+    };
+
+    let expected: Vec<_> = match member(schema, "type") {
+        Some(JsonValue::String(ty)) => ty.unescape().ok().into_iter().collect(),
+        Some(JsonValue::Array(types)) => types
+            .elems
+            .iter()
+            .filter_map(|ty| match ty {
+                JsonValue::String(ty) => ty.unescape().ok(),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    if !expected.is_empty() && !expected.iter().any(|ty| is_type(value, ty)) {
+        violation(SchemaViolationKind::Type {
+            expected: expected.into_iter().map(Cow::into_owned).collect(),
+        });
+    }
+
+    match value {
+        JsonValue::Object(object) => {
+            let Some(JsonValue::Array(required)) = member(schema, "required") else {
+                return;
+            };
+            for key in &required.elems {
+                let JsonValue::String(key) = key else {
+                    continue;
+                };
+                let Ok(key) = key.unescape() else {
+                    continue;
+                };
+                if member(object, &key).is_none() {
+                    violation(SchemaViolationKind::MissingRequired {
+                        key: key.into_owned(),
+                    });
+                }
+            }
+        }
+        JsonValue::Number(number) => {
+            let Some(number) = number.to_decimal() else {
+                return;
+            };
+            let limit = |keyword| match member(schema, keyword) {
+                Some(JsonValue::Number(limit)) => limit.to_decimal(),
+                _ => None,
+            };
+
+            if let Some(limit) = limit("minimum").filter(|limit| number < *limit) {
+                violation(SchemaViolationKind::BelowMinimum {
+                    limit,
+                    exclusive: false,
+                });
+            }
+            if let Some(limit) = limit("exclusiveMinimum").filter(|limit| number <= *limit) {
+                violation(SchemaViolationKind::BelowMinimum {
+                    limit,
+                    exclusive: true,
+                });
+            }
+            if let Some(limit) = limit("maximum").filter(|limit| number > *limit) {
+                violation(SchemaViolationKind::AboveMaximum {
+                    limit,
+                    exclusive: false,
+                });
+            }
+            if let Some(limit) = limit("exclusiveMaximum").filter(|limit| number >= *limit) {
+                violation(SchemaViolationKind::AboveMaximum {
+                    limit,
+                    exclusive: true,
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns `true` if a value is of a JSON Schema type.
+fn is_type(value: &JsonValue, ty: &str) -> bool {
+    match (ty, value) {
+        ("integer", JsonValue::Number(number)) => number
+            .to_decimal()
+            .is_some_and(|number| number.is_integer()),
+        _ => value.kind().as_str() == ty,
+    }
+}
+
+/// Returns the value of an object member.
+///
+/// Unlike [`Object::get`], the key is not split into a path.
+fn member<'a>(object: &'a Object, key: &str) -> Option<&'a JsonValue> {
+    object
+        .elems
+        .iter()
+        .find(|kv| kv.key.0.as_str() == key)
+        .map(|kv| &kv.value)
+}
+
+/// Returns the value of a string keyword of a schema, with its escape sequences decoded.
+fn keyword_str<'a>(schema: &'a Object, keyword: &str) -> Option<Cow<'a, str>> {
+    match member(schema, keyword)? {
+        JsonValue::String(value) => value.unescape().ok(),
+        _ => None,
+    }
+}
+
+fn join(path: &str, key: &str) -> std::string::String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+fn escape_pointer(token: &str) -> Cow<'_, str> {
+    if token.contains(['~', '/']) {
+        Cow::Owned(token.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::Borrowed(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r##"{
+        "title": "Accounts",
+        "type": "object",
+        "properties": {
+            "owner": {"type": "string"},
+            "accounts": {"type": "array", "items": {"$ref": "#/$defs/account"}},
+            "meta": {"type": "object", "additionalProperties": true}
+        },
+        "$defs": {
+            "account": {
+                "type": "object",
+                "properties": {
+                    "iban": {"title": "IBAN", "type": "string"},
+                    "balance": {"title": "Balance", "type": "number"}
+                }
+            }
+        }
+    }"##;
+
+    const VALUE: &str = r#"{"owner": "Alice", "accounts": [
+        {"iban": "DE89370400440532013000", "balance": 12.5},
+        {"iban": "GB29NWBK60161331926819", "balance": -3, "pin": "1234"}
+    ], "meta": {"x": 1}, "debug": true}"#;
+
+    #[test]
+    fn test_schema_map() {
+        let schema = Schema::parse(SCHEMA).unwrap();
+        let mapping = schema.map(&parse_str(VALUE).unwrap());
+
+        let root = &mapping.fields[0];
+        assert_eq!(root.path, "");
+        assert_eq!(root.title.as_deref(), Some("Accounts"));
+
+        let balances: Vec<_> = mapping.get("accounts[].balance").collect();
+        assert_eq!(balances.len(), 2);
+        assert_eq!(balances[1].pointer, "/accounts/1/balance");
+        assert_eq!(balances[1].value, "-3");
+
+        let ibans: Vec<_> = mapping
+            .with_title("IBAN")
+            .map(|field| &field.value)
+            .collect();
+        assert_eq!(ibans, ["DE89370400440532013000", "GB29NWBK60161331926819"]);
+
+        let unexpected: Vec<_> = mapping
+            .unexpected
+            .iter()
+            .map(|field| field.pointer.as_str())
+            .collect();
+        assert_eq!(unexpected, ["/accounts/1/pin", "/debug"]);
+        assert!(mapping.is_valid());
+    }
+
+    #[test]
+    fn test_schema_validate() {
+        let schema = Schema::parse(
+            r#"{"type": "object", "required": ["id", "amount"], "properties": {
+                "id": {"type": "integer", "minimum": 1},
+                "amount": {"type": "number", "exclusiveMinimum": 0, "maximum": 1e6},
+                "note": {"type": ["string", "null"]},
+                "items": {"type": "array", "items": {"type": "object", "required": ["sku"]}}
+            }}"#,
+        )
+        .unwrap();
+
+        let src = r#"{"id": 1.5, "amount": 1000000.01, "note": 3, "items": [{"sku": "a"}, {}]}"#;
+        let mapping = schema.map(&parse_str(src).unwrap());
+
+        let violations: Vec<_> = mapping
+            .violations
+            .iter()
+            .map(|violation| {
+                let range = violation.indices.min().unwrap()..violation.indices.end().unwrap();
+                (violation.pointer.as_str(), &src[range], &violation.kind)
+            })
+            .collect();
+        assert_eq!(
+            violations,
+            [
+                (
+                    "/id",
+                    "1.5",
+                    &SchemaViolationKind::Type {
+                        expected: vec!["integer".to_string()]
+                    }
+                ),
+                (
+                    "/amount",
+                    "1000000.01",
+                    &SchemaViolationKind::AboveMaximum {
+                        limit: Decimal::parse("1e6").unwrap(),
+                        exclusive: false
+                    }
+                ),
+                (
+                    "/note",
+                    "3",
+                    &SchemaViolationKind::Type {
+                        expected: vec!["string".to_string(), "null".to_string()]
+                    }
+                ),
+                (
+                    "/items/1",
+                    "{}",
+                    &SchemaViolationKind::MissingRequired {
+                        key: "sku".to_string()
+                    }
+                ),
+            ]
+        );
+        assert!(!mapping.is_valid());
+
+        let mapping = schema.map(&parse_str(r#"{"id": 1, "amount": 0}"#).unwrap());
+        assert_eq!(
+            mapping.violations[0].kind,
+            SchemaViolationKind::BelowMinimum {
+                limit: Decimal::parse("0").unwrap(),
+                exclusive: true
+            }
+        );
+        assert_eq!(mapping.violations.len(), 1);
+    }
+
+    #[test]
+    fn test_schema_parse_at() {
+        let openapi = r##"{"paths": {"/accounts": {"get": {"responses": {"200": {"content":
+            {"application/json": {"schema": {"$ref": "#/components/schemas/Account"}}}}}}}},
+            "components": {"schemas": {"Account": {"properties": {"id": {"title": "ID"}}}}}}"##;
+        let schema = Schema::parse_at(
+            openapi,
+            "/paths/~1accounts/get/responses/200/content/application~1json/schema",
+        )
+        .unwrap();
+        let mapping = schema.map(&parse_str(r#"{"id": 7}"#).unwrap());
+
+        assert_eq!(mapping.with_title("ID").next().unwrap().value, "7");
+        assert!(mapping.unexpected.is_empty());
+
+        assert!(Schema::parse_at(openapi, "/paths/missing").is_err());
+        assert!(
+            Schema::parse_at(openapi, "/paths/~1accounts/get/responses/200/content/x").is_err()
+        );
+    }
+
+    #[test]
+    fn test_schema_cyclic_ref() {
+        let schema =
+            Schema::parse(r##"{"$ref": "#/$defs/a", "$defs": {"a": {"$ref": "#/$defs/a"}}}"##)
+                .unwrap();
+        let mapping = schema.map(&parse_str("1").unwrap());
+
+        assert!(mapping.fields.is_empty());
+    }
+}
@@ -1,10 +1,9 @@
-use bytes::Bytes;
 use pest::{iterators::Pair as PestPair, Parser};
 use types::KeyValue;
 
 use super::types::{self, JsonValue};
 
-use crate::{ParseError, Span};
+use crate::{ParseError, Source, Span};
 
 #[derive(pest_derive::Parser)]
 #[grammar = "json/json.pest"]
@@ -12,7 +11,7 @@ struct JsonParser;
 
 /// Parse a JSON value from a source string.
 pub fn parse_str(src: &str) -> Result<JsonValue, ParseError> {
-    let src = Bytes::copy_from_slice(src.as_bytes());
+    let src = Source::copy_from_slice(src.as_bytes());
 
     // # Safety
     // `src` was passed as a string slice, so it is guaranteed to be valid UTF-8.
@@ -20,48 +19,55 @@ pub fn parse_str(src: &str) -> Result<JsonValue, ParseError> {
 
     let value = JsonParser::parse(Rule::value, src_str)?
         .next()
-        .ok_or_else(|| ParseError("no json value is present in source".to_string()))?;
+        .ok_or_else(|| ParseError::new("no json value is present in source".to_string()))?;
 
     // Since json.pest grammar prohibits leading characters but allows trailing
     // characters, we prohibit trailing characters here.
     if value.as_str().len() != src.len() {
-        return Err(ParseError(
-            "trailing characters are present in source".to_string(),
-        ));
+        return Err(
+            ParseError::new("trailing characters are present in source".to_string())
+                .with_label(value.as_str().len()..src.len(), "trailing characters"),
+        );
     }
 
-    Ok(JsonValue::from_pair(src.clone(), value))
+    Ok(JsonValue::from_pair(&src, value))
 }
 
 /// Parse a JSON value from a byte slice.
 pub fn parse_slice(src: &[u8]) -> Result<JsonValue, ParseError> {
-    let src = Bytes::copy_from_slice(src);
-    parse(src)
+    parse(Source::copy_from_slice(src))
 }
 
 /// Parse a JSON value from source bytes.
-pub fn parse(src: Bytes) -> Result<JsonValue, ParseError> {
-    let src_str = std::str::from_utf8(&src)?;
+pub fn parse(src: impl Into<Source>) -> Result<JsonValue, ParseError> {
+    let src = src.into();
+    let src_str = std::str::from_utf8(&src).map_err(|err| {
+        let start = err.valid_up_to();
+        let end = err.error_len().map_or(src.len(), |len| start + len);
+
+        ParseError::from(err).with_label(start..end, "invalid UTF-8")
+    })?;
 
     let value = JsonParser::parse(Rule::value, src_str)?
         .next()
-        .ok_or_else(|| ParseError("no json value is present in source".to_string()))?;
+        .ok_or_else(|| ParseError::new("no json value is present in source".to_string()))?;
 
     // Since json.pest grammar prohibits leading characters but allows trailing
     // characters, we prohibit trailing characters here.
     if value.as_str().len() != src.len() {
-        return Err(ParseError(
-            "trailing characters are present in source".to_string(),
-        ));
+        return Err(
+            ParseError::new("trailing characters are present in source".to_string())
+                .with_label(value.as_str().len()..src.len(), "trailing characters"),
+        );
     }
 
-    Ok(JsonValue::from_pair(src.clone(), value))
+    Ok(JsonValue::from_pair(&src, value))
 }
 
 macro_rules! impl_from_pair {
     ($ty:ty, $rule:ident) => {
         impl $ty {
-            fn from_pair(src: Bytes, pair: PestPair<'_, Rule>) -> Self {
+            fn from_pair(src: &Source, pair: PestPair<'_, Rule>) -> Self {
                 assert!(matches!(pair.as_rule(), Rule::$rule));
 
                 Self(Span::new_from_str(src, pair.as_str()))
@@ -76,11 +82,18 @@ impl_from_pair!(types::Bool, bool);
 impl_from_pair!(types::Null, null);
 impl_from_pair!(types::String, string);
 
+/// Returns the string within a quoted string.
+fn unquote(pair: PestPair<'_, Rule>) -> PestPair<'_, Rule> {
+    assert!(matches!(pair.as_rule(), Rule::quoted_string));
+
+    pair.into_inner().next().expect("string is present")
+}
+
 impl types::KeyValue {
-    fn from_pair(src: Bytes, pair: PestPair<'_, Rule>) -> Self {
+    fn from_pair(src: &Source, pair: PestPair<'_, Rule>) -> Self {
         assert!(matches!(pair.as_rule(), Rule::pair));
 
-        let span = Span::new_from_str(src.clone(), pair.as_str().trim_end());
+        let span = Span::new_from_str(src, pair.as_str().trim_end());
 
         let mut pairs = pair.into_inner();
 
@@ -89,46 +102,46 @@ impl types::KeyValue {
 
         Self {
             span,
-            key: types::JsonKey::from_pair(src.clone(), key),
-            value: types::JsonValue::from_pair(src.clone(), value),
+            key: types::JsonKey::from_pair(src, unquote(key)),
+            value: types::JsonValue::from_pair(src, value),
         }
     }
 }
 
 impl types::Object {
-    fn from_pair(src: Bytes, pair: PestPair<'_, Rule>) -> Self {
+    fn from_pair(src: &Source, pair: PestPair<'_, Rule>) -> Self {
         assert!(matches!(pair.as_rule(), Rule::object));
 
         Self {
-            span: Span::new_from_str(src.clone(), pair.as_str()),
+            span: Span::new_from_str(src, pair.as_str()),
             elems: pair
                 .into_inner()
-                .map(|pair| KeyValue::from_pair(src.clone(), pair))
+                .map(|pair| KeyValue::from_pair(src, pair))
                 .collect(),
         }
     }
 }
 
 impl types::Array {
-    fn from_pair(src: Bytes, pair: PestPair<'_, Rule>) -> Self {
+    fn from_pair(src: &Source, pair: PestPair<'_, Rule>) -> Self {
         assert!(matches!(pair.as_rule(), Rule::array));
 
         Self {
-            span: Span::new_from_str(src.clone(), pair.as_str()),
+            span: Span::new_from_str(src, pair.as_str()),
             elems: pair
                 .into_inner()
-                .map(|pair| types::JsonValue::from_pair(src.clone(), pair))
+                .map(|pair| types::JsonValue::from_pair(src, pair))
                 .collect(),
         }
     }
 }
 
 impl types::JsonValue {
-    fn from_pair(src: Bytes, pair: PestPair<'_, Rule>) -> Self {
+    fn from_pair(src: &Source, pair: PestPair<'_, Rule>) -> Self {
         match pair.as_rule() {
             Rule::object => Self::Object(types::Object::from_pair(src, pair)),
             Rule::array => Self::Array(types::Array::from_pair(src, pair)),
-            Rule::string => Self::String(types::String::from_pair(src, pair)),
+            Rule::quoted_string => Self::String(types::String::from_pair(src, unquote(pair))),
             Rule::number => Self::Number(types::Number::from_pair(src, pair)),
             Rule::bool => Self::Bool(types::Bool::from_pair(src, pair)),
             Rule::null => Self::Null(types::Null::from_pair(src, pair)),
@@ -154,6 +167,20 @@ mod tests {
         assert_eq!(value.get("arr").unwrap().span(), "[1, 2, 3]");
     }
 
+    #[test]
+    fn test_string_whitespace() {
+        let src = r#"{ " a": " b ", "c":"  "}"#;
+
+        let value = parse_str(src).unwrap();
+        let JsonValue::Object(obj) = &value else {
+            panic!("value is an object");
+        };
+
+        assert_eq!(obj.elems[0].key.span(), " a");
+        assert_eq!(obj.elems[0].value.span(), " b ");
+        assert_eq!(value.get("c").unwrap().span(), "  ");
+    }
+
     #[test]
     fn test_err_leading_characters() {
         let src = " {\"foo\": \"bar\"}";
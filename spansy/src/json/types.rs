@@ -1,8 +1,12 @@
-use std::ops::{Index, Range};
+use std::{
+    borrow::Cow,
+    ops::{Index, Range},
+};
 
+use bytes::Bytes;
 use utils::range::{Difference, RangeSet, ToRangeSet};
 
-use crate::{Span, Spanned};
+use crate::{json::Decimal, offset::impl_offset, MultiSpan, Offset, ParseError, Span, Spanned};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -22,7 +26,84 @@ pub enum JsonValue {
     Object(Object),
 }
 
+/// The kind of a [`JsonValue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JsonKind {
+    /// A null value.
+    Null,
+    /// A boolean value.
+    Bool,
+    /// A number value.
+    Number,
+    /// A string value.
+    String,
+    /// An array value.
+    Array,
+    /// An object value.
+    Object,
+}
+
+impl JsonKind {
+    /// Returns the name of the kind as a JSON Schema type, such as `boolean` or `object`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JsonKind::Null => "null",
+            JsonKind::Bool => "boolean",
+            JsonKind::Number => "number",
+            JsonKind::String => "string",
+            JsonKind::Array => "array",
+            JsonKind::Object => "object",
+        }
+    }
+}
+
+impl std::fmt::Display for JsonKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 impl JsonValue {
+    /// Returns the kind of the value.
+    ///
+    /// The kind is known from the structure of the value, so it can be claimed without
+    /// revealing the content of the value.
+    pub fn kind(&self) -> JsonKind {
+        match self {
+            JsonValue::Null(_) => JsonKind::Null,
+            JsonValue::Bool(_) => JsonKind::Bool,
+            JsonValue::Number(_) => JsonKind::Number,
+            JsonValue::String(_) => JsonKind::String,
+            JsonValue::Array(_) => JsonKind::Array,
+            JsonValue::Object(_) => JsonKind::Object,
+        }
+    }
+
+    /// Returns the number of elements of an array or members of an object, or `None` if the
+    /// value is neither.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spansy::json::{parse_str, JsonKind};
+    ///
+    /// let value = parse_str(r#"{"residents": ["a", "b", "c"], "name": "Tatooine"}"#).unwrap();
+    ///
+    /// let residents = value.get("residents").unwrap();
+    /// assert_eq!(residents.kind(), JsonKind::Array);
+    /// assert_eq!(residents.child_count(), Some(3));
+    /// assert_eq!(value.child_count(), Some(2));
+    /// assert_eq!(value.get("name").unwrap().child_count(), None);
+    /// ```
+    pub fn child_count(&self) -> Option<usize> {
+        match self {
+            JsonValue::Array(v) => Some(v.len()),
+            JsonValue::Object(v) => Some(v.len()),
+            _ => None,
+        }
+    }
+
     /// Returns the span corresponding to the value.
     pub fn into_span(self) -> Span<str> {
         match self {
@@ -34,28 +115,85 @@ impl JsonValue {
             JsonValue::Object(v) => v.span,
         }
     }
+}
 
-    /// Shifts the span range by the given offset.
-    pub fn offset(&mut self, offset: usize) {
+impl Offset for JsonValue {
+    fn offset(&mut self, offset: usize) {
         match self {
-            JsonValue::Null(v) => v.0.offset(offset),
-            JsonValue::Bool(v) => v.0.offset(offset),
-            JsonValue::Number(v) => v.0.offset(offset),
-            JsonValue::String(v) => v.0.offset(offset),
+            JsonValue::Null(v) => v.offset(offset),
+            JsonValue::Bool(v) => v.offset(offset),
+            JsonValue::Number(v) => v.offset(offset),
+            JsonValue::String(v) => v.offset(offset),
+            JsonValue::Array(v) => v.offset(offset),
+            JsonValue::Object(v) => v.offset(offset),
+        }
+    }
+}
+
+impl JsonValue {
+    /// Returns a copy of the value whose spans view the bytes of `src` at the same indices.
+    ///
+    /// This can be used to view the value in a modified copy of the source, such as a
+    /// masked transcript, without changing the original.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The new source, which must contain the indices of the value.
+    pub fn with_data(&self, src: &Bytes) -> Result<Self, ParseError> {
+        let mut value = self.clone();
+        value.set_data(src)?;
+
+        Ok(value)
+    }
+
+    /// Maps the spans from positions in the data of `base` to the indices of `base`.
+    pub(crate) fn rebase(&mut self, base: &MultiSpan) {
+        match self {
+            JsonValue::Null(v) => v.0.rebase(base),
+            JsonValue::Bool(v) => v.0.rebase(base),
+            JsonValue::Number(v) => v.0.rebase(base),
+            JsonValue::String(v) => v.0.rebase(base),
             JsonValue::Array(v) => {
-                v.span.offset(offset);
-                v.elems.iter_mut().for_each(|v| v.offset(offset))
+                v.span.rebase(base);
+                for elem in &mut v.elems {
+                    elem.rebase(base);
+                }
             }
             JsonValue::Object(v) => {
-                v.span.offset(offset);
-                v.elems.iter_mut().for_each(|kv| {
-                    kv.span.offset(offset);
-                    kv.key.offset(offset);
-                    kv.value.offset(offset);
-                })
+                v.span.rebase(base);
+                for kv in &mut v.elems {
+                    kv.span.rebase(base);
+                    kv.key.0.rebase(base);
+                    kv.value.rebase(base);
+                }
             }
         }
     }
+
+    pub(crate) fn set_data(&mut self, src: &Bytes) -> Result<(), ParseError> {
+        match self {
+            JsonValue::Null(v) => v.0 = v.0.with_data(src)?,
+            JsonValue::Bool(v) => v.0 = v.0.with_data(src)?,
+            JsonValue::Number(v) => v.0 = v.0.with_data(src)?,
+            JsonValue::String(v) => v.0 = v.0.with_data(src)?,
+            JsonValue::Array(v) => {
+                v.span = v.span.with_data(src)?;
+                for elem in &mut v.elems {
+                    elem.set_data(src)?;
+                }
+            }
+            JsonValue::Object(v) => {
+                v.span = v.span.with_data(src)?;
+                for kv in &mut v.elems {
+                    kv.span = kv.span.with_data(src)?;
+                    kv.key.0 = kv.key.0.with_data(src)?;
+                    kv.value.set_data(src)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl JsonValue {
@@ -83,6 +221,60 @@ impl JsonValue {
             JsonValue::Object(v) => v.get(path),
         }
     }
+
+    /// Get a reference to the value at the given JSON pointer (RFC 6901).
+    ///
+    /// Unlike [`JsonValue::get`], the pointer can address keys which contain `.`, and
+    /// object keys are compared with their escape sequences decoded. Array indices must be
+    /// written without a sign or leading zeros, e.g. `/01` and `/+1` do not match any element.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spansy::json::parse_str;
+    /// use spansy::Spanned;
+    ///
+    /// let src = "{\"a.b\": {\"c/d\": [42, 14]}}";
+    ///
+    /// let value = parse_str(src).unwrap();
+    ///
+    /// assert_eq!(value.pointer("/a.b/c~1d/1").unwrap().span(), "14");
+    /// assert_eq!(value.pointer("").unwrap(), &value);
+    /// ```
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .try_fold(self, |value, token| {
+                let token = token.replace("~1", "/").replace("~0", "~");
+                match value {
+                    JsonValue::Object(object) => object
+                        .elems
+                        .iter()
+                        .find(|kv| kv.key.unescape().is_ok_and(|key| key == token))
+                        .map(|kv| &kv.value),
+                    JsonValue::Array(array) => array.elems.get(parse_array_index(&token)?),
+                    _ => None,
+                }
+            })
+    }
+}
+
+/// Parses an array index of a JSON pointer.
+///
+/// ```text
+/// array-index = %x30 / ( %x31-39 *(%x30-39) )
+/// ```
+fn parse_array_index(token: &str) -> Option<usize> {
+    if !token.bytes().all(|b| b.is_ascii_digit()) || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+
+    token.parse().ok()
 }
 
 impl Spanned<str> for JsonValue {
@@ -244,6 +436,16 @@ pub struct Array {
 }
 
 impl Array {
+    /// Returns the number of elements of the array.
+    pub fn len(&self) -> usize {
+        self.elems.len()
+    }
+
+    /// Returns `true` if the array has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.elems.is_empty()
+    }
+
     /// Get a reference to the value using the given path.
     pub fn get(&self, path: &str) -> Option<&JsonValue> {
         let mut path_iter = path.split('.');
@@ -300,6 +502,16 @@ pub struct Object {
 }
 
 impl Object {
+    /// Returns the number of key value pairs of the object.
+    pub fn len(&self) -> usize {
+        self.elems.len()
+    }
+
+    /// Returns `true` if the object has no key value pairs.
+    pub fn is_empty(&self) -> bool {
+        self.elems.is_empty()
+    }
+
     /// Get a reference to the value using the given path.
     pub fn get(&self, path: &str) -> Option<&JsonValue> {
         let mut path_iter = path.split('.');
@@ -315,6 +527,34 @@ impl Object {
         }
     }
 
+    /// Returns an iterator over the key value pairs of the object, in document order.
+    pub fn iter(&self) -> std::slice::Iter<'_, KeyValue> {
+        self.elems.iter()
+    }
+
+    /// Returns an iterator over the key value pairs of the object, sorted by key.
+    ///
+    /// Keys are compared by the bytes of their decoded UTF-8 text, so the order does not
+    /// depend on how the keys are escaped, on the platform or on the locale. The sort is
+    /// stable: pairs with equal keys are yielded in document order. Keys with invalid escape
+    /// sequences are compared by their raw text.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = &KeyValue> {
+        let mut elems: Vec<_> = self
+            .elems
+            .iter()
+            .map(|kv| {
+                let key = kv
+                    .key
+                    .unescape()
+                    .unwrap_or(Cow::Borrowed(kv.key.0.as_str()));
+                (key, kv)
+            })
+            .collect();
+        elems.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        elems.into_iter().map(|(_, kv)| kv)
+    }
+
     /// Returns the indices of the object, excluding the key value pairs.
     pub fn without_pairs(&self) -> RangeSet<usize> {
         let mut indices = self.span.indices.clone();
@@ -345,11 +585,6 @@ macro_rules! impl_type {
             pub fn into_span(self) -> Span<str> {
                 self.$span
             }
-
-            /// Shifts the span range by the given offset.
-            pub fn offset(&mut self, offset: usize) {
-                self.$span.offset(offset);
-            }
         }
 
         impl Spanned<str> for $ty {
@@ -432,6 +667,44 @@ macro_rules! impl_type {
     };
 }
 
+impl Number {
+    /// Returns the number exactly as it appears in the source.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Returns `true` if the number has no fraction or exponent.
+    pub fn is_integer(&self) -> bool {
+        !self.as_str().contains(['.', 'e', 'E'])
+    }
+
+    /// Returns the number as an `i64`, if it is an integer within range.
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_str().parse().ok().filter(|_| self.is_integer())
+    }
+
+    /// Returns the number as a `u64`, if it is a non-negative integer within range.
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_str().parse().ok().filter(|_| self.is_integer())
+    }
+
+    /// Returns the number as an `f64`.
+    ///
+    /// This conversion is lossy for numbers which can not be represented exactly, use
+    /// [`Number::to_decimal`] for lossless access.
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_str().parse().ok()
+    }
+
+    /// Returns the number as an arbitrary precision decimal.
+    ///
+    /// Returns `None` if the span is not a valid JSON number, which is only possible if its
+    /// data was replaced, or if its exponent overflows an `i64`.
+    pub fn to_decimal(&self) -> Option<Decimal> {
+        Decimal::parse(self.as_str())
+    }
+}
+
 impl_type!(JsonKey, 0);
 impl_type!(Null, 0);
 impl_type!(Bool, 0);
@@ -441,6 +714,15 @@ impl_type!(Array, span);
 impl_type!(Object, span);
 impl_type!(KeyValue, span);
 
+impl_offset!(JsonKey(_));
+impl_offset!(Null(_));
+impl_offset!(Bool(_));
+impl_offset!(Number(_));
+impl_offset!(String(_));
+impl_offset!(Array { span, elems });
+impl_offset!(Object { span, elems });
+impl_offset!(KeyValue { span, key, value });
+
 #[cfg(test)]
 mod tests {
     use utils::range::IndexRanges;
@@ -458,6 +740,44 @@ mod tests {
         assert_eq!(value.get("foo").unwrap(), "bar");
     }
 
+    #[test]
+    fn test_obj_iter_sorted() {
+        let src = r#"{"b": 1, "\u0061": 2, "B": 3, "é": 4, "a": 5, "aa": 6}"#;
+        let JsonValue::Object(obj) = parse_str(src).unwrap() else {
+            panic!("expected object");
+        };
+
+        let in_order: Vec<_> = obj.iter().map(|kv| kv.value.span().as_str()).collect();
+        assert_eq!(in_order, ["1", "2", "3", "4", "5", "6"]);
+
+        // Equal keys keep their document order, regardless of escaping.
+        let sorted: Vec<_> = obj
+            .iter_sorted()
+            .map(|kv| kv.value.span().as_str())
+            .collect();
+        assert_eq!(sorted, ["3", "2", "5", "6", "1", "4"]);
+    }
+
+    #[test]
+    fn test_kind_and_child_count() {
+        let src = r#"{"n": null, "b": true, "x": 1.5, "s": "", "a": [], "o": {"k": [0, 1]}}"#;
+        let value = parse_str(src).unwrap();
+
+        let kinds: Vec<_> = ["n", "b", "x", "s", "a", "o"]
+            .into_iter()
+            .map(|key| value.get(key).unwrap().kind().as_str())
+            .collect();
+        assert_eq!(
+            kinds,
+            ["null", "boolean", "number", "string", "array", "object"]
+        );
+
+        assert_eq!(value.child_count(), Some(6));
+        assert_eq!(value.get("a").unwrap().child_count(), Some(0));
+        assert_eq!(value.get("o.k").unwrap().child_count(), Some(2));
+        assert_eq!(value.get("s").unwrap().child_count(), None);
+    }
+
     #[test]
     fn test_array_index() {
         let src = "{\"foo\": [42, 14]}";
@@ -467,6 +787,17 @@ mod tests {
         assert_eq!(value.get("foo.1").unwrap(), "14");
     }
 
+    #[test]
+    fn test_pointer_array_index() {
+        let value = parse_str("[10, 11, 12]").unwrap();
+
+        assert_eq!(value.pointer("/0").unwrap(), "10");
+        assert_eq!(value.pointer("/2").unwrap(), "12");
+        for pointer in ["/01", "/+1", "/-1", "/ 1", "/", "/3", "/1a"] {
+            assert!(value.pointer(pointer).is_none(), "{pointer:?}");
+        }
+    }
+
     #[test]
     fn test_nested_index() {
         let src = "{\"foo\": {\"bar\": [42, 14]}}";
@@ -476,6 +807,65 @@ mod tests {
         assert_eq!(value.get("foo.bar.1").unwrap(), "14");
     }
 
+    #[test]
+    fn test_number_precision() {
+        let value = parse_str("[12345678901234567890123, -7, 0.1]").unwrap();
+        let JsonValue::Array(array) = value else {
+            panic!("expected array");
+        };
+        let numbers: Vec<_> = array
+            .elems
+            .iter()
+            .map(|elem| match elem {
+                JsonValue::Number(number) => number,
+                _ => panic!("expected number"),
+            })
+            .collect();
+
+        assert_eq!(numbers[0].as_u64(), None);
+        assert_eq!(
+            numbers[0].to_decimal().unwrap().to_string(),
+            "12345678901234567890123"
+        );
+        assert_eq!(numbers[0].span().indices(), 1..24);
+        assert_eq!(numbers[1].as_i64(), Some(-7));
+        assert_eq!(numbers[1].as_u64(), None);
+        assert_eq!(numbers[2].as_i64(), None);
+        assert_eq!(numbers[2].as_f64(), Some(0.1));
+        assert_eq!(numbers[2].to_decimal().unwrap().exponent(), -1);
+
+        // Exponents at the limits of an `i64`.
+        for (src, expected) in [
+            ("1e-9223372036854775808", "1e-9223372036854775808"),
+            ("12e9223372036854775807", "1.2e9223372036854775808"),
+        ] {
+            let JsonValue::Number(number) = parse_str(src).unwrap() else {
+                panic!("expected number");
+            };
+            assert_eq!(number.to_decimal().unwrap().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_with_data() {
+        let value = parse_str("{\"foo\": [42, 14]}").unwrap();
+        let masked = value
+            .with_data(&Bytes::from_static(b"{\"foo\": [XX, 14]}"))
+            .unwrap();
+
+        assert_eq!(masked.get("foo.0").unwrap(), "XX");
+        assert_eq!(
+            masked.get("foo.0").unwrap().span().indices(),
+            value.get("foo.0").unwrap().span().indices()
+        );
+        assert_eq!(value.get("foo.0").unwrap(), "42");
+
+        assert!(value.with_data(&Bytes::from_static(b"{}")).is_err());
+        assert!(value
+            .with_data(&Bytes::from_static(b"{\"foo\": [\xff\xff, 14]}"))
+            .is_err());
+    }
+
     #[test]
     fn test_key_value_without_value() {
         let src = "{\"foo\": \"bar\"\n}";
@@ -7,35 +7,195 @@ use std::{fmt::Debug, marker::PhantomData, ops::Range};
 
 use bytes::Bytes;
 
+pub mod annotate;
+mod budget;
+#[cfg(feature = "commit")]
+pub mod commit;
+pub mod framed;
 pub(crate) mod helpers;
 pub mod http;
+#[cfg(feature = "http2")]
+pub mod http2;
 pub mod json;
+mod multi;
+mod offset;
+mod source;
+#[cfg(feature = "testutil")]
+pub mod testutil;
+pub mod xml;
 
-use utils::range::{RangeSet, ToRangeSet};
+use utils::range::{RangeSet, ToRangeSet, Union};
+
+pub use budget::{BudgetExceeded, MemoryBudget};
+pub use multi::MultiSpan;
+pub use offset::Offset;
+pub use source::Source;
 
 /// A parsing error.
+///
+/// If the error was caused by another error, such as invalid UTF-8, the cause is returned by
+/// [`source`](std::error::Error::source).
+///
+/// With the `diagnostics` feature, the error implements [`miette::Diagnostic`], labeling the
+/// offending bytes of the source. Attach the source with `miette::Report::with_source_code` to
+/// render them.
 #[derive(Debug, thiserror::Error)]
-#[error("parsing error: {0}")]
-pub struct ParseError(String);
+#[error("parsing error: {msg}")]
+pub struct ParseError {
+    msg: String,
+    #[source]
+    source: Option<Box<dyn std::error::Error + Send + Sync + 'static>>,
+    /// The ranges of the offending bytes in the source, with a description of each.
+    labels: Vec<(Range<usize>, String)>,
+}
+
+impl ParseError {
+    pub(crate) fn new(msg: impl Into<String>) -> Self {
+        Self {
+            msg: msg.into(),
+            source: None,
+            labels: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_source(
+        msg: impl Into<String>,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            source: Some(Box::new(source)),
+            ..Self::new(msg)
+        }
+    }
+
+    /// Labels the offending bytes at `range` in the source.
+    pub(crate) fn with_label(mut self, range: Range<usize>, label: impl Into<String>) -> Self {
+        self.labels.push((range, label.into()));
+        self
+    }
+
+    /// Returns the ranges of the offending bytes in the source, if known.
+    pub fn ranges(&self) -> impl Iterator<Item = &Range<usize>> {
+        self.labels.iter().map(|(range, _)| range)
+    }
+
+    /// Returns the cause of the error if it was caused by exceeding a [`MemoryBudget`].
+    pub fn budget_exceeded(&self) -> Option<&BudgetExceeded> {
+        self.source.as_ref()?.downcast_ref()
+    }
+
+    /// Returns the cause of the error if it was caused by exceeding the
+    /// [`IteratorLimits`](http::IteratorLimits) of an iterator.
+    pub fn limit_exceeded(&self) -> Option<&http::LimitExceeded> {
+        self.source.as_ref()?.downcast_ref()
+    }
 
-impl<R: pest::RuleType> From<pest::error::Error<R>> for ParseError {
+    /// Returns the cause of the error if it was caused by an invalid chunk size line.
+    pub fn chunk_size_error(&self) -> Option<&http::ChunkSizeError> {
+        self.source.as_ref()?.downcast_ref()
+    }
+}
+
+impl<R: pest::RuleType + Send + Sync + 'static> From<pest::error::Error<R>> for ParseError {
     fn from(value: pest::error::Error<R>) -> Self {
-        Self(value.to_string())
+        let range = match value.location {
+            pest::error::InputLocation::Pos(pos) => pos..pos,
+            pest::error::InputLocation::Span((start, end)) => start..end,
+        };
+        let label = value.variant.message().into_owned();
+
+        Self::with_source("invalid syntax", value).with_label(range, label)
     }
 }
 
 impl From<std::str::Utf8Error> for ParseError {
     fn from(value: std::str::Utf8Error) -> Self {
-        Self(value.to_string())
+        Self::with_source("invalid UTF-8", value)
+    }
+}
+
+impl From<std::num::ParseIntError> for ParseError {
+    fn from(value: std::num::ParseIntError) -> Self {
+        Self::with_source("invalid integer", value)
+    }
+}
+
+impl From<httparse::Error> for ParseError {
+    fn from(value: httparse::Error) -> Self {
+        Self::with_source("invalid HTTP message", value)
     }
 }
 
+#[cfg(feature = "diagnostics")]
+impl miette::Diagnostic for ParseError {
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        if self.labels.is_empty() {
+            return None;
+        }
+
+        Some(Box::new(self.labels.iter().map(|(range, label)| {
+            miette::LabeledSpan::new_with_span(Some(label.clone()), range.clone())
+        })))
+    }
+}
+
+// Parsed values must be shareable across threads, e.g. when moved between async tasks.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<ParseError>();
+    assert_send_sync::<Span>();
+    assert_send_sync::<Span<str>>();
+    assert_send_sync::<json::JsonValue>();
+    assert_send_sync::<http::Request>();
+    assert_send_sync::<http::Response>();
+    assert_send_sync::<http::Session>();
+    assert_send_sync::<http::Requests>();
+    assert_send_sync::<http::Responses>();
+    assert_send_sync::<http::ParserConfig>();
+    assert_send_sync::<http::RequestRef<'static>>();
+    assert_send_sync::<http::ResponseRef<'static>>();
+};
+
 /// A spanned value.
+///
+/// The trait is object safe, so values of different types can be collected as
+/// `Box<dyn Spanned>`, which implements [`ToRangeSet`].
 pub trait Spanned<T: ?Sized = [u8]> {
     /// Get a reference to the span of the value.
     fn span(&self) -> &Span<T>;
 }
 
+impl<T: ?Sized, S: Spanned<T> + ?Sized> Spanned<T> for &S {
+    fn span(&self) -> &Span<T> {
+        (**self).span()
+    }
+}
+
+impl<T: ?Sized, S: Spanned<T> + ?Sized> Spanned<T> for Box<S> {
+    fn span(&self) -> &Span<T> {
+        (**self).span()
+    }
+}
+
+impl<T: ?Sized> Spanned<T> for Span<T> {
+    fn span(&self) -> &Span<T> {
+        self
+    }
+}
+
+impl<T: ?Sized> ToRangeSet<usize> for dyn Spanned<T> + '_ {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span().indices.clone()
+    }
+}
+
+impl<T: ?Sized> ToRangeSet<usize> for dyn Spanned<T> + Send + Sync + '_ {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span().indices.clone()
+    }
+}
+
 /// A span of a source string.
 #[derive(PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -137,24 +297,156 @@ impl<T: ?Sized> Span<T> {
         self.indices.is_empty()
     }
 
-    /// Shifts the span indices by the given offset.
+    /// Joins this span with an adjacent span of the same source.
     ///
-    /// # Panics
+    /// The spans may be given in either order, and the joined span covers the indices of
+    /// both. This can be used to recombine adjacent values, such as a header name and value,
+    /// into one region.
+    ///
+    /// # Errors
     ///
-    /// Panics if the offset causes the indices to overflow `usize::MAX`.
-    pub fn offset(&mut self, offset: usize) {
-        self.indices.shift_right(&offset);
+    /// Returns an error if the spans are not adjacent, i.e. if the last index of one span is
+    /// not immediately followed by the first index of the other.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spansy::{http::parse_request, Spanned};
+    ///
+    /// let request = parse_request(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n").unwrap();
+    /// let method = request.request.method.span();
+    /// let target = request.request.target.span();
+    ///
+    /// // The method and target are separated by a space.
+    /// assert!(method.try_join(target).is_err());
+    ///
+    /// let line = request.request.span();
+    /// let headers = request.headers[0].span();
+    /// let joined = line.as_byte_span().try_join(headers).unwrap();
+    /// assert_eq!(joined, b"GET / HTTP/1.1\r\nHost: example.com\r\n".as_slice());
+    /// assert_eq!(joined, 0..35);
+    /// ```
+    pub fn try_join(&self, other: &Self) -> Result<Self, ParseError> {
+        let (first, second) = match (self.indices.end(), other.indices.min()) {
+            _ if self.is_empty() || other.is_empty() => (self, other),
+            (Some(end), Some(min)) if end == min => (self, other),
+            _ if other.indices.end() == self.indices.min() => (other, self),
+            _ => {
+                return Err(ParseError::new(format!(
+                    "spans at {:?} and {:?} are not adjacent",
+                    self.indices, other.indices
+                )))
+            }
+        };
+
+        let data = if first.is_empty() {
+            second.data.clone()
+        } else if second.is_empty() {
+            first.data.clone()
+        } else {
+            [first.data(), second.data()].concat().into()
+        };
+
+        Ok(Self {
+            data,
+            indices: first.indices.union(&second.indices),
+            _pd: PhantomData,
+        })
+    }
+
+    /// Returns the bytes of `src` at the span indices.
+    fn data_from(&self, src: &Bytes) -> Result<Bytes, ParseError> {
+        if let Some(end) = self.indices.end().filter(|end| *end > src.len()) {
+            return Err(ParseError::new(format!(
+                "span end {end} exceeds source {}",
+                src.len()
+            )));
+        }
+
+        Ok(match self.indices.len_ranges() {
+            0 => Bytes::new(),
+            1 => src.slice(self.indices.min().unwrap()..self.indices.end().unwrap()),
+            _ => self
+                .indices
+                .iter_ranges()
+                .flat_map(|range| src[range].iter().copied())
+                .collect::<Vec<_>>()
+                .into(),
+        })
+    }
+
+    /// Maps the span from positions in the data of `base` to the indices of `base`.
+    ///
+    /// This is used for values which were parsed from the data of a span, such as a body.
+    pub(crate) fn rebase(&mut self, base: &MultiSpan) {
+        let indices: Vec<_> = self
+            .indices
+            .iter_ranges()
+            .flat_map(|range| base.indices_of(range).into_inner())
+            .collect();
+
+        self.indices = RangeSet::from(indices);
+    }
+
+    /// Returns the indices corresponding to the given range of the span data.
+    fn indices_of(&self, range: Range<usize>) -> RangeSet<usize> {
+        let mut indices = Vec::new();
+        let mut pos = 0;
+        for src_range in self.indices.iter_ranges() {
+            let len = src_range.len();
+            let start = range.start.max(pos);
+            let end = range.end.min(pos + len);
+            if start < end {
+                indices.push(src_range.start + start - pos..src_range.start + end - pos);
+            }
+            pos += len;
+        }
+
+        RangeSet::from(indices)
     }
 }
 
 impl Span<str> {
+    /// Creates a new string span of the bytes of `src` at `range`.
+    ///
+    /// This allows spanned types to be built outside of this crate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the range is not within the source, or if the bytes are not valid
+    /// UTF-8.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spansy::{Source, Span};
+    ///
+    /// let src = Source::from_static(b"Host: example.com");
+    /// let host = Span::<str>::try_new(&src, 6..17).unwrap();
+    ///
+    /// assert_eq!(host, "example.com");
+    /// assert_eq!(host, 6..17);
+    /// assert!(Span::<str>::try_new(&src, 6..18).is_err());
+    /// ```
+    pub fn try_new(src: &Source, range: Range<usize>) -> Result<Self, ParseError> {
+        check_range(src, &range)?;
+        std::str::from_utf8(&src[range.clone()])
+            .map_err(|err| ParseError::from(err).with_label(range.clone(), "invalid UTF-8"))?;
+
+        Ok(Self {
+            data: src.slice(range.clone()),
+            indices: range.into(),
+            _pd: PhantomData,
+        })
+    }
+
     /// Create a new string span.
     ///
     /// # Panics
     ///
     /// Panics if the given range is not within the source bytes, or
     /// if the span is not a valid UTF-8 string.
-    pub(crate) fn new_str(src: Bytes, range: Range<usize>) -> Self {
+    pub(crate) fn new_str(src: &Source, range: Range<usize>) -> Self {
         assert!(
             std::str::from_utf8(&src[range.clone()]).is_ok(),
             "span is not a valid UTF-8 string"
@@ -172,8 +464,8 @@ impl Span<str> {
     /// # Panics
     ///
     /// Panics if the given slice is not within the source bytes.
-    pub(crate) fn new_from_str(src: Bytes, span: &str) -> Self {
-        let range = helpers::get_span_range(src.as_ref(), span.as_bytes());
+    pub(crate) fn new_from_str(src: &Source, span: &str) -> Self {
+        let range = helpers::get_span_range(src, span.as_bytes());
 
         Self {
             data: src.slice(range.clone()),
@@ -199,6 +491,45 @@ impl Span<str> {
     pub fn to_byte_span(&self) -> Span<[u8]> {
         self.into()
     }
+
+    /// Returns a subspan of this span.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given range is not within the span data, or is not on character
+    /// boundaries.
+    pub(crate) fn slice_str(&self, range: Range<usize>) -> Self {
+        assert!(
+            self.as_str().is_char_boundary(range.start)
+                && self.as_str().is_char_boundary(range.end),
+            "range is not on character boundaries"
+        );
+        let span = self.as_byte_span().slice(range);
+
+        Self {
+            data: span.data,
+            indices: span.indices,
+            _pd: PhantomData,
+        }
+    }
+
+    /// Returns a copy of the span which views the bytes of `src` at the same indices.
+    ///
+    /// Returns an error if the new bytes are not a valid UTF-8 string.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The new source, which must contain the span indices.
+    pub fn with_data(&self, src: &Bytes) -> Result<Self, ParseError> {
+        let data = self.data_from(src)?;
+        std::str::from_utf8(&data)?;
+
+        Ok(Self {
+            data,
+            indices: self.indices.clone(),
+            _pd: PhantomData,
+        })
+    }
 }
 
 impl AsRef<Span<[u8]>> for Span<str> {
@@ -218,12 +549,34 @@ impl AsRef<str> for Span<str> {
 }
 
 impl Span<[u8]> {
+    /// Create an empty byte span, which has no data or indices.
+    pub(crate) const fn empty() -> Self {
+        Self {
+            data: Bytes::new(),
+            indices: RangeSet::empty(),
+            _pd: PhantomData,
+        }
+    }
+
+    /// Creates a new byte span of the bytes of `src` at `range`.
+    ///
+    /// This allows spanned types to be built outside of this crate.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the range is not within the source.
+    pub fn try_new(src: &Source, range: Range<usize>) -> Result<Self, ParseError> {
+        check_range(src, &range)?;
+
+        Ok(Self::new_bytes(src, range))
+    }
+
     /// Create a new byte span.
     ///
     /// # Panics
     ///
     /// Panics if the given range is not within the source bytes.
-    pub(crate) fn new_bytes(src: Bytes, range: Range<usize>) -> Self {
+    pub(crate) fn new_bytes(src: &Source, range: Range<usize>) -> Self {
         assert!(src.len() >= range.end, "span is not within source bytes");
 
         Self {
@@ -233,10 +586,72 @@ impl Span<[u8]> {
         }
     }
 
+    /// Create a new byte span from its data and the indices of the data in the source.
+    pub(crate) fn from_parts(data: Bytes, indices: RangeSet<usize>) -> Self {
+        debug_assert_eq!(
+            data.len(),
+            indices.len(),
+            "data and indices have equal length"
+        );
+
+        Self {
+            data,
+            indices,
+            _pd: PhantomData,
+        }
+    }
+
     /// Converts this type to a byte slice.
     pub fn as_bytes(&self) -> &[u8] {
         self.as_ref()
     }
+
+    /// Returns a copy of the span which views the bytes of `src` at the same indices.
+    ///
+    /// This can be used to view a span in a modified copy of the source, such as a masked
+    /// transcript, without changing the original.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The new source, which must contain the span indices.
+    pub fn with_data(&self, src: &Bytes) -> Result<Self, ParseError> {
+        Ok(Self {
+            data: self.data_from(src)?,
+            indices: self.indices.clone(),
+            _pd: PhantomData,
+        })
+    }
+
+    /// Returns a subspan of this span.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the given range is not within the span data.
+    pub(crate) fn slice(&self, range: Range<usize>) -> Self {
+        assert!(range.end <= self.data.len(), "range is not within span");
+
+        Self {
+            data: self.data.slice(range.clone()),
+            indices: self.indices_of(range),
+            _pd: PhantomData,
+        }
+    }
+}
+
+/// Checks that a range is within the source.
+fn check_range(src: &Source, range: &Range<usize>) -> Result<(), ParseError> {
+    if range.start > range.end || range.end > src.len() {
+        return Err(ParseError::new(format!(
+            "range {range:?} is not within source of length {}",
+            src.len()
+        ))
+        .with_label(
+            range.start.min(src.len())..src.len(),
+            "range exceeds source",
+        ));
+    }
+
+    Ok(())
 }
 
 impl From<Span<str>> for Span<[u8]> {
@@ -336,3 +751,54 @@ impl<T: ?Sized> PartialEq<Span<T>> for &Range<usize> {
         other == *self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_try_join() {
+        let src = Source::from_static(b"Host: example.com");
+        let name = Span::new_str(&src, 0..4);
+        let colon = Span::new_str(&src, 4..5);
+        let value = Span::new_str(&src, 6..17);
+
+        let joined = colon.try_join(&name).unwrap();
+        assert_eq!(joined, "Host:");
+        assert_eq!(joined, 0..5);
+        assert!(joined.try_join(&value).is_err());
+        assert!(name.try_join(&name).is_err());
+
+        let empty = Span::new_str(&src, 5..5);
+        assert_eq!(value.try_join(&empty).unwrap(), value);
+
+        // Spans with several ranges are joined at their outer ends.
+        let split = Span::from_parts(Bytes::from_static(b"H:"), RangeSet::from([0..1, 4..5]));
+        let joined = split.try_join(&Span::new_bytes(&src, 5..6)).unwrap();
+        assert_eq!(joined, b"H: ".as_slice());
+        assert_eq!(joined.indices(), &RangeSet::from([0..1, 4..6]));
+    }
+
+    #[test]
+    fn test_span_try_new() {
+        let src = Source::from_static(b"caf\xc3\xa9");
+
+        let span = Span::<[u8]>::try_new(&src, 1..4).unwrap();
+        assert_eq!(span, b"af\xc3".as_slice());
+        assert_eq!(span, 1..4);
+        assert_eq!(Span::<str>::try_new(&src, 0..5).unwrap(), "caf\u{e9}");
+        assert!(Span::<str>::try_new(&src, 0..4).is_err());
+        assert!(Span::<str>::try_new(&src, 5..5).unwrap().is_empty());
+
+        for range in [0..6, 6..6, Range { start: 3, end: 2 }] {
+            assert!(
+                Span::<[u8]>::try_new(&src, range.clone()).is_err(),
+                "{range:?}"
+            );
+            assert!(
+                Span::<str>::try_new(&src, range.clone()).is_err(),
+                "{range:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::ops::Range;
+
+use bytes::Bytes;
+use utils::range::{RangeSet, ToRangeSet};
+
+use crate::{Offset, ParseError, Span};
+
+/// A span whose data is assembled from an ordered list of segments of a source.
+///
+/// The data of a segment is either a copy of its source bytes, such as the data of a chunk of
+/// a chunked body, or is decoded from them, such as an unescaped character of a JSON string.
+/// Positions in the data can be mapped to the indices of the source they were taken or decoded
+/// from.
+///
+/// # Example
+///
+/// ```
+/// use spansy::http::parse_response;
+///
+/// let src = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+///     5\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+/// let response = parse_response(src).unwrap();
+/// let data = response.body.unwrap().chunked.unwrap().data().clone();
+///
+/// assert_eq!(data.as_bytes(), b"hello world");
+/// assert_eq!(data.indices_of(3..8).into_inner(), [53..55, 60..63]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultiSpan {
+    pub(crate) data: Bytes,
+    segments: Vec<Segment>,
+}
+
+/// A segment of a [`MultiSpan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Segment {
+    /// The indices of the segment in the source.
+    ///
+    /// The indices of a verbatim segment are contiguous.
+    indices: RangeSet<usize>,
+    /// The range of the segment in the data.
+    data: Range<usize>,
+    /// Whether the data is a copy of the source bytes, rather than decoded from them.
+    verbatim: bool,
+}
+
+impl MultiSpan {
+    /// Returns the data of the span.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns the length of the data in bytes.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if the data is empty.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns the number of segments.
+    pub fn len_segments(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns an iterator over the source indices and the data of each segment, in order.
+    pub fn iter_segments(&self) -> impl Iterator<Item = (&RangeSet<usize>, &[u8])> {
+        self.segments
+            .iter()
+            .map(|segment| (&segment.indices, &self.data[segment.data.clone()]))
+    }
+
+    /// Returns the source indices of the span.
+    pub fn indices(&self) -> RangeSet<usize> {
+        self.indices_of(0..self.data.len())
+    }
+
+    /// Returns the source indices of the given range of the data.
+    ///
+    /// A position in the data of a decoded segment maps to all of the indices of the segment.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is not within the data.
+    pub fn indices_of(&self, range: Range<usize>) -> RangeSet<usize> {
+        assert!(range.end <= self.data.len(), "range is not within span");
+
+        if range.is_empty() {
+            return RangeSet::default();
+        }
+
+        let first = self
+            .segments
+            .partition_point(|segment| segment.data.end <= range.start);
+
+        let mut indices = Vec::new();
+        for segment in self.segments[first..]
+            .iter()
+            .take_while(|segment| segment.data.start < range.end)
+        {
+            if segment.verbatim {
+                let src_start = segment.indices.min().expect("segment is not empty");
+                let start = range.start.max(segment.data.start) - segment.data.start;
+                let end = range.end.min(segment.data.end) - segment.data.start;
+                indices.push(src_start + start..src_start + end);
+            } else {
+                indices.extend(segment.indices.iter_ranges());
+            }
+        }
+
+        RangeSet::from(indices)
+    }
+
+    /// Returns the equivalent [`Span`], or `None` if any segment is decoded.
+    pub fn to_span(&self) -> Option<Span> {
+        self.segments
+            .iter()
+            .all(|segment| segment.verbatim)
+            .then(|| Span::from_parts(self.data.clone(), self.indices()))
+    }
+
+    /// Returns a copy of the span which views the bytes of `src` at the same indices.
+    ///
+    /// The data of decoded segments is not changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The new source, which must contain the span indices.
+    pub fn with_data(&self, src: &Bytes) -> Result<Self, ParseError> {
+        let mut builder = MultiSpanBuilder::default();
+        for (indices, data) in self.iter_segments() {
+            if self.segments[builder.segments.len()].verbatim {
+                builder.push_verbatim(
+                    src,
+                    indices.iter_ranges().next().expect("segment is not empty"),
+                )?;
+            } else {
+                builder.push_decoded(indices.clone(), data);
+            }
+        }
+
+        Ok(builder.build())
+    }
+}
+
+impl Offset for MultiSpan {
+    fn offset(&mut self, offset: usize) {
+        for segment in &mut self.segments {
+            segment.indices.shift_right(&offset);
+        }
+    }
+}
+
+impl ToRangeSet<usize> for MultiSpan {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.indices()
+    }
+}
+
+impl From<&Span> for MultiSpan {
+    fn from(span: &Span) -> Self {
+        let mut builder = MultiSpanBuilder::default();
+        builder.push_span(span);
+        builder.build()
+    }
+}
+
+impl PartialEq<[u8]> for MultiSpan {
+    fn eq(&self, other: &[u8]) -> bool {
+        self.as_bytes() == other
+    }
+}
+
+impl PartialEq<&[u8]> for MultiSpan {
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.as_bytes() == *other
+    }
+}
+
+/// A builder of a [`MultiSpan`], whose segments must be pushed in source order.
+#[derive(Debug, Default)]
+pub(crate) struct MultiSpanBuilder {
+    data: Vec<u8>,
+    segments: Vec<Segment>,
+}
+
+impl MultiSpanBuilder {
+    /// Pushes a segment whose data is the bytes of `src` at `range`.
+    pub(crate) fn push_verbatim(
+        &mut self,
+        src: &[u8],
+        range: Range<usize>,
+    ) -> Result<(), ParseError> {
+        let data = src.get(range.clone()).ok_or_else(|| {
+            ParseError::new(format!(
+                "span end {} exceeds source {}",
+                range.end,
+                src.len()
+            ))
+        })?;
+        self.push(RangeSet::from(range), data, true);
+
+        Ok(())
+    }
+
+    /// Pushes a segment for each contiguous range of the span, whose data is the span data.
+    pub(crate) fn push_span<T: ?Sized>(&mut self, span: &Span<T>) {
+        let mut pos = 0;
+        for range in span.indices.iter_ranges() {
+            let len = range.len();
+            self.push(range.into(), &span.data[pos..pos + len], true);
+            pos += len;
+        }
+    }
+
+    /// Pushes a segment whose data was decoded from the bytes at `indices`.
+    pub(crate) fn push_decoded(&mut self, indices: RangeSet<usize>, data: &[u8]) {
+        self.push(indices, data, false);
+    }
+
+    fn push(&mut self, indices: RangeSet<usize>, data: &[u8], verbatim: bool) {
+        if indices.is_empty() {
+            return;
+        }
+
+        debug_assert!(
+            self.segments
+                .last()
+                .and_then(|segment| segment.indices.end())
+                .zip(indices.min())
+                .is_none_or(|(end, start)| end <= start),
+            "segments are pushed in source order"
+        );
+
+        let start = self.data.len();
+        self.data.extend_from_slice(data);
+        self.segments.push(Segment {
+            indices,
+            data: start..self.data.len(),
+            verbatim,
+        });
+    }
+
+    pub(crate) fn build(self) -> MultiSpan {
+        MultiSpan {
+            data: self.data.into(),
+            segments: self.segments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn multi_span() -> MultiSpan {
+        // The source "ab\x41cd--ef", where the escape `\x41` is decoded to "A".
+        let src = Bytes::from_static(b"ab\\x41cd--ef");
+        let mut builder = MultiSpanBuilder::default();
+        builder.push_verbatim(&src, 0..2).unwrap();
+        builder.push_decoded(RangeSet::from(2..6), b"A");
+        builder.push_verbatim(&src, 6..8).unwrap();
+        builder.push_verbatim(&src, 10..12).unwrap();
+        builder.build()
+    }
+
+    #[test]
+    fn test_multi_span_indices() {
+        let span = multi_span();
+
+        assert_eq!(span.as_bytes(), b"abAcdef");
+        assert_eq!(span.len_segments(), 4);
+        assert_eq!(span.indices(), RangeSet::from([(0..8), (10..12)]));
+
+        assert_eq!(span.indices_of(0..1), RangeSet::from(0..1));
+        assert_eq!(span.indices_of(1..3), RangeSet::from(1..6));
+        assert_eq!(span.indices_of(3..4), RangeSet::from(6..7));
+        assert_eq!(span.indices_of(4..6), RangeSet::from([(7..8), (10..11)]));
+        assert!(span.indices_of(3..3).is_empty());
+    }
+
+    #[test]
+    fn test_multi_span_to_span() {
+        assert_eq!(multi_span().to_span(), None);
+
+        let src = Bytes::from_static(b"ab--cd");
+        let mut builder = MultiSpanBuilder::default();
+        builder.push_verbatim(&src, 0..2).unwrap();
+        builder.push_verbatim(&src, 4..6).unwrap();
+        let span = builder.build().to_span().unwrap();
+
+        assert_eq!(span.as_bytes(), b"abcd");
+        assert_eq!(span.indices(), &RangeSet::from([(0..2), (4..6)]));
+    }
+
+    #[test]
+    fn test_multi_span_with_data() {
+        let span = multi_span()
+            .with_data(&Bytes::from_static(b"XY\\x41ZW--VU"))
+            .unwrap();
+
+        assert_eq!(span.as_bytes(), b"XYAZWVU");
+        assert_eq!(span.indices(), multi_span().indices());
+    }
+}
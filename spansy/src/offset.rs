@@ -0,0 +1,144 @@
+use crate::Span;
+
+/// A value whose spans can be shifted, such as a message parsed from a slice of a larger
+/// source.
+///
+/// # Example
+///
+/// ```
+/// use spansy::{http::parse_request, Offset, Spanned};
+///
+/// let mut request = parse_request(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+/// request.offset(100);
+///
+/// assert_eq!(request.request.method.span().indices().min(), Some(100));
+/// ```
+///
+/// Implementations must shift every span and range the value holds, so that they stay
+/// consistent with each other:
+///
+/// ```
+/// use spansy::{json::JsonValue, Offset, Span};
+///
+/// struct Field {
+///     name: Span<str>,
+///     value: Option<JsonValue>,
+/// }
+///
+/// impl Offset for Field {
+///     fn offset(&mut self, offset: usize) {
+///         self.name.offset(offset);
+///         self.value.offset(offset);
+///     }
+/// }
+/// ```
+pub trait Offset {
+    /// Shifts the span indices by the given offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset causes the indices to overflow `usize::MAX`.
+    fn offset(&mut self, offset: usize);
+}
+
+impl<T: ?Sized> Offset for Span<T> {
+    fn offset(&mut self, offset: usize) {
+        self.indices.shift_right(&offset);
+    }
+}
+
+impl<T: Offset + ?Sized> Offset for Box<T> {
+    fn offset(&mut self, offset: usize) {
+        (**self).offset(offset);
+    }
+}
+
+impl<T: Offset> Offset for Option<T> {
+    fn offset(&mut self, offset: usize) {
+        if let Some(value) = self {
+            value.offset(offset);
+        }
+    }
+}
+
+impl<T: Offset> Offset for Vec<T> {
+    fn offset(&mut self, offset: usize) {
+        for value in self {
+            value.offset(offset);
+        }
+    }
+}
+
+/// Implements [`Offset`] for a struct by offsetting each of its fields.
+///
+/// Every field must be listed, either as a field to offset or, after a `;`, as a field which
+/// has no spans. The struct is destructured exhaustively, so adding a field without listing it
+/// is a compile error.
+///
+/// ```ignore
+/// impl_offset!(Chunk { span, extension, data; size });
+/// impl_offset!(HeaderName(_));
+/// ```
+macro_rules! impl_offset {
+    ($ty:ident(_)) => {
+        impl $crate::Offset for $ty {
+            fn offset(&mut self, offset: usize) {
+                let Self(span) = self;
+                $crate::Offset::offset(span, offset);
+            }
+        }
+    };
+    ($ty:ident { $($field:ident),* $(; $($skip:ident),*)? }) => {
+        impl $crate::Offset for $ty {
+            fn offset(&mut self, offset: usize) {
+                let Self { $($field,)* $($($skip: _,)*)? } = self;
+                $($crate::Offset::offset($field, offset);)*
+            }
+        }
+    };
+}
+
+pub(crate) use impl_offset;
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use crate::{
+        http::{parse_request, Requests, Responses},
+        testutil::harness::{assert_request, assert_response},
+    };
+
+    use super::*;
+
+    const REQUEST: &[u8] = b"POST /a?b=c HTTP/1.1\r\nHost: localhost\r\n\
+        Content-Type: application/json\r\nContent-Length: 23\r\n\r\n\
+        {\"a\": [1, {\"b\": null}]}";
+
+    const RESPONSE: &[u8] = b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 200 OK\r\n\
+        Set-Cookie: id=a3fWa; Secure\r\nContent-Type: application/json\r\n\
+        Transfer-Encoding: chunked\r\n\r\n4;x=y\r\n{\"a\"\r\n5\r\n: 42}\r\n0\r\n\r\n";
+
+    fn padded(src: &[u8], offset: usize) -> Bytes {
+        let mut padded = vec![b'x'; offset];
+        padded.extend_from_slice(src);
+        padded.into()
+    }
+
+    #[test]
+    fn test_offset_matches_parse_at() {
+        let mut request = parse_request(REQUEST).unwrap();
+        request.offset(10);
+        let src = padded(REQUEST, 10);
+        let expected = Requests::new_at(src.clone(), 10).next().unwrap().unwrap();
+        assert_request(&src, &expected);
+        assert_eq!(request, expected);
+
+        let mut response = Responses::new_from_slice(RESPONSE).next().unwrap().unwrap();
+        response.offset(10);
+        let src = padded(RESPONSE, 10);
+        let expected = Responses::new_at(src.clone(), 10).next().unwrap().unwrap();
+        assert_response(&src, &expected);
+        assert_eq!(response, expected);
+    }
+}
@@ -0,0 +1,187 @@
+use std::{
+    fmt,
+    ops::{Deref, Range},
+};
+
+use bytes::Bytes;
+
+/// The source bytes which values are parsed from.
+///
+/// A source is a cheap handle to an immutable, reference-counted buffer which is shared by
+/// every span parsed from it. The buffer may be owned by anything which lends its bytes, such
+/// as a `Vec<u8>` or a memory mapping, so the parsers do not depend on how it is stored.
+///
+/// # Example
+///
+/// ```
+/// use spansy::{http::Requests, Source};
+///
+/// let src = Source::from_owner(b"GET / HTTP/1.1\r\n\r\n".to_vec());
+/// let request = Requests::new(src).next().unwrap().unwrap();
+///
+/// assert_eq!(request.request.method.as_str(), "GET");
+/// ```
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Source(Bytes);
+
+impl Source {
+    /// Returns a source which borrows static bytes.
+    pub const fn from_static(src: &'static [u8]) -> Self {
+        Self(Bytes::from_static(src))
+    }
+
+    /// Returns a source holding a copy of the given bytes.
+    pub fn copy_from_slice(src: &[u8]) -> Self {
+        Self(Bytes::copy_from_slice(src))
+    }
+
+    /// Returns a source which views the bytes of `owner`.
+    ///
+    /// The owner is kept alive until the source and every span parsed from it are dropped.
+    pub fn from_owner<T>(owner: T) -> Self
+    where
+        T: AsRef<[u8]> + Send + 'static,
+    {
+        Self(Bytes::from_owner(owner))
+    }
+
+    /// Returns a source which maps the file at `path` into memory.
+    ///
+    /// The file is not read into memory up front, so large transcripts can be parsed without
+    /// loading them whole. The mapping is kept alive until the source and every span parsed
+    /// from it are dropped.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the mapping is alive, including by
+    /// other processes. Otherwise the bytes seen by spans may change, which is undefined
+    /// behavior.
+    #[cfg(feature = "mmap")]
+    pub unsafe fn from_file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // # Safety
+        // The caller guarantees that the file is not modified while the mapping is alive.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+
+        Ok(Self::from_owner(mmap))
+    }
+
+    /// Returns the source bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the source as `Bytes`, without copying.
+    pub fn to_bytes(&self) -> Bytes {
+        self.0.clone()
+    }
+
+    /// Returns the bytes in the given range of the source, without copying.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is not within the source.
+    pub(crate) fn slice(&self, range: Range<usize>) -> Bytes {
+        self.0.slice(range)
+    }
+}
+
+impl fmt::Debug for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // A source may be too large to print, so only its length is shown.
+        f.debug_struct("Source").field("len", &self.len()).finish()
+    }
+}
+
+impl Deref for Source {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Source {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Bytes> for Source {
+    fn from(src: Bytes) -> Self {
+        Self(src)
+    }
+}
+
+impl From<Vec<u8>> for Source {
+    fn from(src: Vec<u8>) -> Self {
+        Self(Bytes::from(src))
+    }
+}
+
+impl From<&'static [u8]> for Source {
+    fn from(src: &'static [u8]) -> Self {
+        Self::from_static(src)
+    }
+}
+
+impl From<Source> for Bytes {
+    fn from(src: Source) -> Self {
+        src.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{http::Requests, testutil::harness::assert_request, Spanned};
+
+    use super::*;
+
+    #[test]
+    fn test_spans_share_source() {
+        let src = Source::from(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n".to_vec());
+        let request = Requests::new(src.clone()).next().unwrap().unwrap();
+        assert_request(src.as_bytes(), &request);
+
+        let value = request.headers[0].value.span().data();
+        assert_eq!(value, b"example.com");
+        assert!(src.as_bytes().as_ptr_range().contains(&value.as_ptr()));
+        assert_eq!(format!("{src:?}"), "Source { len: 37 }");
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_source_from_file() {
+        use crate::{http::Session, testutil::harness::assert_session};
+
+        let dir = std::env::temp_dir().join(format!("spansy-mmap-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let sent = dir.join("sent");
+        let received = dir.join("received");
+        std::fs::write(&sent, b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        std::fs::write(&received, b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nhi").unwrap();
+
+        // Safety: the files are not modified while mapped.
+        let session = unsafe {
+            Session::new(
+                Source::from_file(&sent).unwrap(),
+                Source::from_file(&received).unwrap(),
+            )
+        }
+        .unwrap();
+        assert_session(
+            b"GET / HTTP/1.1\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nhi",
+            &session,
+        );
+        let response = session.exchanges[0].response.as_ref().unwrap();
+        assert_eq!(response.body.as_ref().unwrap().span(), b"hi".as_slice());
+
+        std::fs::write(&sent, b"").unwrap();
+        // Safety: the file is not modified while mapped.
+        assert!(unsafe { Source::from_file(&sent) }.unwrap().is_empty());
+        assert!(unsafe { Source::from_file(dir.join("missing")) }.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,199 @@
+//! Golden vectors of the chunked transfer coding (RFC 9112, section 7.1).
+//!
+//! Each vector is a response with a chunked body and the expected spans of its body and
+//! chunks, covering edge cases of the chunk framing. The vectors lock in the framing
+//! semantics of the parser, so implementations in other languages can be checked against
+//! the same spans.
+//!
+//! # Example
+//!
+//! ```
+//! use spansy::{http::parse_response, testutil::chunked::CHUNKED_VECTORS};
+//!
+//! for vector in CHUNKED_VECTORS {
+//!     let response = parse_response(vector.src).unwrap();
+//!     vector.assert_matches(&response);
+//! }
+//! ```
+
+use std::ops::Range;
+
+use crate::{http::Response, Spanned};
+
+/// The expected spans of a chunk, relative to the source of the response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedChunk {
+    /// The chunk, including the chunk size line and the CRLF following the data.
+    pub span: Range<usize>,
+    /// The chunk size.
+    pub size: usize,
+    /// The chunk extension, including the leading `;`.
+    pub extension: Option<Range<usize>>,
+    /// The chunk data.
+    pub data: Range<usize>,
+}
+
+/// A response with a chunked body and the expected spans of the body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkedVector {
+    /// The name of the vector.
+    pub name: &'static str,
+    /// The edge case covered by the vector.
+    pub description: &'static str,
+    /// The response.
+    pub src: &'static [u8],
+    /// The body, including the chunk framing and the trailer section.
+    pub body: Range<usize>,
+    /// The chunks, excluding the last chunk which has a size of zero.
+    pub chunks: &'static [ExpectedChunk],
+}
+
+impl ChunkedVector {
+    /// Asserts that the spans of the parsed response are the expected spans.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any span differs.
+    pub fn assert_matches(&self, response: &Response) {
+        let body = response.body.as_ref().expect("response has a body");
+        assert_eq!(body.span(), &self.body, "{}: body span", self.name);
+
+        let chunks = &body.chunked.as_ref().expect("body is chunked").chunks;
+        assert_eq!(
+            chunks.len(),
+            self.chunks.len(),
+            "{}: chunk count",
+            self.name
+        );
+        for (actual, expected) in chunks.iter().zip(self.chunks) {
+            assert_eq!(actual.span(), &expected.span, "{}: chunk span", self.name);
+            assert_eq!(actual.size, expected.size, "{}: chunk size", self.name);
+            assert_eq!(
+                actual
+                    .extension
+                    .as_ref()
+                    .map(|extension| extension.indices().clone()),
+                expected.extension.clone().map(Into::into),
+                "{}: chunk extension span",
+                self.name
+            );
+            assert_eq!(actual.data, expected.data, "{}: chunk data span", self.name);
+        }
+    }
+}
+
+/// The golden vectors of the chunked transfer coding.
+pub const CHUNKED_VECTORS: &[ChunkedVector] = &[
+    ChunkedVector {
+        name: "extension",
+        description: "A chunk extension with a name and a value.",
+        src: b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5;name=value\r\nhello\r\n0\r\n\r\n",
+        body: 47..73,
+        chunks: &[
+            ExpectedChunk { span: 47..68, size: 5, extension: Some(48..59), data: 61..66 },
+        ],
+    },
+    ChunkedVector {
+        name: "quoted_extension",
+        description: "Chunk extensions with whitespace, a quoted value and several parameters.",
+        src: b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5 ;a=\"x;y\";b\r\nhello\r\n0;last\r\n\r\n",
+        body: 47..78,
+        chunks: &[
+            ExpectedChunk { span: 47..68, size: 5, extension: Some(49..59), data: 61..66 },
+        ],
+    },
+    ChunkedVector {
+        name: "trailers",
+        description: "A trailer section with two fields.",
+        src: b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n0\r\nExpires: 0\r\nX-Sum: abc\r\n\r\n",
+        body: 47..86,
+        chunks: &[
+            ExpectedChunk { span: 47..57, size: 5, extension: None, data: 50..55 },
+        ],
+    },
+    ChunkedVector {
+        name: "empty",
+        description: "A body with only the last chunk.",
+        src: b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n",
+        body: 47..52,
+        chunks: &[
+        ],
+    },
+    ChunkedVector {
+        name: "leading_zeros",
+        description: "Chunk sizes with leading zeros.",
+        src: b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n005\r\nhello\r\n000\r\n\r\n",
+        body: 47..66,
+        chunks: &[
+            ExpectedChunk { span: 47..59, size: 5, extension: None, data: 52..57 },
+        ],
+    },
+    ChunkedVector {
+        name: "uppercase_hex",
+        description: "Chunk sizes in uppercase and lowercase hexadecimal.",
+        src: b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nA\r\n0123456789\r\nb\r\n0123456789a\r\n0\r\n\r\n",
+        body: 47..83,
+        chunks: &[
+            ExpectedChunk { span: 47..62, size: 10, extension: None, data: 50..60 },
+            ExpectedChunk { span: 62..78, size: 11, extension: None, data: 65..76 },
+        ],
+    },
+    ChunkedVector {
+        name: "crlf_in_data",
+        description: "Chunk data which contains CRLF, including at its end.",
+        src: b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n6\r\nab\r\ncd\r\n4\r\n\r\n\r\n\r\n0\r\n\r\n",
+        body: 47..72,
+        chunks: &[
+            ExpectedChunk { span: 47..58, size: 6, extension: None, data: 50..56 },
+            ExpectedChunk { span: 58..67, size: 4, extension: None, data: 61..65 },
+        ],
+    },
+    ChunkedVector {
+        name: "many_chunks",
+        description: "Several chunks of a single byte.",
+        src: b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n1\r\na\r\n1\r\nb\r\n1\r\nc\r\n0\r\n\r\n",
+        body: 47..70,
+        chunks: &[
+            ExpectedChunk { span: 47..53, size: 1, extension: None, data: 50..51 },
+            ExpectedChunk { span: 53..59, size: 1, extension: None, data: 56..57 },
+            ExpectedChunk { span: 59..65, size: 1, extension: None, data: 62..63 },
+        ],
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::{parse_response, Responses},
+        testutil::harness::assert_response,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_chunked_vectors() {
+        for vector in CHUNKED_VECTORS {
+            let response = parse_response(vector.src).unwrap();
+            assert_response(vector.src, &response);
+            vector.assert_matches(&response);
+            assert_eq!(response.span(), &(0..vector.src.len()), "{}", vector.name);
+        }
+    }
+
+    #[test]
+    fn test_chunked_vectors_pipelined() {
+        // The framing alone delimits each response, so the vectors can be pipelined.
+        let src: Vec<u8> = CHUNKED_VECTORS
+            .iter()
+            .flat_map(|v| v.src.to_vec())
+            .collect();
+        let responses: Vec<_> = Responses::new_from_slice(&src)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(responses.len(), CHUNKED_VECTORS.len());
+        for response in &responses {
+            assert_response(&src, response);
+        }
+    }
+}
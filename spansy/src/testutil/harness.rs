@@ -0,0 +1,415 @@
+//! Assertions that parsed messages reconstruct their source byte for byte.
+//!
+//! The spans of a message must partition it: the start line, the headers, the framing and the
+//! body together cover every byte of the message exactly once, and each span views exactly
+//! the bytes of the source at its indices. The content of a body is checked likewise, down to
+//! each JSON value and XML node.
+//!
+//! # Example
+//!
+//! ```
+//! use spansy::{http::parse_request, testutil::harness::assert_request};
+//!
+//! let src = b"POST / HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: 8\r\n\r\n{\"a\": 1}";
+//! assert_request(src, &parse_request(src).unwrap());
+//! ```
+
+use utils::range::{Difference, RangeSet, Subset, ToRangeSet, Union};
+
+use crate::{
+    http::{Body, BodyContent, Header, Request, Response, Session},
+    json::JsonValue,
+    xml::{Element, Node, XmlDocument},
+    Span, Spanned,
+};
+
+/// Asserts that a request and all of its components reconstruct `src`.
+///
+/// The indices of the request are relative to the start of `src`.
+///
+/// # Panics
+///
+/// Panics if a span does not view the bytes of `src` at its indices, or if the spans do not
+/// partition the request.
+#[track_caller]
+pub fn assert_request(src: &[u8], request: &Request) {
+    let line = &request.request;
+    assert_parts(
+        src,
+        line.span.as_byte_span(),
+        &[
+            line.method.span().as_byte_span(),
+            line.target.span().as_byte_span(),
+            &line.version(),
+        ],
+    );
+
+    assert_message(
+        src,
+        &request.span,
+        line.span.as_byte_span(),
+        &request.headers,
+        request.body.as_ref(),
+    );
+}
+
+/// Asserts that a response, its interim responses and all of their components reconstruct
+/// `src`.
+///
+/// The indices of the response are relative to the start of `src`.
+///
+/// # Panics
+///
+/// Panics if a span does not view the bytes of `src` at its indices, or if the spans do not
+/// partition the response.
+#[track_caller]
+pub fn assert_response(src: &[u8], response: &Response) {
+    for interim in &response.interim {
+        assert_response(src, interim);
+    }
+
+    let status = &response.status;
+    assert_parts(
+        src,
+        status.span.as_byte_span(),
+        &[
+            &status.version(),
+            status.code.span().as_byte_span(),
+            status.reason.span().as_byte_span(),
+        ],
+    );
+
+    assert_message(
+        src,
+        &response.span,
+        status.span.as_byte_span(),
+        &response.headers,
+        response.body.as_ref(),
+    );
+}
+
+/// Asserts that the messages of a session reconstruct the data sent and received.
+///
+/// # Panics
+///
+/// Panics if a request does not reconstruct `sent`, or a response does not reconstruct
+/// `received`.
+#[track_caller]
+pub fn assert_session(sent: &[u8], received: &[u8], session: &Session) {
+    for exchange in &session.exchanges {
+        assert_request(sent, &exchange.request);
+        if let Some(response) = &exchange.response {
+            assert_response(received, response);
+        }
+    }
+}
+
+/// Asserts that the start line, headers, framing and body partition a message.
+#[track_caller]
+fn assert_message(
+    src: &[u8],
+    message: &Span,
+    start_line: &Span,
+    headers: &[Header],
+    body: Option<&Body>,
+) {
+    assert_bytes(src, message);
+    assert_eq!(
+        message.indices().len_ranges(),
+        1,
+        "message is not contiguous"
+    );
+
+    let mut parts = vec![start_line];
+    parts.extend(headers.iter().map(|header| &header.span));
+    parts.extend(body.map(|body| &body.span));
+    let framing = assert_parts(src, message, &parts);
+
+    // The only framing of the message is the line terminating the header section.
+    let head_end = body
+        .and_then(|body| body.span.indices().min())
+        .or(message.indices().end())
+        .unwrap_or_default();
+    let framing_bytes = bytes_at(src, &framing);
+    assert!(
+        matches!(framing_bytes.as_slice(), b"\r\n" | b"\n"),
+        "unaccounted bytes in message: {framing_bytes:?}"
+    );
+    assert_eq!(
+        framing.end(),
+        Some(head_end),
+        "framing is not after the headers"
+    );
+
+    for header in headers {
+        assert_header(src, header);
+    }
+    if let Some(body) = body {
+        assert_body(src, body);
+    }
+}
+
+/// Asserts that the name and value of a header are within it.
+#[track_caller]
+fn assert_header(src: &[u8], header: &Header) {
+    assert_parts(
+        src,
+        &header.span,
+        &[header.name.span().as_byte_span(), header.value.span()],
+    );
+}
+
+/// Asserts that the chunks and trailers of a body partition it, and that its content views
+/// the body.
+#[track_caller]
+fn assert_body(src: &[u8], body: &Body) {
+    let content = body.content.span();
+    assert_bytes(src, content);
+    assert!(
+        content.indices().is_subset(body.span.indices()),
+        "content is not within the body"
+    );
+    assert_content(src, &body.content);
+
+    let Some(chunked) = &body.chunked else {
+        return;
+    };
+
+    let mut parts: Vec<&Span> = chunked.chunks().iter().map(|chunk| &chunk.span).collect();
+    parts.extend(chunked.trailers().iter().map(|trailer| &trailer.span));
+    let framing = assert_parts(src, &body.span, &parts);
+
+    // The remaining framing is the last chunk and the line terminating the trailer section.
+    let framing_bytes = bytes_at(src, &framing);
+    assert!(
+        framing_bytes.starts_with(b"0") && framing_bytes.ends_with(b"\n"),
+        "unaccounted bytes in chunked body: {framing_bytes:?}"
+    );
+    assert_bytes(src, chunked.data());
+
+    for chunk in chunked.chunks() {
+        let mut chunk_parts = vec![&chunk.data];
+        chunk_parts.extend(chunk.extension.as_ref());
+        assert_parts(src, &chunk.span, &chunk_parts);
+        assert_eq!(chunk.data.len(), chunk.size);
+    }
+    for trailer in chunked.trailers() {
+        assert_header(src, trailer);
+    }
+}
+
+/// Asserts that the parsed content of a body is within its content and views the bytes of
+/// `src`.
+#[track_caller]
+fn assert_content(src: &[u8], content: &BodyContent) {
+    match content {
+        BodyContent::Json(value) => assert_json(src, value),
+        BodyContent::Xml(document) => assert_xml(src, document),
+        BodyContent::Compressed { raw, decoded, json } => {
+            // The decoded content does not view the source, but maps to the encoded content.
+            assert!(
+                decoded.indices().is_subset(raw.indices()),
+                "decoded content is not within the encoded content"
+            );
+            if let Some(json) = json {
+                assert!(
+                    json.span().indices().is_subset(&decoded.indices()),
+                    "JSON value is not within the decoded content"
+                );
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Asserts that the members or elements of a JSON value are disjoint parts of it.
+#[track_caller]
+fn assert_json(src: &[u8], value: &JsonValue) {
+    let span = value.span().as_byte_span();
+    match value {
+        JsonValue::Object(object) => {
+            let pairs: Vec<&Span> = object
+                .elems
+                .iter()
+                .map(|kv| kv.span.as_byte_span())
+                .collect();
+            assert_parts(src, span, &pairs);
+
+            for kv in &object.elems {
+                assert_parts(
+                    src,
+                    kv.span.as_byte_span(),
+                    &[kv.key.span().as_byte_span(), kv.value.span().as_byte_span()],
+                );
+                assert_json(src, &kv.value);
+            }
+        }
+        JsonValue::Array(array) => {
+            let elems: Vec<&Span> = array
+                .elems
+                .iter()
+                .map(|elem| elem.span().as_byte_span())
+                .collect();
+            assert_parts(src, span, &elems);
+
+            for elem in &array.elems {
+                assert_json(src, elem);
+            }
+        }
+        _ => assert_bytes(src, span),
+    }
+}
+
+/// Asserts that the declarations and the root element are disjoint parts of an XML document.
+#[track_caller]
+fn assert_xml(src: &[u8], document: &XmlDocument) {
+    let mut parts: Vec<&Span> = document
+        .declaration
+        .iter()
+        .chain(&document.doctype)
+        .map(Span::as_byte_span)
+        .collect();
+    parts.push(document.root.span().as_byte_span());
+    assert_parts(src, document.span().as_byte_span(), &parts);
+
+    assert_element(src, &document.root);
+}
+
+/// Asserts that the name, attributes and children of an XML element are disjoint parts of it.
+#[track_caller]
+fn assert_element(src: &[u8], element: &Element) {
+    let mut parts = vec![element.name.as_byte_span()];
+    parts.extend(
+        element
+            .attributes
+            .iter()
+            .map(|attribute| attribute.span().as_byte_span()),
+    );
+    parts.extend(
+        element
+            .children
+            .iter()
+            .map(|child| child.span().as_byte_span()),
+    );
+    assert_parts(src, element.span().as_byte_span(), &parts);
+
+    for attribute in &element.attributes {
+        assert_parts(
+            src,
+            attribute.span().as_byte_span(),
+            &[
+                attribute.name.as_byte_span(),
+                attribute.value.as_byte_span(),
+            ],
+        );
+    }
+    for child in &element.children {
+        if let Node::Element(element) = child {
+            assert_element(src, element);
+        }
+    }
+}
+
+/// Asserts that `parts` are disjoint, lie within `whole` and view the bytes of `src` at their
+/// indices.
+///
+/// Returns the indices of `whole` which are not covered by any part.
+#[track_caller]
+fn assert_parts(src: &[u8], whole: &Span, parts: &[&Span]) -> RangeSet<usize> {
+    assert_bytes(src, whole);
+
+    let mut covered = RangeSet::default();
+    for part in parts {
+        assert_bytes(src, *part);
+
+        let indices = part.indices();
+        assert!(
+            indices.is_subset(whole.indices()),
+            "{indices:?} is not within {:?}",
+            whole.indices()
+        );
+        assert_eq!(
+            covered.union(indices).len(),
+            covered.len() + indices.len(),
+            "{indices:?} overlaps another part"
+        );
+        covered = covered.union(indices);
+    }
+
+    whole.indices().difference(&covered)
+}
+
+/// Asserts that a span views the bytes of `src` at its indices.
+#[track_caller]
+fn assert_bytes(src: &[u8], span: &impl ToBytes) {
+    assert_eq!(span.bytes(), bytes_at(src, &span.to_range_set()));
+}
+
+/// Returns the bytes of `src` at `indices`, in order.
+fn bytes_at(src: &[u8], indices: &RangeSet<usize>) -> Vec<u8> {
+    indices
+        .iter_ranges()
+        .flat_map(|range| src[range].iter().copied())
+        .collect()
+}
+
+/// A span whose bytes can be compared with the source.
+trait ToBytes: ToRangeSet<usize> {
+    fn bytes(&self) -> &[u8];
+}
+
+impl ToBytes for Span {
+    fn bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl ToBytes for crate::MultiSpan {
+    fn bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::{parse_request, Requests, Responses};
+
+    use super::*;
+
+    macro_rules! fixture {
+        ($name:literal) => {
+            (
+                include_bytes!(concat!("../../tests/fixtures/", $name, "/sent.http")).as_slice(),
+                include_bytes!(concat!("../../tests/fixtures/", $name, "/received.http"))
+                    .as_slice(),
+            )
+        };
+    }
+
+    #[test]
+    fn test_fixtures() {
+        for (sent, received) in [
+            fixture!("bank_json"),
+            fixture!("chunked_html"),
+            fixture!("github_api"),
+            fixture!("openai_sse"),
+        ] {
+            for request in Requests::new_from_slice(sent) {
+                assert_request(sent, &request.unwrap());
+            }
+            for response in Responses::new_from_slice(received) {
+                assert_response(received, &response.unwrap());
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "unaccounted bytes in message")]
+    fn test_unaccounted_bytes() {
+        let src = b"GET / HTTP/1.1\r\nHost: a\r\n\r\n";
+        let mut request = parse_request(src).unwrap();
+        request.headers.clear();
+
+        assert_request(src, &request);
+    }
+}
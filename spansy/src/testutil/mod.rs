@@ -0,0 +1,375 @@
+//! Generation of randomized HTTP transcripts with known spans, for testing.
+//!
+//! # Example
+//!
+//! ```
+//! use spansy::{http::Session, testutil::TranscriptGenerator};
+//!
+//! let transcript = TranscriptGenerator::new(0).generate();
+//! let session = Session::new_from_slice(&transcript.sent, &transcript.received).unwrap();
+//!
+//! for (exchange, expected) in session.exchanges.iter().zip(&transcript.requests) {
+//!     expected.assert_matches(&exchange.request);
+//! }
+//! ```
+
+pub mod chunked;
+pub mod harness;
+
+use std::ops::Range;
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    http::{Header, Request, Response},
+    Spanned,
+};
+
+/// A generated transcript of an HTTP/1.1 connection, with the expected span of each part.
+#[derive(Debug, Clone)]
+pub struct Transcript {
+    /// The data sent by the client.
+    pub sent: Vec<u8>,
+    /// The data received by the client.
+    pub received: Vec<u8>,
+    /// The expected spans of the requests, relative to `sent`.
+    pub requests: Vec<ExpectedRequest>,
+    /// The expected spans of the responses, relative to `received`.
+    pub responses: Vec<ExpectedResponse>,
+}
+
+/// The expected spans of a header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedHeader {
+    /// The header, including the CRLF.
+    pub span: Range<usize>,
+    /// The header name.
+    pub name: Range<usize>,
+    /// The header value.
+    pub value: Range<usize>,
+}
+
+/// The expected spans of a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedRequest {
+    /// The request.
+    pub span: Range<usize>,
+    /// The request method.
+    pub method: Range<usize>,
+    /// The request target.
+    pub target: Range<usize>,
+    /// The request headers.
+    pub headers: Vec<ExpectedHeader>,
+    /// The request body, including the chunk framing of a chunked body.
+    pub body: Option<Range<usize>>,
+}
+
+impl ExpectedRequest {
+    /// Asserts that the spans of the parsed request are the expected spans.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any span differs.
+    pub fn assert_matches(&self, request: &Request) {
+        assert_eq!(request.span(), &self.span, "request span");
+        assert_eq!(request.request.method.span(), &self.method, "method span");
+        assert_eq!(request.request.target.span(), &self.target, "target span");
+        assert_headers(&request.headers, &self.headers);
+        assert_eq!(
+            request
+                .body
+                .as_ref()
+                .map(|body| body.span().indices().clone()),
+            self.body.clone().map(Into::into),
+            "body span"
+        );
+    }
+}
+
+/// The expected spans of a response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedResponse {
+    /// The response.
+    pub span: Range<usize>,
+    /// The status code.
+    pub code: Range<usize>,
+    /// The reason phrase.
+    pub reason: Range<usize>,
+    /// The response headers.
+    pub headers: Vec<ExpectedHeader>,
+    /// The response body, including the chunk framing of a chunked body.
+    pub body: Option<Range<usize>>,
+}
+
+impl ExpectedResponse {
+    /// Asserts that the spans of the parsed response are the expected spans.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any span differs.
+    pub fn assert_matches(&self, response: &Response) {
+        assert_eq!(response.span(), &self.span, "response span");
+        assert_eq!(response.status.code.span(), &self.code, "code span");
+        assert_eq!(response.status.reason.span(), &self.reason, "reason span");
+        assert_headers(&response.headers, &self.headers);
+        assert_eq!(
+            response
+                .body
+                .as_ref()
+                .map(|body| body.span().indices().clone()),
+            self.body.clone().map(Into::into),
+            "body span"
+        );
+    }
+}
+
+fn assert_headers(actual: &[Header], expected: &[ExpectedHeader]) {
+    assert_eq!(actual.len(), expected.len(), "header count");
+    for (actual, expected) in actual.iter().zip(expected) {
+        assert_eq!(actual.span(), &expected.span, "header span");
+        assert_eq!(actual.name.span(), &expected.name, "header name span");
+        assert_eq!(actual.value.span(), &expected.value, "header value span");
+    }
+}
+
+/// A generator of randomized, valid HTTP/1.1 transcripts.
+///
+/// Transcripts mix `GET` and `POST` requests, JSON and plain text bodies, identity and chunked
+/// transfer codings and duplicate headers. Generation is deterministic for a given seed.
+#[derive(Debug, Clone)]
+pub struct TranscriptGenerator {
+    rng: StdRng,
+    max_exchanges: usize,
+}
+
+impl TranscriptGenerator {
+    /// Returns a new generator seeded with `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            max_exchanges: 4,
+        }
+    }
+
+    /// Sets the maximum number of request and response pairs in a transcript.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_exchanges` is zero.
+    pub fn max_exchanges(mut self, max_exchanges: usize) -> Self {
+        assert!(max_exchanges > 0, "a transcript has at least one exchange");
+        self.max_exchanges = max_exchanges;
+        self
+    }
+
+    /// Generates a transcript.
+    pub fn generate(&mut self) -> Transcript {
+        let mut sent = Writer::default();
+        let mut received = Writer::default();
+        let mut requests = Vec::new();
+        let mut responses = Vec::new();
+
+        for _ in 0..self.rng.gen_range(1..=self.max_exchanges) {
+            requests.push(self.request(&mut sent));
+            responses.push(self.response(&mut received));
+        }
+
+        Transcript {
+            sent: sent.buf,
+            received: received.buf,
+            requests,
+            responses,
+        }
+    }
+
+    fn request(&mut self, w: &mut Writer) -> ExpectedRequest {
+        let start = w.pos();
+        let method = w.write(["GET", "POST"].choose(&mut self.rng).unwrap());
+        w.write(" ");
+        let target = w.write(&format!("/{}", self.token(1..12)));
+        w.write(" HTTP/1.1\r\n");
+
+        let mut headers = vec![w.header("Host", "example.com")];
+        let body = (w.buf[method.clone()] == *b"POST").then(|| self.body());
+        let body = self.headers_and_body(w, &mut headers, body);
+
+        ExpectedRequest {
+            span: start..w.pos(),
+            method,
+            target,
+            headers,
+            body,
+        }
+    }
+
+    fn response(&mut self, w: &mut Writer) -> ExpectedResponse {
+        let start = w.pos();
+        let (code, reason) = *[("200", "OK"), ("201", "Created"), ("404", "Not Found")]
+            .choose(&mut self.rng)
+            .unwrap();
+
+        w.write("HTTP/1.1 ");
+        let code = w.write(code);
+        w.write(" ");
+        let reason = w.write(reason);
+        w.write("\r\n");
+
+        let mut headers = vec![w.header("Server", "spansy")];
+        let body = Some(self.body());
+        let body = self.headers_and_body(w, &mut headers, body);
+
+        ExpectedResponse {
+            span: start..w.pos(),
+            code,
+            reason,
+            headers,
+            body,
+        }
+    }
+
+    /// Writes the remaining headers, the empty line and the body, returning the range of the
+    /// body if it is not empty.
+    fn headers_and_body(
+        &mut self,
+        w: &mut Writer,
+        headers: &mut Vec<ExpectedHeader>,
+        body: Option<(&'static str, String)>,
+    ) -> Option<Range<usize>> {
+        // Duplicate headers are valid and must be preserved.
+        for _ in 0..self.rng.gen_range(0..3) {
+            let value = self.token(1..8);
+            headers.push(w.header("Cache-Control", &value));
+        }
+
+        let Some((content_type, body)) = body else {
+            w.write("\r\n");
+            return None;
+        };
+
+        headers.push(w.header("Content-Type", content_type));
+
+        if self.rng.gen_bool(0.5) {
+            headers.push(w.header("Transfer-Encoding", "chunked"));
+            w.write("\r\n");
+
+            let start = w.pos();
+            let mut rest = body.as_str();
+            while !rest.is_empty() {
+                let (chunk, tail) = rest.split_at(self.rng.gen_range(1..=rest.len()));
+                w.write(&format!("{:x}\r\n{chunk}\r\n", chunk.len()));
+                rest = tail;
+            }
+            w.write("0\r\n\r\n");
+
+            Some(start..w.pos())
+        } else {
+            headers.push(w.header("Content-Length", &body.len().to_string()));
+            w.write("\r\n");
+
+            let body = w.write(&body);
+
+            (!body.is_empty()).then_some(body)
+        }
+    }
+
+    /// Returns a random body and its content type.
+    fn body(&mut self) -> (&'static str, String) {
+        if self.rng.gen_bool(0.5) {
+            let fields = (0..self.rng.gen_range(0..4))
+                .map(|idx| format!("\"k{idx}\": \"{}\"", self.token(0..8)))
+                .collect::<Vec<_>>();
+
+            ("application/json", format!("{{{}}}", fields.join(", ")))
+        } else {
+            ("text/plain", self.token(0..32))
+        }
+    }
+
+    /// Returns a random alphanumeric token with a length in `len`.
+    fn token(&mut self, len: Range<usize>) -> String {
+        let len = self.rng.gen_range(len);
+
+        (0..len)
+            .map(|_| char::from(self.rng.sample(rand::distributions::Alphanumeric)))
+            .collect()
+    }
+}
+
+/// Writes a transcript, tracking the position of each part.
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn pos(&self) -> usize {
+        self.buf.len()
+    }
+
+    fn write(&mut self, s: &str) -> Range<usize> {
+        let start = self.pos();
+        self.buf.extend_from_slice(s.as_bytes());
+
+        start..self.pos()
+    }
+
+    fn header(&mut self, name: &str, value: &str) -> ExpectedHeader {
+        let start = self.pos();
+        let name = self.write(name);
+        self.write(": ");
+        let value = self.write(value);
+        self.write("\r\n");
+
+        ExpectedHeader {
+            span: start..self.pos(),
+            name,
+            value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        http::{Requests, Responses},
+        testutil::harness::{assert_request, assert_response},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_generated_transcripts_parse() {
+        let mut generator = TranscriptGenerator::new(0).max_exchanges(8);
+
+        for _ in 0..64 {
+            let transcript = generator.generate();
+
+            let requests = Requests::new_from_slice(&transcript.sent)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(requests.len(), transcript.requests.len());
+            for (request, expected) in requests.iter().zip(&transcript.requests) {
+                assert_request(&transcript.sent, request);
+                expected.assert_matches(request);
+            }
+
+            let responses = Responses::new_from_slice(&transcript.received)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(responses.len(), transcript.responses.len());
+            for (response, expected) in responses.iter().zip(&transcript.responses) {
+                assert_response(&transcript.received, response);
+                expected.assert_matches(response);
+            }
+        }
+    }
+
+    #[test]
+    fn test_generator_is_deterministic() {
+        let a = TranscriptGenerator::new(7).generate();
+        let b = TranscriptGenerator::new(7).generate();
+
+        assert_eq!(a.sent, b.sent);
+        assert_eq!(a.received, b.received);
+    }
+}
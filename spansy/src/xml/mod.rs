@@ -0,0 +1,32 @@
+//! XML span parsing.
+//!
+//! This module provides an XML parser which computes the span of each element, attribute and
+//! text node of a document, analogous to the [`json`](crate::json) module.
+//!
+//! The parser supports the subset of XML 1.0 found in HTTP bodies, such as SOAP envelopes. It
+//! checks that the document is well-formed, but does not validate it. Character and entity
+//! references are not expanded, and the internal subset of a document type declaration is not
+//! parsed.
+//!
+//! # Example
+//!
+//! ```
+//! use spansy::{xml, Spanned};
+//!
+//! let src = "<?xml version=\"1.0\"?><balance currency=\"EUR\"><amount>42</amount></balance>";
+//!
+//! let document = xml::parse_str(src).unwrap();
+//!
+//! assert_eq!(document.root.name, "balance");
+//! assert_eq!(document.get("amount").unwrap().text().unwrap(), "42");
+//!
+//! // The span of the `currency` attribute value is 40..43 within the source string.
+//! let currency = document.root.attribute("currency").unwrap();
+//! assert_eq!(currency.value.indices(), 40..43);
+//! ```
+
+mod span;
+mod types;
+
+pub use span::{parse, parse_slice, parse_str};
+pub use types::{Attribute, Element, Node, XmlDocument};
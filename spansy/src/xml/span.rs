@@ -0,0 +1,221 @@
+use pest::{iterators::Pair as PestPair, Parser};
+
+use super::types::{Attribute, Element, Node, XmlDocument};
+
+use crate::{ParseError, Source, Span};
+
+#[derive(pest_derive::Parser)]
+#[grammar = "xml/xml.pest"]
+struct XmlParser;
+
+/// Parse an XML document from a source string.
+pub fn parse_str(src: &str) -> Result<XmlDocument, ParseError> {
+    parse(Source::copy_from_slice(src.as_bytes()))
+}
+
+/// Parse an XML document from a byte slice.
+pub fn parse_slice(src: &[u8]) -> Result<XmlDocument, ParseError> {
+    parse(Source::copy_from_slice(src))
+}
+
+/// Parse an XML document from source bytes.
+pub fn parse(src: impl Into<Source>) -> Result<XmlDocument, ParseError> {
+    let src = src.into();
+    let src_str = std::str::from_utf8(&src).map_err(|err| {
+        let start = err.valid_up_to();
+        let end = err.error_len().map_or(src.len(), |len| start + len);
+
+        ParseError::from(err).with_label(start..end, "invalid UTF-8")
+    })?;
+
+    let document = XmlParser::parse(Rule::document, src_str)?
+        .next()
+        .expect("document is present");
+
+    Ok(XmlDocument::from_pair(&src, document))
+}
+
+impl XmlDocument {
+    fn from_pair(src: &Source, pair: PestPair<'_, Rule>) -> Self {
+        assert!(matches!(pair.as_rule(), Rule::document));
+
+        let span = Span::new_from_str(src, pair.as_str());
+        let mut declaration = None;
+        let mut doctype = None;
+        let mut root = None;
+        for pair in pair.into_inner() {
+            match pair.as_rule() {
+                Rule::declaration => declaration = Some(Span::new_from_str(src, pair.as_str())),
+                Rule::doctype => doctype = Some(Span::new_from_str(src, pair.as_str())),
+                Rule::element => root = Some(Element::from_pair(src, pair)),
+                // Comments and processing instructions outside of the root element are only
+                // covered by the span of the document.
+                _ => {}
+            }
+        }
+
+        Self {
+            span,
+            declaration,
+            doctype,
+            root: root.expect("root element is present"),
+        }
+    }
+}
+
+impl Element {
+    fn from_pair(src: &Source, pair: PestPair<'_, Rule>) -> Self {
+        assert!(matches!(pair.as_rule(), Rule::element));
+
+        let span = Span::new_from_str(src, pair.as_str());
+        let mut pairs = pair.into_inner();
+        let name = Span::new_from_str(src, pairs.next().expect("name is present").as_str());
+
+        let mut attributes = Vec::new();
+        let mut children = Vec::new();
+        for pair in pairs {
+            let span = || Span::new_from_str(src, pair.as_str());
+            match pair.as_rule() {
+                Rule::attribute => attributes.push(Attribute::from_pair(src, pair)),
+                Rule::element => children.push(Node::Element(Element::from_pair(src, pair))),
+                Rule::text => children.push(Node::Text(span())),
+                Rule::cdata => children.push(Node::CData(Span::new_from_str(
+                    src,
+                    pair.into_inner()
+                        .next()
+                        .expect("CDATA text is present")
+                        .as_str(),
+                ))),
+                Rule::comment => children.push(Node::Comment(span())),
+                Rule::pi => children.push(Node::ProcessingInstruction(span())),
+                rule => unreachable!("unexpected matched rule: {:?}", rule),
+            }
+        }
+
+        Self {
+            span,
+            name,
+            attributes,
+            children,
+        }
+    }
+}
+
+impl Attribute {
+    fn from_pair(src: &Source, pair: PestPair<'_, Rule>) -> Self {
+        assert!(matches!(pair.as_rule(), Rule::attribute));
+
+        let span = Span::new_from_str(src, pair.as_str());
+        let mut pairs = pair.into_inner();
+        let name = pairs.next().expect("name is present");
+        let value = pairs.next().expect("value is present");
+
+        Self {
+            span,
+            name: Span::new_from_str(src, name.as_str()),
+            value: Span::new_from_str(src, value.as_str()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utils::range::RangeSet;
+
+    use crate::Spanned;
+
+    use super::*;
+
+    const SOAP: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <soap:Envelope xmlns:soap=\"http://www.w3.org/2003/05/soap-envelope\">\n\
+        <soap:Body>\n\
+        <!-- balance -->\n\
+        <m:Balance xmlns:m='urn:bank' currency = \"EUR\">1&amp;2</m:Balance>\n\
+        <m:Note><![CDATA[<b>hi</b>]]></m:Note>\n\
+        <m:Empty/>\n\
+        </soap:Body>\n\
+        </soap:Envelope>\n";
+
+    #[test]
+    fn test_parse_document() {
+        let document = parse_str(SOAP).unwrap();
+
+        assert_eq!(document.span(), SOAP);
+        assert_eq!(
+            document.declaration.as_ref().unwrap(),
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>"
+        );
+        assert_eq!(document.root.name, "soap:Envelope");
+        assert_eq!(
+            document.root.attribute("xmlns:soap").unwrap().value,
+            "http://www.w3.org/2003/05/soap-envelope"
+        );
+
+        let body = document.get("soap:Body").unwrap();
+        assert_eq!(body.elements().count(), 3);
+        assert!(
+            matches!(&body.children[1], Node::Comment(comment) if comment == "<!-- balance -->")
+        );
+
+        let balance = body.child("m:Balance").unwrap();
+        assert_eq!(balance.text().unwrap(), "1&amp;2");
+        let currency = balance.attribute("currency").unwrap();
+        assert_eq!(currency.span(), "currency = \"EUR\"");
+        assert_eq!(currency.value, "EUR");
+        assert_eq!(balance.attribute("xmlns:m").unwrap().value, "urn:bank");
+
+        assert_eq!(
+            document.get("soap:Body/m:Note").unwrap().text().unwrap(),
+            "<b>hi</b>"
+        );
+        let empty = document.get("soap:Body/m:Empty").unwrap();
+        assert_eq!(empty.span(), "<m:Empty/>");
+        assert!(empty.children.is_empty());
+        assert!(document.get("soap:Body/m:Missing").is_none());
+    }
+
+    #[test]
+    fn test_without_content() {
+        let src = "<a x=\"1\">b<c/></a>";
+        let document = parse_str(src).unwrap();
+
+        assert_eq!(
+            document.root.without_content(),
+            RangeSet::from([0..9, 14..18])
+        );
+        assert_eq!(
+            document.root.attributes[0].without_value(),
+            RangeSet::from([3..6, 7..8])
+        );
+    }
+
+    #[test]
+    fn test_parse_doctype() {
+        let src = "<!DOCTYPE note [<!ENTITY a \"b\">]>\n<note>&a;</note>";
+        let document = parse_str(src).unwrap();
+
+        assert_eq!(
+            document.doctype.unwrap(),
+            "<!DOCTYPE note [<!ENTITY a \"b\">]>"
+        );
+        assert_eq!(document.root.text().unwrap(), "&a;");
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        for src in [
+            "",
+            "<a>",
+            "<a></b>",
+            "<a><b></a></b>",
+            "<a/><b/>",
+            "text",
+            "<a x=\"1>",
+            "<a x=1/>",
+            "<a/>trailing",
+            " <?xml version=\"1.0\"?><a/>",
+        ] {
+            assert!(parse_str(src).is_err(), "{src:?}");
+        }
+    }
+}
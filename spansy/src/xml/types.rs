@@ -0,0 +1,300 @@
+use bytes::Bytes;
+use utils::range::{Difference, RangeSet, ToRangeSet};
+
+use crate::{offset::impl_offset, MultiSpan, Offset, ParseError, Span, Spanned};
+
+/// An XML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct XmlDocument {
+    pub(crate) span: Span<str>,
+
+    /// The XML declaration, e.g. `<?xml version="1.0"?>`.
+    pub declaration: Option<Span<str>>,
+    /// The document type declaration, e.g. `<!DOCTYPE html>`.
+    pub doctype: Option<Span<str>>,
+    /// The root element.
+    pub root: Element,
+}
+
+impl XmlDocument {
+    /// Returns the element at the given path, relative to the root element.
+    ///
+    /// See [`Element::get`].
+    pub fn get(&self, path: &str) -> Option<&Element> {
+        self.root.get(path)
+    }
+
+    /// Returns a copy of the document whose spans view the bytes of `src` at the same indices.
+    ///
+    /// This can be used to view the document in a modified copy of the source, such as a
+    /// masked transcript, without changing the original.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The new source, which must contain the span indices.
+    pub fn with_data(&self, src: &Bytes) -> Result<Self, ParseError> {
+        let mut document = self.clone();
+        document.set_data(src)?;
+
+        Ok(document)
+    }
+
+    /// Maps the spans from positions in the data of `base` to the indices of `base`.
+    pub(crate) fn rebase(&mut self, base: &MultiSpan) {
+        self.span.rebase(base);
+        for span in self.declaration.iter_mut().chain(&mut self.doctype) {
+            span.rebase(base);
+        }
+        self.root.rebase(base);
+    }
+
+    pub(crate) fn set_data(&mut self, src: &Bytes) -> Result<(), ParseError> {
+        self.span = self.span.with_data(src)?;
+        for span in self.declaration.iter_mut().chain(&mut self.doctype) {
+            *span = span.with_data(src)?;
+        }
+        self.root.set_data(src)
+    }
+}
+
+impl_offset!(XmlDocument {
+    span,
+    declaration,
+    doctype,
+    root
+});
+
+impl Spanned<str> for XmlDocument {
+    fn span(&self) -> &Span<str> {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for XmlDocument {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+/// An XML element, from its start tag to its end tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Element {
+    pub(crate) span: Span<str>,
+
+    /// The name of the element, including any namespace prefix, e.g. `soap:Body`.
+    pub name: Span<str>,
+    /// The attributes of the element, in order.
+    pub attributes: Vec<Attribute>,
+    /// The content of the element, in order.
+    pub children: Vec<Node>,
+}
+
+impl Element {
+    /// Returns the first attribute with the given name.
+    pub fn attribute(&self, name: &str) -> Option<&Attribute> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name.as_str() == name)
+    }
+
+    /// Returns an iterator over the child elements, in order.
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|child| match child {
+            Node::Element(element) => Some(element),
+            _ => None,
+        })
+    }
+
+    /// Returns the first child element with the given name.
+    pub fn child(&self, name: &str) -> Option<&Element> {
+        self.elements()
+            .find(|element| element.name.as_str() == name)
+    }
+
+    /// Returns the descendant element at the given path.
+    ///
+    /// The path is a sequence of element names separated by `/`, each selecting the first
+    /// child element with that name. Names are compared including their namespace prefix.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spansy::{xml, Spanned};
+    ///
+    /// let src = "<account><owner>Alice</owner><balance currency=\"EUR\">42</balance></account>";
+    /// let document = xml::parse_str(src).unwrap();
+    ///
+    /// let balance = document.get("balance").unwrap();
+    /// assert_eq!(balance.text().unwrap(), "42");
+    /// assert_eq!(balance.attribute("currency").unwrap().value, "EUR");
+    /// assert_eq!(balance.span().indices(), 29..65);
+    /// ```
+    pub fn get(&self, path: &str) -> Option<&Element> {
+        path.split('/')
+            .try_fold(self, |element, name| element.child(name))
+    }
+
+    /// Returns the text content of the element, if it consists of a single text or CDATA
+    /// node.
+    ///
+    /// Character and entity references are not expanded.
+    pub fn text(&self) -> Option<&Span<str>> {
+        match self.children.as_slice() {
+            [Node::Text(text) | Node::CData(text)] => Some(text),
+            _ => None,
+        }
+    }
+
+    /// Returns the indices of the element excluding its content, i.e. its tags.
+    pub fn without_content(&self) -> RangeSet<usize> {
+        self.children
+            .iter()
+            .fold(self.span.indices.clone(), |indices, child| {
+                indices.difference(&child.span().indices)
+            })
+    }
+
+    fn rebase(&mut self, base: &MultiSpan) {
+        self.span.rebase(base);
+        self.name.rebase(base);
+        for attribute in &mut self.attributes {
+            attribute.span.rebase(base);
+            attribute.name.rebase(base);
+            attribute.value.rebase(base);
+        }
+        for child in &mut self.children {
+            match child {
+                Node::Element(element) => element.rebase(base),
+                Node::Text(span)
+                | Node::CData(span)
+                | Node::Comment(span)
+                | Node::ProcessingInstruction(span) => span.rebase(base),
+            }
+        }
+    }
+
+    fn set_data(&mut self, src: &Bytes) -> Result<(), ParseError> {
+        self.span = self.span.with_data(src)?;
+        self.name = self.name.with_data(src)?;
+        for attribute in &mut self.attributes {
+            attribute.span = attribute.span.with_data(src)?;
+            attribute.name = attribute.name.with_data(src)?;
+            attribute.value = attribute.value.with_data(src)?;
+        }
+        for child in &mut self.children {
+            match child {
+                Node::Element(element) => element.set_data(src)?,
+                Node::Text(span)
+                | Node::CData(span)
+                | Node::Comment(span)
+                | Node::ProcessingInstruction(span) => *span = span.with_data(src)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl_offset!(Element {
+    span,
+    name,
+    attributes,
+    children
+});
+
+impl Spanned<str> for Element {
+    fn span(&self) -> &Span<str> {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for Element {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+/// An attribute of an element, e.g. `currency="EUR"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Attribute {
+    pub(crate) span: Span<str>,
+
+    /// The name of the attribute.
+    pub name: Span<str>,
+    /// The value of the attribute, excluding the quotes.
+    ///
+    /// Character and entity references are not expanded.
+    pub value: Span<str>,
+}
+
+impl Attribute {
+    /// Returns the indices of the attribute excluding its value.
+    pub fn without_value(&self) -> RangeSet<usize> {
+        self.span.indices.difference(&self.value.indices)
+    }
+}
+
+impl_offset!(Attribute { span, name, value });
+
+impl Spanned<str> for Attribute {
+    fn span(&self) -> &Span<str> {
+        &self.span
+    }
+}
+
+impl ToRangeSet<usize> for Attribute {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span.indices.clone()
+    }
+}
+
+/// A node of the content of an element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Node {
+    /// A child element.
+    Element(Element),
+    /// Text, including whitespace between elements.
+    ///
+    /// Character and entity references are not expanded.
+    Text(Span<str>),
+    /// The text of a CDATA section, excluding the `<![CDATA[` and `]]>` delimiters.
+    CData(Span<str>),
+    /// A comment, including the `<!--` and `-->` delimiters.
+    Comment(Span<str>),
+    /// A processing instruction, including the `<?` and `?>` delimiters.
+    ProcessingInstruction(Span<str>),
+}
+
+impl Offset for Node {
+    fn offset(&mut self, offset: usize) {
+        match self {
+            Node::Element(element) => element.offset(offset),
+            Node::Text(span)
+            | Node::CData(span)
+            | Node::Comment(span)
+            | Node::ProcessingInstruction(span) => span.offset(offset),
+        }
+    }
+}
+
+impl Spanned<str> for Node {
+    fn span(&self) -> &Span<str> {
+        match self {
+            Node::Element(element) => &element.span,
+            Node::Text(span)
+            | Node::CData(span)
+            | Node::Comment(span)
+            | Node::ProcessingInstruction(span) => span,
+        }
+    }
+}
+
+impl ToRangeSet<usize> for Node {
+    fn to_range_set(&self) -> RangeSet<usize> {
+        self.span().indices.clone()
+    }
+}
@@ -0,0 +1,362 @@
+//! End-to-end tests against sanitized captures of real-world APIs.
+//!
+//! Each fixture in `tests/fixtures` is a transcript of the bytes sent and received over a
+//! connection, with credentials and personal data replaced by placeholders of the same shape.
+//! The tests run the workflow of a prover selectively disclosing a transcript:
+//!
+//! 1. Parse the transcript into a [`Session`].
+//! 2. Select the ranges to reveal, such as the structure of each message and the values of
+//!    chosen JSON fields.
+//! 3. Redact the transcript, replacing every byte which is not revealed.
+//! 4. Reconstruct each message over the redacted transcript and check that it differs from
+//!    the original exactly in the redacted ranges.
+
+use bytes::Bytes;
+use spansy::{
+    framed::{Messages, SingleLine},
+    http::{compare_redacted, BodyContent, Request, Response, Session},
+    json::{self, JsonValue},
+    testutil::harness::assert_session,
+    Offset, Spanned,
+};
+use utils::range::{Difference, RangeSet, Subset, Union};
+
+/// The byte which replaces redacted bytes.
+const REDACTED: u8 = b'X';
+
+macro_rules! fixture {
+    ($name:literal) => {
+        (
+            include_bytes!(concat!("fixtures/", $name, "/sent.http")).as_slice(),
+            include_bytes!(concat!("fixtures/", $name, "/received.http")).as_slice(),
+        )
+    };
+}
+
+/// Returns the indices of a JSON value excluding its strings, numbers, booleans and nulls, so the
+/// keys and structure of the value are revealed.
+fn json_structure(value: &JsonValue) -> RangeSet<usize> {
+    match value {
+        JsonValue::Object(obj) => obj.elems.iter().fold(obj.without_pairs(), |indices, kv| {
+            indices
+                .union(&kv.without_value())
+                .union(&json_structure(&kv.value))
+        }),
+        JsonValue::Array(arr) => arr
+            .elems
+            .iter()
+            .fold(arr.without_values(), |indices, elem| {
+                indices.union(&json_structure(elem))
+            }),
+        _ => RangeSet::default(),
+    }
+}
+
+/// Returns a copy of `src` where every byte outside of `revealed` is redacted.
+fn redact(src: &[u8], revealed: &RangeSet<usize>) -> Bytes {
+    let mut redacted = vec![REDACTED; src.len()];
+    for range in revealed.iter_ranges() {
+        redacted[range.clone()].copy_from_slice(&src[range]);
+    }
+    redacted.into()
+}
+
+/// Checks that `view`, a reconstruction of `original` over a redacted source, differs from it
+/// exactly where the bytes were not revealed.
+fn check_reconstruction<T: ?Sized>(
+    original: &impl Spanned<T>,
+    view: &impl Spanned<T>,
+    revealed: &RangeSet<usize>,
+) {
+    let redacted = original.span().indices().difference(revealed);
+
+    assert_eq!(compare_redacted(original, view, &redacted), Ok(()));
+    if redacted.len_ranges() > 0 {
+        // Redacted bytes which happen to match the replacement byte are not reported.
+        let diff = compare_redacted(original, view, &RangeSet::default()).unwrap_err();
+        assert!(diff.is_subset(&redacted));
+    }
+}
+
+/// Reconstructs the requests and responses of a session over the redacted transcripts.
+fn reconstruct(
+    session: &Session,
+    sent: &[u8],
+    received: &[u8],
+    sent_revealed: &RangeSet<usize>,
+    received_revealed: &RangeSet<usize>,
+) -> (Vec<Request>, Vec<Response>) {
+    let sent_redacted = redact(sent, sent_revealed);
+    let received_redacted = redact(received, received_revealed);
+
+    let requests = session
+        .requests()
+        .map(|request| {
+            let view = request.with_data(&sent_redacted).unwrap();
+            check_reconstruction(request, &view, sent_revealed);
+            view
+        })
+        .collect();
+    let responses = session
+        .responses()
+        .map(|response| {
+            let view = response.with_data(&received_redacted).unwrap();
+            check_reconstruction(response, &view, received_revealed);
+            view
+        })
+        .collect();
+
+    (requests, responses)
+}
+
+#[test]
+fn test_github_api() {
+    let (sent, received) = fixture!("github_api");
+    let session = Session::new_from_slice(sent, received).unwrap();
+    assert_session(sent, received, &session);
+
+    let request = &session.exchanges[0].request;
+    assert_eq!(request.request.target.as_str(), "/user");
+    let response = session.response_for("/user").unwrap();
+    assert_eq!(response.status.code.as_str(), "200");
+
+    // Reveal the request without the authorization token, and the login and public repository
+    // count of the user.
+    let authorization = request.first_header_value("authorization").unwrap();
+    let sent_revealed = RangeSet::from(0..sent.len()).difference(authorization.span().indices());
+
+    let login = response.json("/login").unwrap();
+    let repos = response.json("/public_repos").unwrap();
+    assert_eq!(login.span(), "octocat");
+    assert_eq!(repos.span(), "8");
+    let BodyContent::Json(body) = &response.body.as_ref().unwrap().content else {
+        panic!("body is JSON");
+    };
+    let received_revealed = response
+        .without_data()
+        .union(&json_structure(body))
+        .union(login.span().indices())
+        .union(repos.span().indices());
+
+    let (requests, responses) =
+        reconstruct(&session, sent, received, &sent_revealed, &received_revealed);
+
+    let authorization = requests[0].first_header_value("authorization").unwrap();
+    assert!(authorization.as_bytes().iter().all(|b| *b == REDACTED));
+    assert_eq!(
+        requests[0].first_header_value("host").unwrap().as_bytes(),
+        b"api.github.com"
+    );
+
+    // The structure of the body is kept, so the revealed fields can still be looked up.
+    let BodyContent::Json(body) = &responses[0].body.as_ref().unwrap().content else {
+        panic!("body is JSON");
+    };
+    assert_eq!(body.pointer("/login").unwrap().span(), "octocat");
+    assert_eq!(body.pointer("/public_repos").unwrap().span(), "8");
+    assert_eq!(
+        body.pointer("/email").unwrap().span(),
+        "XXXXXXXXXXXXXXXXXXX"
+    );
+}
+
+#[test]
+fn test_openai_sse() {
+    let (sent, received) = fixture!("openai_sse");
+    let session = Session::new_from_slice(sent, received).unwrap();
+    assert_session(sent, received, &session);
+
+    let request = &session.exchanges[0].request;
+    assert_eq!(request.json("/stream").unwrap().span(), "true");
+
+    // Event streams are not parsed, so the events are framed as lines of the body.
+    let body = session.responses().next().unwrap().body.as_ref().unwrap();
+    assert!(matches!(body.content, BodyContent::Binary(_)));
+    let body_start = body.span().indices().min().unwrap();
+
+    let mut content = Vec::new();
+    for message in Messages::new_from_slice(body.span().as_bytes(), b"\n", SingleLine) {
+        let mut message = message.unwrap();
+        message.offset(body_start);
+
+        let line = &message.lines[0].content;
+        let Some(data) = line.as_bytes().strip_prefix(b"data: ") else {
+            continue;
+        };
+        if data == b"[DONE]" {
+            continue;
+        }
+
+        let mut event = json::parse_slice(data).unwrap();
+        event.offset(line.indices().min().unwrap() + b"data: ".len());
+        // The first event only sets the role, with empty content.
+        if let Some(delta) = event.pointer("/choices/0/delta/content") {
+            if !delta.span().is_empty() {
+                content.push(delta.clone());
+            }
+        }
+    }
+
+    let text: String = content.iter().map(|value| value.span().as_str()).collect();
+    assert_eq!(text, "Hello world");
+
+    // Reveal the prompt and the streamed content only.
+    let prompt = request.json("/messages/0/content").unwrap();
+    assert_eq!(prompt.span(), "Say hello");
+    let sent_revealed = request
+        .without_data()
+        .union(&json_structure(request.json("").unwrap()))
+        .union(prompt.span().indices());
+    let received_revealed = content.iter().fold(
+        session.responses().next().unwrap().without_data(),
+        |revealed, value| revealed.union(value.span().indices()),
+    );
+
+    let (requests, responses) =
+        reconstruct(&session, sent, received, &sent_revealed, &received_revealed);
+    assert_eq!(
+        requests[0].json("/messages/0/content").unwrap().span(),
+        "Say hello"
+    );
+    assert_eq!(requests[0].json("/model").unwrap().span(), "XXXXXXXXXXX");
+
+    let body = responses[0].body.as_ref().unwrap().span().as_bytes();
+    for value in &content {
+        let start = value.span().indices().min().unwrap() - body_start;
+        assert_eq!(
+            &body[start..start + value.span().len()],
+            value.span().as_byte_span()
+        );
+    }
+}
+
+#[test]
+fn test_bank_json() {
+    let (sent, received) = fixture!("bank_json");
+    let session = Session::new_from_slice(sent, received).unwrap();
+    assert_session(sent, received, &session);
+    assert_eq!(session.exchanges.len(), 2);
+
+    let login = &session.exchanges[0].request;
+    let password = login.form_field("password").unwrap();
+    assert_eq!(password.value, b"hunter2".as_slice());
+
+    let token = session
+        .response_for("/oauth/token")
+        .unwrap()
+        .oauth_token()
+        .unwrap()
+        .unwrap();
+
+    let account = session.response_for("/v1/accounts/current").unwrap();
+    let available = account.json("/balance/available").unwrap();
+    let currency = account.json("/account/currency").unwrap();
+    assert_eq!(available.span(), "1234.56");
+    assert_eq!(currency.span(), "EUR");
+
+    // Reveal the requests without the credentials, the token response without the tokens, and
+    // the available balance and its currency.
+    let mut sent_revealed = RangeSet::from(0..sent.len());
+    for field in login.form().unwrap() {
+        sent_revealed = sent_revealed.difference(field.value.indices());
+    }
+    for request in session.requests() {
+        for name in ["authorization", "cookie"] {
+            if let Some(value) = request.first_header_value(name) {
+                sent_revealed = sent_revealed.difference(value.span().indices());
+            }
+        }
+    }
+
+    let mut received_revealed = token.reveal_non_tokens();
+    for response in session.responses().skip(1) {
+        received_revealed = received_revealed.union(&response.without_data());
+        if let BodyContent::Json(body) = &response.body.as_ref().unwrap().content {
+            received_revealed = received_revealed.union(&json_structure(body));
+        }
+    }
+    let received_revealed = received_revealed
+        .union(available.span().indices())
+        .union(currency.span().indices());
+
+    let (requests, responses) =
+        reconstruct(&session, sent, received, &sent_revealed, &received_revealed);
+
+    let password = requests[0].form_field("password").unwrap();
+    assert_eq!(password.value, b"XXXXXXX".as_slice());
+    assert_eq!(
+        requests[0].form_field("username").unwrap().name,
+        b"username".as_slice()
+    );
+    assert_eq!(
+        requests[1].request.target.as_str(),
+        "/v1/accounts/current?include=transactions"
+    );
+
+    assert_eq!(
+        responses[1].json("/balance/available").unwrap().span(),
+        "1234.56"
+    );
+    assert_eq!(
+        responses[1].json("/account/currency").unwrap().span(),
+        "EUR"
+    );
+    assert!(responses[1]
+        .json("/account/iban")
+        .unwrap()
+        .span()
+        .as_str()
+        .bytes()
+        .all(|b| b == REDACTED));
+}
+
+#[test]
+fn test_chunked_html() {
+    let (sent, received) = fixture!("chunked_html");
+    let session = Session::new_from_slice(sent, received).unwrap();
+    assert_session(sent, received, &session);
+    assert!(session.exchanges[0].connection().is_close());
+
+    let response = session.responses().next().unwrap();
+    let body = response.body.as_ref().unwrap();
+    assert!(matches!(body.content, BodyContent::Binary(_)));
+    assert_eq!(body.chunked.as_ref().unwrap().chunks().len(), 3);
+
+    // The title is split across two chunks, so its indices skip the chunk framing.
+    let content = body.content.span().as_bytes();
+    let start = find(content, b"<title>").unwrap();
+    let end = find(content, b"</title>").unwrap() + b"</title>".len();
+    let title: RangeSet<usize> = body
+        .content
+        .span()
+        .indices()
+        .iter()
+        .skip(start)
+        .take(end - start)
+        .map(|idx| idx..idx + 1)
+        .collect::<Vec<_>>()
+        .into();
+    assert_eq!(title.len_ranges(), 2);
+
+    let sent_revealed = RangeSet::from(0..sent.len());
+    let received_revealed = response.head_span().indices().union(&title);
+
+    let (_, responses) = reconstruct(&session, sent, received, &sent_revealed, &received_revealed);
+
+    // The chunk framing is redacted, so the content is reconstructed from the title only.
+    let content = responses[0]
+        .body
+        .as_ref()
+        .unwrap()
+        .content
+        .span()
+        .as_bytes();
+    assert_eq!(&content[start..end], b"<title>Example Domain</title>");
+    assert!(content[..start].iter().all(|b| *b == REDACTED));
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
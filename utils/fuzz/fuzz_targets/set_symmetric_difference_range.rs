@@ -0,0 +1,30 @@
+#![no_main]
+
+use std::{collections::HashSet, ops::Range};
+
+use libfuzzer_sys::fuzz_target;
+
+use tlsn_utils_fuzz::{assert_invariants, SmallSet};
+
+use utils::range::*;
+
+fuzz_target!(|r: (SmallSet, Range<u8>)| {
+    let (set, range) = r;
+
+    let set: RangeSet<u8> = set.into();
+
+    let h1: HashSet<u8> = HashSet::from_iter(set.iter());
+    let h2: HashSet<u8> = HashSet::from_iter(range.clone());
+
+    let symmetric_difference = set.symmetric_difference(&range);
+    let h3: HashSet<u8> = HashSet::from_iter(symmetric_difference.iter());
+
+    assert_eq!(
+        h3,
+        h1.symmetric_difference(&h2)
+            .copied()
+            .collect::<HashSet<_>>()
+    );
+
+    assert_invariants(symmetric_difference);
+});
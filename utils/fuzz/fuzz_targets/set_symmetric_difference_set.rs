@@ -0,0 +1,30 @@
+#![no_main]
+
+use std::collections::HashSet;
+
+use libfuzzer_sys::fuzz_target;
+
+use tlsn_utils_fuzz::{assert_invariants, SmallSet};
+
+use utils::range::*;
+
+fuzz_target!(|r: (SmallSet, SmallSet)| {
+    let s1: RangeSet<u8> = r.0.into();
+    let s2: RangeSet<u8> = r.1.into();
+
+    let h1: HashSet<u8> = HashSet::from_iter(s1.iter());
+    let h2: HashSet<u8> = HashSet::from_iter(s2.iter());
+
+    let symmetric_difference = s1.symmetric_difference(&s2);
+    let h3: HashSet<u8> = HashSet::from_iter(symmetric_difference.iter());
+
+    assert_eq!(
+        h3,
+        h1.symmetric_difference(&h2)
+            .copied()
+            .collect::<HashSet<_>>()
+    );
+    assert_eq!(symmetric_difference, s2.symmetric_difference(&s1));
+
+    assert_invariants(symmetric_difference);
+});
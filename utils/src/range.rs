@@ -0,0 +1,3 @@
+//! Range sets and range set algebra, re-exported from `tlsn-utils-ranges`.
+
+pub use utils_ranges::*;